@@ -13,6 +13,9 @@
 # See the License for the specific language governing permissions and
 # limitations under the License.
 
+# Any target supporting `DUMP_TEST_DIR`: `abac`, `abac-type-directed`,
+# `validation-drt`, `validation-drt-type-directed`, `entity-validation` or
+# `request-validation`
 FUZZ_TARGET="${FUZZ_TARGET:-abac}"
 TIMEOUT_MINS="${TIMEOUT:-15}" # 15m = 900s
 JOBS="${JOBS:-4}"
@@ -0,0 +1,24 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![no_main]
+
+use cedar_drt_inner::{fuzz_target, solver_guided};
+#[cfg(feature = "prt")]
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
+
+// Authorization DRT on requests and entities synthesized by the Lean SymCC
+fuzz_target!(|input: solver_guided::FuzzTargetInput| solver_guided::fuzz_target(input));
@@ -26,6 +26,7 @@ use cedar_policy_generators::{
 use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
 use log::info;
 use std::collections::BTreeMap;
+use std::sync::LazyLock;
 
 #[derive(Debug, Clone)]
 struct Input {
@@ -34,7 +35,7 @@ struct Input {
 }
 
 /// settings for this fuzz target
-const SETTINGS: ABACSettings = ABACSettings {
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
     match_types: false,
     enable_extensions: true,
     max_depth: 3,
@@ -50,7 +51,8 @@ const SETTINGS: ABACSettings = ABACSettings {
     enable_action_in_constraints: true,
     per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
     total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
+    ..ABACSettings::default_fuzz()
+});
 
 impl<'a> Arbitrary<'a> for Input {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
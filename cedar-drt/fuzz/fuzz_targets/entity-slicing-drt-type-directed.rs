@@ -32,6 +32,7 @@ use cedar_policy_generators::{
 use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
 use log::debug;
 use std::convert::TryFrom;
+use std::sync::LazyLock;
 
 /// Input expected by this fuzz target:
 /// An ABAC hierarchy, schema, and 8 associated policies
@@ -49,7 +50,7 @@ struct FuzzTargetInput {
 }
 
 /// settings for this fuzz target
-const SETTINGS: ABACSettings = ABACSettings {
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
     match_types: true,
     enable_extensions: true,
     max_depth: 7,
@@ -62,7 +63,8 @@ const SETTINGS: ABACSettings = ABACSettings {
     enable_action_in_constraints: true,
     per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
     total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
+    ..ABACSettings::default_fuzz()
+});
 
 impl<'a> Arbitrary<'a> for FuzzTargetInput {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
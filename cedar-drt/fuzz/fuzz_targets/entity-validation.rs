@@ -15,75 +15,10 @@
  */
 
 #![no_main]
-use cedar_drt::{
-    logger::{initialize_log, TOTAL_MSG},
-    tests::run_ent_val_test,
-    CedarLeanEngine,
-};
-use cedar_drt_inner::fuzz_target;
 
-use cedar_policy::{Entities, Schema};
-use cedar_testing::cedar_test_impl::time_function;
+use cedar_drt_inner::{entity_validation, fuzz_target};
+#[cfg(feature = "prt")]
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
 
-use cedar_policy_generators::{
-    hierarchy::Hierarchy, hierarchy::HierarchyGenerator, schema, settings::ABACSettings,
-};
-use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
-use log::{debug, info};
-
-/// Input expected by this fuzz target
-#[derive(Debug, Clone)]
-pub struct FuzzTargetInput {
-    /// generated schema
-    pub schema: schema::Schema,
-    /// generated hierarchy
-    pub hierarchy: Hierarchy,
-}
-
-/// settings for this fuzz target
-const SETTINGS: ABACSettings = ABACSettings {
-    match_types: false,
-    enable_extensions: true,
-    max_depth: 7,
-    max_width: 7,
-    enable_additional_attributes: true,
-    enable_like: true,
-    enable_action_groups_and_attrs: true,
-    enable_arbitrary_func_call: true,
-    enable_unknowns: false,
-    enable_action_in_constraints: true,
-    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
-    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
-
-impl<'a> Arbitrary<'a> for FuzzTargetInput {
-    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
-        let schema: schema::Schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
-        let hierarchy = schema.arbitrary_hierarchy(u)?;
-        Ok(Self { schema, hierarchy })
-    }
-
-    fn try_size_hint(
-        depth: usize,
-    ) -> arbitrary::Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
-        Ok(arbitrary::size_hint::and_all(&[
-            schema::Schema::arbitrary_size_hint(depth)?,
-            HierarchyGenerator::size_hint(depth),
-        ]))
-    }
-}
-
-fuzz_target!(|input: FuzzTargetInput| {
-    initialize_log();
-
-    let engine = CedarLeanEngine::new();
-
-    // generate a schema
-    if let Ok(schema) = Schema::try_from(input.schema) {
-        debug!("Schema: {:?}", schema);
-        if let Ok(entities) = Entities::try_from(input.hierarchy) {
-            let (_, total_dur) = time_function(|| run_ent_val_test(&engine, schema, entities));
-            info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
-        }
-    }
-});
+// Fuzzing of entity validation.
+fuzz_target!(|input: entity_validation::FuzzTargetInput| entity_validation::fuzz_target(input));
@@ -15,113 +15,10 @@
  */
 
 #![no_main]
-use cedar_drt::{
-    logger::initialize_log,
-    tests::{drop_some_entities, run_eval_test},
-    CedarLeanEngine,
-};
-use cedar_drt_inner::fuzz_target;
 
-use cedar_policy::Entities;
-use cedar_policy_core::ast::Expr;
-use cedar_policy_generators::err::Error;
-use cedar_policy_generators::hierarchy::HierarchyGenerator;
-use cedar_policy_generators::schema::{arbitrary_schematype_with_bounded_depth, Schema};
-use cedar_policy_generators::settings::ABACSettings;
-use cedar_policy_generators::{abac::ABACRequest, schema::schematype_to_type};
-use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
-use log::debug;
-use std::convert::TryFrom;
-
-/// Input expected by this fuzz target:
-/// An ABAC hierarchy, policy, and 8 associated requests
-#[derive(Debug, Clone)]
-pub struct FuzzTargetInput {
-    /// generated schema
-    pub schema: Schema,
-    /// generated entity slice
-    pub entities: Entities,
-    /// generated expression
-    pub expression: Expr,
-    /// the requests to try for this hierarchy and policy. We try 8 requests per
-    /// policy/hierarchy
-    pub request: ABACRequest,
-}
-
-/// settings for this fuzz target
-const SETTINGS: ABACSettings = ABACSettings {
-    match_types: true,
-    enable_extensions: true,
-    max_depth: 3,
-    max_width: 3,
-    enable_additional_attributes: false,
-    enable_like: true,
-    enable_action_groups_and_attrs: true,
-    enable_arbitrary_func_call: true,
-    enable_unknowns: false,
-    enable_action_in_constraints: true,
-    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
-    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
-
-impl<'a> Arbitrary<'a> for FuzzTargetInput {
-    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
-        let schema = Schema::arbitrary(SETTINGS.clone(), u)?;
-        let hierarchy = schema.arbitrary_hierarchy(u)?;
-        let toplevel_type = schematype_to_type(
-            schema.schemafile(),
-            &arbitrary_schematype_with_bounded_depth(
-                &SETTINGS,
-                schema.entity_types(),
-                SETTINGS.max_depth,
-                u,
-            )?,
-            schema.namespace(),
-        );
-        let expr_gen = schema.exprgenerator(Some(&hierarchy));
-        let expression = expr_gen.generate_expr_for_type(&toplevel_type, SETTINGS.max_depth, u)?;
-
-        let request = schema.arbitrary_request(&hierarchy, u)?;
-        let all_entities = Entities::try_from(hierarchy).map_err(Error::EntitiesError)?;
-        let entities = drop_some_entities(all_entities, u)?;
-        Ok(Self {
-            schema,
-            entities,
-            expression,
-            request,
-        })
-    }
-
-    fn try_size_hint(
-        depth: usize,
-    ) -> arbitrary::Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
-        Ok(arbitrary::size_hint::and_all(&[
-            Schema::arbitrary_size_hint(depth)?,
-            HierarchyGenerator::size_hint(depth),
-            Schema::arbitrary_policy_size_hint(&SETTINGS, depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-        ]))
-    }
-}
+use cedar_drt_inner::{eval_type_directed, fuzz_target};
+#[cfg(feature = "prt")]
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
 
 // Type-directed fuzzing of expression evaluation.
-fuzz_target!(|input: FuzzTargetInput| {
-    initialize_log();
-    let def_impl = CedarLeanEngine::new();
-    debug!("Schema: {}\n", input.schema.schemafile_string());
-    debug!("expr: {}\n", input.expression);
-    debug!("Entities: {}\n", input.entities.as_ref());
-    run_eval_test(
-        &def_impl,
-        &input.request.into(),
-        &input.expression.into(),
-        &input.entities,
-    )
-});
+fuzz_target!(|input: eval_type_directed::FuzzTargetInput| eval_type_directed::fuzz_target(input));
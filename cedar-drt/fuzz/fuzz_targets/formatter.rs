@@ -34,6 +34,7 @@ use rand::{Rng, SeedableRng};
 use similar_asserts::SimpleDiff;
 use smol_str::SmolStr;
 use std::collections::HashMap;
+use std::sync::LazyLock;
 use uuid::Builder;
 
 // A thin wrapper for policy
@@ -47,7 +48,7 @@ struct FuzzTargetInput {
 
 // settings for this fuzz target
 // copy-pasted from abac.rs
-const SETTINGS: ABACSettings = ABACSettings {
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
     match_types: false,
     enable_extensions: true,
     max_depth: 7,
@@ -60,7 +61,8 @@ const SETTINGS: ABACSettings = ABACSettings {
     enable_action_in_constraints: true,
     per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
     total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
+    ..ABACSettings::default_fuzz()
+});
 
 impl<'a> Arbitrary<'a> for FuzzTargetInput {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -26,6 +26,7 @@ use itertools::Itertools;
 use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
 use log::debug;
 use serde::Serialize;
+use std::sync::LazyLock;
 
 #[derive(Debug, Clone, Serialize)]
 struct FuzzTargetInput {
@@ -35,7 +36,7 @@ struct FuzzTargetInput {
 
 // settings for this fuzz target
 // copy-pasted from abac.rs
-const SETTINGS: ABACSettings = ABACSettings {
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
     match_types: false,
     enable_extensions: true,
     max_depth: 7,
@@ -48,7 +49,8 @@ const SETTINGS: ABACSettings = ABACSettings {
     enable_action_in_constraints: true,
     per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
     total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
+    ..ABACSettings::default_fuzz()
+});
 
 impl<'a> Arbitrary<'a> for FuzzTargetInput {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -0,0 +1,136 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![no_main]
+
+use cedar_drt::{logger::initialize_log, CedarLeanEngine};
+use cedar_drt_inner::protobuf_roundtrip::FuzzTargetInput;
+use cedar_drt_inner::roundtrip_entities;
+use cedar_drt_inner::{fuzz_target, schemas::Equiv};
+
+use cedar_lean_ffi::CedarLeanFfi;
+use cedar_policy::{Entities, Expression, Policy, Request, Schema};
+
+#[cfg(feature = "prt")]
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
+use log::debug;
+use std::str::FromStr;
+
+// Encode each value as protobuf, decode it with the Lean decoders, and check
+// that the decoded Lean value is equivalent to the Rust original.
+//
+// The FFI renders the decoded Lean policies and expressions as Cedar text (and
+// schemas as JSON) and parses them with the Rust parsers, so the comparisons
+// below are of Rust ASTs. A Rust parser bug that affects the original and the
+// rendered Lean value alike cancels out here; the parsers are covered by the
+// `roundtrip` and `*-roundtrip` targets instead.
+fuzz_target!(|input: FuzzTargetInput| {
+    initialize_log();
+    let lean_engine = CedarLeanEngine::new();
+    let lean_ffi = lean_engine.get_ffi();
+    let policy = Policy::from(input.policy);
+    let request = Request::from(input.request);
+    debug!("Policy: {policy}");
+    debug!("Request: {request}");
+
+    roundtrip_policy(lean_ffi, &policy);
+    roundtrip_expr(lean_ffi, &policy);
+    roundtrip_request(lean_ffi, &request);
+    roundtrip_entities(lean_ffi, &input.entities);
+    roundtrip_schema(lean_ffi, &input.schema);
+});
+
+fn roundtrip_policy(lean_ffi: &CedarLeanFfi, policy: &Policy) {
+    let decoded = lean_ffi
+        .decode_policy(policy)
+        .expect("Lean failed to decode Policy");
+    assert_eq!(policy.id(), decoded.id());
+    // Lean policies do not carry annotations, so compare everything else
+    let (original, decoded) = (policy.as_ref(), decoded.as_ref());
+    similar_asserts::assert_eq!(original.effect(), decoded.effect());
+    similar_asserts::assert_eq!(
+        original.principal_constraint(),
+        decoded.principal_constraint()
+    );
+    similar_asserts::assert_eq!(original.action_constraint(), decoded.action_constraint());
+    similar_asserts::assert_eq!(
+        original.resource_constraint(),
+        decoded.resource_constraint()
+    );
+    assert!(
+        decoded
+            .non_scope_constraints()
+            .eq_shape(original.non_scope_constraints()),
+        "{}",
+        similar_asserts::SimpleDiff::from_str(
+            &decoded.non_scope_constraints().to_string(),
+            &original.non_scope_constraints().to_string(),
+            "lean",
+            "rust"
+        )
+    );
+}
+
+fn roundtrip_expr(lean_ffi: &CedarLeanFfi, policy: &Policy) {
+    let expr = Expression::from_str(&policy.as_ref().non_scope_constraints().to_string())
+        .expect("Failed to parse policy condition as an Expression");
+    let decoded = lean_ffi
+        .decode_expr(&expr)
+        .expect("Lean failed to decode Expr");
+    similar_asserts::assert_eq!(expr.to_string(), decoded.to_string());
+}
+
+fn roundtrip_entities(lean_ffi: &CedarLeanFfi, entities: &Entities) {
+    let decoded = lean_ffi
+        .decode_entities(entities)
+        .expect("Lean failed to decode Entities");
+    roundtrip_entities::pretty_assert_entities_deep_eq(entities, &decoded);
+}
+
+fn roundtrip_request(lean_ffi: &CedarLeanFfi, request: &Request) {
+    let decoded = lean_ffi
+        .decode_request(request)
+        .expect("Lean failed to decode Request");
+    assert_eq!(request.principal(), decoded.principal());
+    assert_eq!(request.action(), decoded.action());
+    assert_eq!(request.resource(), decoded.resource());
+    assert_eq!(
+        request.context().map(|c| c.as_ref()),
+        decoded.context().map(|c| c.as_ref())
+    );
+}
+
+fn roundtrip_schema(lean_ffi: &CedarLeanFfi, schema: &Schema) {
+    let decoded = lean_ffi
+        .decode_schema(schema)
+        .expect("Lean failed to decode Schema");
+    Equiv::equiv(schema.as_ref(), decoded.as_ref()).unwrap();
+    let envs = |schema: &Schema| {
+        let mut envs = schema
+            .request_envs()
+            .map(|env| {
+                (
+                    env.principal().to_string(),
+                    env.action().to_string(),
+                    env.resource().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        envs.sort();
+        envs
+    };
+    similar_asserts::assert_eq!(envs(schema), envs(&decoded));
+}
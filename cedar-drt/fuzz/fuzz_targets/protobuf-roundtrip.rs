@@ -16,80 +16,16 @@
 
 #![no_main]
 
+use cedar_drt_inner::protobuf_roundtrip::FuzzTargetInput;
 use cedar_drt_inner::roundtrip_entities;
 use cedar_drt_inner::{fuzz_target, schemas::Equiv};
 
-use cedar_policy::{proto, Entities, Entity, Policy, PolicySet, Request, Schema};
+use cedar_policy::{proto, Entities, Policy, PolicySet, Request, Schema};
 
-use libfuzzer_sys::arbitrary::{self, MaxRecursionReached};
+#[cfg(feature = "prt")]
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
 use prost::Message;
 
-use crate::arbitrary::Arbitrary;
-use crate::arbitrary::Unstructured;
-use cedar_policy_generators::{
-    abac::ABACPolicy, abac::ABACRequest, hierarchy::HierarchyGenerator, schema,
-    settings::ABACSettings,
-};
-
-#[derive(Debug, Clone)]
-struct FuzzTargetInput {
-    request: ABACRequest,
-    policy: ABACPolicy,
-    entities: Entities,
-    schema: Schema,
-}
-
-// settings for this fuzz target
-// copy-pasted from abac.rs
-const SETTINGS: ABACSettings = ABACSettings {
-    match_types: false,
-    enable_extensions: true,
-    max_depth: 7,
-    max_width: 7,
-    enable_additional_attributes: false,
-    enable_like: true,
-    enable_action_groups_and_attrs: true,
-    enable_arbitrary_func_call: false,
-    enable_unknowns: false,
-    enable_action_in_constraints: true,
-    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
-    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
-
-impl<'a> Arbitrary<'a> for FuzzTargetInput {
-    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
-        let schema: schema::Schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
-        let hierarchy = schema.arbitrary_hierarchy(u)?;
-        let request = schema.arbitrary_request(&hierarchy, u)?;
-        let policy = schema.arbitrary_policy(&hierarchy, u)?;
-
-        let entities = Entities::from_entities(
-            hierarchy.entities().map(|x| Entity::from(x.to_owned())),
-            None,
-        )
-        .expect("Failed to create entities");
-
-        let schema = schema
-            .try_into()
-            .expect("Failed to convert schema to ValidatorSchema");
-
-        Ok(Self {
-            request,
-            policy,
-            entities,
-            schema,
-        })
-    }
-
-    fn try_size_hint(depth: usize) -> Result<(usize, Option<usize>), MaxRecursionReached> {
-        Ok(arbitrary::size_hint::and_all(&[
-            schema::Schema::arbitrary_size_hint(depth)?,
-            HierarchyGenerator::size_hint(depth),
-            schema::Schema::arbitrary_policy_size_hint(&SETTINGS, depth),
-        ]))
-    }
-}
-
 fuzz_target!(|input: FuzzTargetInput| {
     let policy = Policy::from(input.policy);
     let mut policies = PolicySet::new();
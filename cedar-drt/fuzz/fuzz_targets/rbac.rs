@@ -17,7 +17,7 @@
 #![no_main]
 
 use cedar_drt::{
-    logger::{initialize_log, TOTAL_MSG},
+    logger::{initialize_log, record_timing, TimingEvent, DRT_ENGINE, TOTAL, TOTAL_MSG},
     tests::run_auth_test,
     CedarLeanEngine,
 };
@@ -140,8 +140,14 @@ impl<'a> Arbitrary<'a> for FuzzTargetInput {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
         let hierarchy = RBACHierarchy(
             HierarchyGenerator {
+                // RBAC policies don't read attributes or tags, but both
+                // engines still have to load entities with heterogeneous,
+                // schema-violating attributes and tags
                 mode: HierarchyGeneratorMode::Arbitrary {
-                    attributes_mode: AttributesMode::NoAttributesOrTags,
+                    attributes_mode: AttributesMode::Arbitrary {
+                        max_attrs: 2,
+                        with_tags: true,
+                    },
                 },
                 num_entities: cedar_policy_generators::hierarchy::NumEntities::RangePerEntityType(
                     0..=4,
@@ -187,8 +193,8 @@ impl<'a> Arbitrary<'a> for FuzzTargetInput {
     }
 }
 
-// Fuzzing a single, pure-RBAC policy, with associated pure-RBAC hierarchy and
-// pure-RBAC requests.
+// Fuzzing a single, pure-RBAC policy, with an associated hierarchy (whose
+// entities carry arbitrary attributes and tags) and pure-RBAC requests.
 fuzz_target!(|input: FuzzTargetInput| {
     initialize_log();
     if let Ok(entities) = Entities::try_from(input.hierarchy) {
@@ -214,6 +220,7 @@ fuzz_target!(|input: FuzzTargetInput| {
             let (_, dur) =
                 time_function(|| run_auth_test(&lean_engine, &request, &policy_set, &entities));
             info!("{}{}", TOTAL_MSG, dur.as_nanos());
+            record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, dur));
         }
     }
 });
@@ -15,108 +15,10 @@
  */
 
 #![no_main]
-use cedar_drt::{
-    logger::{initialize_log, TOTAL_MSG},
-    tests::run_req_val_test,
-    CedarLeanEngine,
-};
-use cedar_drt_inner::fuzz_target;
 
-use cedar_policy::{Request, Schema};
-use cedar_testing::cedar_test_impl::time_function;
+use cedar_drt_inner::{fuzz_target, request_validation};
+#[cfg(feature = "prt")]
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
 
-use cedar_policy_generators::{
-    abac::ABACRequest, hierarchy::Hierarchy, hierarchy::HierarchyGenerator, schema,
-    settings::ABACSettings,
-};
-use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
-use log::{debug, info};
-
-/// Input expected by this fuzz target
-#[derive(Debug, Clone)]
-pub struct FuzzTargetInput {
-    /// generated schema
-    pub schema: schema::Schema,
-    /// generated hierarchy
-    pub hierarchy: Hierarchy,
-    /// the requests to try for this schema and hierarchy. We try 8 requests per
-    /// schema/hierarchy
-    pub requests: [ABACRequest; 8],
-}
-
-/// settings for this fuzz target
-const SETTINGS: ABACSettings = ABACSettings {
-    match_types: false,
-    enable_extensions: true,
-    max_depth: 7,
-    max_width: 7,
-    enable_additional_attributes: true,
-    enable_like: true,
-    enable_action_groups_and_attrs: true,
-    enable_arbitrary_func_call: true,
-    enable_unknowns: false,
-    enable_action_in_constraints: true,
-    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
-    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
-
-impl<'a> Arbitrary<'a> for FuzzTargetInput {
-    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
-        let schema: schema::Schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
-        let hierarchy = schema.arbitrary_hierarchy(u)?;
-        let requests = [
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-        ];
-        Ok(Self {
-            schema,
-            hierarchy,
-            requests,
-        })
-    }
-
-    fn try_size_hint(
-        depth: usize,
-    ) -> arbitrary::Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
-        Ok(arbitrary::size_hint::and_all(&[
-            schema::Schema::arbitrary_size_hint(depth)?,
-            HierarchyGenerator::size_hint(depth),
-            schema::Schema::arbitrary_request_size_hint(depth),
-            schema::Schema::arbitrary_request_size_hint(depth),
-            schema::Schema::arbitrary_request_size_hint(depth),
-            schema::Schema::arbitrary_request_size_hint(depth),
-            schema::Schema::arbitrary_request_size_hint(depth),
-            schema::Schema::arbitrary_request_size_hint(depth),
-            schema::Schema::arbitrary_request_size_hint(depth),
-            schema::Schema::arbitrary_request_size_hint(depth),
-        ]))
-    }
-}
-
-// Non-type-directed fuzzing of (strict) validation.
-fuzz_target!(|input: FuzzTargetInput| {
-    initialize_log();
-
-    // generate a schema
-    if let Ok(schema) = Schema::try_from(input.schema) {
-        let def_impl = CedarLeanEngine::new();
-        debug!("Schema: {:?}", schema);
-        let requests = input
-            .requests
-            .into_iter()
-            .map(Request::from)
-            .collect::<Vec<_>>();
-        for request in requests.iter().cloned() {
-            debug!("Request: {request}");
-            let (_, total_dur) =
-                time_function(|| run_req_val_test(&def_impl, schema.clone(), request));
-            info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
-        }
-    }
-});
+// Fuzzing of request validation.
+fuzz_target!(|input: request_validation::FuzzTargetInput| request_validation::fuzz_target(input));
@@ -0,0 +1,65 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![no_main]
+
+use cedar_drt_inner::{fuzz_target, roundtrip_entities};
+
+use cedar_policy::Entities;
+
+use cedar_policy_core::extensions::Extensions;
+
+use cedar_policy_generators::hierarchy::{
+    AttributesMode, HierarchyGenerator, HierarchyGeneratorMode, NumEntities,
+};
+use libfuzzer_sys::arbitrary::{self, Arbitrary, MaxRecursionReached, Unstructured};
+
+#[derive(Debug)]
+struct FuzzTargetInput {
+    pub entities: Entities,
+}
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let entities: Entities = HierarchyGenerator {
+            mode: HierarchyGeneratorMode::Arbitrary {
+                attributes_mode: AttributesMode::Arbitrary {
+                    max_attrs: 4,
+                    with_tags: true,
+                },
+            },
+            num_entities: NumEntities::RangePerEntityType(0..=4),
+            u,
+            extensions: Extensions::all_available(),
+        }
+        .generate()?
+        .try_into()
+        .expect("Should be able to get entities from hierarchy.");
+        Ok(Self { entities })
+    }
+
+    fn try_size_hint(
+        depth: usize,
+    ) -> std::result::Result<(usize, Option<usize>), MaxRecursionReached> {
+        Ok(HierarchyGenerator::size_hint(depth))
+    }
+}
+
+// Round trip entities with attributes and tags of mixed types, which don't
+// conform to any schema, through their JSON format
+fuzz_target!(|input: FuzzTargetInput| {
+    roundtrip_entities::fuzz_target(input.entities, None);
+});
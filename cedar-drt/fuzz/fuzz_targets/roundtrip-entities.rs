@@ -22,6 +22,7 @@ use cedar_policy::{Entities, Schema};
 
 use cedar_policy_generators::{hierarchy::HierarchyGenerator, schema, settings::ABACSettings};
 use libfuzzer_sys::arbitrary::{self, Arbitrary, MaxRecursionReached, Unstructured};
+use std::sync::LazyLock;
 
 #[derive(Debug)]
 struct FuzzTargetInput {
@@ -29,7 +30,7 @@ struct FuzzTargetInput {
     pub entities: Entities,
 }
 
-const SETTINGS: ABACSettings = ABACSettings {
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
     match_types: true,
     enable_extensions: true,
     max_depth: 10,
@@ -42,7 +43,8 @@ const SETTINGS: ABACSettings = ABACSettings {
     enable_action_in_constraints: true,
     per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
     total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
+    ..ABACSettings::default_fuzz()
+});
 
 impl<'a> Arbitrary<'a> for FuzzTargetInput {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -28,6 +28,7 @@ use cedar_policy_generators::{
 use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
 use log::debug;
 use std::sync::Arc;
+use std::sync::LazyLock;
 
 // A thin wrapper for policy
 #[derive(Debug, Clone)]
@@ -38,7 +39,7 @@ struct FuzzTargetInput {
 
 // settings for this fuzz target
 // copy-pasted from abac.rs
-const SETTINGS: ABACSettings = ABACSettings {
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
     match_types: false,
     enable_extensions: true,
     max_depth: 7,
@@ -51,7 +52,8 @@ const SETTINGS: ABACSettings = ABACSettings {
     enable_action_in_constraints: true,
     per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
     total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
+    ..ABACSettings::default_fuzz()
+});
 
 impl<'a> Arbitrary<'a> for FuzzTargetInput {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
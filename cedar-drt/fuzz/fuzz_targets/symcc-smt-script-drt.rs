@@ -19,7 +19,7 @@ use cedar_drt::{logger::initialize_log, CedarLeanEngine};
 
 use cedar_drt_inner::{
     fuzz_target,
-    symcc::{compile_policies, total_action_request_env_limit, BuffSolver},
+    symcc::{compile_policies, smtlib_of_check_asserts, total_action_request_env_limit},
 };
 
 use cedar_policy::{Policy, PolicySet, Schema};
@@ -31,17 +31,9 @@ use cedar_policy_generators::{
 use libfuzzer_sys::arbitrary::{self, Arbitrary, MaxRecursionReached, Unstructured};
 use log::debug;
 use std::convert::TryFrom;
-
-use cedar_policy_symcc::{compile_always_allows, CedarSymCompiler, SymEnv, WellFormedAsserts};
-
 use std::sync::LazyLock;
 
-static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
-    tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()
-        .unwrap()
-});
+use cedar_policy_symcc::{compile_always_allows, SymEnv};
 
 /// Input expected by this fuzz target
 #[derive(Debug, Clone)]
@@ -53,7 +45,7 @@ pub struct FuzzTargetInput {
 }
 
 /// settings for this fuzz target
-const SETTINGS: ABACSettings = ABACSettings {
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
     match_types: true,
     enable_extensions: true,
     max_depth: 3,
@@ -66,7 +58,8 @@ const SETTINGS: ABACSettings = ABACSettings {
     enable_action_in_constraints: true,
     per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
     total_action_request_env_limit: total_action_request_env_limit(),
-};
+    ..ABACSettings::default_fuzz()
+});
 
 impl<'a> Arbitrary<'a> for FuzzTargetInput {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -87,19 +80,6 @@ impl<'a> Arbitrary<'a> for FuzzTargetInput {
     }
 }
 
-fn smtlib_of_check_asserts(rust_asserts: &WellFormedAsserts<'_>) -> Result<String, String> {
-    Ok(RUNTIME.block_on(async {
-        let mut solver =
-            CedarSymCompiler::new(BuffSolver::new()).expect("solver construction should succeed");
-        match solver.check_sat(rust_asserts).await {
-            Ok(_) | Err(cedar_policy_symcc::err::Error::SolverUnknown) => {
-                Ok(solver.solver_mut().contents())
-            }
-            Err(e) => Err(e.to_string()),
-        }
-    })?)
-}
-
 // Fuzzing target checking that Term to SMT-LIB encodings generated by Lean and Rust SymCC implementations are equivalent
 fuzz_target!(|input: FuzzTargetInput| {
     initialize_log();
@@ -19,63 +19,125 @@ use cedar_drt::{logger::initialize_log, CedarLeanEngine};
 
 use cedar_drt_inner::{
     fuzz_target,
-    symcc::{compile_well_typed_policies, total_action_request_env_limit},
+    symcc::{
+        assert_asserts_eq, assert_smtlib_of_compiled_eq, compile_well_typed_policies,
+        compile_well_typed_policy, compile_well_typed_policy_pair, policy_set, FuzzTargetInput,
+        PolicySetCheck, PolicySetPairCheck, POLICY_SET_CHECKS, POLICY_SET_PAIR_CHECKS,
+    },
 };
 
-use cedar_policy::{Policy, PolicySet, Schema};
+use cedar_lean_ffi::CedarLeanFfi;
 
-use cedar_policy_generators::{
-    abac::ABACPolicy, hierarchy::HierarchyGenerator, schema, settings::ABACSettings,
-};
+use cedar_policy::{PolicySet, RequestEnv, Schema};
 
-use libfuzzer_sys::arbitrary::{self, Arbitrary, MaxRecursionReached, Unstructured};
 use log::debug;
-use std::{collections::BTreeSet, convert::TryFrom};
-
-use cedar_policy_symcc::{compile_always_allows, term::Term, WellTypedPolicies};
+use std::convert::TryFrom;
 
-/// Input expected by this fuzz target
-#[derive(Debug, Clone)]
-pub struct FuzzTargetInput {
-    /// generated schema
-    pub schema: schema::Schema,
-    /// generated policy
-    pub policy: ABACPolicy,
-}
-
-/// settings for this fuzz target
-const SETTINGS: ABACSettings = ABACSettings {
-    match_types: true,
-    enable_extensions: true,
-    max_depth: 3,
-    max_width: 3,
-    enable_additional_attributes: false,
-    enable_like: true,
-    enable_action_groups_and_attrs: true,
-    enable_arbitrary_func_call: true,
-    enable_unknowns: false,
-    enable_action_in_constraints: true,
-    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
-    total_action_request_env_limit: total_action_request_env_limit(),
-};
+use cedar_policy_symcc::{compile_never_errors, WellTypedPolicies, WellTypedPolicy};
 
-impl<'a> Arbitrary<'a> for FuzzTargetInput {
-    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
-        let schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
-        let hierarchy = schema.arbitrary_hierarchy(u)?;
-        let policy = schema.arbitrary_policy(&hierarchy, u)?;
+// For each check, we compare the asserts Lean and Rust generate for the
+// policies produced by the Rust validator, and the SMTLib scripts that Lean and
+// Rust produce from the Rust asserts.
 
-        Ok(Self { schema, policy })
+fn check_never_errors(
+    lean_ffi: &CedarLeanFfi,
+    policyset: &PolicySet,
+    schema: &Schema,
+    req_env: &RequestEnv,
+) {
+    for policy in policyset.policies() {
+        if let Ok(well_typed_policy) = WellTypedPolicy::from_policy(policy, req_env, schema) {
+            assert_asserts_eq(
+                "never-errors",
+                lean_ffi.asserts_of_check_never_errors_on_original(
+                    well_typed_policy.policy(),
+                    schema,
+                    req_env,
+                ),
+                compile_well_typed_policy(
+                    compile_never_errors,
+                    &well_typed_policy,
+                    schema,
+                    req_env,
+                ),
+            );
+            assert_smtlib_of_compiled_eq(
+                "never-errors",
+                lean_ffi,
+                |sym_env| compile_never_errors(&well_typed_policy, sym_env),
+                schema,
+                req_env,
+            );
+        }
     }
+}
 
-    fn try_size_hint(
-        depth: usize,
-    ) -> std::result::Result<(usize, Option<usize>), MaxRecursionReached> {
-        Ok(arbitrary::size_hint::and_all(&[
-            schema::Schema::arbitrary_size_hint(depth)?,
-            HierarchyGenerator::size_hint(depth),
-        ]))
-    }
+fn check_policy_set(
+    check: &PolicySetCheck,
+    lean_ffi: &CedarLeanFfi,
+    well_typed_policies: &WellTypedPolicies,
+    schema: &Schema,
+    req_env: &RequestEnv,
+) {
+    assert_asserts_eq(
+        check.name,
+        (check.lean_asserts_on_original)(
+            lean_ffi,
+            &well_typed_policies.policy_set().clone().try_into().unwrap(),
+            schema,
+            req_env,
+        ),
+        compile_well_typed_policies(check.rust, well_typed_policies, schema, req_env),
+    );
+    assert_smtlib_of_compiled_eq(
+        check.name,
+        lean_ffi,
+        |sym_env| (check.rust)(well_typed_policies, sym_env),
+        schema,
+        req_env,
+    );
+}
+
+fn check_policy_set_pair(
+    check: &PolicySetPairCheck,
+    lean_ffi: &CedarLeanFfi,
+    well_typed_policies1: &WellTypedPolicies,
+    well_typed_policies2: &WellTypedPolicies,
+    schema: &Schema,
+    req_env: &RequestEnv,
+) {
+    assert_asserts_eq(
+        check.name,
+        (check.lean_asserts_on_original)(
+            lean_ffi,
+            &well_typed_policies1
+                .policy_set()
+                .clone()
+                .try_into()
+                .unwrap(),
+            &well_typed_policies2
+                .policy_set()
+                .clone()
+                .try_into()
+                .unwrap(),
+            schema,
+            req_env,
+        ),
+        compile_well_typed_policy_pair(
+            check.rust,
+            well_typed_policies1,
+            well_typed_policies2,
+            schema,
+            req_env,
+        ),
+    );
+    assert_smtlib_of_compiled_eq(
+        check.name,
+        lean_ffi,
+        |sym_env| (check.rust)(well_typed_policies1, well_typed_policies2, sym_env),
+        schema,
+        req_env,
+    );
 }
 
 // Fuzzing target checking that terms generated by Lean and Rust SymCC implementations are equivalent
@@ -83,63 +145,40 @@ fuzz_target!(|input: FuzzTargetInput| {
     initialize_log();
     let len_engine = CedarLeanEngine::new();
     let lean_ffi = len_engine.get_ffi();
-    let mut policyset = PolicySet::new();
-    let policy: Policy = input.policy.into();
-    policyset.add(policy.clone()).unwrap();
+    let policyset1 = policy_set(input.policies1);
+    let policyset2 = policy_set(input.policies2);
     debug!("Schema: {}\n", input.schema.schemafile_string());
-    debug!("Policies: {policy}\n");
+    debug!("Policies 1: {policyset1}\n");
+    debug!("Policies 2: {policyset2}\n");
 
     if let Ok(schema) = Schema::try_from(input.schema) {
         for req_env in schema.request_envs() {
-            // The validator DRT property we've been testing is that
-            // rust_passes_validation => lean_passes_validation
-            // So, we run the Rust validator first and obtain a well-typed
-            // policy. This policy should be also well-typed according to Lean.
-            if let Ok(well_typed_policies) =
-                WellTypedPolicies::from_policies(&policyset, &req_env, &schema)
+            check_never_errors(lean_ffi, &policyset1, &schema, &req_env);
+            check_never_errors(lean_ffi, &policyset2, &schema, &req_env);
+
+            let well_typed_policies1 =
+                WellTypedPolicies::from_policies(&policyset1, &req_env, &schema);
+            let well_typed_policies2 =
+                WellTypedPolicies::from_policies(&policyset2, &req_env, &schema);
+            for well_typed_policies in [&well_typed_policies1, &well_typed_policies2] {
+                if let Ok(well_typed_policies) = well_typed_policies {
+                    for check in POLICY_SET_CHECKS.iter() {
+                        check_policy_set(check, lean_ffi, well_typed_policies, &schema, &req_env);
+                    }
+                }
+            }
+            if let (Ok(well_typed_policies1), Ok(well_typed_policies2)) =
+                (&well_typed_policies1, &well_typed_policies2)
             {
-                // We use `asserts_of_check_never_errors_on_original` instead
-                // of `asserts_of_check_never_errors` because there are
-                // discrepancies between the policies generated by Lean and Rust
-                // validators. And the enforcer is senstive to such
-                // discrepancies.
-                match (
-                    lean_ffi.asserts_of_check_always_allows_on_original(
-                        &well_typed_policies.policy_set().clone().try_into().unwrap(),
-                        &schema,
-                        &req_env,
-                    ),
-                    compile_well_typed_policies(
-                        compile_always_allows,
-                        &well_typed_policies,
+                for check in POLICY_SET_PAIR_CHECKS.iter() {
+                    check_policy_set_pair(
+                        check,
+                        lean_ffi,
+                        well_typed_policies1,
+                        well_typed_policies2,
                         &schema,
                         &req_env,
-                    ),
-                ) {
-                    (Ok(Ok(lean_asserts)), Ok(rust_asserts)) => {
-                        let lean_asserts = lean_asserts
-                            .into_iter()
-                            .map(|t| Term::try_from(t).expect("term conversion should succeed"))
-                            .collect::<BTreeSet<_>>();
-                        let rust_asserts =
-                            BTreeSet::from_iter(rust_asserts.as_ref().into_iter().cloned());
-                        similar_asserts::assert_eq!(
-                            lean_asserts,
-                            rust_asserts,
-                            "Lean terms: {lean_asserts:?}, Rust terms: {rust_asserts:?}"
-                        );
-                    }
-                    (Ok(Ok(lean_asserts)), Err(err)) => {
-                        debug!("Lean asserts: {lean_asserts:?}");
-                        panic!("Rust errors while Lean does not: {err}")
-                    }
-                    (Err(err), Ok(_)) => {
-                        panic!("Lean errors while Rust does not: {err}")
-                    }
-                    (Ok(Err(err)), Ok(_)) => {
-                        panic!("Lean errors while Rust does not: {err}")
-                    }
-                    (_, _) => {}
+                    );
                 }
             }
         }
@@ -0,0 +1,308 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+#![no_main]
+use cedar_drt::logger::initialize_log;
+
+use cedar_drt_inner::{
+    fuzz_target,
+    symcc::{
+        local_cvc5_with_timeout, policy_set, FuzzTargetInput, LeanCex, PolicySetCheck,
+        PolicySetPairCheck, POLICY_SET_CHECKS, POLICY_SET_PAIR_CHECKS,
+    },
+};
+
+use cedar_lean_ffi::{CedarLeanFfi, FfiError, SolverConfig, SolverKind};
+
+use cedar_policy::{Authorizer, Entities, PolicySet, Request, RequestEnv, Schema};
+
+use log::{debug, info};
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use cedar_policy_symcc::{
+    compile_never_errors,
+    err::{EncodeError, Error as SymCCError, Result as SymCCResult},
+    CedarSymCompiler, Env, SymEnv, WellFormedAsserts, WellTypedPolicies, WellTypedPolicy,
+};
+
+static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+});
+
+/// Number of checks skipped so far because a solver timed out or returned unknown
+static SKIPPED_CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Time both solvers are given for each check
+const SOLVER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Record that `check` was skipped
+fn skip(check: &str, reason: &str) {
+    let skipped = SKIPPED_CHECKS.fetch_add(1, Ordering::Relaxed) + 1;
+    info!("{check}: skipped ({reason}); {skipped} checks skipped so far");
+}
+
+/// Why a SymCC check failed, so that Lean and Rust only count as agreeing
+/// when both fail for the same reason
+#[derive(Debug, PartialEq, Eq)]
+enum FailureKind {
+    /// the SMT-LIB encoder does not support a string or pattern
+    Encode,
+    /// running the solver failed
+    Solver,
+    /// any other failure
+    Other,
+}
+
+impl FailureKind {
+    fn of_lean(err: &FfiError) -> Self {
+        match err {
+            // The Lean encoder and solver interface fail with `IO` errors
+            FfiError::LeanBackendError(msg) if msg.starts_with("IO error") => {
+                if msg.contains("not supported in SMT-LIB") {
+                    Self::Encode
+                } else {
+                    Self::Solver
+                }
+            }
+            _ => Self::Other,
+        }
+    }
+
+    fn of_rust(err: &SymCCError) -> Self {
+        match err {
+            SymCCError::EncodeError(
+                EncodeError::EncodeStringFailed(_) | EncodeError::EncodePatternFailed(_),
+            ) => Self::Encode,
+            err if format!("{err:?}").starts_with("Solver") => Self::Solver,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Run the Rust SymCC on the compiled `asserts` against a local CVC5 with the
+/// same per-query time limit as the Lean one
+fn rust_check_sat(asserts: SymCCResult<WellFormedAsserts<'_>>) -> SymCCResult<Option<Env>> {
+    let asserts = asserts?;
+    RUNTIME.block_on(async {
+        let mut solver = CedarSymCompiler::new(
+            local_cvc5_with_timeout(SOLVER_TIMEOUT).expect("CVC5 should exist"),
+        )
+        .expect("solver construction should succeed");
+        solver.check_sat(&asserts).await
+    })
+}
+
+/// Check that Lean and Rust agree on whether `check` holds, and that every
+/// counterexample they produce is a real one when replayed through the authorizer
+fn compare_verdicts(
+    check: &str,
+    lean_cex: LeanCex,
+    rust_cex: SymCCResult<Option<Env>>,
+    is_cex: impl Fn(&Request, &Entities) -> bool,
+) {
+    match (lean_cex, rust_cex) {
+        (_, Err(SymCCError::SolverUnknown)) => skip(check, "Rust solver timed out"),
+        (Err(FfiError::SolverUnknown(_)), _) => skip(check, "Lean solver timed out"),
+        // The Rust encoder does not support some strings and patterns
+        (Ok(_), Err(err)) if FailureKind::of_rust(&err) == FailureKind::Encode => {
+            skip(check, &format!("Rust encoder failed: {err}"))
+        }
+        (Ok(lean_cex), Ok(rust_cex)) => {
+            match (&lean_cex, &rust_cex) {
+                (Some(_), None) => {
+                    panic!("{check}: Lean finds a counterexample while Rust does not")
+                }
+                (None, Some(_)) => {
+                    panic!("{check}: Rust finds a counterexample while Lean does not")
+                }
+                _ => {}
+            }
+            if let Some(env) = lean_cex {
+                match (env.to_request(), env.to_entities()) {
+                    (Ok(request), Ok(entities)) => {
+                        assert!(
+                            is_cex(&request, &entities),
+                            "{check}: Lean SymCC produced a wrong counterexample:\nRequest: {request:?}\nEntities: {entities:?}"
+                        );
+                    }
+                    (Err(err), _) | (_, Err(err)) => {
+                        skip(check, &format!("cannot replay Lean counterexample: {err}"))
+                    }
+                }
+            }
+            if let Some(env) = rust_cex {
+                assert!(
+                    is_cex(&env.request, &env.entities),
+                    "{check}: Rust SymCC produced a wrong counterexample:\nRequest: {:?}\nEntities: {:?}",
+                    env.request,
+                    env.entities
+                );
+            }
+        }
+        (Err(err), Ok(_)) => panic!("{check}: Lean errors while Rust does not: {err}"),
+        (Ok(_), Err(err)) => panic!("{check}: Rust errors while Lean does not: {err}"),
+        (Err(lean_err), Err(rust_err)) => {
+            let (lean_kind, rust_kind) = (
+                FailureKind::of_lean(&lean_err),
+                FailureKind::of_rust(&rust_err),
+            );
+            assert!(
+                lean_kind == rust_kind && lean_kind != FailureKind::Other,
+                "{check}: Lean and Rust fail differently:\nLean: {lean_err}\nRust: {rust_err}"
+            );
+            skip(check, &format!("both fail ({lean_kind:?})"));
+        }
+    }
+}
+
+fn check_never_errors(
+    lean_ffi: &CedarLeanFfi,
+    policyset: &PolicySet,
+    schema: &Schema,
+    req_env: &RequestEnv,
+    sym_env: &SymEnv,
+) {
+    for policy in policyset.policies() {
+        if let Ok(well_typed_policy) = WellTypedPolicy::from_policy(policy, req_env, schema) {
+            let mut single = PolicySet::new();
+            single.add(policy.clone()).unwrap();
+            compare_verdicts(
+                "never-errors",
+                lean_ffi.run_check_never_errors_with_cex(policy, schema, req_env),
+                rust_check_sat(compile_never_errors(&well_typed_policy, sym_env)),
+                |request, entities| {
+                    Authorizer::new()
+                        .is_authorized(request, &single, entities)
+                        .diagnostics()
+                        .errors()
+                        .next()
+                        .is_some()
+                },
+            );
+        }
+    }
+}
+
+fn check_policy_set(
+    check: &PolicySetCheck,
+    lean_ffi: &CedarLeanFfi,
+    (policyset, well_typed_policies): (&PolicySet, &WellTypedPolicies),
+    schema: &Schema,
+    req_env: &RequestEnv,
+    sym_env: &SymEnv,
+) {
+    compare_verdicts(
+        check.name,
+        (check.lean_with_cex)(lean_ffi, policyset, schema, req_env),
+        rust_check_sat((check.rust)(well_typed_policies, sym_env)),
+        |request, entities| {
+            (check.is_cex)(&Authorizer::new().is_authorized(request, policyset, entities))
+        },
+    );
+}
+
+fn check_policy_set_pair(
+    check: &PolicySetPairCheck,
+    lean_ffi: &CedarLeanFfi,
+    (policyset1, well_typed_policies1): (&PolicySet, &WellTypedPolicies),
+    (policyset2, well_typed_policies2): (&PolicySet, &WellTypedPolicies),
+    schema: &Schema,
+    req_env: &RequestEnv,
+    sym_env: &SymEnv,
+) {
+    compare_verdicts(
+        check.name,
+        (check.lean_with_cex)(lean_ffi, policyset1, policyset2, schema, req_env),
+        rust_check_sat((check.rust)(
+            well_typed_policies1,
+            well_typed_policies2,
+            sym_env,
+        )),
+        |request, entities| {
+            let authorizer = Authorizer::new();
+            (check.is_cex)(
+                &authorizer.is_authorized(request, policyset1, entities),
+                &authorizer.is_authorized(request, policyset2, entities),
+            )
+        },
+    );
+}
+
+// Fuzzing target checking that Lean and Rust SymCC reach the same verdict on every check
+// when run against a local CVC5, and that their counterexamples are true counterexamples
+fuzz_target!(|input: FuzzTargetInput| {
+    initialize_log();
+    let lean_ffi = CedarLeanFfi::with_solver_config(
+        SolverConfig::new(SolverKind::Cvc5).with_timeout(SOLVER_TIMEOUT),
+    );
+    let policyset1 = policy_set(input.policies1);
+    let policyset2 = policy_set(input.policies2);
+    debug!("Schema: {}\n", input.schema.schemafile_string());
+    debug!("Policies 1: {policyset1}\n");
+    debug!("Policies 2: {policyset2}\n");
+
+    if let Ok(schema) = Schema::try_from(input.schema) {
+        for req_env in schema.request_envs() {
+            let Ok(sym_env) = SymEnv::new(&schema, &req_env) else {
+                continue;
+            };
+            check_never_errors(&lean_ffi, &policyset1, &schema, &req_env, &sym_env);
+            check_never_errors(&lean_ffi, &policyset2, &schema, &req_env, &sym_env);
+
+            let well_typed_policies1 =
+                WellTypedPolicies::from_policies(&policyset1, &req_env, &schema);
+            let well_typed_policies2 =
+                WellTypedPolicies::from_policies(&policyset2, &req_env, &schema);
+            for (policyset, well_typed_policies) in [
+                (&policyset1, &well_typed_policies1),
+                (&policyset2, &well_typed_policies2),
+            ] {
+                if let Ok(well_typed_policies) = well_typed_policies {
+                    for check in POLICY_SET_CHECKS.iter() {
+                        check_policy_set(
+                            check,
+                            &lean_ffi,
+                            (policyset, well_typed_policies),
+                            &schema,
+                            &req_env,
+                            &sym_env,
+                        );
+                    }
+                }
+            }
+            if let (Ok(well_typed_policies1), Ok(well_typed_policies2)) =
+                (&well_typed_policies1, &well_typed_policies2)
+            {
+                for check in POLICY_SET_PAIR_CHECKS.iter() {
+                    check_policy_set_pair(
+                        check,
+                        &lean_ffi,
+                        (&policyset1, well_typed_policies1),
+                        (&policyset2, well_typed_policies2),
+                        &schema,
+                        &req_env,
+                        &sym_env,
+                    );
+                }
+            }
+        }
+    }
+});
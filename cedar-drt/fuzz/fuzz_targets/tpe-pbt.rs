@@ -18,15 +18,11 @@
 use cedar_drt::logger::initialize_log;
 use cedar_drt_inner::fuzz_target;
 use cedar_policy_core::{
-    ast::{self, EntityUID, Expr, Request, RequestSchema, Value},
+    ast::{self, Expr, Request, RequestSchema},
     entities::Entities,
     evaluator::Evaluator,
     extensions::Extensions,
-    tpe::{
-        entities::{PartialEntities, PartialEntity},
-        is_authorized,
-        request::{PartialEntityUID, PartialRequest},
-    },
+    tpe::{entities::PartialEntities, is_authorized, request::PartialRequest},
     validator::{CoreSchema, ValidationMode, Validator, ValidatorSchema},
 };
 use cedar_policy_generators::{
@@ -37,8 +33,9 @@ use cedar_policy_generators::{
 };
 use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
 use log::debug;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::sync::LazyLock;
 
 /// Input expected by this fuzz target:
 /// An ABAC hierarchy, schema, and 8 associated policies
@@ -58,7 +55,7 @@ struct FuzzTargetInput {
 }
 
 /// settings for this fuzz target
-const SETTINGS: ABACSettings = ABACSettings {
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
     match_types: true,
     enable_extensions: true,
     max_depth: 7,
@@ -71,62 +68,27 @@ const SETTINGS: ABACSettings = ABACSettings {
     enable_action_in_constraints: true,
     per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
     total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
-};
-
-fn make_partial_request(
-    req: &ABACRequest,
-    u: &mut Unstructured<'_>,
-) -> arbitrary::Result<PartialRequest> {
-    Ok(PartialRequest::new_unchecked(
-        PartialEntityUID {
-            ty: req.principal.entity_type().clone(),
-            eid: if u.ratio(1, 4)? {
-                None
-            } else {
-                Some(req.principal.eid().clone())
-            },
-        },
-        PartialEntityUID {
-            ty: req.resource.entity_type().clone(),
-            eid: if u.ratio(1, 4)? {
-                None
-            } else {
-                Some(req.resource.eid().clone())
-            },
-        },
-        req.action.clone(),
-        None,
-    ))
-}
+    ..ABACSettings::default_fuzz()
+});
 
 impl<'a> Arbitrary<'a> for FuzzTargetInput {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
         let schema: Schema = Schema::arbitrary(SETTINGS.clone(), u)?;
         let hierarchy = schema.arbitrary_hierarchy(u)?;
         let policy = schema.arbitrary_policy(&hierarchy, u)?;
-        let requests = [
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-            schema.arbitrary_request(&hierarchy, u)?,
-        ];
-        let partial_requests = requests
-            .iter()
-            .map(|req| make_partial_request(req, u))
-            .collect::<arbitrary::Result<Vec<_>>>()?
-            .try_into()
-            .unwrap();
+        let (requests, partial_requests): (Vec<_>, Vec<_>) = (0..8)
+            .map(|_| schema.arbitrary_partial_request(&hierarchy, u))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        let partial_entities = hierarchy.arbitrary_partial_entities(u)?;
         Ok(Self {
             schema,
-            hierarchy: hierarchy.clone(),
+            hierarchy,
             policy,
-            requests,
-            partial_requests,
-            partial_entities: entities_to_partial_entities(hierarchy.entities(), u)?,
+            requests: requests.try_into().unwrap(),
+            partial_requests: partial_requests.try_into().unwrap(),
+            partial_entities,
         })
     }
 
@@ -137,14 +99,15 @@ impl<'a> Arbitrary<'a> for FuzzTargetInput {
             Schema::arbitrary_size_hint(depth)?,
             HierarchyGenerator::size_hint(depth),
             Schema::arbitrary_policy_size_hint(&SETTINGS, depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
-            Schema::arbitrary_request_size_hint(depth),
+            Schema::arbitrary_partial_request_size_hint(depth),
+            Schema::arbitrary_partial_request_size_hint(depth),
+            Schema::arbitrary_partial_request_size_hint(depth),
+            Schema::arbitrary_partial_request_size_hint(depth),
+            Schema::arbitrary_partial_request_size_hint(depth),
+            Schema::arbitrary_partial_request_size_hint(depth),
+            Schema::arbitrary_partial_request_size_hint(depth),
+            Schema::arbitrary_partial_request_size_hint(depth),
+            Hierarchy::arbitrary_partial_entities_size_hint(depth),
         ]))
     }
 }
@@ -163,59 +126,6 @@ fn passes_request_validation(schema: &ValidatorSchema, request: &ast::Request) -
         .is_ok()
 }
 
-fn entity_to_partial_entity(
-    entity: &ast::Entity,
-    u: &mut Unstructured<'_>,
-    leafs: &HashSet<EntityUID>,
-) -> arbitrary::Result<PartialEntity> {
-    Ok(PartialEntity {
-        uid: entity.uid().clone(),
-        attrs: if u.ratio(1, 4)? {
-            None
-        } else {
-            Some(BTreeMap::from_iter(entity.attrs().map(|(k, v)| {
-                (k.clone(), Value::try_from(v.clone()).unwrap())
-            })))
-        },
-        ancestors: if leafs.contains(entity.uid()) {
-            if u.ratio(1, 4)? {
-                None
-            } else {
-                Some(HashSet::from_iter(entity.ancestors().cloned()))
-            }
-        } else {
-            Some(HashSet::from_iter(entity.ancestors().cloned()))
-        },
-        tags: if u.ratio(1, 4)? {
-            None
-        } else {
-            Some(BTreeMap::from_iter(entity.tags().map(|(k, v)| {
-                (k.clone(), Value::try_from(v.clone()).unwrap())
-            })))
-        },
-    })
-}
-
-fn entities_to_partial_entities<'a>(
-    entities: impl Iterator<Item = &'a ast::Entity>,
-    u: &mut Unstructured<'_>,
-) -> arbitrary::Result<PartialEntities> {
-    let entities: HashSet<ast::Entity> = HashSet::from_iter(entities.cloned());
-    let mut leafs: HashSet<_> = entities.iter().map(|e| e.uid().clone()).collect();
-    for e in &entities {
-        for a in e.ancestors() {
-            leafs.remove(a);
-        }
-    }
-    Ok(PartialEntities::from_entities_unchecked(
-        entities
-            .iter()
-            .map(|e| Ok((e.uid().clone(), entity_to_partial_entity(e, u, &leafs)?)))
-            .collect::<arbitrary::Result<Vec<(ast::EntityUID, PartialEntity)>>>()?
-            .into_iter(),
-    ))
-}
-
 fn test_weak_equiv(residual: &Expr, e: &Expr, req: &Request, entities: &Entities) -> bool {
     let eval = Evaluator::new(req.clone(), entities, Extensions::all_available());
     let slots = HashMap::new();
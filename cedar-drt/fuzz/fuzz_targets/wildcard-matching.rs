@@ -16,14 +16,35 @@
 
 #![no_main]
 
+use cedar_drt::CedarLeanEngine;
 use cedar_drt_inner::fuzz_target;
 
-use cedar_policy_core::ast::{Pattern, PatternElem};
+use cedar_policy::{Context, Entities, EntityUid, Expression, Request};
+use cedar_policy_core::ast::{Expr, Pattern, PatternElem};
 use libfuzzer_sys::arbitrary::{self, Arbitrary, Result, Unstructured};
 use regex::{escape, Regex};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Environment variable that, if set, lets generated characters range over all
+/// code points (except surrogates) instead of just the Basic Multilingual Plane
+const WILDCARD_NON_BMP: &str = "WILDCARD_NON_BMP";
+
+/// Whether generated characters are restricted to the Basic Multilingual
+/// Plane, i.e., whether [`WILDCARD_NON_BMP`] is unset
+fn bmp_only() -> bool {
+    static BMP_ONLY: OnceLock<bool> = OnceLock::new();
+    *BMP_ONLY.get_or_init(|| std::env::var_os(WILDCARD_NON_BMP).is_none())
+}
+
+/// The Lean engine, created on first use and shared by all inputs
+fn lean_engine() -> &'static CedarLeanEngine {
+    static LEAN_ENGINE: OnceLock<CedarLeanEngine> = OnceLock::new();
+    LEAN_ENGINE.get_or_init(CedarLeanEngine::new)
+}
 
 /// Input expected by this fuzz target:
-/// A pattern and a string that matches it
+/// A pattern and a string that is likely to match it
 #[derive(Debug, Clone)]
 struct FuzzTargetInput {
     /// generated pattern
@@ -34,7 +55,7 @@ struct FuzzTargetInput {
 
 /// A wrapper struct for valid characters:
 /// A character `c` is valid if it satisfies two criteria:
-/// 1. c as u32 <= 0xffff (i.e., c is in the Basic Multilingual Plane.)
+/// 1. c as u32 <= 0xffff (i.e., c is in the Basic Multilingual Plane) unless `WILDCARD_NON_BMP` is set
 /// 2. char::from_u32(c as u32).is_some() (i.e., c is not a surrogate code point.)
 #[derive(Debug, Clone)]
 struct ValidChar(char);
@@ -49,11 +70,12 @@ impl Arbitrary<'_> for ValidChar {
     fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
         // High Surrogate: U+D800–U+DBFF
         // Low Surrogate: U+DC00–U+DFFF
-        // Pick a value beween 0 and 0x10000 minus the number of surrogate values
-        let mut v: u32 = u.int_in_range(0..=(0x10000 - 8 * 0x100))?;
-        if (0xD800..=0xDFFF).contains(&v) {
-            // shift v to range 0xE000 to 0xFFFF
-            v = 0xE000 + v - 0xD800;
+        // Pick a value between 0 and the largest code point minus the number of surrogate values
+        let max = if bmp_only() { 0xFFFF } else { char::MAX as u32 };
+        let mut v: u32 = u.int_in_range(0..=(max - 8 * 0x100))?;
+        if v >= 0xD800 {
+            // shift v past the surrogates
+            v += 8 * 0x100;
         }
         Ok(Self(std::char::from_u32(v).expect("valid char!")))
     }
@@ -69,6 +91,8 @@ impl Arbitrary<'_> for ValidChar {
 #[derive(arbitrary::Arbitrary, Debug, Clone)]
 enum PatternElemWithValidChar {
     Char(ValidChar),
+    // A literal `*`, which has to be escaped in the pattern
+    EscapedStar,
     Wildcard,
 }
 
@@ -76,6 +100,7 @@ impl From<PatternElemWithValidChar> for PatternElem {
     fn from(pevc: PatternElemWithValidChar) -> Self {
         match pevc {
             PatternElemWithValidChar::Char(vc) => Self::Char(vc.0),
+            PatternElemWithValidChar::EscapedStar => Self::Char('*'),
             PatternElemWithValidChar::Wildcard => Self::Wildcard,
         }
     }
@@ -145,13 +170,34 @@ fn wildcard_match_regex<'a>(
         .is_match(text)
 }
 
+/// Evaluate `text like pattern` with the Lean definition in `Cedar/Spec/Wildcard.lean`
+fn wildcard_match_lean(lean_engine: &CedarLeanEngine, text: &str, pattern: &Pattern) -> bool {
+    let expr = Expression::from_str(&format!("{} like \"{pattern}\"", Expr::val(text)))
+        .expect("`like` expression should parse");
+    let uid = EntityUid::from_str(r#"User::"alice""#).unwrap();
+    let request = Request::new(uid.clone(), uid.clone(), uid, Context::empty(), None).unwrap();
+    lean_engine
+        .get_ffi()
+        .check_evaluate(
+            &expr,
+            &Entities::empty(),
+            &request,
+            Some(&Expression::new_bool(true)),
+        )
+        .expect("Lean evaluation should succeed")
+}
+
 fuzz_target!(|input: FuzzTargetInput| {
-    // Ensure wildcard matching is equivalent to the regex version
+    // Ensure wildcard matching is equivalent to the regex version and to the Lean spec
     let regex_result = wildcard_match_regex(&input.string, &input.pattern);
-    let rust_result = Pattern::from(input.pattern).wildcard_match(&input.string);
-    assert_eq!(
-        regex_result, rust_result,
-        "\nregex result: {};rust result:{}.\n",
-        regex_result, rust_result
+    let pattern = Pattern::from(input.pattern);
+    let rust_result = pattern.wildcard_match(&input.string);
+    let lean_result = wildcard_match_lean(lean_engine(), &input.string, &pattern);
+    assert!(
+        regex_result == rust_result && rust_result == lean_result,
+        "\nregex result: {};rust result:{};lean result:{}.\n",
+        regex_result,
+        rust_result,
+        lean_result
     );
 });
@@ -0,0 +1,179 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cedar_drt::{
+    dump::dump,
+    external_engine::DrtEngine,
+    logger::{initialize_log, record_timing, TimingEvent, DRT_ENGINE, TOTAL, TOTAL_MSG},
+    metrics,
+    shrink::{dump_shrunk_auth_test, AuthTestCase},
+    tests::check_auth_test,
+};
+
+use crate::schemas;
+
+use cedar_policy::{Authorizer, Entities, PolicySet, Request, SchemaFragment};
+
+use cedar_policy_generators::{
+    abac::ABACRequest, err::Error, hierarchy::HierarchyGenerator, policy_set::GeneratedPolicySet,
+    schema::Schema, settings::ABACSettings,
+};
+
+use cedar_testing::cedar_test_impl::time_function;
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
+use log::{debug, info};
+use std::convert::TryFrom;
+use std::sync::LazyLock;
+
+/// Input expected by this fuzz target:
+/// An ABAC hierarchy, policy set, and 8 associated requests
+#[derive(Debug, Clone)]
+pub struct FuzzTargetInput {
+    /// generated schema
+    pub schema: Schema,
+    /// generated hierarchy
+    pub entities: Entities,
+    /// generated policies, templates and template links
+    pub policies: GeneratedPolicySet,
+    /// the requests to try for this hierarchy and policy set. We try 8
+    /// requests per policy set/hierarchy
+    pub requests: [ABACRequest; 8],
+}
+
+/// settings for this fuzz target
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
+    match_types: false,
+    enable_extensions: true,
+    max_depth: 3,
+    max_width: 7,
+    enable_additional_attributes: false,
+    enable_like: true,
+    // ABAC fuzzing restricts the use of action because it is used to generate
+    // the corpus tests which will be run on Cedar and CedarCLI.
+    // These packages only expose the restricted action behavior.
+    enable_action_groups_and_attrs: false,
+    enable_arbitrary_func_call: true,
+    enable_unknowns: false,
+    enable_action_in_constraints: true,
+    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+    ..ABACSettings::default_fuzz()
+});
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let schema = Schema::arbitrary(SETTINGS.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let policies = GeneratedPolicySet::arbitrary_for_hierarchy(&schema, &hierarchy, u)?;
+        let requests = [
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+        ];
+
+        let cedar_schema = cedar_policy::Schema::try_from(schema.clone()).unwrap();
+        let entities = Entities::try_from(hierarchy).map_err(|_| Error::NotEnoughData)?;
+        let entities = schemas::add_actions_to_entities(&cedar_schema, entities)?;
+
+        Ok(Self {
+            schema,
+            entities,
+            policies,
+            requests,
+        })
+    }
+
+    fn try_size_hint(
+        depth: usize,
+    ) -> arbitrary::Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
+        Ok(arbitrary::size_hint::and_all(&[
+            Schema::arbitrary_size_hint(depth)?,
+            HierarchyGenerator::size_hint(depth),
+            GeneratedPolicySet::arbitrary_for_hierarchy_size_hint(depth),
+            Schema::arbitrary_request_size_hint(depth),
+            Schema::arbitrary_request_size_hint(depth),
+            Schema::arbitrary_request_size_hint(depth),
+            Schema::arbitrary_request_size_hint(depth),
+            Schema::arbitrary_request_size_hint(depth),
+            Schema::arbitrary_request_size_hint(depth),
+            Schema::arbitrary_request_size_hint(depth),
+            Schema::arbitrary_request_size_hint(depth),
+        ]))
+    }
+}
+
+pub fn fuzz_target(input: FuzzTargetInput) {
+    initialize_log();
+    metrics::record_input("abac");
+    let policyset = PolicySet::try_from(input.policies).unwrap();
+    debug!("Policies: {policyset}");
+    debug!("Entities: {}", input.entities.as_ref());
+    let requests = input
+        .requests
+        .into_iter()
+        .map(Request::from)
+        .collect::<Vec<_>>();
+
+    let engine = DrtEngine::from_env();
+
+    for request in requests.iter().cloned() {
+        debug!("Request: {request}");
+        let (result, total_dur) =
+            time_function(|| check_auth_test(&engine, &request, &policyset, &input.entities));
+        info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
+        record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, total_dur));
+        match result {
+            Ok(response) => metrics::record_authorization(&response),
+            Err(msg) => {
+                let case = AuthTestCase {
+                    schema: SchemaFragment::try_from(input.schema.clone()).unwrap(),
+                    policies: policyset.clone(),
+                    entities: input.entities.clone(),
+                    request,
+                };
+                dump_shrunk_auth_test(&engine, &case);
+                panic!("{msg}");
+            }
+        }
+    }
+    if let Ok(test_name) = std::env::var("DUMP_TEST_NAME") {
+        // `dump` maps the policy ids in the responses to the ids the policies
+        // get when the corpus is re-parsed
+        let responses = requests
+            .iter()
+            .map(|request| {
+                let authorizer = Authorizer::new();
+                authorizer.is_authorized(request, &policyset, &input.entities)
+            })
+            .collect::<Vec<_>>();
+        let dump_dir = std::env::var("DUMP_TEST_DIR").unwrap_or_else(|_| ".".to_string());
+        dump(
+            dump_dir,
+            &test_name,
+            &SchemaFragment::try_from(input.schema).unwrap(),
+            &policyset,
+            &input.entities,
+            std::iter::zip(requests, responses),
+        )
+        .expect("failed to dump test case");
+    }
+}
@@ -0,0 +1,294 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Soundness of level validation with respect to entity slicing: if a policy
+//! set validates at level `n`, then authorizing with the entities sliced to
+//! depth `n` from the request gives the same response as with all entities.
+//! This is the theorem `validateWithLevel_is_sound` of the Lean formalization,
+//! checked here on both the Rust and the Lean implementations.
+
+use cedar_drt::{
+    logger::{initialize_log, record_timing, TimingEvent, DRT_ENGINE, TOTAL, TOTAL_MSG},
+    metrics, CedarLeanEngine,
+};
+
+use cedar_policy::{
+    Authorizer, Entities, EntityUid, Policy, PolicySet, Request, Response, Schema, ValidationMode,
+    Validator,
+};
+use cedar_policy_core::ast::{self, PartialValue, Value, ValueKind};
+use cedar_policy_core::entities::Dereference;
+use cedar_testing::cedar_test_impl::{time_function, CedarTestImplementation, TestResult};
+
+use cedar_policy_generators::{
+    abac::{ABACPolicy, ABACRequest},
+    hierarchy::{Hierarchy, HierarchyGenerator},
+    schema,
+    settings::ABACSettings,
+    size_hint_utils::size_hint_for_range,
+};
+use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
+use log::{debug, info};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::LazyLock;
+
+/// Input expected by this fuzz target
+#[derive(Debug, Clone)]
+pub struct FuzzTargetInput {
+    /// generated schema
+    pub schema: schema::Schema,
+    /// generated hierarchy
+    pub hierarchy: Hierarchy,
+    /// the policy which we will see if it validates at `level`
+    pub policy: ABACPolicy,
+    /// the requests to try, if the policy validates
+    pub requests: [ABACRequest; 8],
+    /// level to validate the policy at and to slice the entities to
+    pub level: usize,
+}
+
+/// settings for this fuzz target
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
+    match_types: true,
+    enable_extensions: true,
+    max_depth: 7,
+    max_width: 7,
+    enable_additional_attributes: true,
+    enable_like: true,
+    enable_action_groups_and_attrs: true,
+    enable_arbitrary_func_call: true,
+    enable_unknowns: false,
+    enable_action_in_constraints: true,
+    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+    ..ABACSettings::default_fuzz()
+});
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let schema: schema::Schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let policy = schema.arbitrary_policy(&hierarchy, u)?;
+        let requests = [
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+        ];
+        let level = u.int_in_range(0..=SETTINGS.max_depth + 1)?;
+        Ok(Self {
+            schema,
+            hierarchy,
+            policy,
+            requests,
+            level,
+        })
+    }
+
+    fn try_size_hint(
+        depth: usize,
+    ) -> arbitrary::Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
+        Ok(arbitrary::size_hint::and_all(&[
+            schema::Schema::arbitrary_size_hint(depth)?,
+            HierarchyGenerator::size_hint(depth),
+            schema::Schema::arbitrary_policy_size_hint(&SETTINGS, depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            size_hint_for_range(0, SETTINGS.max_depth + 1),
+        ]))
+    }
+}
+
+/// Add the entities directly referenced by `v` to `uids`. Like
+/// `Value.sliceEUIDs` in the Lean formalization, this looks inside records
+/// but not inside sets.
+fn value_slice_euids(v: &Value, uids: &mut HashSet<ast::EntityUID>) {
+    match v.value_kind() {
+        ValueKind::Lit(ast::Literal::EntityUID(uid)) => {
+            uids.insert(uid.as_ref().clone());
+        }
+        ValueKind::Record(attrs) => {
+            for v in attrs.values() {
+                value_slice_euids(v, uids);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn partial_value_slice_euids(v: &PartialValue, uids: &mut HashSet<ast::EntityUID>) {
+    if let PartialValue::Value(v) = v {
+        value_slice_euids(v, uids);
+    }
+}
+
+/// The entities reachable from `request` in at most `level` steps, where each
+/// step follows the entities referenced by the attributes and tags of the
+/// entities reached so far (`Entities.sliceAtLevel` in the Lean
+/// formalization). Unlike the Lean definition, which fails if the request
+/// references a missing entity, missing entities are left out of the slice.
+pub fn slice_entities_at_level(entities: &Entities, request: &Request, level: usize) -> Entities {
+    let request = request.as_ref();
+    let mut work: HashSet<ast::EntityUID> =
+        [request.principal(), request.action(), request.resource()]
+            .into_iter()
+            .filter_map(|var| var.uid().cloned())
+            .collect();
+    if let Some(context) = request.context() {
+        partial_value_slice_euids(&PartialValue::from(context.clone()), &mut work);
+    }
+
+    let mut slice = HashSet::new();
+    for _ in 0..level {
+        let mut next = HashSet::new();
+        for uid in work.iter() {
+            if let Dereference::Data(entity) = entities.as_ref().entity(uid) {
+                for (_, v) in entity.attrs().chain(entity.tags()) {
+                    partial_value_slice_euids(v, &mut next);
+                }
+            }
+        }
+        slice.extend(work);
+        work = next;
+    }
+
+    Entities::from_entities(
+        slice
+            .into_iter()
+            .filter_map(|uid| entities.get(&EntityUid::from(uid)).cloned()),
+        None,
+    )
+    .expect("a subset of valid entities should be valid")
+}
+
+/// Check that `res_slice` and `res` are the same response
+fn check_same_responses(engine: &str, res: &Response, res_slice: &Response) {
+    let ids = |res: &Response| {
+        (
+            res.diagnostics()
+                .reason()
+                .map(ToString::to_string)
+                .collect::<BTreeSet<_>>(),
+            res.diagnostics()
+                .errors()
+                .map(|err| err.policy_id().to_string())
+                .collect::<BTreeSet<_>>(),
+        )
+    };
+    assert_eq!(
+        (res.decision(), ids(res)),
+        (res_slice.decision(), ids(res_slice)),
+        "{engine} authorization response differed with and without entity slicing at the validated level"
+    );
+}
+
+pub fn fuzz_target(input: FuzzTargetInput) {
+    initialize_log();
+    metrics::record_input("level-validation-slicing");
+    let lean_engine = CedarLeanEngine::new();
+
+    let Ok(schema) = Schema::try_from(input.schema) else {
+        return;
+    };
+    // The property only holds for entities and requests that validate
+    let Ok(entities) = Entities::try_from(input.hierarchy) else {
+        return;
+    };
+    let Ok(entities) = Entities::from_entities(entities.iter().cloned(), Some(&schema)) else {
+        return;
+    };
+    let requests: Vec<Request> = input
+        .requests
+        .into_iter()
+        .map(Request::from)
+        .filter(|request| {
+            Request::new(
+                request.principal().unwrap().clone(),
+                request.action().unwrap().clone(),
+                request.resource().unwrap().clone(),
+                request.context().unwrap().clone(),
+                Some(&schema),
+            )
+            .is_ok()
+        })
+        .collect();
+
+    let mut policyset = PolicySet::new();
+    policyset.add(Policy::from(input.policy)).unwrap();
+    let level = input.level;
+    debug!("Schema: {schema:?}");
+    debug!("Policies: {policyset}");
+    debug!("Entities: {}", entities.as_ref());
+    debug!("Level: {level}");
+
+    let (_, total_dur) = time_function(|| {
+        let rust_passed = Validator::new(schema.clone())
+            .validate_with_level(&policyset, ValidationMode::Strict, level as u32)
+            .validation_passed();
+        let lean_passed = match lean_engine.validate_with_level(
+            &schema,
+            &policyset,
+            ValidationMode::Strict,
+            level as i32,
+        ) {
+            TestResult::Success(res) => res.validation_passed(),
+            TestResult::Failure(_) => false,
+        };
+        if !rust_passed && !lean_passed {
+            return;
+        }
+
+        let authorizer = Authorizer::new();
+        for request in requests.iter() {
+            let entity_slice = slice_entities_at_level(&entities, request, level);
+            debug!("Request: {request}");
+            debug!("Entity slice: {}", entity_slice.as_ref());
+            if rust_passed {
+                check_same_responses(
+                    "Rust",
+                    &authorizer.is_authorized(request, &policyset, &entities),
+                    &authorizer.is_authorized(request, &policyset, &entity_slice),
+                );
+            }
+            if lean_passed {
+                match (
+                    lean_engine.is_authorized(request, &policyset, &entities),
+                    lean_engine.is_authorized(request, &policyset, &entity_slice),
+                ) {
+                    (TestResult::Success(res), TestResult::Success(res_slice)) => assert_eq!(
+                        res.response, res_slice.response,
+                        "Lean authorization response differed with and without entity slicing at the validated level"
+                    ),
+                    (TestResult::Failure(err), _) | (_, TestResult::Failure(err)) => {
+                        panic!("Lean authorization failed: {err}")
+                    }
+                }
+            }
+        }
+    });
+    info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
+    record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, total_dur));
+}
@@ -18,7 +18,17 @@ mod prt;
 #[cfg(not(feature = "prt"))]
 pub use libfuzzer_sys::fuzz_target;
 
+pub mod abac;
+pub mod abac_type_directed;
+pub mod entity_validation;
+pub mod eval_type_directed;
+pub mod level_slicing;
+pub mod multi_namespace;
+pub mod protobuf_roundtrip;
+pub mod request_validation;
 pub mod roundtrip_entities;
 pub mod schemas;
+pub mod solver_guided;
 pub mod symcc;
 pub mod validation_drt;
+pub mod validation_mutation;
@@ -0,0 +1,134 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Validation and authorization DRT on schemas spanning several namespaces,
+//! whose declarations refer to each other with unqualified, qualified and
+//! `__cedar`-qualified names.
+
+use cedar_drt::{
+    external_engine::DrtEngine,
+    logger::{initialize_log, record_timing, TimingEvent, DRT_ENGINE, TOTAL, TOTAL_MSG},
+    metrics,
+    tests::{run_auth_test, run_val_test},
+};
+
+use cedar_policy::{Entities, Policy, PolicySet, Request, Schema, ValidationMode};
+
+use cedar_policy_generators::{
+    abac::{ABACPolicy, ABACRequest},
+    hierarchy::{Hierarchy, HierarchyGenerator},
+    schema,
+    settings::ABACSettings,
+};
+use cedar_testing::cedar_test_impl::time_function;
+use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
+use log::{debug, info};
+use std::sync::LazyLock;
+
+/// Input expected by this fuzz target
+#[derive(Debug, Clone)]
+pub struct FuzzTargetInput {
+    /// generated schema, spanning several namespaces
+    pub schema: schema::Schema,
+    /// generated hierarchy
+    pub hierarchy: Hierarchy,
+    /// generated policy
+    pub policy: ABACPolicy,
+    /// the requests to try for this hierarchy and policy
+    pub requests: [ABACRequest; 4],
+}
+
+/// settings for this fuzz target
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
+    match_types: true,
+    enable_extensions: true,
+    max_depth: 5,
+    max_width: 5,
+    enable_additional_attributes: true,
+    enable_like: true,
+    enable_action_groups_and_attrs: true,
+    enable_arbitrary_func_call: true,
+    enable_unknowns: false,
+    enable_action_in_constraints: true,
+    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+    ..ABACSettings::default_fuzz()
+});
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let schema = schema::Schema::arbitrary_multi_namespace(SETTINGS.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let policy = schema.arbitrary_policy(&hierarchy, u)?;
+        let requests = [
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+        ];
+        Ok(Self {
+            schema,
+            hierarchy,
+            policy,
+            requests,
+        })
+    }
+
+    fn try_size_hint(
+        depth: usize,
+    ) -> arbitrary::Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
+        Ok(arbitrary::size_hint::and_all(&[
+            schema::Schema::arbitrary_size_hint(depth)?,
+            HierarchyGenerator::size_hint(depth),
+            schema::Schema::arbitrary_policy_size_hint(&SETTINGS, depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+        ]))
+    }
+}
+
+pub fn fuzz_target(input: FuzzTargetInput) {
+    initialize_log();
+    metrics::record_input("multi-namespace-drt");
+    let engine = DrtEngine::from_env();
+    debug!("Schema: {}\n", input.schema.schemafile_string());
+
+    let Ok(schema) = Schema::try_from(input.schema) else {
+        return;
+    };
+    let Ok(entities) = Entities::try_from(input.hierarchy) else {
+        return;
+    };
+    let mut policyset = PolicySet::new();
+    policyset.add(Policy::from(input.policy)).unwrap();
+    debug!("Policies: {policyset}\n");
+    debug!("Entities: {}\n", entities.as_ref());
+
+    let (_, total_dur) =
+        time_function(|| run_val_test(&engine, schema, &policyset, ValidationMode::Strict));
+    info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
+    record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, total_dur));
+
+    for request in input.requests.into_iter().map(Request::from) {
+        debug!("Request : {request}");
+        let (_, total_dur) =
+            time_function(|| run_auth_test(&engine, &request, &policyset, &entities));
+        info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
+        record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, total_dur));
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The input shared by the `protobuf-roundtrip` and `protobuf-lean-roundtrip`
+//! targets, so that the Rust and Lean protobuf decoders are fuzzed on the same
+//! distribution of values
+
+use cedar_policy::{Entities, Entity, Schema};
+use cedar_policy_generators::{
+    abac::ABACPolicy, abac::ABACRequest, hierarchy::HierarchyGenerator, schema,
+    settings::ABACSettings,
+};
+use libfuzzer_sys::arbitrary::{self, Arbitrary, MaxRecursionReached, Unstructured};
+use std::sync::LazyLock;
+
+/// Input expected by the protobuf round trip targets: a policy, request,
+/// entities and schema to encode as protobuf
+#[derive(Debug, Clone)]
+pub struct FuzzTargetInput {
+    /// generated request
+    pub request: ABACRequest,
+    /// generated policy
+    pub policy: ABACPolicy,
+    /// generated entities
+    pub entities: Entities,
+    /// generated schema
+    pub schema: Schema,
+}
+
+// settings for the protobuf round trip targets
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
+    match_types: false,
+    enable_extensions: true,
+    max_depth: 7,
+    max_width: 7,
+    enable_additional_attributes: false,
+    enable_like: true,
+    enable_action_groups_and_attrs: true,
+    enable_arbitrary_func_call: false,
+    enable_unknowns: false,
+    enable_action_in_constraints: true,
+    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+    ..ABACSettings::default_fuzz()
+});
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let schema: schema::Schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let request = schema.arbitrary_request(&hierarchy, u)?;
+        let policy = schema.arbitrary_policy(&hierarchy, u)?;
+
+        let entities = Entities::from_entities(
+            hierarchy.entities().map(|x| Entity::from(x.to_owned())),
+            None,
+        )
+        .expect("Failed to create entities");
+
+        let schema = schema
+            .try_into()
+            .expect("Failed to convert schema to ValidatorSchema");
+
+        Ok(Self {
+            request,
+            policy,
+            entities,
+            schema,
+        })
+    }
+
+    fn try_size_hint(depth: usize) -> Result<(usize, Option<usize>), MaxRecursionReached> {
+        Ok(arbitrary::size_hint::and_all(&[
+            schema::Schema::arbitrary_size_hint(depth)?,
+            HierarchyGenerator::size_hint(depth),
+            schema::Schema::arbitrary_policy_size_hint(&SETTINGS, depth),
+        ]))
+    }
+}
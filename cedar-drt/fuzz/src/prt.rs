@@ -119,6 +119,8 @@ macro_rules! fuzz_target {
                     );
                 });
             });
+            // write the final generator metrics (if the `metrics` feature is enabled)
+            ::cedar_drt::metrics::dump_metrics(env!("CARGO_BIN_NAME"));
         }
     };
 
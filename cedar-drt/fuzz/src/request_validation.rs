@@ -0,0 +1,135 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cedar_drt::{
+    dump::dump_request_validation,
+    external_engine::DrtEngine,
+    logger::{initialize_log, record_timing, TimingEvent, DRT_ENGINE, TOTAL, TOTAL_MSG},
+    metrics,
+    tests::run_req_val_test,
+};
+
+use cedar_policy::{Request, Schema, SchemaFragment};
+use cedar_testing::cedar_test_impl::time_function;
+
+use cedar_policy_generators::{
+    abac::ABACRequest, hierarchy::Hierarchy, hierarchy::HierarchyGenerator, schema,
+    settings::ABACSettings,
+};
+use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
+use log::{debug, info};
+use std::sync::LazyLock;
+
+/// Input expected by this fuzz target
+#[derive(Debug, Clone)]
+pub struct FuzzTargetInput {
+    /// generated schema
+    pub schema: schema::Schema,
+    /// generated hierarchy
+    pub hierarchy: Hierarchy,
+    /// the requests to try for this schema and hierarchy. We try 8 requests per
+    /// schema/hierarchy
+    pub requests: [ABACRequest; 8],
+}
+
+/// settings for this fuzz target
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
+    match_types: false,
+    enable_extensions: true,
+    max_depth: 7,
+    max_width: 7,
+    enable_additional_attributes: true,
+    enable_like: true,
+    enable_action_groups_and_attrs: true,
+    enable_arbitrary_func_call: true,
+    enable_unknowns: false,
+    enable_action_in_constraints: true,
+    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+    ..ABACSettings::default_fuzz()
+});
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let schema: schema::Schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let requests = [
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+            schema.arbitrary_request(&hierarchy, u)?,
+        ];
+        Ok(Self {
+            schema,
+            hierarchy,
+            requests,
+        })
+    }
+
+    fn try_size_hint(
+        depth: usize,
+    ) -> arbitrary::Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
+        Ok(arbitrary::size_hint::and_all(&[
+            schema::Schema::arbitrary_size_hint(depth)?,
+            HierarchyGenerator::size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+            schema::Schema::arbitrary_request_size_hint(depth),
+        ]))
+    }
+}
+
+pub fn fuzz_target(input: FuzzTargetInput) {
+    initialize_log();
+    metrics::record_input("request-validation");
+
+    let dump_test_name = std::env::var("DUMP_TEST_NAME").ok();
+    let schema_fragment = dump_test_name
+        .as_ref()
+        .and_then(|_| SchemaFragment::try_from(input.schema.clone()).ok());
+
+    // generate a schema
+    if let Ok(schema) = Schema::try_from(input.schema) {
+        let def_impl = DrtEngine::from_env();
+        debug!("Schema: {:?}", schema);
+        let requests = input
+            .requests
+            .into_iter()
+            .map(Request::from)
+            .collect::<Vec<_>>();
+        for request in requests.iter().cloned() {
+            debug!("Request: {request}");
+            let (_, total_dur) =
+                time_function(|| run_req_val_test(&def_impl, schema.clone(), request));
+            info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
+            record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, total_dur));
+        }
+        if let (Some(test_name), Some(schema)) = (dump_test_name, schema_fragment) {
+            let dump_dir = std::env::var("DUMP_TEST_DIR").unwrap_or_else(|_| ".".to_string());
+            dump_request_validation(dump_dir, &test_name, &schema, requests)
+                .expect("failed to dump test case");
+        }
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Authorization DRT on requests and entities that the Lean SymCC synthesizes
+//! to satisfy, or just miss, the condition of each policy, so that policy
+//! bodies are fully evaluated and decisions sit on the boundaries between
+//! policies.
+
+use crate::symcc::{
+    arbitrary_policies, arbitrary_policies_size_hint, policy_set, solver_guided_envs, SETTINGS,
+};
+
+use cedar_drt::{
+    external_engine::DrtEngine,
+    logger::{initialize_log, record_timing, TimingEvent, DRT_ENGINE, TOTAL, TOTAL_MSG},
+    metrics,
+    tests::run_auth_test,
+};
+
+use cedar_lean_ffi::{CedarLeanFfi, SolverConfig, SolverKind};
+
+use cedar_policy::{RequestEnv, Schema, ValidationMode, Validator};
+
+use cedar_policy_generators::{abac::ABACPolicy, hierarchy::HierarchyGenerator, schema};
+
+use cedar_testing::cedar_test_impl::time_function;
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary, MaxRecursionReached, Unstructured};
+use log::{debug, info};
+use std::time::Duration;
+
+/// Input expected by this fuzz target
+#[derive(Debug, Clone)]
+pub struct FuzzTargetInput {
+    /// generated schema
+    pub schema: schema::Schema,
+    /// generated policies
+    pub policies: Vec<ABACPolicy>,
+    /// the request environments in which the solver looks for requests, for
+    /// each policy
+    pub req_envs: Vec<Vec<RequestEnv>>,
+}
+
+/// Maximum number of request environments sampled for each policy. Each one
+/// costs two solver calls.
+const MAX_REQUEST_ENVS_PER_POLICY: u32 = 2;
+
+/// Time the solver is given for each check
+const SOLVER_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Sample up to [`MAX_REQUEST_ENVS_PER_POLICY`] of the request environments
+/// of `schema`
+fn arbitrary_req_envs(
+    req_envs: &[RequestEnv],
+    u: &mut Unstructured<'_>,
+) -> arbitrary::Result<Vec<RequestEnv>> {
+    let mut sampled = Vec::new();
+    if !req_envs.is_empty() {
+        u.arbitrary_loop(Some(1), Some(MAX_REQUEST_ENVS_PER_POLICY), |u| {
+            sampled.push(u.choose(req_envs)?.clone());
+            Ok(std::ops::ControlFlow::Continue(()))
+        })?;
+    }
+    Ok(sampled)
+}
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let policies = arbitrary_policies(&schema, &hierarchy, u)?;
+        let all_req_envs: Vec<RequestEnv> = Schema::try_from(schema.clone())
+            .map(|schema| schema.request_envs().collect())
+            .unwrap_or_default();
+        let req_envs = policies
+            .iter()
+            .map(|_| arbitrary_req_envs(&all_req_envs, u))
+            .collect::<arbitrary::Result<_>>()?;
+        Ok(Self {
+            schema,
+            policies,
+            req_envs,
+        })
+    }
+
+    fn try_size_hint(
+        depth: usize,
+    ) -> std::result::Result<(usize, Option<usize>), MaxRecursionReached> {
+        Ok(arbitrary::size_hint::and_all(&[
+            schema::Schema::arbitrary_size_hint(depth)?,
+            HierarchyGenerator::size_hint(depth),
+            arbitrary_policies_size_hint(&SETTINGS, depth),
+        ]))
+    }
+}
+
+pub fn fuzz_target(input: FuzzTargetInput) {
+    initialize_log();
+    metrics::record_input("abac-solver-guided");
+    let def_impl = DrtEngine::from_env();
+    let Ok(schema) = Schema::try_from(input.schema) else {
+        return;
+    };
+    let policyset = policy_set(input.policies.clone());
+    debug!("Policies: {policyset}");
+
+    // SymCC only supports policies that validate, so inputs whose policies
+    // don't are skipped before calling the solver
+    let passed = Validator::new(schema.clone())
+        .validate(&policyset, ValidationMode::Strict)
+        .validation_passed();
+    metrics::record_validation(passed);
+    if !passed {
+        return;
+    }
+
+    let lean_ffi = CedarLeanFfi::with_solver_config(
+        SolverConfig::new(SolverKind::Cvc5).with_timeout(SOLVER_TIMEOUT),
+    );
+    for (policy, req_envs) in input.policies.iter().zip(&input.req_envs) {
+        for (request, entities) in solver_guided_envs(&lean_ffi, &policy.0, &schema, req_envs) {
+            debug!("Request: {request}");
+            debug!("Entities: {}", entities.as_ref());
+            let (_, total_dur) =
+                time_function(|| run_auth_test(&def_impl, &request, &policyset, &entities));
+            info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
+            record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, total_dur));
+        }
+    }
+}
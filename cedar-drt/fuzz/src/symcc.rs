@@ -14,12 +14,39 @@
  * limitations under the License.
  */
 
-use cedar_policy::{PolicySet, RequestEnv, Schema};
+use cedar_drt::metrics;
+use cedar_lean_ffi::{CedarLeanFfi, Env as LeanEnv, FfiError, Term as LeanTerm};
+use cedar_policy::{
+    Decision as AuthzDecision, Entities, Policy, PolicyId, PolicySet, Request, RequestEnv,
+    Response, Schema,
+};
+use cedar_policy_generators::{
+    abac::ABACPolicy,
+    hierarchy::{Hierarchy, HierarchyGenerator},
+    policy::GeneratedPolicy,
+    schema,
+    settings::ABACSettings,
+};
 use cedar_policy_symcc::{
-    err::SolverError,
-    solver::{Decision, Solver, WriterSolver},
-    Asserts, SymEnv, WellFormedAsserts, WellTypedPolicies,
+    compile_always_allows, compile_always_denies, compile_disjoint, compile_equivalent,
+    compile_implies,
+    err::{Result as SymCCResult, SolverError},
+    solver::{Decision, LocalSolver, Solver, WriterSolver},
+    term::Term,
+    Asserts, CedarSymCompiler, SymEnv, WellFormedAsserts, WellTypedPolicies, WellTypedPolicy,
 };
+use libfuzzer_sys::arbitrary::{self, Arbitrary, MaxRecursionReached, Unstructured};
+use log::debug;
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+});
 
 /// An implementation of [`Solver`] that stores the SMTLib text in a buffer
 /// and allows the use of the solver to take the contents of the buffer
@@ -75,6 +102,38 @@ pub fn compile_well_typed_policies(
     Ok(asserts.asserts().clone())
 }
 
+/// Compile a well-typed policy to `Asserts`
+pub fn compile_well_typed_policy(
+    func: impl for<'a> Fn(
+        &WellTypedPolicy,
+        &'a SymEnv,
+    ) -> cedar_policy_symcc::err::Result<WellFormedAsserts<'a>>,
+    policy: &WellTypedPolicy,
+    schema: &Schema,
+    req_env: &RequestEnv,
+) -> Result<Asserts, String> {
+    let sym_env = SymEnv::new(&schema, &req_env).map_err(|err| err.to_string())?;
+    let asserts = func(policy, &sym_env).map_err(|err| err.to_string())?;
+    Ok(asserts.asserts().clone())
+}
+
+/// Compile a pair of well-typed policy sets to `Asserts`
+pub fn compile_well_typed_policy_pair(
+    func: impl for<'a> Fn(
+        &WellTypedPolicies,
+        &WellTypedPolicies,
+        &'a SymEnv,
+    ) -> cedar_policy_symcc::err::Result<WellFormedAsserts<'a>>,
+    policies1: &WellTypedPolicies,
+    policies2: &WellTypedPolicies,
+    schema: &Schema,
+    req_env: &RequestEnv,
+) -> Result<Asserts, String> {
+    let sym_env = SymEnv::new(&schema, &req_env).map_err(|err| err.to_string())?;
+    let asserts = func(policies1, policies2, &sym_env).map_err(|err| err.to_string())?;
+    Ok(asserts.asserts().clone())
+}
+
 /// Compile a policy set to `WellFormedAsserts`
 pub fn compile_policies<'a>(
     func: impl for<'b> Fn(
@@ -91,11 +150,355 @@ pub fn compile_policies<'a>(
     func(&well_typed_policies, &sym_env).map_err(|err| err.to_string())
 }
 
+/// Obtain the SMTLib script that the Rust SymCC sends to the solver to check `asserts`
+pub fn smtlib_of_check_asserts(asserts: &WellFormedAsserts<'_>) -> Result<String, String> {
+    RUNTIME.block_on(async {
+        let mut solver =
+            CedarSymCompiler::new(BuffSolver::new()).expect("solver construction should succeed");
+        match solver.check_sat(asserts).await {
+            Ok(_) | Err(cedar_policy_symcc::err::Error::SolverUnknown) => {
+                Ok(solver.solver_mut().contents())
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    })
+}
+
+/// Panic unless the Lean and Rust SymCC encode the asserts compiled by `func`
+/// to the same SMTLib script. Rust drives the term generation, and Lean
+/// encodes the Rust asserts, so that only the encodings are compared.
+pub fn assert_smtlib_of_compiled_eq(
+    check: &str,
+    lean_ffi: &CedarLeanFfi,
+    func: impl for<'a> FnOnce(&'a SymEnv) -> cedar_policy_symcc::err::Result<WellFormedAsserts<'a>>,
+    schema: &Schema,
+    req_env: &RequestEnv,
+) {
+    let Ok(sym_env) = SymEnv::new(&schema, &req_env) else {
+        return;
+    };
+    let Ok(rust_asserts) = func(&sym_env) else {
+        return;
+    };
+    let lean_asserts = rust_asserts
+        .asserts()
+        .iter()
+        .map(|assert| assert.clone().into())
+        .collect();
+    assert_smtlib_eq(
+        check,
+        lean_ffi.smtlib_of_check_asserts(&lean_asserts, schema, req_env),
+        smtlib_of_check_asserts(&rust_asserts),
+    );
+}
+
+/// Panic unless the Lean and Rust SymCC produced the same asserts for `check`, or both failed
+pub fn assert_asserts_eq(
+    check: &str,
+    lean_asserts: Result<Result<Vec<LeanTerm>, String>, FfiError>,
+    rust_asserts: Result<Asserts, String>,
+) {
+    match (lean_asserts, rust_asserts) {
+        (Ok(Ok(lean_asserts)), Ok(rust_asserts)) => {
+            let lean_asserts = lean_asserts
+                .into_iter()
+                .map(|t| Term::try_from(t).expect("term conversion should succeed"))
+                .collect::<BTreeSet<_>>();
+            let rust_asserts = BTreeSet::from_iter(rust_asserts.as_ref().into_iter().cloned());
+            similar_asserts::assert_eq!(
+                lean_asserts,
+                rust_asserts,
+                "{check}: Lean terms: {lean_asserts:?}, Rust terms: {rust_asserts:?}"
+            );
+        }
+        (Ok(Ok(lean_asserts)), Err(err)) => {
+            debug!("Lean asserts: {lean_asserts:?}");
+            panic!("{check}: Rust errors while Lean does not: {err}")
+        }
+        (Err(err), Ok(_)) => {
+            panic!("{check}: Lean errors while Rust does not: {err}")
+        }
+        (Ok(Err(err)), Ok(_)) => {
+            panic!("{check}: Lean errors while Rust does not: {err}")
+        }
+        (_, _) => {}
+    }
+}
+
+/// Panic unless the Lean and Rust SymCC produced the same SMTLib script for `check`, or both failed
+pub fn assert_smtlib_eq(
+    check: &str,
+    lean_smtlib: Result<String, FfiError>,
+    rust_smtlib: Result<String, String>,
+) {
+    match (lean_smtlib, rust_smtlib) {
+        (Ok(lean_smtlib), Ok(rust_smtlib)) => {
+            similar_asserts::assert_eq!(
+                rust_smtlib,
+                lean_smtlib,
+                "{check}:\nRust:\n{rust_smtlib}\nLean:\n{lean_smtlib}"
+            );
+        }
+        (Ok(_), Err(e)) => {
+            panic!("{check}: Rust encoding should succeed: {e}");
+        }
+        (Err(e), Ok(_)) => {
+            panic!("{check}: Lean encoding should succeed: {e}");
+        }
+        (Err(_), Err(_)) => {}
+    }
+}
+
+/// Requests and entities on which `policy`'s scope matches and its condition
+/// evaluates to `true`, and ones on which it evaluates to `false`, as found by
+/// the Lean SymCC in each of the request environments `req_envs`.
+///
+/// They are the counterexamples to `always-denies` for the
+/// [`GeneratedPolicy::condition_probe`]s of `policy`. Checks the solver can't
+/// decide, and counterexamples that don't convert to a request and entities,
+/// are skipped. The outcome of every check is recorded with
+/// [`metrics::record_solver_check`].
+pub fn solver_guided_envs(
+    lean_ffi: &CedarLeanFfi,
+    policy: &GeneratedPolicy,
+    schema: &Schema,
+    req_envs: &[RequestEnv],
+) -> Vec<(Request, Entities)> {
+    let mut envs = Vec::new();
+    for negate in [false, true] {
+        let mut probe = PolicySet::new();
+        probe
+            .add(Policy::from(policy.condition_probe(negate)))
+            .unwrap();
+        for req_env in req_envs {
+            match lean_ffi.run_check_always_denies_with_cex(&probe, schema, req_env) {
+                Ok(Some(env)) => match (env.to_request(), env.to_entities()) {
+                    (Ok(request), Ok(entities)) => {
+                        metrics::record_solver_check("counterexample");
+                        envs.push((request, entities))
+                    }
+                    (Err(err), _) | (_, Err(err)) => {
+                        metrics::record_solver_check("unconvertible");
+                        debug!("cannot convert counterexample: {err}")
+                    }
+                },
+                Ok(None) => metrics::record_solver_check("no_counterexample"),
+                Err(FfiError::SolverUnknown(_)) => metrics::record_solver_check("unknown"),
+                Err(err) => {
+                    metrics::record_solver_check("failed");
+                    debug!("no counterexample for {req_env:?}: {err}")
+                }
+            }
+        }
+    }
+    envs
+}
+
 /// The limit on the total number of request envs specific to symcc
 pub const fn total_action_request_env_limit() -> usize {
     128
 }
 
+/// A local CVC5 that gives up on each query after `timeout`, with the same
+/// arguments as the CVC5 the Lean SymCC spawns for
+/// `SolverConfig::new(SolverKind::Cvc5).with_timeout(timeout)`, so that
+/// the two implementations time out on the same queries
+pub fn local_cvc5_with_timeout(timeout: Duration) -> Result<LocalSolver, SolverError> {
+    LocalSolver::cvc5_with_args([
+        "--quiet".to_string(),
+        "--lang".to_string(),
+        "smt".to_string(),
+        format!("--tlimit-per={}", timeout.as_millis()),
+    ])
+}
+
+/// Input of the fuzz targets comparing the Lean and Rust SymCC on every check
+#[derive(Debug, Clone)]
+pub struct FuzzTargetInput {
+    /// generated schema
+    pub schema: schema::Schema,
+    /// generated policies of the first policy set
+    pub policies1: Vec<ABACPolicy>,
+    /// generated policies of the second policy set, compared against the first one
+    pub policies2: Vec<ABACPolicy>,
+}
+
+/// settings for the fuzz targets comparing the Lean and Rust SymCC
+pub static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
+    match_types: true,
+    enable_extensions: true,
+    max_depth: 3,
+    max_width: 3,
+    enable_additional_attributes: false,
+    enable_like: true,
+    enable_action_groups_and_attrs: true,
+    enable_arbitrary_func_call: true,
+    enable_unknowns: false,
+    enable_action_in_constraints: true,
+    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+    total_action_request_env_limit: total_action_request_env_limit(),
+    ..ABACSettings::default_fuzz()
+});
+
+/// maximum number of policies in each generated policy set
+pub const MAX_POLICIES: u32 = 3;
+
+/// Generate between 1 and [`MAX_POLICIES`] policies
+pub fn arbitrary_policies(
+    schema: &schema::Schema,
+    hierarchy: &Hierarchy,
+    u: &mut Unstructured<'_>,
+) -> arbitrary::Result<Vec<ABACPolicy>> {
+    let mut policies = Vec::new();
+    u.arbitrary_loop(Some(1), Some(MAX_POLICIES), |u| {
+        policies.push(schema.arbitrary_policy(hierarchy, u)?);
+        Ok(std::ops::ControlFlow::Continue(()))
+    })?;
+    Ok(policies)
+}
+
+/// size hint for [`arbitrary_policies()`]
+pub fn arbitrary_policies_size_hint(
+    settings: &ABACSettings,
+    depth: usize,
+) -> (usize, Option<usize>) {
+    schema::Schema::arbitrary_policy_size_hint(settings, depth)
+}
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let policies1 = arbitrary_policies(&schema, &hierarchy, u)?;
+        let policies2 = arbitrary_policies(&schema, &hierarchy, u)?;
+
+        Ok(Self {
+            schema,
+            policies1,
+            policies2,
+        })
+    }
+
+    fn try_size_hint(
+        depth: usize,
+    ) -> std::result::Result<(usize, Option<usize>), MaxRecursionReached> {
+        Ok(arbitrary::size_hint::and_all(&[
+            schema::Schema::arbitrary_size_hint(depth)?,
+            HierarchyGenerator::size_hint(depth),
+            arbitrary_policies_size_hint(&SETTINGS, depth),
+            arbitrary_policies_size_hint(&SETTINGS, depth),
+        ]))
+    }
+}
+
+/// Build a policy set from the generated policies, renaming them to avoid policy id collisions
+pub fn policy_set(policies: Vec<ABACPolicy>) -> PolicySet {
+    let mut policyset = PolicySet::new();
+    for (i, policy) in policies.into_iter().enumerate() {
+        let policy = Policy::from(policy).new_id(PolicyId::new(format!("policy{i}")));
+        policyset.add(policy).unwrap();
+    }
+    policyset
+}
+
+/// Asserts generated by the Lean SymCC
+pub type LeanAsserts = Result<Result<Vec<LeanTerm>, String>, FfiError>;
+
+/// Counterexample found by the Lean SymCC
+pub type LeanCex = Result<Option<LeanEnv>, FfiError>;
+
+// The validator DRT property we've been testing is that
+// rust_passes_validation => lean_passes_validation
+// So, the checks below are only compared on policies that pass the Rust
+// validator, which should be also well-typed according to Lean. When comparing
+// asserts, we use the `asserts_of_check_*_on_original` functions on the
+// policies produced by the Rust validator instead of `asserts_of_check_*`
+// because there are discrepancies between the policies generated by Lean and
+// Rust validators. And the enforcer is senstive to such discrepancies.
+
+/// Lean and Rust implementations of a verification condition on a single policy set
+pub struct PolicySetCheck {
+    /// name of the check, used in error messages
+    pub name: &'static str,
+    /// Lean asserts for the policies produced by the Rust validator
+    pub lean_asserts_on_original:
+        fn(&CedarLeanFfi, &PolicySet, &Schema, &RequestEnv) -> LeanAsserts,
+    /// Lean verdict and counterexample for the original policies
+    pub lean_with_cex: fn(&CedarLeanFfi, &PolicySet, &Schema, &RequestEnv) -> LeanCex,
+    /// Rust asserts for the well-typed policies
+    pub rust: for<'a> fn(&WellTypedPolicies, &'a SymEnv) -> SymCCResult<WellFormedAsserts<'a>>,
+    /// Whether the response to a request witnesses that the check does not hold
+    pub is_cex: fn(&Response) -> bool,
+}
+
+/// The checks on a single policy set
+pub const POLICY_SET_CHECKS: [PolicySetCheck; 2] = [
+    PolicySetCheck {
+        name: "always-allows",
+        lean_asserts_on_original: CedarLeanFfi::asserts_of_check_always_allows_on_original,
+        lean_with_cex: CedarLeanFfi::run_check_always_allows_with_cex,
+        rust: compile_always_allows,
+        is_cex: |response| response.decision() == AuthzDecision::Deny,
+    },
+    PolicySetCheck {
+        name: "always-denies",
+        lean_asserts_on_original: CedarLeanFfi::asserts_of_check_always_denies_on_original,
+        lean_with_cex: CedarLeanFfi::run_check_always_denies_with_cex,
+        rust: compile_always_denies,
+        is_cex: |response| response.decision() == AuthzDecision::Allow,
+    },
+];
+
+/// Lean and Rust implementations of a verification condition on a pair of policy sets
+pub struct PolicySetPairCheck {
+    /// name of the check, used in error messages
+    pub name: &'static str,
+    /// Lean asserts for the policies produced by the Rust validator
+    pub lean_asserts_on_original:
+        fn(&CedarLeanFfi, &PolicySet, &PolicySet, &Schema, &RequestEnv) -> LeanAsserts,
+    /// Lean verdict and counterexample for the original policies
+    pub lean_with_cex: fn(&CedarLeanFfi, &PolicySet, &PolicySet, &Schema, &RequestEnv) -> LeanCex,
+    /// Rust asserts for the well-typed policies
+    pub rust: for<'a> fn(
+        &WellTypedPolicies,
+        &WellTypedPolicies,
+        &'a SymEnv,
+    ) -> SymCCResult<WellFormedAsserts<'a>>,
+    /// Whether the responses of the two policy sets to a request witness that the check does not hold
+    pub is_cex: fn(&Response, &Response) -> bool,
+}
+
+/// The checks on a pair of policy sets
+pub const POLICY_SET_PAIR_CHECKS: [PolicySetPairCheck; 3] = [
+    PolicySetPairCheck {
+        name: "equivalent",
+        lean_asserts_on_original: CedarLeanFfi::asserts_of_check_equivalent_on_original,
+        lean_with_cex: CedarLeanFfi::run_check_equivalent_with_cex,
+        rust: compile_equivalent,
+        is_cex: |response1, response2| response1.decision() != response2.decision(),
+    },
+    PolicySetPairCheck {
+        name: "implies",
+        lean_asserts_on_original: CedarLeanFfi::asserts_of_check_implies_on_original,
+        lean_with_cex: CedarLeanFfi::run_check_implies_with_cex,
+        rust: compile_implies,
+        is_cex: |response1, response2| {
+            response1.decision() == AuthzDecision::Allow
+                && response2.decision() == AuthzDecision::Deny
+        },
+    },
+    PolicySetPairCheck {
+        name: "disjoint",
+        lean_asserts_on_original: CedarLeanFfi::asserts_of_check_disjoint_on_original,
+        lean_with_cex: CedarLeanFfi::run_check_disjoint_with_cex,
+        rust: compile_disjoint,
+        is_cex: |response1, response2| {
+            response1.decision() == AuthzDecision::Allow
+                && response2.decision() == AuthzDecision::Allow
+        },
+    },
+];
+
 #[cfg(test)]
 mod buff_solver_tests {
     use cedar_policy_symcc::{solver::Solver, SmtLibScript};
@@ -15,15 +15,18 @@
  */
 
 use cedar_drt::{
-    logger::{initialize_log, TOTAL_MSG},
-    tests::run_val_test,
-    CedarLeanEngine,
+    dump::dump_validation,
+    external_engine::DrtEngine,
+    logger::{initialize_log, record_timing, TimingEvent, DRT_ENGINE, TOTAL, TOTAL_MSG},
+    metrics,
+    shrink::{dump_shrunk_val_test, shrinking_enabled, ValTestCase},
+    tests::check_val_test,
 };
 
-use cedar_policy::{Policy, PolicySet, Schema, ValidationMode};
+use cedar_policy::{PolicySet, Schema, SchemaFragment, ValidationMode};
 
 use cedar_policy_generators::{
-    abac::ABACPolicy, hierarchy::HierarchyGenerator, schema, settings::ABACSettings,
+    hierarchy::HierarchyGenerator, policy_set::GeneratedPolicySet, schema, settings::ABACSettings,
 };
 use cedar_testing::cedar_test_impl::time_function;
 use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
@@ -34,8 +37,8 @@ use log::{debug, info};
 pub struct FuzzTargetInput<const TYPE_DIRECTED: bool> {
     /// generated schema
     pub schema: schema::Schema,
-    /// generated policy
-    pub policy: ABACPolicy,
+    /// generated policies, templates and template links
+    pub policies: GeneratedPolicySet,
 }
 
 impl<const TYPE_DIRECTED: bool> FuzzTargetInput<TYPE_DIRECTED> {
@@ -53,6 +56,7 @@ impl<const TYPE_DIRECTED: bool> FuzzTargetInput<TYPE_DIRECTED> {
             enable_action_in_constraints: true,
             per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
             total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+            ..ABACSettings::default_fuzz()
         }
     }
 }
@@ -61,8 +65,8 @@ impl<'a, const TYPE_DIRECTED: bool> Arbitrary<'a> for FuzzTargetInput<TYPE_DIREC
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
         let schema: schema::Schema = schema::Schema::arbitrary(Self::settings(), u)?;
         let hierarchy = schema.arbitrary_hierarchy(u)?;
-        let policy = schema.arbitrary_policy(&hierarchy, u)?;
-        Ok(Self { schema, policy })
+        let policies = GeneratedPolicySet::arbitrary_for_hierarchy(&schema, &hierarchy, u)?;
+        Ok(Self { schema, policies })
     }
 
     fn try_size_hint(
@@ -71,28 +75,63 @@ impl<'a, const TYPE_DIRECTED: bool> Arbitrary<'a> for FuzzTargetInput<TYPE_DIREC
         Ok(arbitrary::size_hint::and_all(&[
             schema::Schema::arbitrary_size_hint(depth)?,
             HierarchyGenerator::size_hint(depth),
-            schema::Schema::arbitrary_policy_size_hint(&Self::settings(), depth),
+            GeneratedPolicySet::arbitrary_for_hierarchy_size_hint(depth),
         ]))
     }
 }
 
 pub fn fuzz_target<const TYPE_DIRECTED: bool>(input: FuzzTargetInput<TYPE_DIRECTED>) {
     initialize_log();
-    let def_impl = CedarLeanEngine::new();
+    metrics::record_input(if TYPE_DIRECTED {
+        "validation-drt-type-directed"
+    } else {
+        "validation-drt"
+    });
+    let def_impl = DrtEngine::from_env();
+
+    let dump_test_name = std::env::var("DUMP_TEST_NAME").ok();
+    let schema_fragment = (shrinking_enabled() || dump_test_name.is_some())
+        .then(|| SchemaFragment::try_from(input.schema.clone()).ok())
+        .flatten();
 
     // generate a schema
     if let Ok(schema) = Schema::try_from(input.schema) {
         debug!("Schema: {:?}", schema);
 
-        // generate a policy
-        let mut policyset = PolicySet::new();
-        let policy: Policy = input.policy.into();
-        policyset.add(policy).unwrap();
+        // generate a policy set
+        let policyset = PolicySet::try_from(input.policies).unwrap();
         debug!("Policies: {policyset}");
 
         // run the policy through both validators and compare the result
-        let (_, total_dur) =
-            time_function(|| run_val_test(&def_impl, schema, &policyset, ValidationMode::Strict));
+        let (result, total_dur) =
+            time_function(|| check_val_test(&def_impl, schema, &policyset, ValidationMode::Strict));
         info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
+        record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, total_dur));
+        match result {
+            Ok(passed) => metrics::record_validation(passed),
+            Err(msg) => {
+                if let Some(schema) = schema_fragment {
+                    let case = ValTestCase {
+                        schema,
+                        policies: policyset.clone(),
+                        mode: ValidationMode::Strict,
+                    };
+                    dump_shrunk_val_test(&def_impl, &case);
+                }
+                panic!("{msg}");
+            }
+        }
+
+        if let (Some(test_name), Some(schema)) = (dump_test_name, schema_fragment) {
+            let dump_dir = std::env::var("DUMP_TEST_DIR").unwrap_or_else(|_| ".".to_string());
+            dump_validation(
+                dump_dir,
+                &test_name,
+                &schema,
+                &policyset,
+                ValidationMode::Strict,
+            )
+            .expect("failed to dump test case");
+        }
     }
 }
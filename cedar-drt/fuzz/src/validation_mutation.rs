@@ -0,0 +1,175 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Negative validation tests: a policy that validates, with one injected
+//! error, must be rejected by both the Rust and the Lean validator with the
+//! expected kind of error.
+
+use cedar_drt::{
+    logger::{initialize_log, record_timing, TimingEvent, DRT_ENGINE, TOTAL, TOTAL_MSG},
+    metrics, CedarLeanEngine,
+};
+use cedar_lean_ffi::ValidationResponse;
+
+use cedar_policy::{
+    Policy, PolicySet, Schema, ValidationError, ValidationMode, ValidationResult,
+    ValidationWarning, Validator,
+};
+use cedar_testing::cedar_test_impl::time_function;
+
+use cedar_policy_generators::{
+    abac::ABACPolicy,
+    hierarchy::{Hierarchy, HierarchyGenerator},
+    mutation::{ExpectedValidationError, MutatedPolicy},
+    schema,
+    settings::ABACSettings,
+};
+use libfuzzer_sys::arbitrary::{self, Arbitrary, Unstructured};
+use log::{debug, info};
+use std::sync::LazyLock;
+
+/// Input expected by this fuzz target
+#[derive(Debug, Clone)]
+pub struct FuzzTargetInput {
+    /// generated schema
+    pub schema: schema::Schema,
+    /// generated hierarchy
+    pub hierarchy: Hierarchy,
+    /// the policy before the mutation, which is expected to validate
+    pub policy: ABACPolicy,
+    /// `policy` with one injected error
+    pub mutated: MutatedPolicy,
+}
+
+/// settings for this fuzz target
+static SETTINGS: LazyLock<ABACSettings> = LazyLock::new(|| ABACSettings {
+    match_types: true,
+    enable_extensions: true,
+    max_depth: 7,
+    max_width: 7,
+    enable_additional_attributes: true,
+    enable_like: true,
+    enable_action_groups_and_attrs: true,
+    enable_arbitrary_func_call: true,
+    enable_unknowns: false,
+    enable_action_in_constraints: true,
+    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+    ..ABACSettings::default_fuzz()
+});
+
+impl<'a> Arbitrary<'a> for FuzzTargetInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let schema: schema::Schema = schema::Schema::arbitrary(SETTINGS.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let policy = schema.arbitrary_policy(&hierarchy, u)?;
+        let mutated = MutatedPolicy::arbitrary(policy.0.clone(), &schema, &hierarchy, u)?;
+        Ok(Self {
+            schema,
+            hierarchy,
+            policy,
+            mutated,
+        })
+    }
+
+    fn try_size_hint(
+        depth: usize,
+    ) -> arbitrary::Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
+        Ok(arbitrary::size_hint::and_all(&[
+            schema::Schema::arbitrary_size_hint(depth)?,
+            HierarchyGenerator::size_hint(depth),
+            schema::Schema::arbitrary_policy_size_hint(&SETTINGS, depth),
+            MutatedPolicy::arbitrary_size_hint(depth),
+        ]))
+    }
+}
+
+/// Whether the Rust validation result contains the expected kind of error
+fn rust_reports(result: &ValidationResult, expected: ExpectedValidationError) -> bool {
+    match expected {
+        ExpectedValidationError::UnexpectedType => result
+            .validation_errors()
+            .any(|e| matches!(e, ValidationError::UnexpectedType(_))),
+        ExpectedValidationError::AttrNotFound => result
+            .validation_errors()
+            .any(|e| matches!(e, ValidationError::UnsafeAttributeAccess(_))),
+        ExpectedValidationError::UnknownEntity => result
+            .validation_errors()
+            .any(|e| matches!(e, ValidationError::UnrecognizedEntityType(_))),
+        ExpectedValidationError::ImpossiblePolicy => {
+            result
+                .validation_errors()
+                .any(|e| matches!(e, ValidationError::HierarchyNotRespected(_)))
+                || result
+                    .validation_warnings()
+                    .any(|w| matches!(w, ValidationWarning::ImpossiblePolicy(_)))
+        }
+    }
+}
+
+pub fn fuzz_target(input: FuzzTargetInput) {
+    initialize_log();
+    metrics::record_input("validation-mutation-drt");
+    let Ok(schema) = Schema::try_from(input.schema) else {
+        return;
+    };
+    let validator = Validator::new(schema.clone());
+
+    // the mutation is only meaningful if the original policy is free of errors
+    // and warnings, so that the injected error is the only one
+    let mut original = PolicySet::new();
+    original.add(Policy::from(input.policy)).unwrap();
+    let result = validator.validate(&original, ValidationMode::Strict);
+    if !result.validation_passed() || result.validation_warnings().next().is_some() {
+        return;
+    }
+
+    let mutation = input.mutated.mutation;
+    let expected = mutation.expected_error();
+    let mut policyset = PolicySet::new();
+    policyset.add(Policy::from(input.mutated.policy)).unwrap();
+    debug!("Schema: {schema:?}");
+    debug!("Policies: {policyset}");
+    debug!("Mutation: {mutation:?}");
+
+    let lean_engine = CedarLeanEngine::new();
+    let (_, total_dur) = time_function(|| {
+        let result = validator.validate(&policyset, ValidationMode::Strict);
+        assert!(
+            rust_reports(&result, expected),
+            "Rust validator should report {expected:?} for {mutation:?}, but got:\n{result}"
+        );
+
+        match lean_engine
+            .get_ffi()
+            .validate_timed(&policyset, &schema, &ValidationMode::Strict)
+        {
+            Ok(timed_result) => match timed_result.result() {
+                ValidationResponse::Error(err) => assert_eq!(
+                    err,
+                    expected.lean_name(),
+                    "Lean validator should report {expected:?} for {mutation:?}"
+                ),
+                ValidationResponse::Ok(_) => panic!(
+                    "Lean validator should report {expected:?} for {mutation:?}, but it passed"
+                ),
+            },
+            Err(e) => panic!("Lean validation failed: {e}"),
+        }
+    });
+    info!("{}{}", TOTAL_MSG, total_dur.as_nanos());
+    record_timing(TimingEvent::new(TOTAL, DRT_ENGINE, total_dur));
+}
@@ -0,0 +1,178 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Replays the stored inputs of the fuzz targets (`corpus/<target>` and
+//! `artifacts/<target>`) as ordinary tests, one test per file, so that fixed
+//! bugs stay fixed without running the fuzzer.
+//!
+//! Each input is decoded exactly as `libfuzzer-sys` decodes it, through the
+//! target's `FuzzTargetInput`, and inputs that fail to decode are skipped (as
+//! the fuzzer skips them). Run a single target's inputs with, e.g.,
+//! `cargo test --test corpus-replay -- abac/`.
+//!
+//! Only targets whose property body lives in `cedar-drt-inner` can be
+//! replayed; the others are listed in [`NOT_REPLAYED`], and the `coverage`
+//! test checks that every target is one or the other.
+
+use cedar_drt_inner::{
+    abac, abac_type_directed, entity_validation, eval_type_directed, level_slicing,
+    multi_namespace, request_validation, solver_guided, validation_drt, validation_mutation,
+};
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
+use libtest_mimic::{Arguments, Trial};
+use std::path::{Path, PathBuf};
+
+/// Directories (relative to this crate) in which the inputs of each target are stored
+const INPUT_DIRS: [&str; 2] = ["corpus", "artifacts"];
+
+/// Targets whose `FuzzTargetInput` and property body live in the target binary
+/// (`fuzz_targets/<target>.rs`) rather than in `cedar-drt-inner`, so their
+/// stored inputs aren't replayed. To replay one, move its input and body into a
+/// library module and add it to `main`.
+const NOT_REPLAYED: [&str; 30] = [
+    "common-type-resolution",
+    "convert-policy-cedar-to-json",
+    "convert-policy-json-to-cedar",
+    "convert-schema-cedar-to-json",
+    "convert-schema-json-to-cedar",
+    "entity-slicing-drt-type-directed",
+    "formatter",
+    "formatter-bytes",
+    "json-schema-roundtrip",
+    "level-validation-drt",
+    "policy-set-roundtrip",
+    "protobuf-lean-roundtrip",
+    "protobuf-roundtrip",
+    "rbac",
+    "rbac-authorizer",
+    "roundtrip",
+    "roundtrip-entities",
+    "roundtrip-entities-bytes",
+    "roundtrip-entities-schemaless",
+    "schema-roundtrip",
+    "simple-parser",
+    "symcc-cex-pbt",
+    "symcc-smt-script-drt",
+    "symcc-term-drt",
+    "symcc-term-roundtrip-abac-type-directed",
+    "symcc-verdict-drt",
+    "tpe-pbt",
+    "validation-pbt",
+    "validation-pbt-type-directed",
+    "wildcard-matching",
+];
+
+/// The stored inputs of `target`
+fn stored_inputs(target: &str) -> Vec<(&'static str, PathBuf)> {
+    let mut inputs = Vec::new();
+    for dir in INPUT_DIRS {
+        let dir_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir).join(target);
+        // Targets without stored inputs have no directory
+        let Ok(entries) = std::fs::read_dir(&dir_path) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry.expect("failed to access stored input").path();
+            if path.is_file() {
+                inputs.push((dir, path));
+            }
+        }
+    }
+    inputs.sort();
+    inputs
+}
+
+/// One test per stored input of `target`, running `fuzz_target` on the decoded input
+fn trials<T>(target: &'static str, fuzz_target: fn(T)) -> (&'static str, Vec<Trial>)
+where
+    T: for<'a> Arbitrary<'a> + 'static,
+{
+    let trials = stored_inputs(target)
+        .into_iter()
+        .map(|(dir, path)| {
+            let file_name = path
+                .file_name()
+                .expect("stored inputs are files")
+                .to_string_lossy()
+                .into_owned();
+            Trial::test(format!("{target}/{dir}/{file_name}"), move || {
+                let bytes = std::fs::read(&path)
+                    .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+                if let Ok(input) = T::arbitrary_take_rest(Unstructured::new(&bytes)) {
+                    fuzz_target(input);
+                }
+                Ok(())
+            })
+        })
+        .collect();
+    (target, trials)
+}
+
+/// A test that every target in `fuzz_targets` is either `replayed` or listed
+/// in [`NOT_REPLAYED`], so that new targets aren't silently left out
+fn coverage(replayed: Vec<&'static str>) -> Trial {
+    Trial::test("coverage", move || {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz_targets");
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|err| format!("failed to read {}: {err}", dir.display()))?;
+        for entry in entries {
+            let path = entry.map_err(|err| err.to_string())?.path();
+            let Some(target) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            match (replayed.contains(&target), NOT_REPLAYED.contains(&target)) {
+                (true, false) | (false, true) => (),
+                (false, false) => {
+                    return Err(format!(
+                        "`{target}` is neither replayed nor listed in `NOT_REPLAYED`"
+                    )
+                    .into())
+                }
+                (true, true) => {
+                    return Err(
+                        format!("`{target}` is replayed but listed in `NOT_REPLAYED`").into(),
+                    )
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+fn main() {
+    let args = Arguments::from_args();
+    let replayed = [
+        trials("abac", abac::fuzz_target),
+        trials("abac-solver-guided", solver_guided::fuzz_target),
+        trials("abac-type-directed", abac_type_directed::fuzz_target),
+        trials("eval-type-directed", eval_type_directed::fuzz_target),
+        trials("entity-validation", entity_validation::fuzz_target),
+        trials("level-validation-slicing", level_slicing::fuzz_target),
+        trials("multi-namespace-drt", multi_namespace::fuzz_target),
+        trials("request-validation", request_validation::fuzz_target),
+        trials("validation-drt", validation_drt::fuzz_target::<false>),
+        trials(
+            "validation-drt-type-directed",
+            validation_drt::fuzz_target::<true>,
+        ),
+        trials("validation-mutation-drt", validation_mutation::fuzz_target),
+    ];
+    let mut tests = vec![coverage(
+        replayed.iter().map(|(target, _)| *target).collect(),
+    )];
+    tests.extend(replayed.into_iter().flat_map(|(_, trials)| trials));
+    libtest_mimic::run(&args, tests).exit();
+}
@@ -0,0 +1,150 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A stand-in external engine implementing the protocol of
+//! `cedar_drt::external_engine` with `cedar-policy`.
+//!
+//! Usage: `EXTERNAL_ENGINE=path/to/external_engine_stub cargo fuzz run <target>`
+
+use cedar_drt::external_engine::schema_of_json;
+use cedar_policy::{
+    eval_expression, Authorizer, Context, Decision, Entities, EntityUid, Expression, PolicySet,
+    Request, Schema, ValidationMode, Validator,
+};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+fn field<'a>(request: &'a Value, name: &str) -> Result<&'a Value, String> {
+    request.get(name).ok_or_else(|| format!("missing `{name}`"))
+}
+
+fn parse_policies(request: &Value) -> Result<PolicySet, String> {
+    PolicySet::from_json_value(field(request, "policies")?.clone()).map_err(|e| e.to_string())
+}
+
+fn parse_entities(request: &Value, schema: Option<&Schema>) -> Result<Entities, String> {
+    Entities::from_json_value(field(request, "entities")?.clone(), schema)
+        .map_err(|e| e.to_string())
+}
+
+fn parse_request(request: &Value, schema: Option<&Schema>) -> Result<Request, String> {
+    let request = field(request, "request")?;
+    let uid = |name| EntityUid::from_json(field(request, name)?.clone()).map_err(|e| e.to_string());
+    let action = uid("action")?;
+    let context = Context::from_json_value(
+        field(request, "context")?.clone(),
+        schema.map(|schema| (schema, &action)),
+    )
+    .map_err(|e| e.to_string())?;
+    Request::new(uid("principal")?, action, uid("resource")?, context, schema)
+        .map_err(|e| e.to_string())
+}
+
+fn parse_expr(expr: &str) -> Result<Expression, String> {
+    Expression::from_str(expr).map_err(|e| e.to_string())
+}
+
+fn parse_mode(request: &Value) -> Result<ValidationMode, String> {
+    match field(request, "mode")?.as_str() {
+        Some("strict") => Ok(ValidationMode::Strict),
+        Some("permissive") => Ok(ValidationMode::Permissive),
+        #[cfg(feature = "partial-validate")]
+        Some("partial") => Ok(ValidationMode::Partial),
+        mode => Err(format!("unknown validation mode {mode:?}")),
+    }
+}
+
+fn errors(errors: impl Iterator<Item = impl ToString>) -> Value {
+    json!({ "errors": errors.map(|e| e.to_string()).collect::<Vec<_>>() })
+}
+
+fn handle(request: &Value) -> Result<Value, String> {
+    match field(request, "op")?.as_str() {
+        Some("authorize") => {
+            let response = Authorizer::new().is_authorized(
+                &parse_request(request, None)?,
+                &parse_policies(request)?,
+                &parse_entities(request, None)?,
+            );
+            Ok(json!({
+                "decision": match response.decision() {
+                    Decision::Allow => "allow",
+                    Decision::Deny => "deny",
+                },
+                "reason": response.diagnostics().reason().map(ToString::to_string).collect::<Vec<_>>(),
+                "errors": response.diagnostics().errors().map(|e| e.policy_id().to_string()).collect::<Vec<_>>(),
+            }))
+        }
+        Some("evaluate") => {
+            let req = parse_request(request, None)?;
+            let entities = parse_entities(request, None)?;
+            let expr = parse_expr(
+                field(request, "expr")?
+                    .as_str()
+                    .ok_or("`expr` should be a string")?,
+            )?;
+            let actual = eval_expression(&req, &entities, &expr).ok();
+            let expected = match field(request, "expected")?.as_str() {
+                Some(expected) => Some(
+                    eval_expression(&req, &entities, &parse_expr(expected)?)
+                        .map_err(|e| e.to_string())?,
+                ),
+                None => None,
+            };
+            Ok(json!({ "matches": actual == expected }))
+        }
+        Some("validate") => {
+            let validator = Validator::new(schema_of_json(field(request, "schema")?)?);
+            let policies = parse_policies(request)?;
+            let mode = parse_mode(request)?;
+            let result = match request.get("level").and_then(Value::as_u64) {
+                Some(level) => validator.validate_with_level(&policies, mode, level as u32),
+                None => validator.validate(&policies, mode),
+            };
+            Ok(errors(result.validation_errors()))
+        }
+        Some("validate_entities") => {
+            let schema = schema_of_json(field(request, "schema")?)?;
+            Ok(errors(
+                parse_entities(request, Some(&schema)).err().into_iter(),
+            ))
+        }
+        Some("validate_request") => {
+            let schema = schema_of_json(field(request, "schema")?)?;
+            Ok(errors(
+                parse_request(request, Some(&schema)).err().into_iter(),
+            ))
+        }
+        op => Err(format!("unknown operation {op:?}")),
+    }
+}
+
+fn main() {
+    let mut stdout = std::io::stdout().lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.expect("failed to read request");
+        let response = match serde_json::from_str(&line)
+            .map_err(|e| e.to_string())
+            .and_then(|request| handle(&request))
+        {
+            Ok(result) => json!({ "ok": result }),
+            Err(err) => json!({ "error": err }),
+        };
+        writeln!(stdout, "{response}").expect("failed to write response");
+        stdout.flush().expect("failed to write response");
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Summarize the timing events written through `TIMING_LOG`.
+//!
+//! Usage: `summarize_timings <events.jsonl> [--baseline <summary.json>]
+//! [--save-baseline <summary.json>] [--tolerance <fraction>]`
+//!
+//! Prints the median/p90/p99 of each operation. With `--baseline`, also
+//! reports every operation whose median or p90 grew by more than the tolerance
+//! (default 0.2) and exits with status 1 if there are any.
+
+use cedar_drt::logger::{find_timing_regressions, read_timing_events, summarize_timings};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: summarize_timings <events.jsonl> [--baseline <summary.json>] [--save-baseline <summary.json>] [--tolerance <fraction>]";
+
+fn main() -> ExitCode {
+    let mut events_file = None;
+    let mut baseline_file = None;
+    let mut save_baseline_file = None;
+    let mut tolerance = 0.2;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--baseline" => baseline_file = args.next(),
+            "--save-baseline" => save_baseline_file = args.next(),
+            "--tolerance" => match args.next().and_then(|t| t.parse().ok()) {
+                Some(t) => tolerance = t,
+                None => {
+                    eprintln!("{USAGE}");
+                    return ExitCode::FAILURE;
+                }
+            },
+            _ if events_file.is_none() => events_file = Some(arg),
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let Some(events_file) = events_file else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let events = read_timing_events(&events_file)
+        .unwrap_or_else(|e| panic!("failed to read {events_file}: {e}"));
+    let summaries = summarize_timings(&events);
+    for summary in &summaries {
+        println!("{summary}");
+    }
+
+    if let Some(save_baseline_file) = save_baseline_file {
+        let json = serde_json::to_string_pretty(&summaries).expect("failed to serialize summary");
+        std::fs::write(&save_baseline_file, json)
+            .unwrap_or_else(|e| panic!("failed to write {save_baseline_file}: {e}"));
+    }
+
+    if let Some(baseline_file) = baseline_file {
+        let baseline = std::fs::read_to_string(&baseline_file)
+            .unwrap_or_else(|e| panic!("failed to read {baseline_file}: {e}"));
+        let baseline = serde_json::from_str::<Vec<_>>(&baseline)
+            .unwrap_or_else(|e| panic!("failed to parse {baseline_file}: {e}"));
+        let regressions = find_timing_regressions(&baseline, &summaries, tolerance);
+        if !regressions.is_empty() {
+            println!("\nRegressions against {baseline_file}:");
+            for regression in &regressions {
+                println!("{regression}");
+            }
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
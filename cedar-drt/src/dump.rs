@@ -14,13 +14,129 @@
  * limitations under the License.
  */
 
-use cedar_policy::{AuthorizationError, Policy};
+use cedar_policy::{
+    AuthorizationError, EntityUid, PolicyId, SlotId, ValidationError, ValidationMode,
+};
 use cedar_policy_core::entities::TypeAndId;
 
 use cedar_policy_generators::collections::HashMap;
-use cedar_testing::cedar_test_impl::RustEngine;
-use cedar_testing::integration_testing::{perform_integration_test, JsonRequest, JsonTest};
-use std::{io::Write, path::Path, str::FromStr};
+use cedar_testing::cedar_test_impl::{
+    CedarTestImplementation, RustEngine, TestResult, ValidationComparisonMode,
+};
+use cedar_testing::integration_testing::{
+    perform_integration_test, perform_integration_test_from_json_custom,
+    resolve_integration_test_path, JsonRequest, JsonTest,
+};
+use log::warn;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Suffix of the test cases with template links written by [`dump`]
+pub const LINKED_TEST_SUFFIX: &str = ".linked.json";
+/// Suffix of the validation test cases written by [`dump_validation`]
+pub const VALIDATION_TEST_SUFFIX: &str = ".validation.json";
+/// Suffix of the entity validation test cases written by [`dump_entity_validation`]
+pub const ENTITY_VALIDATION_TEST_SUFFIX: &str = ".entity_validation.json";
+/// Suffix of the request validation test cases written by [`dump_request_validation`]
+pub const REQUEST_VALIDATION_TEST_SUFFIX: &str = ".request_validation.json";
+
+/// A validation error expected for a dumped validation test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonValidationError {
+    /// id of the policy with the error, as assigned when the policy file is re-parsed
+    pub policy_id: String,
+    /// the kind of the error, i.e., the name of its `ValidationError` variant
+    pub kind: String,
+    /// the error message reported by `cedar-policy` (for information only)
+    pub message: String,
+}
+
+/// A template link of a dumped test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLink {
+    /// id of the template, as assigned when the policy file is re-parsed
+    pub template_id: String,
+    /// id of the template-linked policy
+    pub new_id: String,
+    /// the entities the slots (`?principal` or `?resource`) are linked to
+    pub values: BTreeMap<String, serde_json::Value>,
+}
+
+/// A test case with template links, i.e., a `JsonTest` whose policies also
+/// include the links in `links`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLinkedTest {
+    /// the test case, without the links
+    #[serde(flatten)]
+    pub test: JsonTest,
+    /// the template links
+    pub links: Vec<JsonLink>,
+}
+
+/// A validation test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonValidationTest {
+    /// schema file
+    pub schema: String,
+    /// policy file
+    pub policies: String,
+    /// the template links of the policies
+    #[serde(default)]
+    pub links: Vec<JsonLink>,
+    /// the validation mode, as written by [`dump_validation_mode`]
+    pub mode: String,
+    /// whether the policies validate
+    pub should_validate: bool,
+    /// the validation errors expected for each policy
+    pub errors: Vec<JsonValidationError>,
+}
+
+/// An entity validation test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonEntityValidationTest {
+    /// schema file
+    pub schema: String,
+    /// entities file
+    pub entities: String,
+    /// whether the entities validate against the schema
+    pub should_validate: bool,
+}
+
+/// A request in a request validation test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonValidationRequest {
+    /// principal of the request
+    pub principal: serde_json::Value,
+    /// action of the request
+    pub action: serde_json::Value,
+    /// resource of the request
+    pub resource: serde_json::Value,
+    /// context of the request
+    pub context: serde_json::Value,
+    /// whether the request validates against the schema
+    pub should_validate: bool,
+}
+
+/// A request validation test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonRequestValidationTest {
+    /// schema file
+    pub schema: String,
+    /// the requests to validate
+    pub requests: Vec<JsonValidationRequest>,
+}
 
 /// Dump testcase to a directory.
 ///
@@ -29,6 +145,13 @@ use std::{io::Write, path::Path, str::FromStr};
 ///
 /// `testcasename`: a name to use for the testcase. Will be used in various
 /// filenames etc.
+///
+/// Static policies and templates are written to the policy file, and the ids
+/// in the expected responses are replaced by the ids the policies get when the
+/// policy file is re-parsed. If there are template-linked policies, the test
+/// case is written to `<testcasename>.linked.json` with the links in its
+/// `links` field, and can be replayed with [`perform_dumped_test_from_json`];
+/// otherwise it is written to `<testcasename>.json`.
 pub fn dump(
     dirname: impl AsRef<Path>,
     testcasename: &str,
@@ -41,47 +164,17 @@ pub fn dump(
     // generators), then ignore it. The corpus test format currently has no way
     // to convey that the input should fail to parse.
     if !well_formed(policies) {
+        warn!("Not dumping test case {testcasename}: the policies cannot be re-parsed");
         return Ok(());
     }
 
     let dirname = dirname.as_ref();
     std::fs::create_dir_all(dirname)?;
 
-    let schema_filename = dirname.join(format!("{testcasename}.cedarschema"));
-    let policies_filename = dirname.join(format!("{testcasename}.cedar"));
-    let entities_filename = dirname.join(format!("{testcasename}.entities.json"));
-    let testcase_filename = dirname.join(format!("{testcasename}.json"));
-
-    let mut schema_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .append(false)
-        .truncate(true)
-        .open(&schema_filename)?;
-    let schema_text = schema.to_cedarschema().unwrap();
-    writeln!(schema_file, "{schema_text}")?;
-
-    let mut policies_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .append(false)
-        .truncate(true)
-        .open(&policies_filename)?;
-    let static_policies: Vec<_> = policies
-        .as_ref()
-        .static_policies()
-        .map(ToString::to_string)
-        .collect();
-    let policy_text = static_policies.join("\n");
-    writeln!(policies_file, "{policy_text}")?;
-
-    let entities_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .append(false)
-        .truncate(true)
-        .open(&entities_filename)?;
-    entities.write_to_json(entities_file).unwrap();
+    let schema_text = write_schema(dirname, testcasename, schema)?;
+    let dumped_policies = format_policies(dirname, testcasename, policies);
+    dumped_policies.write()?;
+    let entities_filename = write_entities(dirname, testcasename, entities)?;
 
     let requests: Vec<JsonRequest> = requests
         .into_iter()
@@ -98,18 +191,19 @@ pub fn dump(
             ),
             validate_request: true,
             decision: a.decision(),
-            reason: cedar_policy::Response::from(a.clone())
+            reason: a
                 .diagnostics()
                 .reason()
-                .cloned()
+                .map(|id| dumped_policies.dumped_id(id))
                 .collect(),
-            errors: cedar_policy::Response::from(a)
+            errors: a
                 .diagnostics()
                 .errors()
                 .map(|e| match e {
-                    AuthorizationError::PolicyEvaluationError(e) => e.policy_id(),
+                    AuthorizationError::PolicyEvaluationError(e) => {
+                        dumped_policies.dumped_id(e.policy_id())
+                    }
                 })
-                .cloned()
                 .collect(),
         })
         .collect();
@@ -117,24 +211,27 @@ pub fn dump(
     let should_validate = passes_validation(schema.clone(), policies);
 
     let testcase = JsonTest {
-        schema: schema_filename.display().to_string(),
-        policies: policies_filename.display().to_string(),
-        entities: entities_filename.display().to_string(),
+        schema: schema_filename(dirname, testcasename),
+        policies: dumped_policies.filename.clone(),
+        entities: entities_filename,
         should_validate,
         requests: requests.clone(),
     };
-
-    let testcase_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .append(false)
-        .truncate(true)
-        .open(testcase_filename)?;
-    serde_json::to_writer_pretty(testcase_file, &testcase)?;
+    if dumped_policies.links.is_empty() {
+        write_json(&dirname.join(format!("{testcasename}.json")), &testcase)?;
+    } else {
+        write_json(
+            &dirname.join(format!("{testcasename}{LINKED_TEST_SUFFIX}")),
+            &JsonLinkedTest {
+                test: testcase,
+                links: dumped_policies.links.clone(),
+            },
+        )?;
+    }
 
     // The generated test case should successfully run
     check_test(
-        policy_text,
+        &dumped_policies,
         schema_text,
         entities,
         should_validate,
@@ -145,21 +242,678 @@ pub fn dump(
     Ok(())
 }
 
+/// Dump a validation testcase to a directory, recording the validation errors
+/// `cedar-policy` reports for each policy.
+///
+/// See [`dump`] for the meaning of `dirname` and `testcasename`, and for how
+/// templates are written. The test case is written to
+/// `<testcasename>.validation.json`, with the template links in its `links`
+/// field, and can be replayed with [`perform_dumped_test_from_json`].
+pub fn dump_validation(
+    dirname: impl AsRef<Path>,
+    testcasename: &str,
+    schema: &cedar_policy::SchemaFragment,
+    policies: &cedar_policy::PolicySet,
+    mode: ValidationMode,
+) -> std::io::Result<()> {
+    if !well_formed(policies) {
+        warn!("Not dumping test case {testcasename}: the policies cannot be re-parsed");
+        return Ok(());
+    }
+    let Some(mode_name) = dump_validation_mode(mode) else {
+        warn!("Not dumping test case {testcasename}: unsupported validation mode {mode:?}");
+        return Ok(());
+    };
+    let Ok(validator_schema) = cedar_policy::Schema::try_from(schema.clone()) else {
+        warn!("Not dumping test case {testcasename}: the schema is invalid");
+        return Ok(());
+    };
+
+    let dirname = dirname.as_ref();
+    std::fs::create_dir_all(dirname)?;
+
+    let schema_text = write_schema(dirname, testcasename, schema)?;
+    let dumped_policies = format_policies(dirname, testcasename, policies);
+    dumped_policies.write()?;
+
+    let result = cedar_policy::Validator::new(validator_schema).validate(policies, mode);
+    let errors = result
+        .validation_errors()
+        .map(|e| JsonValidationError {
+            policy_id: dumped_policies.dumped_id(e.policy_id()).to_string(),
+            kind: validation_error_kind(e),
+            message: e.to_string(),
+        })
+        .collect();
+    let testcase = JsonValidationTest {
+        schema: schema_filename(dirname, testcasename),
+        policies: dumped_policies.filename.clone(),
+        links: dumped_policies.links.clone(),
+        mode: mode_name.to_string(),
+        should_validate: result.validation_passed(),
+        errors,
+    };
+    write_json(
+        &dirname.join(format!("{testcasename}{VALIDATION_TEST_SUFFIX}")),
+        &testcase,
+    )?;
+
+    // The generated test case should successfully run
+    check_validation_test(&dumped_policies, &schema_text, &testcase);
+
+    Ok(())
+}
+
+/// Dump an entity validation testcase to a directory. Whether the entities
+/// validate is determined by `cedar-policy`.
+///
+/// See [`dump`] for the meaning of `dirname` and `testcasename`. The test case
+/// is written to `<testcasename>.entity_validation.json`, and can be replayed
+/// with [`perform_dumped_test_from_json`].
+pub fn dump_entity_validation(
+    dirname: impl AsRef<Path>,
+    testcasename: &str,
+    schema: &cedar_policy::SchemaFragment,
+    entities: &cedar_policy::Entities,
+) -> std::io::Result<()> {
+    let Ok(validator_schema) = cedar_policy::Schema::try_from(schema.clone()) else {
+        warn!("Not dumping test case {testcasename}: the schema is invalid");
+        return Ok(());
+    };
+
+    let dirname = dirname.as_ref();
+    std::fs::create_dir_all(dirname)?;
+
+    let schema_text = write_schema(dirname, testcasename, schema)?;
+    let entities_filename = write_entities(dirname, testcasename, entities)?;
+    let should_validate =
+        cedar_policy::Entities::from_entities(entities.iter().cloned(), Some(&validator_schema))
+            .is_ok();
+    let testcase = JsonEntityValidationTest {
+        schema: schema_filename(dirname, testcasename),
+        entities: entities_filename,
+        should_validate,
+    };
+    write_json(
+        &dirname.join(format!("{testcasename}{ENTITY_VALIDATION_TEST_SUFFIX}")),
+        &testcase,
+    )?;
+
+    // The generated test case should successfully run
+    let parsed_schema = parse_schema(&schema_text);
+    assert_eq!(
+        cedar_policy::Entities::from_json_value(
+            entities.as_ref().to_json_value().unwrap(),
+            Some(&parsed_schema),
+        )
+        .is_ok(),
+        should_validate,
+        "entity validation of the dumped test case {testcasename} differs from the original"
+    );
+
+    Ok(())
+}
+
+/// Dump a request validation testcase to a directory. Whether each request
+/// validates is determined by `cedar-policy`.
+///
+/// See [`dump`] for the meaning of `dirname` and `testcasename`. The test case
+/// is written to `<testcasename>.request_validation.json`, and can be replayed
+/// with [`perform_dumped_test_from_json`].
+pub fn dump_request_validation(
+    dirname: impl AsRef<Path>,
+    testcasename: &str,
+    schema: &cedar_policy::SchemaFragment,
+    requests: impl IntoIterator<Item = cedar_policy::Request>,
+) -> std::io::Result<()> {
+    let Ok(validator_schema) = cedar_policy::Schema::try_from(schema.clone()) else {
+        warn!("Not dumping test case {testcasename}: the schema is invalid");
+        return Ok(());
+    };
+
+    let dirname = dirname.as_ref();
+    std::fs::create_dir_all(dirname)?;
+
+    let schema_text = write_schema(dirname, testcasename, schema)?;
+    let requests = requests
+        .into_iter()
+        .map(|q| {
+            let context = q
+                .context()
+                .expect("`dump_request_validation` does not support requests missing context")
+                .clone();
+            let should_validate = cedar_policy::Request::new(
+                q.principal().unwrap().clone(),
+                q.action().unwrap().clone(),
+                q.resource().unwrap().clone(),
+                context.clone(),
+                Some(&validator_schema),
+            )
+            .is_ok();
+            JsonValidationRequest {
+                principal: dump_request_var(q.principal().unwrap()),
+                action: dump_request_var(q.action().unwrap()),
+                resource: dump_request_var(q.resource().unwrap()),
+                context: dump_context(context),
+                should_validate,
+            }
+        })
+        .collect();
+    let testcase = JsonRequestValidationTest {
+        schema: schema_filename(dirname, testcasename),
+        requests,
+    };
+    write_json(
+        &dirname.join(format!("{testcasename}{REQUEST_VALIDATION_TEST_SUFFIX}")),
+        &testcase,
+    )?;
+
+    // The generated test case should successfully run
+    let parsed_schema = parse_schema(&schema_text);
+    for (i, request) in testcase.requests.iter().enumerate() {
+        assert_eq!(
+            parse_validation_request(request, &parsed_schema).is_some(),
+            request.should_validate,
+            "validation of request {i} of the dumped test case {testcasename} differs from the original"
+        );
+    }
+
+    Ok(())
+}
+
+/// Replay a test case written by any of the `dump*` functions with
+/// `test_impl`, dispatching on the file name. Test cases written by [`dump`]
+/// without template links are replayed with
+/// `perform_integration_test_from_json_custom`.
+pub fn perform_dumped_test_from_json(
+    jsonfile: impl AsRef<Path>,
+    test_impl: &impl CedarTestImplementation,
+) {
+    let jsonfile = jsonfile.as_ref();
+    let filename = jsonfile.to_string_lossy();
+    if filename.ends_with(LINKED_TEST_SUFFIX) {
+        perform_linked_test_from_json(jsonfile, test_impl);
+    } else if filename.ends_with(VALIDATION_TEST_SUFFIX) {
+        perform_validation_test_from_json(jsonfile, test_impl);
+    } else if filename.ends_with(ENTITY_VALIDATION_TEST_SUFFIX) {
+        perform_entity_validation_test_from_json(jsonfile, test_impl);
+    } else if filename.ends_with(REQUEST_VALIDATION_TEST_SUFFIX) {
+        perform_request_validation_test_from_json(jsonfile, test_impl);
+    } else {
+        perform_integration_test_from_json_custom(jsonfile, test_impl);
+    }
+}
+
+/// Replay a test case with template links written by [`dump`] with
+/// `test_impl`, panicking if any response differs from the recorded one
+pub fn perform_linked_test_from_json(
+    jsonfile: impl AsRef<Path>,
+    test_impl: &impl CedarTestImplementation,
+) {
+    let jsonfile = jsonfile.as_ref();
+    let testcase: JsonLinkedTest = read_json(jsonfile);
+    let JsonLinkedTest { test, links } = testcase;
+    let schema = read_schema(&test.schema);
+    let policies = read_policies(&test.policies, &links);
+    let entities = cedar_policy::Entities::from_json_str(&read_file(&test.entities), Some(&schema))
+        .unwrap_or_else(|e| panic!("error parsing entities in {}: {e}", test.entities));
+    perform_integration_test(
+        &policies,
+        &entities,
+        &schema,
+        test.should_validate,
+        test.requests,
+        &jsonfile.display().to_string(),
+        test_impl,
+    );
+}
+
+/// Replay a validation test case written by [`dump_validation`] with
+/// `test_impl`, panicking if the result, or the set of policies reporting
+/// errors, differs from the recorded one
+pub fn perform_validation_test_from_json(
+    jsonfile: impl AsRef<Path>,
+    test_impl: &impl CedarTestImplementation,
+) {
+    let jsonfile = jsonfile.as_ref();
+    let testcase: JsonValidationTest = read_json(jsonfile);
+    let schema = read_schema(&testcase.schema);
+    let policies = read_policies(&testcase.policies, &testcase.links);
+    let mode = parse_validation_mode(&testcase.mode)
+        .unwrap_or_else(|| panic!("unknown validation mode {}", testcase.mode));
+    let passed = test_result(
+        test_impl.validate(&schema, &policies, mode),
+        "validation",
+        jsonfile,
+    )
+    .validation_passed();
+    check_validation_result(test_impl, passed, testcase.should_validate, jsonfile);
+
+    // `CedarTestImplementation` only reports whether a policy set validates, so
+    // find the policies with errors by validating each one on its own
+    let mut expected_ids: BTreeSet<String> = testcase
+        .errors
+        .iter()
+        .map(|e| e.policy_id.clone())
+        .collect();
+    for link in &testcase.links {
+        if expected_ids.contains(&link.template_id) {
+            expected_ids.remove(&link.new_id);
+        }
+    }
+    let mut error_ids: BTreeSet<String> = BTreeSet::new();
+    for (id, template_id, policies) in single_policy_sets(&policies) {
+        // Only compare the errors of a link if its template has none
+        if template_id.is_some_and(|t| error_ids.contains(&t.to_string())) {
+            continue;
+        }
+        let passed = test_result(
+            test_impl.validate(&schema, &policies, mode),
+            "validation",
+            jsonfile,
+        )
+        .validation_passed();
+        if !passed {
+            error_ids.insert(id.to_string());
+        }
+    }
+    match test_impl.validation_comparison_mode() {
+        ValidationComparisonMode::AgreeOnAll => {
+            assert_eq!(
+                error_ids,
+                expected_ids,
+                "unexpected policies with validation errors for {}",
+                jsonfile.display()
+            );
+            // The error kinds are only reported by `cedar-policy`
+            let result = cedar_policy::Validator::new(schema).validate(&policies, mode);
+            assert_eq!(
+                error_kinds(result.validation_errors()),
+                expected_error_kinds(&testcase),
+                "unexpected validation error kinds for {}",
+                jsonfile.display()
+            );
+        }
+        ValidationComparisonMode::AgreeOnValid => assert!(
+            error_ids.is_subset(&expected_ids),
+            "unexpected policies with validation errors for {}: {:?}",
+            jsonfile.display(),
+            error_ids.difference(&expected_ids).collect::<Vec<_>>()
+        ),
+    }
+}
+
+/// Replay an entity validation test case written by
+/// [`dump_entity_validation`] with `test_impl`, panicking if the result
+/// differs from the recorded one
+pub fn perform_entity_validation_test_from_json(
+    jsonfile: impl AsRef<Path>,
+    test_impl: &impl CedarTestImplementation,
+) {
+    let jsonfile = jsonfile.as_ref();
+    let testcase: JsonEntityValidationTest = read_json(jsonfile);
+    let schema = read_schema(&testcase.schema);
+    let entities = cedar_policy::Entities::from_json_str(&read_file(&testcase.entities), None)
+        .unwrap_or_else(|e| panic!("error parsing entities in {}: {e}", testcase.entities));
+    let passed = test_result(
+        test_impl.validate_entities(&schema, &entities),
+        "entity validation",
+        jsonfile,
+    )
+    .validation_passed();
+    check_validation_result(test_impl, passed, testcase.should_validate, jsonfile);
+}
+
+/// Replay a request validation test case written by
+/// [`dump_request_validation`] with `test_impl`, panicking if the result for
+/// any request differs from the recorded one
+pub fn perform_request_validation_test_from_json(
+    jsonfile: impl AsRef<Path>,
+    test_impl: &impl CedarTestImplementation,
+) {
+    let jsonfile = jsonfile.as_ref();
+    let testcase: JsonRequestValidationTest = read_json(jsonfile);
+    let schema = read_schema(&testcase.schema);
+    for request in &testcase.requests {
+        let context = cedar_policy::Context::from_json_value(request.context.clone(), None)
+            .unwrap_or_else(|e| panic!("error parsing context in {}: {e}", jsonfile.display()));
+        let parsed_request = cedar_policy::Request::new(
+            EntityUid::from_json(request.principal.clone()).unwrap(),
+            EntityUid::from_json(request.action.clone()).unwrap(),
+            EntityUid::from_json(request.resource.clone()).unwrap(),
+            context,
+            None,
+        )
+        .unwrap_or_else(|e| panic!("error parsing request in {}: {e}", jsonfile.display()));
+        let passed = test_result(
+            test_impl.validate_request(&schema, &parsed_request),
+            "request validation",
+            jsonfile,
+        )
+        .validation_passed();
+        check_validation_result(test_impl, passed, request.should_validate, jsonfile);
+    }
+}
+
+fn read_file(filename: &str) -> String {
+    let path = resolve_integration_test_path(filename);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("error reading {}: {e}", path.display()))
+}
+
+fn read_json<T: DeserializeOwned>(jsonfile: &Path) -> T {
+    let path: PathBuf = resolve_integration_test_path(jsonfile);
+    let contents = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("error reading {}: {e}", path.display()));
+    serde_json::from_str(&contents)
+        .unwrap_or_else(|e| panic!("error parsing {}: {e}", path.display()))
+}
+
+/// Parse the policy file `filename` and re-create the template links `links`
+fn read_policies(filename: &str, links: &[JsonLink]) -> cedar_policy::PolicySet {
+    let mut policies = cedar_policy::PolicySet::from_str(&read_file(filename))
+        .unwrap_or_else(|e| panic!("error parsing policies in {filename}: {e}"));
+    link_policies(&mut policies, links);
+    policies
+}
+
+fn read_schema(filename: &str) -> cedar_policy::Schema {
+    cedar_policy::Schema::from_cedarschema_str(&read_file(filename))
+        .unwrap_or_else(|e| panic!("error parsing schema in {filename}: {e}"))
+        .0
+}
+
+fn test_result<T>(result: TestResult<T>, what: &str, jsonfile: &Path) -> T {
+    match result {
+        TestResult::Success(res) => res,
+        TestResult::Failure(err) => panic!("{what} failed for {}: {err}", jsonfile.display()),
+    }
+}
+
+/// Check a validation result against the recorded one, respecting the
+/// validation comparison mode of `test_impl`
+fn check_validation_result(
+    test_impl: &impl CedarTestImplementation,
+    passed: bool,
+    should_validate: bool,
+    jsonfile: &Path,
+) {
+    match test_impl.validation_comparison_mode() {
+        ValidationComparisonMode::AgreeOnAll => assert_eq!(
+            passed,
+            should_validate,
+            "unexpected validation result for {}",
+            jsonfile.display()
+        ),
+        ValidationComparisonMode::AgreeOnValid => {
+            if should_validate {
+                assert!(
+                    passed,
+                    "unexpected validation result for {}",
+                    jsonfile.display()
+                );
+            }
+        }
+    }
+}
+
+/// The policies of a dumped test case
+struct DumpedPolicies {
+    /// the policy file
+    filename: String,
+    /// the policy file contents
+    text: String,
+    /// ids of the policies and templates, and the ids they get when the policy
+    /// file is re-parsed
+    ids: std::collections::HashMap<PolicyId, PolicyId>,
+    /// the template links, using the re-parsed template ids
+    links: Vec<JsonLink>,
+}
+
+impl DumpedPolicies {
+    /// The id `id` gets when the dumped test case is re-parsed
+    fn dumped_id(&self, id: &PolicyId) -> PolicyId {
+        self.ids.get(id).cloned().unwrap_or_else(|| id.clone())
+    }
+
+    /// Re-parse the dumped policy set, including links
+    fn parse(&self) -> cedar_policy::PolicySet {
+        let mut policies = cedar_policy::PolicySet::from_str(&self.text)
+            .unwrap_or_else(|e| panic!("error re-parsing policy file: {e}"));
+        link_policies(&mut policies, &self.links);
+        policies
+    }
+
+    /// Write the policy file
+    fn write(&self) -> std::io::Result<()> {
+        let mut policies_file = open_for_writing(Path::new(&self.filename))?;
+        writeln!(policies_file, "{}", self.text)
+    }
+}
+
+fn open_for_writing(filename: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(false)
+        .truncate(true)
+        .open(filename)
+}
+
+fn write_json(filename: &Path, value: &impl Serialize) -> std::io::Result<()> {
+    let file = open_for_writing(filename)?;
+    serde_json::to_writer_pretty(file, value)?;
+    Ok(())
+}
+
+fn schema_filename(dirname: &Path, testcasename: &str) -> String {
+    dirname
+        .join(format!("{testcasename}.cedarschema"))
+        .display()
+        .to_string()
+}
+
+/// Write `<testcasename>.cedarschema`, returning the schema text
+fn write_schema(
+    dirname: &Path,
+    testcasename: &str,
+    schema: &cedar_policy::SchemaFragment,
+) -> std::io::Result<String> {
+    let schema_filename = dirname.join(format!("{testcasename}.cedarschema"));
+    let mut schema_file = open_for_writing(&schema_filename)?;
+    let schema_text = schema.to_cedarschema().unwrap();
+    writeln!(schema_file, "{schema_text}")?;
+    Ok(schema_text)
+}
+
+/// Write `<testcasename>.entities.json`, returning the file name
+fn write_entities(
+    dirname: &Path,
+    testcasename: &str,
+    entities: &cedar_policy::Entities,
+) -> std::io::Result<String> {
+    let entities_filename = dirname.join(format!("{testcasename}.entities.json"));
+    let entities_file = open_for_writing(&entities_filename)?;
+    entities.write_to_json(entities_file).unwrap();
+    Ok(entities_filename.display().to_string())
+}
+
+/// Format the policies and templates of `policies` for `<testcasename>.cedar`:
+/// static policies first, then templates, each sorted by id. The template
+/// links, sorted by id, get the ids following those of the templates.
+fn format_policies(
+    dirname: &Path,
+    testcasename: &str,
+    policies: &cedar_policy::PolicySet,
+) -> DumpedPolicies {
+    let policies_filename = dirname.join(format!("{testcasename}.cedar"));
+
+    // The parser assigns ids `policy0`, `policy1`, ... in order of appearance
+    let mut static_policies: Vec<_> = policies.policies().filter(|p| p.is_static()).collect();
+    static_policies.sort_by_key(|p| p.id().to_string());
+    let mut templates: Vec<_> = policies.templates().collect();
+    templates.sort_by_key(|t| t.id().to_string());
+    let mut linked_policies: Vec<_> = policies.policies().filter(|p| !p.is_static()).collect();
+    linked_policies.sort_by_key(|p| p.id().to_string());
+    let mut ids = std::collections::HashMap::new();
+    let mut texts = Vec::new();
+    for policy in &static_policies {
+        ids.insert(
+            policy.id().clone(),
+            PolicyId::new(format!("policy{}", texts.len())),
+        );
+        texts.push(policy.as_ref().to_string());
+    }
+    for template in &templates {
+        ids.insert(
+            template.id().clone(),
+            PolicyId::new(format!("policy{}", texts.len())),
+        );
+        texts.push(template.as_ref().to_string());
+    }
+    let links: Vec<JsonLink> = linked_policies
+        .iter()
+        .enumerate()
+        .map(|(i, policy)| {
+            let new_id = PolicyId::new(format!("policy{}", texts.len() + i));
+            ids.insert(policy.id().clone(), new_id.clone());
+            let template_id = policy
+                .template_id()
+                .expect("template-linked policies have a template");
+            JsonLink {
+                template_id: ids[template_id].to_string(),
+                new_id: new_id.to_string(),
+                values: policy
+                    .template_links()
+                    .expect("template-linked policies have links")
+                    .iter()
+                    .map(|(slot, euid)| (slot.to_string(), dump_request_var(euid)))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    DumpedPolicies {
+        filename: policies_filename.display().to_string(),
+        text: texts.join("\n"),
+        ids,
+        links,
+    }
+}
+
+/// Re-create the template links `links` in the re-parsed `policies`
+fn link_policies(policies: &mut cedar_policy::PolicySet, links: &[JsonLink]) {
+    for link in links {
+        let values = link
+            .values
+            .iter()
+            .map(|(slot, euid)| {
+                let slot = match slot.as_str() {
+                    "?principal" => SlotId::principal(),
+                    "?resource" => SlotId::resource(),
+                    _ => panic!("unknown slot {slot} in link {}", link.new_id),
+                };
+                let euid = EntityUid::from_json(euid.clone())
+                    .unwrap_or_else(|e| panic!("error parsing link {}: {e}", link.new_id));
+                (slot, euid)
+            })
+            .collect();
+        policies
+            .link(
+                PolicyId::new(&link.template_id),
+                PolicyId::new(&link.new_id),
+                values,
+            )
+            .unwrap_or_else(|e| panic!("error re-creating link {}: {e}", link.new_id));
+    }
+}
+
+/// Each static policy, template and template-linked policy of `policies` as a
+/// policy set of its own, with its id. Template-linked policies come last,
+/// together with their template and its id.
+fn single_policy_sets(
+    policies: &cedar_policy::PolicySet,
+) -> impl Iterator<Item = (PolicyId, Option<PolicyId>, cedar_policy::PolicySet)> + '_ {
+    let statics = policies.policies().filter(|p| p.is_static()).map(|p| {
+        let set = cedar_policy::PolicySet::from_policies([p.clone()])
+            .expect("a single policy is a valid policy set");
+        (p.id().clone(), None, set)
+    });
+    let templates = policies.templates().map(|t| {
+        let mut set = cedar_policy::PolicySet::new();
+        set.add_template(t.clone())
+            .expect("a single template is a valid policy set");
+        (t.id().clone(), None, set)
+    });
+    let links = policies.policies().filter(|p| !p.is_static()).map(|p| {
+        let template_id = p
+            .template_id()
+            .expect("template-linked policies have a template");
+        let mut set = cedar_policy::PolicySet::new();
+        set.add_template(
+            policies
+                .template(template_id)
+                .expect("the template of a link is in the policy set")
+                .clone(),
+        )
+        .expect("a single template is a valid policy set");
+        set.link(
+            template_id.clone(),
+            p.id().clone(),
+            p.template_links()
+                .expect("template-linked policies have links"),
+        )
+        .expect("the link is valid in the original policy set");
+        (p.id().clone(), Some(template_id.clone()), set)
+    });
+    statics.chain(templates).chain(links)
+}
+
+/// The name of the `ValidationError` variant of `e`, e.g., `UnexpectedType`
+fn validation_error_kind(e: &ValidationError) -> String {
+    format!("{e:?}")
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect()
+}
+
+/// The ids of the policies with errors recorded in `testcase`, with the error
+/// kinds
+fn expected_error_kinds(testcase: &JsonValidationTest) -> BTreeSet<(String, String)> {
+    testcase
+        .errors
+        .iter()
+        .map(|e| (e.policy_id.clone(), e.kind.clone()))
+        .collect()
+}
+
+/// The ids of the policies reporting `errors`, with the error kinds
+fn error_kinds<'a>(
+    errors: impl Iterator<Item = &'a ValidationError>,
+) -> BTreeSet<(String, String)> {
+    errors
+        .map(|e| (e.policy_id().to_string(), validation_error_kind(e)))
+        .collect()
+}
+
+fn parse_schema(formatted_schema: &str) -> cedar_policy::Schema {
+    cedar_policy::Schema::from_cedarschema_str(formatted_schema)
+        .unwrap_or_else(|e| panic!("error re-parsing schema: {e}"))
+        .0
+}
+
 // Check that the generated test passes the `perform_integration_test` function
 fn check_test(
-    formatted_policies: String,
+    dumped_policies: &DumpedPolicies,
     formatted_schema: String,
     entities: &cedar_policy::Entities,
     should_validate: bool,
     requests: Vec<JsonRequest>,
     test_name: &str,
 ) {
-    let parsed_policies = cedar_policy::PolicySet::from_str(&formatted_policies)
-        .unwrap_or_else(|e| panic!("error re-parsing policy file: {e}"));
+    let parsed_policies = dumped_policies.parse();
 
-    let parsed_schema = cedar_policy::Schema::from_cedarschema_str(&formatted_schema)
-        .unwrap_or_else(|e| panic!("error re-parsing schema: {e}"))
-        .0;
+    let parsed_schema = parse_schema(&formatted_schema);
 
     let parsed_entities = cedar_policy::Entities::from_json_value(
         entities.as_ref().to_json_value().unwrap(),
@@ -180,13 +934,53 @@ fn check_test(
     );
 }
 
+// Check that validating the re-parsed test case gives the recorded result
+fn check_validation_test(
+    dumped_policies: &DumpedPolicies,
+    formatted_schema: &str,
+    testcase: &JsonValidationTest,
+) {
+    let parsed_policies = dumped_policies.parse();
+    let parsed_schema = parse_schema(formatted_schema);
+    let mode = parse_validation_mode(&testcase.mode)
+        .unwrap_or_else(|| panic!("unknown validation mode {}", testcase.mode));
+    let result = cedar_policy::Validator::new(parsed_schema).validate(&parsed_policies, mode);
+    assert_eq!(result.validation_passed(), testcase.should_validate);
+    assert_eq!(
+        error_kinds(result.validation_errors()),
+        expected_error_kinds(testcase)
+    );
+}
+
+/// Parse a dumped request, validating it against `schema`. Returns `None` if
+/// the request does not validate.
+fn parse_validation_request(
+    request: &JsonValidationRequest,
+    schema: &cedar_policy::Schema,
+) -> Option<cedar_policy::Request> {
+    let context = cedar_policy::Context::from_json_value(request.context.clone(), None)
+        .unwrap_or_else(|e| panic!("error re-parsing context: {e}"));
+    cedar_policy::Request::new(
+        EntityUid::from_json(request.principal.clone()).unwrap(),
+        EntityUid::from_json(request.action.clone()).unwrap(),
+        EntityUid::from_json(request.resource.clone()).unwrap(),
+        context,
+        Some(schema),
+    )
+    .ok()
+}
+
 /// Check whether a policy set can be successfully parsed
 fn well_formed(policies: &cedar_policy::PolicySet) -> bool {
     policies
-        .as_ref()
-        .static_policies()
-        .map(ToString::to_string)
-        .all(|p| Policy::from_str(&p).is_ok())
+        .policies()
+        .filter(|p| p.is_static())
+        .map(|p| p.as_ref().to_string())
+        .all(|p| cedar_policy::Policy::from_str(&p).is_ok())
+        && policies
+            .templates()
+            .map(|t| t.as_ref().to_string())
+            .all(|t| cedar_policy::Template::parse(None, &t).is_ok())
 }
 
 /// Check whether a policy set passes validation
@@ -204,14 +998,39 @@ fn passes_validation(
     }
 }
 
+/// The name of a validation mode in dumped test cases, or `None` for modes the
+/// test formats don't support
+pub(crate) fn dump_validation_mode(mode: ValidationMode) -> Option<&'static str> {
+    match mode {
+        ValidationMode::Strict => Some("strict"),
+        ValidationMode::Permissive => Some("permissive"),
+        #[cfg(feature = "partial-validate")]
+        ValidationMode::Partial => Some("partial"),
+        // `ValidationMode` may gain variants in later `cedar-policy` versions
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// The validation mode named `mode` by [`dump_validation_mode`]
+pub(crate) fn parse_validation_mode(mode: &str) -> Option<ValidationMode> {
+    match mode {
+        "strict" => Some(ValidationMode::Strict),
+        "permissive" => Some(ValidationMode::Permissive),
+        #[cfg(feature = "partial-validate")]
+        "partial" => Some(ValidationMode::Partial),
+        _ => None,
+    }
+}
+
 /// Dump the entity uid to a json value
-fn dump_request_var(var: &cedar_policy::EntityUid) -> serde_json::Value {
+pub(crate) fn dump_request_var(var: &cedar_policy::EntityUid) -> serde_json::Value {
     let tyid = TypeAndId::from(var.as_ref());
     serde_json::to_value(tyid).expect("failed to serialize euid")
 }
 
 /// Dump the context to a "natural" json value
-fn dump_context(context: cedar_policy::Context) -> serde_json::Value {
+pub(crate) fn dump_context(context: cedar_policy::Context) -> serde_json::Value {
     let context = context
         .into_iter()
         .map(|(k, pval)| (k, pval.as_ref().to_natural_json().unwrap()))
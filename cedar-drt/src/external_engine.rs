@@ -0,0 +1,501 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Differential testing of Cedar implementations running in another process.
+//!
+//! [`ExternalProcessEngine`] spawns a command and talks to it over
+//! stdin/stdout, one JSON object per line. Each request has an `"op"` field
+//! and the inputs of the operation; the process answers each request with
+//! exactly one line, either `{"ok": <result>}` or `{"error": "<message>"}`.
+//!
+//! | `op` | inputs | result |
+//! |------|--------|--------|
+//! | `authorize` | `policies`, `entities`, `request` | `{"decision": "allow" \| "deny", "reason": [<policy id>], "errors": [<policy id>]}` |
+//! | `evaluate` | `request`, `entities`, `expr`, `expected` | `{"matches": <bool>}` |
+//! | `validate` | `schema`, `policies`, `mode`, optional `level` | `{"errors": [<message>]}` |
+//! | `validate_entities` | `schema`, `entities` | `{"errors": [<message>]}` |
+//! | `validate_request` | `schema`, `request` | `{"errors": [<message>]}` |
+//!
+//! where
+//! - `policies` is a policy set in the Cedar JSON policy set format,
+//! - `entities` is in the Cedar JSON entity format,
+//! - `request` is `{"principal": <uid>, "action": <uid>, "resource": <uid>, "context": <record>}`,
+//!   with uids as `{"type": ..., "id": ...}` and the context in the natural JSON format,
+//! - `expr` is a Cedar expression, and `expected` is a Cedar expression for the
+//!   expected value, or `null` if evaluation is expected to fail; `matches`
+//!   says whether `expr` evaluates to `expected`,
+//! - `schema` is a base64-encoded `Schema` protobuf message (as sent to Lean),
+//! - `mode` is `"strict"` or `"permissive"`, and `level` is the validation level.
+//!
+//! An empty `errors` list means that validation passed.
+//!
+//! The `external_engine_stub` binary implements this protocol with `cedar-policy`.
+
+use crate::dump::{dump_context, dump_request_var, dump_validation_mode};
+use crate::CedarLeanEngine;
+use base64::Engine as _;
+use cedar_policy::{
+    ffi, proto, Decision, Entities, EvalResult, Expression, PolicyId, PolicySet, Request, Schema,
+    ValidationMode,
+};
+use cedar_testing::cedar_test_impl::{
+    CedarTestImplementation, ErrorComparisonMode, TestResponse, TestResult, TestValidationResult,
+    ValidationComparisonMode,
+};
+use miette::miette;
+use prost::Message;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+/// Environment variable holding the command line of the external engine (split
+/// on whitespace). If set, [`DrtEngine::from_env`] tests the external engine
+/// instead of Lean.
+pub const EXTERNAL_ENGINE: &str = "EXTERNAL_ENGINE";
+
+/// A Cedar implementation running in another process
+pub struct ExternalProcessEngine {
+    process: Mutex<ExternalProcess>,
+}
+
+struct ExternalProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+/// A response line
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ExternalResponse {
+    Ok(Value),
+    Error(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorizationResult {
+    decision: String,
+    reason: Vec<String>,
+    errors: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EvaluationResult {
+    matches: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ValidationResult {
+    errors: Vec<String>,
+}
+
+impl ExternalProcessEngine {
+    /// Spawn `program` with `args`
+    pub fn spawn(program: &str, args: &[&str]) -> std::io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(Self {
+            process: Mutex::new(ExternalProcess {
+                child,
+                stdin,
+                stdout,
+            }),
+        })
+    }
+
+    /// The engine started from the command line in [`EXTERNAL_ENGINE`], if set.
+    /// The process is spawned on first use and shared by all callers.
+    pub fn from_env() -> Option<&'static Self> {
+        static ENGINE: OnceLock<Option<ExternalProcessEngine>> = OnceLock::new();
+        ENGINE
+            .get_or_init(|| {
+                let command = std::env::var(EXTERNAL_ENGINE).ok()?;
+                let mut words = command.split_whitespace();
+                let program = words.next()?;
+                let args: Vec<&str> = words.collect();
+                Some(
+                    Self::spawn(program, &args).unwrap_or_else(|e| {
+                        panic!("failed to start external engine `{command}`: {e}")
+                    }),
+                )
+            })
+            .as_ref()
+    }
+
+    /// Send one request and read the result
+    fn call<T: for<'de> Deserialize<'de>>(&self, request: Value) -> Result<T, String> {
+        let mut process = self.process.lock().unwrap_or_else(|e| e.into_inner());
+        let line = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+        writeln!(process.stdin, "{line}")
+            .and_then(|()| process.stdin.flush())
+            .map_err(|e| format!("failed to write to external engine: {e}"))?;
+        let mut response = String::new();
+        match process.stdout.read_line(&mut response) {
+            Ok(0) => Err("external engine closed its output".to_string()),
+            Ok(_) => match serde_json::from_str(&response) {
+                Ok(ExternalResponse::Ok(result)) => serde_json::from_value(result)
+                    .map_err(|e| format!("invalid result from external engine: {e}")),
+                Ok(ExternalResponse::Error(err)) => Err(err),
+                Err(e) => Err(format!("invalid response from external engine: {e}")),
+            },
+            Err(e) => Err(format!("failed to read from external engine: {e}")),
+        }
+    }
+
+    fn validation_call(&self, request: Value) -> TestResult<TestValidationResult> {
+        match self.call::<ValidationResult>(request) {
+            Ok(result) => TestResult::Success(TestValidationResult {
+                errors: result.errors,
+                timing_info: HashMap::new(),
+            }),
+            Err(err) => TestResult::Failure(err),
+        }
+    }
+}
+
+impl Drop for ExternalProcess {
+    fn drop(&mut self) {
+        // Ignore errors: the process may already have exited
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Encode `request` in the protocol format
+pub fn request_to_json(request: &Request) -> Value {
+    json!({
+        "principal": dump_request_var(request.principal().expect("request principal should be known")),
+        "action": dump_request_var(request.action().expect("request action should be known")),
+        "resource": dump_request_var(request.resource().expect("request resource should be known")),
+        "context": dump_context(request.context().expect("request context should be known").clone()),
+    })
+}
+
+/// Encode `policies` in the protocol format
+pub fn policies_to_json(policies: &PolicySet) -> Value {
+    policies
+        .clone()
+        .to_json()
+        .expect("policy set should be serializable")
+}
+
+/// Encode `entities` in the protocol format
+pub fn entities_to_json(entities: &Entities) -> Value {
+    entities
+        .as_ref()
+        .to_json_value()
+        .expect("entities should be serializable")
+}
+
+/// Encode `schema` in the protocol format
+pub fn schema_to_json(schema: &Schema) -> Value {
+    let bytes = proto::models::Schema::from(schema).encode_to_vec();
+    Value::String(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// Decode a schema in the protocol format
+pub fn schema_of_json(schema: &Value) -> Result<Schema, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(schema.as_str().ok_or("schema should be a string")?)
+        .map_err(|e| e.to_string())?;
+    let schema = proto::models::Schema::decode(&bytes[..]).map_err(|e| e.to_string())?;
+    Ok(Schema::from(&schema))
+}
+
+impl CedarTestImplementation for ExternalProcessEngine {
+    fn is_authorized(
+        &self,
+        request: &Request,
+        policies: &PolicySet,
+        entities: &Entities,
+    ) -> TestResult<TestResponse> {
+        let result = self.call::<AuthorizationResult>(json!({
+            "op": "authorize",
+            "policies": policies_to_json(policies),
+            "entities": entities_to_json(entities),
+            "request": request_to_json(request),
+        }));
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => return TestResult::Failure(err),
+        };
+        let decision = match result.decision.as_str() {
+            "allow" => Decision::Allow,
+            "deny" => Decision::Deny,
+            other => return TestResult::Failure(format!("unknown decision {other}")),
+        };
+        let errors = result
+            .errors
+            .iter()
+            .map(|id| ffi::AuthorizationError::new_from_report(PolicyId::new(id), miette!("{id}")))
+            .collect();
+        TestResult::Success(TestResponse {
+            response: ffi::Response::new(
+                decision,
+                result.reason.iter().map(PolicyId::new).collect(),
+                errors,
+            ),
+            timing_info: HashMap::new(),
+        })
+    }
+
+    fn interpret(
+        &self,
+        request: &Request,
+        entities: &Entities,
+        expr: &Expression,
+        expected: Option<EvalResult>,
+    ) -> TestResult<bool> {
+        let expected = expected.map(|v| v.to_string());
+        match self.call::<EvaluationResult>(json!({
+            "op": "evaluate",
+            "request": request_to_json(request),
+            "entities": entities_to_json(entities),
+            "expr": expr.to_string(),
+            "expected": expected,
+        })) {
+            Ok(result) => TestResult::Success(result.matches),
+            Err(err) => TestResult::Failure(err),
+        }
+    }
+
+    fn validate(
+        &self,
+        schema: &Schema,
+        policies: &PolicySet,
+        mode: ValidationMode,
+    ) -> TestResult<TestValidationResult> {
+        let Some(mode) = dump_validation_mode(mode) else {
+            return TestResult::Failure(format!("unsupported validation mode {mode:?}"));
+        };
+        self.validation_call(json!({
+            "op": "validate",
+            "schema": schema_to_json(schema),
+            "policies": policies_to_json(policies),
+            "mode": mode,
+        }))
+    }
+
+    fn validate_with_level(
+        &self,
+        schema: &Schema,
+        policies: &PolicySet,
+        mode: ValidationMode,
+        level: i32,
+    ) -> TestResult<TestValidationResult> {
+        let Some(mode) = dump_validation_mode(mode) else {
+            return TestResult::Failure(format!("unsupported validation mode {mode:?}"));
+        };
+        self.validation_call(json!({
+            "op": "validate",
+            "schema": schema_to_json(schema),
+            "policies": policies_to_json(policies),
+            "mode": mode,
+            "level": level,
+        }))
+    }
+
+    fn validate_request(
+        &self,
+        schema: &Schema,
+        request: &Request,
+    ) -> TestResult<TestValidationResult> {
+        self.validation_call(json!({
+            "op": "validate_request",
+            "schema": schema_to_json(schema),
+            "request": request_to_json(request),
+        }))
+    }
+
+    fn validate_entities(
+        &self,
+        schema: &Schema,
+        entities: &Entities,
+    ) -> TestResult<TestValidationResult> {
+        self.validation_call(json!({
+            "op": "validate_entities",
+            "schema": schema_to_json(schema),
+            "entities": entities_to_json(entities),
+        }))
+    }
+
+    fn error_comparison_mode(&self) -> ErrorComparisonMode {
+        ErrorComparisonMode::PolicyIds
+    }
+
+    fn validation_comparison_mode(&self) -> ValidationComparisonMode {
+        ValidationComparisonMode::AgreeOnValid
+    }
+}
+
+/// The implementation tested by the DRT fuzz targets: the Lean engine, or an
+/// external engine (see [`EXTERNAL_ENGINE`]).
+///
+/// An external engine is checked against the Lean engine as an oracle, on
+/// top of the comparison with `cedar-policy` done by the DRT harness: if the
+/// two disagree, the result is a failure describing the disagreement.
+pub enum DrtEngine {
+    Lean(CedarLeanEngine),
+    External {
+        engine: &'static ExternalProcessEngine,
+        oracle: CedarLeanEngine,
+    },
+}
+
+impl DrtEngine {
+    /// The external engine if [`EXTERNAL_ENGINE`] is set, and the Lean engine otherwise
+    pub fn from_env() -> Self {
+        match ExternalProcessEngine::from_env() {
+            Some(engine) => Self::External {
+                engine,
+                oracle: CedarLeanEngine::new(),
+            },
+            None => Self::Lean(CedarLeanEngine::new()),
+        }
+    }
+
+    /// Run `op` on the tested implementation and, for an external engine, check
+    /// that `agree` holds between its result and the Lean result
+    fn with_oracle<T: std::fmt::Debug>(
+        &self,
+        op: impl Fn(&dyn CedarTestImplementation) -> TestResult<T>,
+        agree: impl FnOnce(&T, &T) -> bool,
+    ) -> TestResult<T> {
+        match self {
+            Self::Lean(lean) => op(lean),
+            Self::External { engine, oracle } => match (op(*engine), op(oracle)) {
+                (TestResult::Success(res), TestResult::Success(lean_res))
+                    if !agree(&res, &lean_res) =>
+                {
+                    TestResult::Failure(format!(
+                        "external engine disagrees with Lean\nExternal: {res:?}\nLean: {lean_res:?}"
+                    ))
+                }
+                (res, _) => res,
+            },
+        }
+    }
+
+    fn active(&self) -> &dyn CedarTestImplementation {
+        match self {
+            Self::Lean(lean) => lean,
+            Self::External { engine, .. } => *engine,
+        }
+    }
+}
+
+fn validation_agrees(res: &TestValidationResult, lean_res: &TestValidationResult) -> bool {
+    res.validation_passed() == lean_res.validation_passed()
+}
+
+impl CedarTestImplementation for DrtEngine {
+    fn is_authorized(
+        &self,
+        request: &Request,
+        policies: &PolicySet,
+        entities: &Entities,
+    ) -> TestResult<TestResponse> {
+        self.with_oracle(
+            |engine| engine.is_authorized(request, policies, entities),
+            |res, lean_res| res.response == lean_res.response,
+        )
+    }
+
+    fn interpret(
+        &self,
+        request: &Request,
+        entities: &Entities,
+        expr: &Expression,
+        expected: Option<EvalResult>,
+    ) -> TestResult<bool> {
+        self.with_oracle(
+            |engine| engine.interpret(request, entities, expr, expected.clone()),
+            |res, lean_res| res == lean_res,
+        )
+    }
+
+    fn validate(
+        &self,
+        schema: &Schema,
+        policies: &PolicySet,
+        mode: ValidationMode,
+    ) -> TestResult<TestValidationResult> {
+        // The Lean validator only supports strict mode
+        if mode != ValidationMode::Strict {
+            return self.active().validate(schema, policies, mode);
+        }
+        self.with_oracle(
+            |engine| engine.validate(schema, policies, mode),
+            validation_agrees,
+        )
+    }
+
+    fn validate_with_level(
+        &self,
+        schema: &Schema,
+        policies: &PolicySet,
+        mode: ValidationMode,
+        level: i32,
+    ) -> TestResult<TestValidationResult> {
+        if mode != ValidationMode::Strict {
+            return self
+                .active()
+                .validate_with_level(schema, policies, mode, level);
+        }
+        self.with_oracle(
+            |engine| engine.validate_with_level(schema, policies, mode, level),
+            validation_agrees,
+        )
+    }
+
+    fn validate_request(
+        &self,
+        schema: &Schema,
+        request: &Request,
+    ) -> TestResult<TestValidationResult> {
+        self.with_oracle(
+            |engine| engine.validate_request(schema, request),
+            validation_agrees,
+        )
+    }
+
+    fn validate_entities(
+        &self,
+        schema: &Schema,
+        entities: &Entities,
+    ) -> TestResult<TestValidationResult> {
+        self.with_oracle(
+            |engine| engine.validate_entities(schema, entities),
+            validation_agrees,
+        )
+    }
+
+    fn error_comparison_mode(&self) -> ErrorComparisonMode {
+        self.active().error_comparison_mode()
+    }
+
+    fn validation_comparison_mode(&self) -> ValidationComparisonMode {
+        self.active().validation_comparison_mode()
+    }
+}
@@ -23,9 +23,14 @@ use cedar_testing::cedar_test_impl::{
     TestValidationResult, ValidationComparisonMode,
 };
 
+use crate::logger::{
+    record_timing, TimingEvent, AUTHORIZATION, ENTITY_VALIDATION, LEAN_ENGINE, LEVEL_VALIDATION,
+    REQUEST_VALIDATION, VALIDATION,
+};
 use cedar_lean_ffi::{CedarLeanFfi, TimedResult, ValidationResponse};
 use miette::miette;
 use std::collections::HashMap;
+use std::time::Duration;
 
 pub struct CedarLeanEngine {
     lean_ffi: CedarLeanFfi,
@@ -54,6 +59,12 @@ impl CedarLeanEngine {
         })
     }
 
+    /// A timing event for the Lean engine, from the duration reported by Lean
+    fn timing_event<T>(operation: &str, timed_result: &TimedResult<T>) -> TimingEvent {
+        let duration = u64::try_from(timed_result.duration()).unwrap_or(u64::MAX);
+        TimingEvent::new(operation, LEAN_ENGINE, Duration::from_nanos(duration))
+    }
+
     fn filter_warnings(
         validation_result: TestResult<TestValidationResult>,
     ) -> TestResult<TestValidationResult> {
@@ -84,6 +95,11 @@ impl CedarTestImplementation for CedarLeanEngine {
             .is_authorized_timed(policies, entities, request)
         {
             Ok(timed_resp) => {
+                record_timing(
+                    Self::timing_event(AUTHORIZATION, &timed_resp)
+                        .with_feature("policies", policies.num_of_policies())
+                        .with_feature("entities", entities.iter().count()),
+                );
                 let errors = timed_resp
                     .result()
                     .erroring_policies()
@@ -146,6 +162,10 @@ impl CedarTestImplementation for CedarLeanEngine {
         );
         match self.lean_ffi.validate_timed(policies, schema, &mode) {
             Ok(timed_result) => {
+                record_timing(
+                    Self::timing_event(VALIDATION, &timed_result)
+                        .with_feature("policies", policies.num_of_policies()),
+                );
                 Self::filter_warnings(Self::validation_to_test_result(timed_result))
             }
             Err(e) => TestResult::Failure(e.to_string()),
@@ -167,6 +187,10 @@ impl CedarTestImplementation for CedarLeanEngine {
         );
         match self.lean_ffi.level_validate_timed(policies, schema, level) {
             Ok(timed_result) => {
+                record_timing(
+                    Self::timing_event(LEVEL_VALIDATION, &timed_result)
+                        .with_feature("policies", policies.num_of_policies()),
+                );
                 Self::filter_warnings(Self::validation_to_test_result(timed_result))
             }
             Err(e) => TestResult::Failure(e.to_string()),
@@ -180,6 +204,7 @@ impl CedarTestImplementation for CedarLeanEngine {
     ) -> TestResult<TestValidationResult> {
         match self.lean_ffi.validate_request_timed(schema, request) {
             Ok(timed_result) => {
+                record_timing(Self::timing_event(REQUEST_VALIDATION, &timed_result));
                 Self::filter_warnings(Self::validation_to_test_result(timed_result))
             }
             Err(e) => TestResult::Failure(e.to_string()),
@@ -193,6 +218,10 @@ impl CedarTestImplementation for CedarLeanEngine {
     ) -> TestResult<TestValidationResult> {
         match self.lean_ffi.validate_entities_timed(schema, entities) {
             Ok(timed_result) => {
+                record_timing(
+                    Self::timing_event(ENTITY_VALIDATION, &timed_result)
+                        .with_feature("entities", entities.iter().count()),
+                );
                 Self::filter_warnings(Self::validation_to_test_result(timed_result))
             }
             Err(e) => TestResult::Failure(e.to_string()),
@@ -1,7 +1,10 @@
 pub mod dump;
+pub mod external_engine;
 mod lean_engine;
 pub mod logger;
+pub mod metrics;
 mod parsing_utils;
+pub mod shrink;
 pub mod tests;
 
 pub use lean_engine::CedarLeanEngine;
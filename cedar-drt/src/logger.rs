@@ -14,6 +14,14 @@
  * limitations under the License.
  */
 use log::warn;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
 
 pub const TOTAL_MSG: &str = "total (ns) : ";
 
@@ -31,3 +39,314 @@ pub fn initialize_log() {
         }
     };
 }
+
+/// Environment variable naming a file to which timing events are appended as
+/// JSON lines
+pub const TIMING_LOG: &str = "TIMING_LOG";
+
+/// Operation names used in timing events
+pub const AUTHORIZATION: &str = "authorization";
+pub const EVALUATION: &str = "evaluation";
+pub const VALIDATION: &str = "validation";
+pub const LEVEL_VALIDATION: &str = "level_validation";
+pub const ENTITY_VALIDATION: &str = "entity_validation";
+pub const REQUEST_VALIDATION: &str = "request_validation";
+/// Operation name for the whole body of a fuzz target
+pub const TOTAL: &str = "total";
+
+/// Engine names used in timing events
+pub const RUST_ENGINE: &str = "rust";
+pub const LEAN_ENGINE: &str = "lean";
+/// Engine name for timings that cover both engines (e.g., [`TOTAL`])
+pub const DRT_ENGINE: &str = "drt";
+
+/// A structured timing measurement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimingEvent {
+    /// the fuzz target (or other executable) that took the measurement
+    pub target: String,
+    /// what was timed, e.g., [`AUTHORIZATION`]
+    pub operation: String,
+    /// which implementation was timed, e.g., [`RUST_ENGINE`]
+    pub engine: String,
+    /// the duration in nanoseconds
+    pub duration_ns: u64,
+    /// size features of the input, e.g., the number of policies
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, u64>,
+}
+
+impl TimingEvent {
+    /// A timing event for the current executable, with no input features
+    pub fn new(operation: &str, engine: &str, duration: Duration) -> Self {
+        Self {
+            target: current_target(),
+            operation: operation.to_string(),
+            engine: engine.to_string(),
+            duration_ns: u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX),
+            features: BTreeMap::new(),
+        }
+    }
+
+    /// Add an input size feature
+    pub fn with_feature(mut self, name: &str, value: usize) -> Self {
+        self.features.insert(name.to_string(), value as u64);
+        self
+    }
+}
+
+/// The name of the current executable, which for fuzz targets is the target name
+fn current_target() -> String {
+    static TARGET: OnceLock<String> = OnceLock::new();
+    TARGET
+        .get_or_init(|| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+/// Sinks for timing events
+struct TimingSinks {
+    /// the `TIMING_LOG` file, opened on first use
+    log: Option<Option<File>>,
+    /// events collected in-process, if aggregation is enabled
+    aggregated: Option<Vec<TimingEvent>>,
+}
+
+static TIMING_SINKS: Mutex<TimingSinks> = Mutex::new(TimingSinks {
+    log: None,
+    aggregated: None,
+});
+
+thread_local! {
+    /// Whether timing events are currently dropped (see [`without_timing`])
+    static TIMING_DISABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn timing_sinks() -> MutexGuard<'static, TimingSinks> {
+    TIMING_SINKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Record a timing event: append it to `TIMING_LOG` (if set), and keep it
+/// in-process if aggregation is enabled
+pub fn record_timing(event: TimingEvent) {
+    if TIMING_DISABLED.get() {
+        return;
+    }
+    let mut sinks = timing_sinks();
+    let log = sinks.log.get_or_insert_with(|| {
+        let filename = std::env::var_os(TIMING_LOG)?;
+        match OpenOptions::new().create(true).append(true).open(&filename) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                warn!("could not open {}: {e}", filename.to_string_lossy());
+                None
+            }
+        }
+    });
+    if let Some(file) = log {
+        let mut line = serde_json::to_string(&event).expect("failed to serialize timing event");
+        line.push('\n');
+        if let Err(e) = file.write_all(line.as_bytes()) {
+            warn!("could not write timing event: {e}");
+        }
+    }
+    if let Some(aggregated) = &mut sinks.aggregated {
+        aggregated.push(event);
+    }
+}
+
+/// Run `f` without recording the timing events of the current thread, e.g.,
+/// while re-running a failing test case to shrink it
+pub fn without_timing<T>(f: impl FnOnce() -> T) -> T {
+    let disabled = TIMING_DISABLED.replace(true);
+    let result = f();
+    TIMING_DISABLED.set(disabled);
+    result
+}
+
+/// Start collecting timing events in-process (see [`take_timing_events`])
+pub fn enable_timing_aggregation() {
+    timing_sinks().aggregated.get_or_insert_with(Vec::new);
+}
+
+/// The timing events collected since aggregation was enabled or events were
+/// last taken
+pub fn take_timing_events() -> Vec<TimingEvent> {
+    timing_sinks()
+        .aggregated
+        .as_mut()
+        .map(std::mem::take)
+        .unwrap_or_default()
+}
+
+/// Read timing events from a JSON-lines file written through `TIMING_LOG`
+pub fn read_timing_events(filename: impl AsRef<Path>) -> std::io::Result<Vec<TimingEvent>> {
+    BufReader::new(File::open(filename)?)
+        .lines()
+        .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+/// Summary statistics (in nanoseconds) for one operation of one engine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimingSummary {
+    pub target: String,
+    pub operation: String,
+    pub engine: String,
+    pub count: usize,
+    pub median_ns: u64,
+    pub p90_ns: u64,
+    pub p99_ns: u64,
+}
+
+impl std::fmt::Display for TimingSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}: n = {}, median = {} ns, p90 = {} ns, p99 = {} ns",
+            self.target,
+            self.operation,
+            self.engine,
+            self.count,
+            self.median_ns,
+            self.p90_ns,
+            self.p99_ns
+        )
+    }
+}
+
+/// The `p`th percentile of `sorted` (nearest-rank); `sorted` must be non-empty
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Summarize timing events per (target, operation, engine), in that order
+pub fn summarize_timings<'a>(
+    events: impl IntoIterator<Item = &'a TimingEvent>,
+) -> Vec<TimingSummary> {
+    let mut durations: BTreeMap<(&str, &str, &str), Vec<u64>> = BTreeMap::new();
+    for event in events {
+        durations
+            .entry((&event.target, &event.operation, &event.engine))
+            .or_default()
+            .push(event.duration_ns);
+    }
+    durations
+        .into_iter()
+        .map(|((target, operation, engine), mut durations)| {
+            durations.sort_unstable();
+            TimingSummary {
+                target: target.to_string(),
+                operation: operation.to_string(),
+                engine: engine.to_string(),
+                count: durations.len(),
+                median_ns: percentile(&durations, 50),
+                p90_ns: percentile(&durations, 90),
+                p99_ns: percentile(&durations, 99),
+            }
+        })
+        .collect()
+}
+
+/// A summary whose median or p90 exceeds the baseline by more than the allowed
+/// tolerance
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingRegression {
+    pub baseline: TimingSummary,
+    pub current: TimingSummary,
+}
+
+impl std::fmt::Display for TimingRegression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}: median {} -> {} ns, p90 {} -> {} ns",
+            self.current.target,
+            self.current.operation,
+            self.current.engine,
+            self.baseline.median_ns,
+            self.current.median_ns,
+            self.baseline.p90_ns,
+            self.current.p90_ns
+        )
+    }
+}
+
+/// Compare `current` against `baseline`, flagging every operation whose median
+/// or p90 grew by more than `tolerance` (e.g., `0.2` for 20%). Operations
+/// missing from the baseline are not flagged.
+pub fn find_timing_regressions(
+    baseline: &[TimingSummary],
+    current: &[TimingSummary],
+    tolerance: f64,
+) -> Vec<TimingRegression> {
+    let exceeds =
+        |baseline: u64, current: u64| current as f64 > baseline as f64 * (1.0 + tolerance);
+    current
+        .iter()
+        .filter_map(|current| {
+            let baseline = baseline.iter().find(|b| {
+                b.target == current.target
+                    && b.operation == current.operation
+                    && b.engine == current.engine
+            })?;
+            (exceeds(baseline.median_ns, current.median_ns)
+                || exceeds(baseline.p90_ns, current.p90_ns))
+            .then(|| TimingRegression {
+                baseline: baseline.clone(),
+                current: current.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn event(operation: &str, duration_ns: u64) -> TimingEvent {
+        TimingEvent {
+            target: "abac".to_string(),
+            operation: operation.to_string(),
+            engine: RUST_ENGINE.to_string(),
+            duration_ns,
+            features: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn summarize_and_compare() {
+        let events: Vec<_> = (1..=100)
+            .map(|i| event(AUTHORIZATION, i))
+            .chain([event(VALIDATION, 7)])
+            .collect();
+        let summaries = summarize_timings(&events);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(
+            (
+                summaries[0].count,
+                summaries[0].median_ns,
+                summaries[0].p90_ns,
+                summaries[0].p99_ns
+            ),
+            (100, 50, 90, 99)
+        );
+        assert_eq!((summaries[1].count, summaries[1].p99_ns), (1, 7));
+
+        let slower = summarize_timings(
+            &events
+                .iter()
+                .map(|e| event(&e.operation, e.duration_ns * 2))
+                .collect::<Vec<_>>(),
+        );
+        let regressions = find_timing_regressions(&summaries, &slower, 0.5);
+        assert_eq!(regressions.len(), 2);
+        assert!(find_timing_regressions(&summaries, &summaries, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generator quality telemetry for fuzz targets.
+//!
+//! Extends the histograms recorded by `cedar_policy_generators::metrics` with
+//! the outcomes of validation and authorization, and writes all of them to
+//! `$METRICS_DIR/<target>.json`. Everything here is a no-op unless the
+//! `metrics` feature is enabled.
+
+use cedar_policy::{Decision, Response};
+use cedar_policy_generators::metrics::{self, record, snapshot};
+use log::warn;
+use serde::Serialize;
+use std::cell::Cell;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+/// Directory to write metrics to. If unset, metrics are recorded but not written.
+pub const METRICS_DIR: &str = "METRICS_DIR";
+/// Number of inputs between writes of the metrics file (default 1000)
+pub const METRICS_INTERVAL: &str = "METRICS_INTERVAL";
+
+/// Histogram of validation outcomes
+pub const VALIDATION: &str = "validation";
+/// Histogram of authorization decisions
+pub const AUTHORIZATION: &str = "authorization";
+/// Histogram of the outcomes of the SymCC checks run to synthesize requests
+pub const SOLVER_CHECKS: &str = "solver_checks";
+
+/// Number of inputs the current fuzz target has run
+static INPUTS: AtomicU64 = AtomicU64::new(0);
+
+/// The target whose metrics are written when the process exits
+static TARGET: OnceLock<String> = OnceLock::new();
+
+thread_local! {
+    /// Whether outcomes are currently dropped (see [`without_metrics`])
+    static METRICS_DISABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The contents of a metrics file
+#[derive(Debug, Serialize)]
+struct MetricsFile<'a> {
+    target: &'a str,
+    inputs: u64,
+    histograms: metrics::Metrics,
+}
+
+/// Record whether a policy set passed validation
+pub fn record_validation(passed: bool) {
+    if METRICS_DISABLED.get() {
+        return;
+    }
+    record(VALIDATION, if passed { "passed" } else { "failed" });
+}
+
+/// Record the decision of `response`, and whether any policy errored
+pub fn record_authorization(response: &Response) {
+    if METRICS_DISABLED.get() {
+        return;
+    }
+    record(
+        AUTHORIZATION,
+        match response.decision() {
+            Decision::Allow => "allow",
+            Decision::Deny => "deny",
+        },
+    );
+    if response.diagnostics().errors().next().is_some() {
+        record(AUTHORIZATION, "errored");
+    }
+}
+
+/// Record the outcome of a SymCC check run to synthesize a request, e.g.,
+/// `"counterexample"` or `"unknown"`
+pub fn record_solver_check(outcome: &str) {
+    if METRICS_DISABLED.get() {
+        return;
+    }
+    record(SOLVER_CHECKS, outcome);
+}
+
+/// Run `f` without recording validation, authorization or solver outcomes on
+/// the current thread, e.g., while re-running a failing test case to shrink it
+pub fn without_metrics<T>(f: impl FnOnce() -> T) -> T {
+    let disabled = METRICS_DISABLED.replace(true);
+    let result = f();
+    METRICS_DISABLED.set(disabled);
+    result
+}
+
+/// Record that `target` is running another input. Every `METRICS_INTERVAL`
+/// inputs, and when the process exits, the metrics are written with
+/// [`dump_metrics`].
+pub fn record_input(target: &str) {
+    if !cfg!(feature = "metrics") {
+        return;
+    }
+    TARGET.get_or_init(|| {
+        // SAFETY: `dump_metrics_at_exit` takes no arguments and doesn't unwind
+        unsafe { libc::atexit(dump_metrics_at_exit) };
+        target.to_string()
+    });
+    let inputs = INPUTS.fetch_add(1, Ordering::Relaxed) + 1;
+    let interval = std::env::var(METRICS_INTERVAL)
+        .ok()
+        .and_then(|i| i.parse().ok())
+        .unwrap_or(1000);
+    if interval != 0 && inputs % interval == 0 {
+        dump_metrics(target);
+    }
+}
+
+/// Write the metrics recorded so far to `$METRICS_DIR/<target>.json`, if
+/// `METRICS_DIR` is set
+pub fn dump_metrics(target: &str) {
+    if !cfg!(feature = "metrics") {
+        return;
+    }
+    if let Ok(dirname) = std::env::var(METRICS_DIR) {
+        if let Err(e) = write_metrics(Path::new(&dirname), target) {
+            warn!("could not write metrics to {dirname}: {e}");
+        }
+    }
+}
+
+/// Write the metrics of the current target when the process exits. libFuzzer
+/// ends the process with `exit`, so statics are never dropped and the inputs
+/// run since the last `METRICS_INTERVAL` would otherwise be lost.
+extern "C" fn dump_metrics_at_exit() {
+    if let Some(target) = TARGET.get() {
+        dump_metrics(target);
+    }
+}
+
+fn write_metrics(dirname: &Path, target: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dirname)?;
+    let contents = MetricsFile {
+        target,
+        inputs: INPUTS.load(Ordering::Relaxed),
+        histograms: snapshot(),
+    };
+    // Write to a temporary file first, so that readers never see a partial file
+    let filename = dirname.join(format!("{target}.json"));
+    let tmp_filename = dirname.join(format!("{target}.json.tmp"));
+    std::fs::write(&tmp_filename, serde_json::to_vec_pretty(&contents)?)?;
+    std::fs::rename(tmp_filename, filename)
+}
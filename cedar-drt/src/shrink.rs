@@ -0,0 +1,670 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Semantic minimization of DRT mismatches.
+//!
+//! libFuzzer minimizes failing inputs at the byte level, which often leaves
+//! large schemas and hierarchies behind. The shrinkers in this module instead
+//! work on the Cedar values themselves: they repeatedly try removing policies,
+//! conjuncts, entities, attributes, ancestors, tags, schema types, actions and
+//! context fields, and keep a change only if the disagreement between
+//! `cedar-policy` and the custom implementation persists.
+//!
+//! All values are edited in their JSON formats (policy ESTs, entity JSON, the
+//! JSON schema format and natural context JSON). A candidate that no longer
+//! parses, or that breaks a schema-conformance property the original test case
+//! had, is rejected.
+
+use crate::dump::{dump, dump_context, dump_validation};
+use crate::logger::without_timing;
+use crate::metrics::without_metrics;
+use crate::tests::{check_auth_test, check_val_test};
+
+use cedar_policy::{
+    Authorizer, Context, Entities, Policy, PolicyId, PolicySet, Request, Schema, SchemaFragment,
+    ValidationMode,
+};
+use cedar_testing::cedar_test_impl::CedarTestImplementation;
+
+use log::{debug, info};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Environment variable naming the directory to which shrunk test cases are dumped
+pub const SHRINK_TEST_DIR: &str = "SHRINK_TEST_DIR";
+/// Environment variable giving the prefix of the names of shrunk test cases
+/// (defaults to `shrunk`)
+pub const SHRINK_TEST_NAME: &str = "SHRINK_TEST_NAME";
+
+/// Number of shrunk test cases dumped by this process
+static SHRUNK_TESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// An authorization test case
+#[derive(Debug, Clone)]
+pub struct AuthTestCase {
+    /// schema (only used for dumping the test case)
+    pub schema: SchemaFragment,
+    /// policies; only static policies are shrunk
+    pub policies: PolicySet,
+    /// entities
+    pub entities: Entities,
+    /// request
+    pub request: Request,
+}
+
+/// A validation test case
+#[derive(Debug, Clone)]
+pub struct ValTestCase {
+    /// schema
+    pub schema: SchemaFragment,
+    /// policies; only static policies are shrunk
+    pub policies: PolicySet,
+    /// validation mode
+    pub mode: ValidationMode,
+}
+
+impl AuthTestCase {
+    /// Whether `custom_impl` and `cedar-policy` disagree on this test case
+    pub fn fails(&self, custom_impl: &impl CedarTestImplementation) -> bool {
+        check_auth_test(custom_impl, &self.request, &self.policies, &self.entities).is_err()
+    }
+
+    /// Shrink a failing test case to a (locally) minimal one that still fails
+    pub fn shrink(self, custom_impl: &impl CedarTestImplementation) -> Self {
+        let conformance = Conformance::of(&self);
+        let template_policies = templates_and_links(&self.policies);
+        let state = serde_json::json!({
+            "schema": self.schema.clone().to_json_value().unwrap_or(Value::Null),
+            "policies": static_policies_json(&self.policies),
+            "entities": self.entities.as_ref().to_json_value().unwrap_or(Value::Null),
+            "context": dump_context(self.request.context().cloned().unwrap_or_else(Context::empty)),
+        });
+        let principal = self.request.principal().cloned();
+        let action = self.request.action().cloned();
+        let resource = self.request.resource().cloned();
+        let to_case = |state: &Value| -> Option<Self> {
+            let schema = schema_of_json(state, &self.schema)?;
+            let validator_schema = Schema::try_from(schema.clone()).ok();
+            let entity_schema = validator_schema.as_ref().filter(|_| conformance.entities);
+            let request_schema = validator_schema.as_ref().filter(|_| conformance.request);
+            let policies = policies_of_json(state, &template_policies)?;
+            let entities =
+                Entities::from_json_value(state["entities"].clone(), entity_schema).ok()?;
+            let context = Context::from_json_value(state["context"].clone(), None).ok()?;
+            let request = Request::new(
+                principal.clone()?,
+                action.clone()?,
+                resource.clone()?,
+                context,
+                request_schema,
+            )
+            .ok()?;
+            let case = Self {
+                schema,
+                policies,
+                entities,
+                request,
+            };
+            (!(conformance.schema && validator_schema.is_none())).then_some(case)
+        };
+        match shrink_json(state, |state| {
+            to_case(state).is_some_and(|case| case.fails(custom_impl))
+        }) {
+            Some(state) => to_case(&state).unwrap_or(self),
+            None => self,
+        }
+    }
+
+    /// Dump the test case, including its templates and template-linked
+    /// policies, with [`dump`]
+    pub fn dump(&self, dirname: impl AsRef<Path>, testcasename: &str) -> std::io::Result<()> {
+        let response =
+            Authorizer::new().is_authorized(&self.request, &self.policies, &self.entities);
+        dump(
+            dirname,
+            testcasename,
+            &self.schema,
+            &self.policies,
+            &self.entities,
+            std::iter::once((self.request.clone(), response)),
+        )
+    }
+}
+
+impl ValTestCase {
+    /// Whether `custom_impl` and `cedar-policy` disagree on this test case
+    pub fn fails(&self, custom_impl: &impl CedarTestImplementation) -> bool {
+        match Schema::try_from(self.schema.clone()) {
+            Ok(schema) => check_val_test(custom_impl, schema, &self.policies, self.mode).is_err(),
+            Err(_) => false,
+        }
+    }
+
+    /// Shrink a failing test case to a (locally) minimal one that still fails
+    pub fn shrink(self, custom_impl: &impl CedarTestImplementation) -> Self {
+        let template_policies = templates_and_links(&self.policies);
+        let state = serde_json::json!({
+            "schema": self.schema.clone().to_json_value().unwrap_or(Value::Null),
+            "policies": static_policies_json(&self.policies),
+        });
+        let to_case = |state: &Value| -> Option<Self> {
+            Some(Self {
+                schema: schema_of_json(state, &self.schema)?,
+                policies: policies_of_json(state, &template_policies)?,
+                mode: self.mode,
+            })
+        };
+        match shrink_json(state, |state| {
+            to_case(state).is_some_and(|case| case.fails(custom_impl))
+        }) {
+            Some(state) => to_case(&state).unwrap_or(self),
+            None => self,
+        }
+    }
+
+    /// Dump the test case, including its templates and template-linked
+    /// policies, with [`dump_validation`]
+    pub fn dump(&self, dirname: impl AsRef<Path>, testcasename: &str) -> std::io::Result<()> {
+        dump_validation(
+            dirname,
+            testcasename,
+            &self.schema,
+            &self.policies,
+            self.mode,
+        )
+    }
+}
+
+/// Whether failing test cases should be shrunk and dumped, i.e., whether
+/// [`SHRINK_TEST_DIR`] is set
+pub fn shrinking_enabled() -> bool {
+    std::env::var_os(SHRINK_TEST_DIR).is_some()
+}
+
+/// If [`SHRINK_TEST_DIR`] is set, shrink `case`, on which `custom_impl` is
+/// known to disagree with `cedar-policy`, and dump it. The caller is still
+/// responsible for reporting the failure.
+pub fn dump_shrunk_auth_test(custom_impl: &impl CedarTestImplementation, case: &AuthTestCase) {
+    if let Ok(dirname) = std::env::var(SHRINK_TEST_DIR) {
+        let shrunk = case.clone().shrink(custom_impl);
+        shrunk
+            .dump(dirname, &shrink_test_name())
+            .expect("failed to dump shrunk test case");
+    }
+}
+
+/// If [`SHRINK_TEST_DIR`] is set, shrink `case`, on which `custom_impl` is
+/// known to disagree with `cedar-policy`, and dump it. The caller is still
+/// responsible for reporting the failure.
+pub fn dump_shrunk_val_test(custom_impl: &impl CedarTestImplementation, case: &ValTestCase) {
+    if let Ok(dirname) = std::env::var(SHRINK_TEST_DIR) {
+        let shrunk = case.clone().shrink(custom_impl);
+        shrunk
+            .dump(dirname, &shrink_test_name())
+            .expect("failed to dump shrunk test case");
+    }
+}
+
+/// A fresh name for a shrunk test case: [`SHRINK_TEST_NAME`] followed by the
+/// process id and a counter, so that a failure never overwrites the dump of
+/// an earlier one (even with several fuzzing jobs)
+fn shrink_test_name() -> String {
+    let prefix = std::env::var(SHRINK_TEST_NAME).unwrap_or_else(|_| "shrunk".to_string());
+    let n = SHRUNK_TESTS.fetch_add(1, Ordering::Relaxed);
+    format!("{prefix}-{}-{n}", std::process::id())
+}
+
+/// Which schema-conformance properties the original authorization test case
+/// had. Shrinking preserves them so that the shrunk case can still be dumped.
+struct Conformance {
+    schema: bool,
+    entities: bool,
+    request: bool,
+}
+
+impl Conformance {
+    fn of(case: &AuthTestCase) -> Self {
+        match Schema::try_from(case.schema.clone()) {
+            Ok(schema) => Self {
+                schema: true,
+                entities: Entities::from_entities(case.entities.iter().cloned(), Some(&schema))
+                    .is_ok(),
+                request: case.request.context().is_some_and(|context| {
+                    Request::new(
+                        case.request.principal().cloned().unwrap(),
+                        case.request.action().cloned().unwrap(),
+                        case.request.resource().cloned().unwrap(),
+                        context.clone(),
+                        Some(&schema),
+                    )
+                    .is_ok()
+                }),
+            },
+            Err(_) => Self {
+                schema: false,
+                entities: false,
+                request: false,
+            },
+        }
+    }
+}
+
+/// The ESTs of the static policies in `policies`, with their ids
+fn static_policies_json(policies: &PolicySet) -> Value {
+    Value::Array(
+        policies
+            .policies()
+            .filter(|p| p.is_static())
+            .filter_map(|p| {
+                Some(serde_json::json!({"id": p.id().to_string(), "est": p.to_json().ok()?}))
+            })
+            .collect(),
+    )
+}
+
+/// The templates and template-linked policies in `policies`, which are kept as is
+fn templates_and_links(policies: &PolicySet) -> PolicySet {
+    let mut result = PolicySet::new();
+    for template in policies.templates() {
+        result
+            .add_template(template.clone())
+            .expect("template ids are unique");
+    }
+    for policy in policies.policies().filter(|p| !p.is_static()) {
+        result
+            .link(
+                policy
+                    .template_id()
+                    .expect("linked policy has a template")
+                    .clone(),
+                policy.id().clone(),
+                policy.template_links().unwrap_or_default(),
+            )
+            .expect("links are valid");
+    }
+    result
+}
+
+fn policies_of_json(state: &Value, template_policies: &PolicySet) -> Option<PolicySet> {
+    let mut policies = template_policies.clone();
+    for policy in state["policies"].as_array()? {
+        let id = PolicyId::new(policy["id"].as_str()?);
+        policies
+            .add(Policy::from_json(Some(id), policy["est"].clone()).ok()?)
+            .ok()?;
+    }
+    Some(policies)
+}
+
+fn schema_of_json(state: &Value, original: &SchemaFragment) -> Option<SchemaFragment> {
+    match &state["schema"] {
+        // The schema could not be converted to JSON, so it is not shrunk
+        Value::Null => Some(original.clone()),
+        json => SchemaFragment::from_json_value(json.clone()).ok(),
+    }
+}
+
+/// A single reduction step on the JSON state of a test case
+#[derive(Debug)]
+enum Edit {
+    /// Remove the array element or object member at the JSON pointer
+    Remove(String),
+    /// Replace the value at the JSON pointer
+    Replace(String, Value),
+}
+
+impl Edit {
+    fn apply(&self, state: &mut Value) -> bool {
+        match self {
+            Self::Remove(pointer) => {
+                let Some((parent, token)) = pointer.rsplit_once('/') else {
+                    return false;
+                };
+                let token = token.replace("~1", "/").replace("~0", "~");
+                match state.pointer_mut(parent) {
+                    Some(Value::Array(elems)) => match token.parse::<usize>() {
+                        Ok(i) if i < elems.len() => {
+                            elems.remove(i);
+                            true
+                        }
+                        _ => false,
+                    },
+                    Some(Value::Object(members)) => members.remove(&token).is_some(),
+                    _ => false,
+                }
+            }
+            Self::Replace(pointer, value) => match state.pointer_mut(pointer) {
+                Some(old) => {
+                    *old = value.clone();
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+/// Greedily apply edits to `state` as long as `still_fails` holds for the
+/// result. Returns `None` if no edit could be applied. The candidates are run
+/// without recording timing events or metrics, so that shrinking does not skew
+/// them.
+fn shrink_json(mut state: Value, mut still_fails: impl FnMut(&Value) -> bool) -> Option<Value> {
+    without_timing(|| {
+        without_metrics(|| {
+            let mut steps = 0;
+            'outer: loop {
+                for edit in edits(&state) {
+                    let mut candidate = state.clone();
+                    if edit.apply(&mut candidate) && still_fails(&candidate) {
+                        debug!("Shrinking step {steps}: {edit:?}");
+                        state = candidate;
+                        steps += 1;
+                        continue 'outer;
+                    }
+                }
+                break;
+            }
+            info!("Shrinking finished after {steps} steps");
+            (steps > 0).then_some(state)
+        })
+    })
+}
+
+/// The JSON pointer tokens of the children of the array or object at `pointer`
+fn children(state: &Value, pointer: &str) -> Vec<String> {
+    match state.pointer(pointer) {
+        Some(Value::Array(elems)) => (0..elems.len()).map(|i| i.to_string()).collect(),
+        Some(Value::Object(members)) => members
+            .keys()
+            .map(|k| k.replace('~', "~0").replace('/', "~1"))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// All single-step reductions of `state`, coarsest first
+fn edits(state: &Value) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let mut remove_children = |pointer: &str| {
+        for child in children(state, pointer) {
+            edits.push(Edit::Remove(format!("{pointer}/{child}")));
+        }
+    };
+
+    // Whole policies, entities, entity types and actions
+    remove_children("/policies");
+    remove_children("/entities");
+    let namespaces = children(state, "/schema");
+    for ns in &namespaces {
+        remove_children(&format!("/schema/{ns}/entityTypes"));
+        remove_children(&format!("/schema/{ns}/actions"));
+        remove_children(&format!("/schema/{ns}/commonTypes"));
+    }
+
+    // Conditions
+    for i in children(state, "/policies") {
+        remove_children(&format!("/policies/{i}/est/conditions"));
+    }
+
+    // Attributes, ancestors, tags, and context fields
+    for i in children(state, "/entities") {
+        remove_children(&format!("/entities/{i}/attrs"));
+        remove_children(&format!("/entities/{i}/parents"));
+        remove_children(&format!("/entities/{i}/tags"));
+    }
+    for ns in &namespaces {
+        for ety in children(state, &format!("/schema/{ns}/entityTypes")) {
+            let ety = format!("/schema/{ns}/entityTypes/{ety}");
+            remove_children(&format!("{ety}/memberOfTypes"));
+            remove_children(&format!("{ety}/shape/attributes"));
+        }
+        for action in children(state, &format!("/schema/{ns}/actions")) {
+            let action = format!("/schema/{ns}/actions/{action}");
+            remove_children(&format!("{action}/memberOf"));
+            remove_children(&format!("{action}/appliesTo/context/attributes"));
+        }
+    }
+    remove_children("/context");
+
+    // Conjuncts: replace `a && b` by `a` or by `b`
+    for i in children(state, "/policies") {
+        for j in children(state, &format!("/policies/{i}/est/conditions")) {
+            let body = format!("/policies/{i}/est/conditions/{j}/body");
+            if let Some(expr) = state.pointer(&body) {
+                conjunct_edits(expr, &body, &mut edits);
+            }
+        }
+    }
+    edits
+}
+
+/// Edits replacing each `&&` node of the EST expression `expr` (at `pointer`) by one of its operands
+fn conjunct_edits(expr: &Value, pointer: &str, edits: &mut Vec<Edit>) {
+    match expr {
+        Value::Object(members) => {
+            if let Some(and) = members.get("&&") {
+                for operand in ["left", "right"] {
+                    if let Some(operand) = and.get(operand) {
+                        edits.push(Edit::Replace(pointer.to_string(), operand.clone()));
+                    }
+                }
+            }
+            for (k, v) in members {
+                let k = k.replace('~', "~0").replace('/', "~1");
+                conjunct_edits(v, &format!("{pointer}/{k}"), edits);
+            }
+        }
+        Value::Array(elems) => {
+            for (i, v) in elems.iter().enumerate() {
+                conjunct_edits(v, &format!("{pointer}/{i}"), edits);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cedar_policy::{Effect, EvalResult, Expression};
+    use cedar_testing::cedar_test_impl::{
+        ErrorComparisonMode, RustEngine, TestResponse, TestResult, TestValidationResult,
+        ValidationComparisonMode,
+    };
+    use std::str::FromStr;
+
+    /// `cedar-policy`, except that it ignores `forbid` policies when authorizing
+    struct IgnoresForbid(RustEngine);
+
+    impl CedarTestImplementation for IgnoresForbid {
+        fn is_authorized(
+            &self,
+            request: &Request,
+            policies: &PolicySet,
+            entities: &Entities,
+        ) -> TestResult<TestResponse> {
+            let permits = PolicySet::from_policies(
+                policies
+                    .policies()
+                    .filter(|p| p.effect() == Effect::Permit)
+                    .cloned(),
+            )
+            .unwrap();
+            self.0.is_authorized(request, &permits, entities)
+        }
+
+        fn interpret(
+            &self,
+            request: &Request,
+            entities: &Entities,
+            expr: &Expression,
+            expected: Option<EvalResult>,
+        ) -> TestResult<bool> {
+            self.0.interpret(request, entities, expr, expected)
+        }
+
+        fn validate(
+            &self,
+            schema: &Schema,
+            policies: &PolicySet,
+            mode: ValidationMode,
+        ) -> TestResult<TestValidationResult> {
+            self.0.validate(schema, policies, mode)
+        }
+
+        fn validate_with_level(
+            &self,
+            schema: &Schema,
+            policies: &PolicySet,
+            mode: ValidationMode,
+            level: i32,
+        ) -> TestResult<TestValidationResult> {
+            self.0.validate_with_level(schema, policies, mode, level)
+        }
+
+        fn validate_request(
+            &self,
+            schema: &Schema,
+            request: &Request,
+        ) -> TestResult<TestValidationResult> {
+            self.0.validate_request(schema, request)
+        }
+
+        fn validate_entities(
+            &self,
+            schema: &Schema,
+            entities: &Entities,
+        ) -> TestResult<TestValidationResult> {
+            self.0.validate_entities(schema, entities)
+        }
+
+        fn error_comparison_mode(&self) -> ErrorComparisonMode {
+            ErrorComparisonMode::PolicyIds
+        }
+
+        fn validation_comparison_mode(&self) -> ValidationComparisonMode {
+            ValidationComparisonMode::AgreeOnAll
+        }
+    }
+
+    #[test]
+    fn shrink_auth_test_case() {
+        let (schema, _) = SchemaFragment::from_cedarschema_str(
+            r#"
+            entity User { age: Long };
+            entity Photo;
+            action view appliesTo {
+                principal: User,
+                resource: Photo,
+                context: { a: Bool, b: Long },
+            };
+            "#,
+        )
+        .unwrap();
+        let validator_schema = Schema::try_from(schema.clone()).unwrap();
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { context.a && context.b > 1 };
+            permit(principal == User::"bob", action, resource);
+            forbid(principal, action, resource) when { context.b > 0 && principal.age > 3 };
+            forbid(principal, action, resource) when { resource == Photo::"x" };
+            "#,
+        )
+        .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {"uid": {"type": "User", "id": "alice"}, "attrs": {"age": 5}, "parents": []},
+                {"uid": {"type": "User", "id": "bob"}, "attrs": {"age": 7}, "parents": []},
+                {"uid": {"type": "Photo", "id": "x"}, "attrs": {}, "parents": []},
+            ]),
+            Some(&validator_schema),
+        )
+        .unwrap();
+        let request = Request::new(
+            r#"User::"alice""#.parse().unwrap(),
+            r#"Action::"view""#.parse().unwrap(),
+            r#"Photo::"y""#.parse().unwrap(),
+            Context::from_json_value(serde_json::json!({"a": true, "b": 2}), None).unwrap(),
+            Some(&validator_schema),
+        )
+        .unwrap();
+        let case = AuthTestCase {
+            schema,
+            policies,
+            entities,
+            request,
+        };
+        let engine = IgnoresForbid(RustEngine::new());
+        assert!(case.fails(&engine));
+
+        let shrunk = case.shrink(&engine);
+        assert!(shrunk.fails(&engine));
+        // A single unconditional `forbid` policy is enough for the engines to
+        // disagree, and no entities are needed
+        let policies: Vec<_> = shrunk.policies.policies().collect();
+        assert_eq!(policies.len(), 1);
+        assert_eq!(policies[0].effect(), Effect::Forbid);
+        assert_eq!(
+            policies[0].to_json().unwrap()["conditions"],
+            serde_json::json!([])
+        );
+        assert_eq!(shrunk.entities.iter().count(), 0);
+    }
+
+    #[test]
+    fn edit_apply() {
+        let mut state = serde_json::json!({"a": [1, 2, 3], "b": {"c/d": true}});
+        assert!(Edit::Remove("/a/1".to_string()).apply(&mut state));
+        assert!(Edit::Remove("/b/c~1d".to_string()).apply(&mut state));
+        assert!(!Edit::Remove("/a/5".to_string()).apply(&mut state));
+        assert!(Edit::Replace("/a/0".to_string(), Value::Null).apply(&mut state));
+        assert_eq!(state, serde_json::json!({"a": [null, 3], "b": {}}));
+    }
+
+    #[test]
+    fn shrink_conjuncts() {
+        let policy = Policy::parse(
+            None,
+            "permit(principal, action, resource) when { context.a && context.b && context.c };",
+        )
+        .unwrap();
+        let state = serde_json::json!({
+            "policies": [{"id": "p", "est": policy.to_json().unwrap()}],
+            "context": {"a": true, "b": true, "c": true},
+        });
+        // The "failure" persists as long as the policy mentions `context.b`
+        let shrunk = shrink_json(state, |state| {
+            serde_json::to_string(&state["policies"])
+                .unwrap()
+                .contains(r#""attr":"b""#)
+        })
+        .expect("shrinking should make progress");
+        let policies = policies_of_json(&shrunk, &PolicySet::new()).unwrap();
+        let policy = policies.policy(&PolicyId::new("p")).unwrap();
+        assert_eq!(
+            policy.to_json().unwrap(),
+            Policy::parse(
+                None,
+                "permit(principal, action, resource) when { context.b };"
+            )
+            .unwrap()
+            .to_json()
+            .unwrap()
+        );
+        assert_eq!(shrunk["context"], serde_json::json!({}));
+    }
+}
@@ -14,6 +14,12 @@
  * limitations under the License.
  */
 
+use crate::logger::{
+    record_timing, TimingEvent, AUTHORIZATION, ENTITY_VALIDATION, LEVEL_VALIDATION,
+    REQUEST_VALIDATION, RUST_ENGINE, VALIDATION,
+};
+use crate::metrics;
+
 use cedar_testing::cedar_test_impl::{
     time_function, CedarTestImplementation, ErrorComparisonMode, TestResult, TestValidationResult,
     ValidationComparisonMode,
@@ -78,10 +84,29 @@ pub fn run_auth_test(
     policies: &PolicySet,
     entities: &Entities,
 ) -> Response {
+    let response = check_auth_test(custom_impl, request, policies, entities)
+        .unwrap_or_else(|msg| panic!("{msg}"));
+    metrics::record_authorization(&response);
+    response
+}
+
+/// Like [`run_auth_test`], but returns a description of the disagreement
+/// instead of panicking
+pub fn check_auth_test(
+    custom_impl: &impl CedarTestImplementation,
+    request: &Request,
+    policies: &PolicySet,
+    entities: &Entities,
+) -> Result<Response, String> {
     let authorizer = Authorizer::new();
     let (rust_res, rust_auth_dur) =
         time_function(|| authorizer.is_authorized(request, policies, entities));
     info!("{}{}", RUST_AUTH_MSG, rust_auth_dur.as_nanos());
+    record_timing(
+        TimingEvent::new(AUTHORIZATION, RUST_ENGINE, rust_auth_dur)
+            .with_feature("policies", policies.num_of_policies())
+            .with_feature("entities", entities.iter().count()),
+    );
 
     let definitional_res = custom_impl.is_authorized(&request, policies, entities);
 
@@ -90,13 +115,13 @@ pub fn run_auth_test(
             // TODO(#175): For now, ignore cases where the Lean code returned an error due to
             // an unknown extension function.
             if err.contains("unknown extension function") {
-                rust_res
+                Ok(rust_res)
             } else {
-                panic!(
+                Err(format!(
                     "Unexpected error for {request}\nPolicies:\n{}\nEntities:\n{}\nError: {err}",
                     &policies,
                     &entities.as_ref()
-                );
+                ))
             }
         }
         TestResult::Success(definitional_res) => {
@@ -129,13 +154,16 @@ pub fn run_auth_test(
                     errors,
                 )
             };
-            assert_eq!(
-                rust_res_for_comparison,
-                definitional_res.response,
-                "Mismatch for {request}\nPolicies:\n{policies}\nEntities:\n{}",
-                entities.as_ref()
-            );
-            rust_res
+            if rust_res_for_comparison == definitional_res.response {
+                Ok(rust_res)
+            } else {
+                Err(format!(
+                    "Mismatch for {request}\nPolicies:\n{policies}\nEntities:\n{}\ncedar-policy response: {:?}\nTest engine response: {:?}\n",
+                    entities.as_ref(),
+                    rust_res_for_comparison,
+                    definitional_res.response,
+                ))
+            }
         }
     }
 }
@@ -148,9 +176,27 @@ pub fn run_val_test(
     policies: &PolicySet,
     mode: ValidationMode,
 ) {
+    let passed =
+        check_val_test(custom_impl, schema, policies, mode).unwrap_or_else(|msg| panic!("{msg}"));
+    metrics::record_validation(passed);
+}
+
+/// Like [`run_val_test`], but returns a description of the disagreement
+/// instead of panicking. On agreement, returns whether the policies validated.
+pub fn check_val_test(
+    custom_impl: &impl CedarTestImplementation,
+    schema: Schema,
+    policies: &PolicySet,
+    mode: ValidationMode,
+) -> Result<bool, String> {
     let validator = Validator::new(schema.clone());
     let (rust_res, rust_validation_dur) = time_function(|| validator.validate(policies, mode));
     info!("{}{}", RUST_VALIDATION_MSG, rust_validation_dur.as_nanos());
+    record_timing(
+        TimingEvent::new(VALIDATION, RUST_ENGINE, rust_validation_dur)
+            .with_feature("policies", policies.num_of_policies()),
+    );
+    let passed = rust_res.validation_passed();
     let definitional_res = custom_impl.validate(&schema, policies, mode);
     compare_validation_results(
         policies,
@@ -158,7 +204,8 @@ pub fn run_val_test(
         custom_impl.validation_comparison_mode(),
         rust_res,
         definitional_res,
-    );
+    )?;
+    Ok(passed)
 }
 
 pub fn run_level_val_test(
@@ -172,6 +219,11 @@ pub fn run_level_val_test(
     let (rust_res, rust_validation_dur) =
         time_function(|| validator.validate_with_level(policies, mode, level as u32));
     info!("{}{}", RUST_VALIDATION_MSG, rust_validation_dur.as_nanos());
+    record_timing(
+        TimingEvent::new(LEVEL_VALIDATION, RUST_ENGINE, rust_validation_dur)
+            .with_feature("policies", policies.num_of_policies())
+            .with_feature("level", level.max(0) as usize),
+    );
     let definitional_res = custom_impl.validate_with_level(&schema, policies, mode, level);
     compare_validation_results(
         policies,
@@ -179,7 +231,8 @@ pub fn run_level_val_test(
         custom_impl.validation_comparison_mode(),
         rust_res,
         definitional_res,
-    );
+    )
+    .unwrap_or_else(|msg| panic!("{msg}"))
 }
 
 fn compare_validation_results(
@@ -188,7 +241,7 @@ fn compare_validation_results(
     comparison_mode: ValidationComparisonMode,
     rust_res: ValidationResult,
     definitional_res: TestResult<TestValidationResult>,
-) {
+) -> Result<(), String> {
     match definitional_res {
         TestResult::Failure(err) => {
             // TODO(#175): For now, ignore cases where the Lean code returned an error due to
@@ -196,10 +249,10 @@ fn compare_validation_results(
             if !err.contains("unknown extension function")
                 && !err.contains("unknown extension type")
             {
-                panic!(
+                return Err(format!(
                     "Unexpected error\nPolicies:\n{}\nSchema:\n{:?}\nError: {err}",
                     &policies, schema
-                );
+                ));
             }
         }
         TestResult::Success(definitional_res) => {
@@ -212,33 +265,36 @@ fn compare_validation_results(
                 // then there are no authorization-time errors modulo some restrictions (C). So (B) ==> (C).
                 // DRT checks that if the `cedar-policy` validator does not return an error (A), then neither
                 // does the spec validator (B). So (A) ==> (B). By transitivity then, (A) ==> (C).
-                assert!(
-                    definitional_res.validation_passed(),
-                    "Mismatch for Policies:\n{}\nSchema:\n{:?}\ncedar-policy response: {:?}\nTest engine response: {:?}\n",
-                    &policies,
-                    schema,
-                    rust_res,
-                    definitional_res,
-                );
+                if !definitional_res.validation_passed() {
+                    return Err(format!(
+                        "Mismatch for Policies:\n{}\nSchema:\n{:?}\ncedar-policy response: {:?}\nTest engine response: {:?}\n",
+                        &policies,
+                        schema,
+                        rust_res,
+                        definitional_res,
+                    ));
+                }
             } else {
                 // If `cedar-policy` returns an error, then only check the spec response
                 // if the validation comparison mode is `AgreeOnAll`.
                 match comparison_mode {
                     ValidationComparisonMode::AgreeOnAll => {
-                        assert!(
-                            !definitional_res.validation_passed(),
-                            "Mismatch for Policies:\n{}\nSchema:\n{:?}\ncedar-policy response: {:?}\nTest engine response: {:?}\n",
-                            &policies,
-                            schema,
-                            rust_res,
-                            definitional_res,
-                        );
+                        if definitional_res.validation_passed() {
+                            return Err(format!(
+                                "Mismatch for Policies:\n{}\nSchema:\n{:?}\ncedar-policy response: {:?}\nTest engine response: {:?}\n",
+                                &policies,
+                                schema,
+                                rust_res,
+                                definitional_res,
+                            ));
+                        }
                     }
                     ValidationComparisonMode::AgreeOnValid => {} // ignore
                 };
             }
         }
     }
+    Ok(())
 }
 
 pub fn run_ent_val_test(
@@ -249,6 +305,10 @@ pub fn run_ent_val_test(
     let (rust_res, rust_auth_dur) =
         time_function(|| Entities::from_entities(entities.iter().cloned(), Some(&schema)));
     info!("{}{}", RUST_ENT_VALIDATION_MSG, rust_auth_dur.as_nanos());
+    record_timing(
+        TimingEvent::new(ENTITY_VALIDATION, RUST_ENGINE, rust_auth_dur)
+            .with_feature("entities", entities.iter().count()),
+    );
     match custom_impl.validate_entities(&schema, &entities) {
         TestResult::Failure(e) => {
             panic!("failed to execute entity validation: {e}");
@@ -287,6 +347,11 @@ pub fn run_req_val_test(
         )
     });
     info!("{}{}", RUST_REQ_VALIDATION_MSG, rust_auth_dur.as_nanos());
+    record_timing(TimingEvent::new(
+        REQUEST_VALIDATION,
+        RUST_ENGINE,
+        rust_auth_dur,
+    ));
 
     match custom_impl.validate_request(&schema, &request) {
         TestResult::Failure(e) => {
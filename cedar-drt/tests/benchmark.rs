@@ -16,24 +16,77 @@
 
 //! Run the standard and definitional implementations of Cedar on the integration
 //! tests are record performance results.
+//!
+//! Besides printing a summary, the results can be written as timing events
+//! (see `cedar_drt::logger`) to the file named by `BENCHMARK_OUTPUT`, one event
+//! per input with the median over `NUM_TRIALS` trials. Compare two runs with
+//! `cargo run --bin summarize_timings -- <events> --baseline <summary>`.
 
 #![cfg(feature = "integration-testing")]
 
 mod integration_tests;
 
+use cedar_drt::logger::{
+    TimingEvent, AUTHORIZATION, ENTITY_VALIDATION, LEAN_ENGINE, LEVEL_VALIDATION,
+    REQUEST_VALIDATION, RUST_ENGINE, VALIDATION,
+};
 use cedar_drt::CedarLeanEngine;
-use cedar_policy::{Entities, PolicySet, Request, Schema, ValidationMode};
-use cedar_testing::cedar_test_impl::{time_function, CedarTestImplementation, RustEngine};
+use cedar_lean_ffi::{CedarLeanFfi, SolverConfig, SolverKind, TimedResult};
+use cedar_policy::{Entities, PolicySet, Request, RequestEnv, Schema, ValidationMode};
+use cedar_policy_generators::{
+    scale::{ScaleSettings, ScaleWorkload},
+    settings::ABACSettings,
+};
+use cedar_policy_symcc::{
+    compile_always_allows, compile_always_denies, compile_disjoint, compile_equivalent,
+    compile_implies, compile_never_errors, err::Result as SymCCResult, solver::LocalSolver,
+    CedarSymCompiler, SymEnv, WellFormedAsserts, WellTypedPolicies, WellTypedPolicy,
+};
+use cedar_testing::cedar_test_impl::{
+    time_function, CedarTestImplementation, Micros, RustEngine, TestResult,
+};
 use cedar_testing::integration_testing::{
     parse_entities_from_test, parse_policies_from_test, parse_request_from_test,
     parse_schema_from_test, resolve_integration_test_path, JsonTest,
 };
 use integration_tests::get_corpus_tests;
 use statrs::statistics::{Data, OrderStatistics};
-use std::{collections::HashMap, path::Path};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 const NUM_TRIALS: u32 = 10;
 
+/// Validation level used for the `level_validation` benchmark
+const VALIDATION_LEVEL: i32 = 1;
+
+/// Environment variable naming the file to write timing events to
+const BENCHMARK_OUTPUT: &str = "BENCHMARK_OUTPUT";
+
+/// Environment variable giving the number of entities of the scale benchmark
+const SCALE_ENTITIES: &str = "SCALE_ENTITIES";
+
+/// Seed of the workload of the scale benchmark
+const SCALE_SEED: u64 = 0;
+
+/// Settings for the schema and expressions of the scale benchmark
+const SCALE_SETTINGS: ABACSettings = ABACSettings {
+    match_types: true,
+    enable_extensions: true,
+    max_depth: 3,
+    max_width: 3,
+    enable_additional_attributes: false,
+    enable_like: true,
+    enable_action_groups_and_attrs: true,
+    enable_arbitrary_func_call: false,
+    enable_unknowns: false,
+    enable_action_in_constraints: true,
+    per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+    total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+    expr_weights: ABACSettings::default_expr_weights(),
+};
+
 /// Parse a file in the integration test format, ignoring the expected
 /// authorization/validation results.
 fn parse_test(jsonfile: impl AsRef<Path>) -> (PolicySet, Entities, Schema, Vec<Request>) {
@@ -69,99 +122,378 @@ fn p99(data: Vec<f64>) -> f64 {
     data.percentile(99)
 }
 
-/// Run every input in the corpus tests through the provided Cedar test implementation,
-/// recording the time(s) required for authorization over NUM_TRIALS trials. The
-/// output reports the median trial value for each input.
+fn median_duration(mut durations: Vec<Duration>) -> Duration {
+    durations.sort_unstable();
+    durations[durations.len() / 2]
+}
+
+/// Per-input timing results, grouped by engine and operation.
 ///
-/// There are two statistics measured: "total" for end-to-end authorization time
-/// and (optionally) "authorize" for the reported _core_ authorization time, which
-/// ignores time required to interface with the test implementation.
-fn get_authorization_timing_results(
-    custom_impl: &dyn CedarTestImplementation,
-) -> HashMap<&str, Vec<f64>> {
-    let tests = get_corpus_tests();
-    let mut results = HashMap::new();
-    results.insert("total", Vec::new());
-    results.insert("authorize", Vec::new());
-    for test in tests {
-        let (policies, entities, _schema, requests) = parse_test(test);
-        for request in requests {
-            let mut total_results = Vec::new();
-            let mut auth_results = Vec::new();
-            for _i in 0..NUM_TRIALS {
-                let (response, duration) =
-                    time_function(|| custom_impl.is_authorized(&request, &policies, &entities));
-                let response = response.expect("Unexpected test failure");
-                total_results.push(duration.as_micros() as f64);
-                if response.timing_info.contains_key("authorize") {
-                    auth_results.push(response.timing_info["authorize"].0 as f64);
+/// There are two statistics measured for each operation: "total" for the
+/// end-to-end time and (optionally) "core" for the time reported by the
+/// implementation itself, which ignores time required to interface with it.
+#[derive(Default)]
+struct Timings {
+    events: Vec<TimingEvent>,
+}
+
+impl Timings {
+    /// Run `f` `NUM_TRIALS` times and record the median total and core times.
+    /// `f` returns the core time (if the implementation reports one), or an
+    /// error, in which case nothing is recorded.
+    fn time(
+        &mut self,
+        engine: &str,
+        operation: &str,
+        mut f: impl FnMut() -> Result<Option<Duration>, String>,
+    ) {
+        let mut total_results = Vec::new();
+        let mut core_results = Vec::new();
+        for _i in 0..NUM_TRIALS {
+            let (core, duration) = time_function(&mut f);
+            match core {
+                Ok(core) => {
+                    total_results.push(duration);
+                    core_results.extend(core);
+                }
+                Err(err) => {
+                    println!("{engine} {operation}: skipped ({err})");
+                    return;
                 }
             }
-            results
-                .get_mut("total")
-                .unwrap()
-                .push(median(total_results));
-            results
-                .get_mut("authorize")
-                .unwrap()
-                .push(median(auth_results));
+        }
+        self.record(engine, &format!("{operation}/total"), total_results);
+        if !core_results.is_empty() {
+            self.record(engine, &format!("{operation}/core"), core_results);
+        }
+    }
+
+    fn record(&mut self, engine: &str, operation: &str, durations: Vec<Duration>) {
+        let mut event = TimingEvent::new(operation, engine, median_duration(durations));
+        event.target = "benchmark".to_string();
+        self.events.push(event);
+    }
+
+    /// Print out a summary of the results
+    fn print_summary(&self) {
+        let mut grouped: BTreeMap<(&str, &str), Vec<f64>> = BTreeMap::new();
+        for event in &self.events {
+            grouped
+                .entry((&event.engine, &event.operation))
+                .or_default()
+                .push(event.duration_ns as f64 / 1000.0);
+        }
+        for ((engine, operation), value) in grouped {
+            println!("{engine} {operation} ({} inputs)", value.len());
+            println!("\tMedian: {:.1} micros", median(value.clone()));
+            println!("\tp90: {:.1} micros", p90(value.clone()));
+            println!("\tp99: {:.1} micros", p99(value));
+        }
+    }
+
+    /// Write the results to `BENCHMARK_OUTPUT`, if set
+    fn write(&self) {
+        if let Ok(filename) = std::env::var(BENCHMARK_OUTPUT) {
+            let mut file = std::fs::File::create(&filename)
+                .unwrap_or_else(|e| panic!("failed to create {filename}: {e}"));
+            for event in &self.events {
+                let line = serde_json::to_string(event).expect("failed to serialize timing event");
+                writeln!(file, "{line}")
+                    .unwrap_or_else(|e| panic!("failed to write {filename}: {e}"));
+            }
         }
     }
-    results
 }
 
-/// Run every input in the corpus tests through the provided Cedar test implementation,
-/// recording the time(s) required for validation over NUM_TRIALS trials. The
-/// output reports the median trial value for each input.
-///
-/// There are two statistics measured: "total" for end-to-end validation time
-/// and (optionally) "validate" for the reported _core_ validation time, which
-/// ignores time required to interface with the test implementation.
-fn get_validation_timing_results(
+/// The core time reported by a `CedarTestImplementation` under `key`
+fn core_time<T>(
+    result: TestResult<T>,
+    timing_info: impl FnOnce(&T) -> &HashMap<String, Micros>,
+    key: &str,
+) -> Result<Option<Duration>, String> {
+    match result {
+        TestResult::Success(res) => Ok(timing_info(&res)
+            .get(key)
+            .map(|micros| Duration::from_micros(micros.0 as u64))),
+        TestResult::Failure(err) => Err(err),
+    }
+}
+
+/// Run every input in the corpus tests through the provided Cedar test
+/// implementation, recording the time(s) required for authorization, validation,
+/// level validation, entity validation and request validation.
+fn get_timing_results(
+    engine: &str,
     custom_impl: &dyn CedarTestImplementation,
-) -> HashMap<&str, Vec<f64>> {
-    let tests = get_corpus_tests();
-    let mut results = HashMap::new();
-    results.insert("total", Vec::new());
-    results.insert("validate", Vec::new());
-    for test in tests {
-        let (policies, _entities, schema, _requests) = parse_test(test);
-        let mut total_results = Vec::new();
-        let mut val_results = Vec::new();
-        for _i in 0..NUM_TRIALS {
-            let (response, duration) =
-                time_function(|| custom_impl.validate(&schema, &policies, ValidationMode::Strict));
-            let response = response.expect("Unexpected test failure");
-            total_results.push(duration.as_micros() as f64);
-            if response.timing_info.contains_key("validate") {
-                val_results.push(response.timing_info["validate"].0 as f64);
-            }
+    timings: &mut Timings,
+) {
+    for test in get_corpus_tests() {
+        let (policies, entities, schema, requests) = parse_test(test);
+        for request in &requests {
+            timings.time(engine, AUTHORIZATION, || {
+                core_time(
+                    custom_impl.is_authorized(request, &policies, &entities),
+                    |res| &res.timing_info,
+                    "authorize",
+                )
+            });
+            timings.time(engine, REQUEST_VALIDATION, || {
+                core_time(
+                    custom_impl.validate_request(&schema, request),
+                    |res| &res.timing_info,
+                    "validate",
+                )
+            });
         }
-        results
-            .get_mut("total")
-            .unwrap()
-            .push(median(total_results));
-        results
-            .get_mut("validate")
-            .unwrap()
-            .push(median(val_results));
+        timings.time(engine, VALIDATION, || {
+            core_time(
+                custom_impl.validate(&schema, &policies, ValidationMode::Strict),
+                |res| &res.timing_info,
+                "validate",
+            )
+        });
+        timings.time(engine, LEVEL_VALIDATION, || {
+            core_time(
+                custom_impl.validate_with_level(
+                    &schema,
+                    &policies,
+                    ValidationMode::Strict,
+                    VALIDATION_LEVEL,
+                ),
+                |res| &res.timing_info,
+                "validate",
+            )
+        });
+        timings.time(engine, ENTITY_VALIDATION, || {
+            core_time(
+                custom_impl.validate_entities(&schema, &entities),
+                |res| &res.timing_info,
+                "validate",
+            )
+        });
     }
-    results
 }
 
-/// Print out a summary of the results
-fn print_summary(auth_times: HashMap<&str, Vec<f64>>, val_times: HashMap<&str, Vec<f64>>) {
-    for (key, value) in auth_times.into_iter() {
-        println!("Authorization - {key}");
-        println!("\tMedian: {:.1} micros", median(value.clone()));
-        println!("\tp90: {:.1} micros", p90(value.clone()));
-        println!("\tp99: {:.1} micros", p99(value));
+/// Generate the workload of the scale benchmark: 10^5 entities (or
+/// `SCALE_ENTITIES`), 2500 policies, most of them template links, and 100
+/// requests of which half are allowed
+fn scale_workload() -> (PolicySet, Entities, Schema, Vec<Request>) {
+    let mut settings = ScaleSettings::new(SCALE_SETTINGS);
+    if let Ok(num_entities) = std::env::var(SCALE_ENTITIES) {
+        settings.num_entities = num_entities
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid {SCALE_ENTITIES}: {e}"));
     }
-    for (key, value) in val_times.into_iter() {
-        println!("Validation - {key}");
-        println!("\tMedian: {:.1} micros", median(value.clone()));
-        println!("\tp90: {:.1} micros", p90(value.clone()));
-        println!("\tp99: {:.1} micros", p99(value));
+    settings.num_requests = 100;
+    let workload = ScaleWorkload::generate(&settings, SCALE_SEED)
+        .unwrap_or_else(|e| panic!("failed to generate the scale workload: {e}"));
+    println!(
+        "Scale workload: {} entities, {} policies, {} requests ({} allowed)",
+        workload.hierarchy.num_entities(),
+        workload.policies.policies().count(),
+        workload.requests.len(),
+        workload.num_allowed()
+    );
+    let policies = PolicySet::try_from(workload.policies).unwrap();
+    let entities = Entities::try_from(workload.hierarchy).unwrap();
+    let schema = Schema::try_from(workload.schema).unwrap();
+    let requests = workload
+        .requests
+        .into_iter()
+        .map(|(request, _)| Request::from(request))
+        .collect();
+    (policies, entities, schema, requests)
+}
+
+/// Run the scale workload through the provided Cedar test implementation,
+/// recording the time(s) required for authorization, validation and entity
+/// validation as `scale_<operation>`.
+fn get_scale_timing_results(
+    engine: &str,
+    custom_impl: &dyn CedarTestImplementation,
+    (policies, entities, schema, requests): &(PolicySet, Entities, Schema, Vec<Request>),
+    timings: &mut Timings,
+) {
+    for request in requests {
+        timings.time(engine, &format!("scale_{AUTHORIZATION}"), || {
+            core_time(
+                custom_impl.is_authorized(request, policies, entities),
+                |res| &res.timing_info,
+                "authorize",
+            )
+        });
+    }
+    timings.time(engine, &format!("scale_{VALIDATION}"), || {
+        core_time(
+            custom_impl.validate(schema, policies, ValidationMode::Strict),
+            |res| &res.timing_info,
+            "validate",
+        )
+    });
+    timings.time(engine, &format!("scale_{ENTITY_VALIDATION}"), || {
+        core_time(
+            custom_impl.validate_entities(schema, entities),
+            |res| &res.timing_info,
+            "validate",
+        )
+    });
+}
+
+/// The core time reported by Lean for a SymCC check
+fn lean_core_time<T>(
+    result: Result<TimedResult<T>, cedar_lean_ffi::FfiError>,
+) -> Result<Option<Duration>, String> {
+    let result = result.map_err(|err| err.to_string())?;
+    Ok(Some(Duration::from_nanos(
+        u64::try_from(result.duration()).unwrap_or(u64::MAX),
+    )))
+}
+
+/// Compile a SymCC check with `compile` and solve it with the Rust SymCC,
+/// extracting a counterexample like the Lean `*_with_cex_timed` checks. The
+/// core time covers compiling, encoding, solving and decoding the model, which
+/// is what Lean reports; the solver process is already running.
+fn rust_check_sat<'a>(
+    runtime: &tokio::runtime::Runtime,
+    compiler: &mut CedarSymCompiler<LocalSolver>,
+    compile: impl FnOnce() -> SymCCResult<WellFormedAsserts<'a>>,
+) -> Result<Option<Duration>, String> {
+    runtime.block_on(async {
+        let start = Instant::now();
+        let asserts = compile().map_err(|err| err.to_string())?;
+        compiler
+            .check_sat(&asserts)
+            .await
+            .map_err(|err| err.to_string())?;
+        Ok(Some(start.elapsed()))
+    })
+}
+
+/// Run each SymCC check on every input in the corpus tests, with both the Lean
+/// and the Rust SymCC against a local CVC5. Both sides extract counterexamples,
+/// and neither times spawning the solver: Lean spawns it before starting its
+/// timer, and the Rust SymCC reuses a single solver process. The pairwise
+/// checks compare each policy set with the same set minus its first policy
+/// (by id), so that they are not trivially unsatisfiable.
+fn get_symcc_timing_results(timings: &mut Timings) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    // The solver process is spawned on the runtime
+    let solver = match runtime.block_on(async { LocalSolver::cvc5() }) {
+        Ok(solver) => solver,
+        Err(err) => {
+            println!("Skipping SymCC benchmarks: CVC5 is not available ({err})");
+            return;
+        }
+    };
+    let mut compiler = CedarSymCompiler::new(solver).expect("solver construction should succeed");
+    let lean_ffi = CedarLeanFfi::with_solver_config(SolverConfig::new(SolverKind::Cvc5));
+    for test in get_corpus_tests() {
+        let (policies, _entities, schema, _requests) = parse_test(test);
+        let Some(first_id) = policies.policies().map(|p| p.id().to_string()).min() else {
+            continue;
+        };
+        let other_policies = PolicySet::from_policies(
+            policies
+                .policies()
+                .filter(|p| p.id().to_string() != first_id)
+                .cloned(),
+        )
+        .expect("a subset of a policy set is a valid policy set");
+        let req_envs: Vec<RequestEnv> = schema.request_envs().collect();
+        for req_env in &req_envs {
+            let Ok(sym_env) = SymEnv::new(&schema, req_env) else {
+                continue;
+            };
+            for policy in policies.policies() {
+                if let Ok(well_typed_policy) =
+                    WellTypedPolicy::from_policy(policy, req_env, &schema)
+                {
+                    timings.time(LEAN_ENGINE, "check_never_errors", || {
+                        lean_core_time(
+                            lean_ffi
+                                .run_check_never_errors_with_cex_timed(policy, &schema, req_env),
+                        )
+                    });
+                    timings.time(RUST_ENGINE, "check_never_errors", || {
+                        rust_check_sat(&runtime, &mut compiler, || {
+                            compile_never_errors(&well_typed_policy, &sym_env)
+                        })
+                    });
+                }
+            }
+            let Ok(well_typed_policies) =
+                WellTypedPolicies::from_policies(&policies, req_env, &schema)
+            else {
+                continue;
+            };
+            let Ok(well_typed_other_policies) =
+                WellTypedPolicies::from_policies(&other_policies, req_env, &schema)
+            else {
+                continue;
+            };
+            timings.time(LEAN_ENGINE, "check_always_allows", || {
+                lean_core_time(
+                    lean_ffi.run_check_always_allows_with_cex_timed(&policies, &schema, req_env),
+                )
+            });
+            timings.time(RUST_ENGINE, "check_always_allows", || {
+                rust_check_sat(&runtime, &mut compiler, || {
+                    compile_always_allows(&well_typed_policies, &sym_env)
+                })
+            });
+            timings.time(LEAN_ENGINE, "check_always_denies", || {
+                lean_core_time(
+                    lean_ffi.run_check_always_denies_with_cex_timed(&policies, &schema, req_env),
+                )
+            });
+            timings.time(RUST_ENGINE, "check_always_denies", || {
+                rust_check_sat(&runtime, &mut compiler, || {
+                    compile_always_denies(&well_typed_policies, &sym_env)
+                })
+            });
+            timings.time(LEAN_ENGINE, "check_equivalent", || {
+                lean_core_time(lean_ffi.run_check_equivalent_with_cex_timed(
+                    &policies,
+                    &other_policies,
+                    &schema,
+                    req_env,
+                ))
+            });
+            timings.time(RUST_ENGINE, "check_equivalent", || {
+                rust_check_sat(&runtime, &mut compiler, || {
+                    compile_equivalent(&well_typed_policies, &well_typed_other_policies, &sym_env)
+                })
+            });
+            timings.time(LEAN_ENGINE, "check_implies", || {
+                lean_core_time(lean_ffi.run_check_implies_with_cex_timed(
+                    &policies,
+                    &other_policies,
+                    &schema,
+                    req_env,
+                ))
+            });
+            timings.time(RUST_ENGINE, "check_implies", || {
+                rust_check_sat(&runtime, &mut compiler, || {
+                    compile_implies(&well_typed_policies, &well_typed_other_policies, &sym_env)
+                })
+            });
+            timings.time(LEAN_ENGINE, "check_disjoint", || {
+                lean_core_time(lean_ffi.run_check_disjoint_with_cex_timed(
+                    &policies,
+                    &other_policies,
+                    &schema,
+                    req_env,
+                ))
+            });
+            timings.time(RUST_ENGINE, "check_disjoint", || {
+                rust_check_sat(&runtime, &mut compiler, || {
+                    compile_disjoint(&well_typed_policies, &well_typed_other_policies, &sym_env)
+                })
+            });
+        }
     }
 }
 
@@ -174,14 +506,38 @@ fn print_summary(auth_times: HashMap<&str, Vec<f64>>, val_times: HashMap<&str, V
 fn print_timing_results() {
     let rust_impl = RustEngine::new();
     let lean_impl = CedarLeanEngine::new();
+    let mut timings = Timings::default();
 
     println!("Running Rust implementation...");
-    let auth_times = get_authorization_timing_results(&rust_impl);
-    let val_times = get_validation_timing_results(&rust_impl);
-    print_summary(auth_times, val_times);
+    get_timing_results(RUST_ENGINE, &rust_impl, &mut timings);
 
     println!("Running Lean implementation...");
-    let auth_times = get_authorization_timing_results(&lean_impl);
-    let val_times = get_validation_timing_results(&lean_impl);
-    print_summary(auth_times, val_times);
+    get_timing_results(LEAN_ENGINE, &lean_impl, &mut timings);
+
+    println!("Running SymCC...");
+    get_symcc_timing_results(&mut timings);
+
+    timings.print_summary();
+    timings.write();
+}
+
+#[test]
+#[ignore] // doesn't test anything, just prints timing results on a large
+          // generated workload -- run this on demand but not in CI.
+fn print_scale_timing_results() {
+    let rust_impl = RustEngine::new();
+    let lean_impl = CedarLeanEngine::new();
+    let mut timings = Timings::default();
+
+    println!("Generating the scale workload...");
+    let workload = scale_workload();
+
+    println!("Running Rust implementation...");
+    get_scale_timing_results(RUST_ENGINE, &rust_impl, &workload, &mut timings);
+
+    println!("Running Lean implementation...");
+    get_scale_timing_results(LEAN_ENGINE, &lean_impl, &workload, &mut timings);
+
+    timings.print_summary();
+    timings.write();
 }
@@ -0,0 +1,145 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Checks the external engine protocol against the `external_engine_stub`
+//! binary, which should agree with `cedar-policy` on everything.
+
+use cedar_drt::external_engine::ExternalProcessEngine;
+use cedar_policy::{
+    eval_expression, Context, Entities, EntityUid, Expression, PolicySet, Request, Schema,
+    ValidationMode,
+};
+use cedar_testing::cedar_test_impl::{CedarTestImplementation, RustEngine, TestResult};
+use std::str::FromStr;
+
+fn stub() -> ExternalProcessEngine {
+    ExternalProcessEngine::spawn(env!("CARGO_BIN_EXE_external_engine_stub"), &[])
+        .expect("failed to start external engine stub")
+}
+
+fn success<T>(result: TestResult<T>) -> T {
+    match result {
+        TestResult::Success(res) => res,
+        TestResult::Failure(err) => panic!("external engine failed: {err}"),
+    }
+}
+
+fn schema() -> Schema {
+    Schema::from_str(
+        r#"
+        entity User { age: Long };
+        entity Photo;
+        action view appliesTo { principal: User, resource: Photo, context: { n: Long } };
+        "#,
+    )
+    .unwrap()
+}
+
+fn policies() -> PolicySet {
+    // The forbid policy errors (overflows) when `context.n` is positive
+    PolicySet::from_str(
+        r#"
+        permit(principal, action, resource) when { principal.age >= 18 };
+        forbid(principal, action, resource) when { context.n + 9223372036854775807 == 1 };
+        "#,
+    )
+    .unwrap()
+}
+
+fn entities() -> Entities {
+    Entities::from_json_str(
+        r#"[{ "uid": { "type": "User", "id": "alice" }, "attrs": { "age": 20 }, "parents": [] }]"#,
+        None,
+    )
+    .unwrap()
+}
+
+fn request(n: i64) -> Request {
+    Request::new(
+        EntityUid::from_str(r#"User::"alice""#).unwrap(),
+        EntityUid::from_str(r#"Action::"view""#).unwrap(),
+        EntityUid::from_str(r#"Photo::"p""#).unwrap(),
+        Context::from_json_value(serde_json::json!({ "n": n }), None).unwrap(),
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn authorization_agrees() {
+    let stub = stub();
+    let rust = RustEngine::new();
+    for n in [0, 1] {
+        let (request, policies, entities) = (request(n), policies(), entities());
+        let expected = success(rust.is_authorized(&request, &policies, &entities));
+        let actual = success(stub.is_authorized(&request, &policies, &entities));
+        assert_eq!(actual.response.decision(), expected.response.decision());
+        assert_eq!(
+            actual.response.diagnostics().reason().collect::<Vec<_>>(),
+            expected.response.diagnostics().reason().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            actual.response.diagnostics().errors().count(),
+            expected.response.diagnostics().errors().count()
+        );
+    }
+}
+
+#[test]
+fn evaluation_agrees() {
+    let stub = stub();
+    let (request, entities) = (request(1), entities());
+    for expr in [
+        "principal.age + context.n",
+        r#"principal.age like "a*""#,
+        r#"{ a: [principal, decimal("1.5")] }"#,
+    ] {
+        let expr = Expression::from_str(expr).unwrap();
+        let expected = eval_expression(&request, &entities, &expr).ok();
+        assert!(success(
+            stub.interpret(&request, &entities, &expr, expected)
+        ));
+    }
+    let expr = Expression::from_str("principal.age + 1").unwrap();
+    let wrong = eval_expression(&request, &entities, &Expression::from_str("0").unwrap()).ok();
+    assert!(!success(stub.interpret(&request, &entities, &expr, wrong)));
+}
+
+#[test]
+fn validation_agrees() {
+    let stub = stub();
+    let schema = schema();
+    let ill_typed = PolicySet::from_str(
+        r#"permit(principal, action, resource) when { principal.age == "old" };"#,
+    )
+    .unwrap();
+    for policies in [policies(), ill_typed] {
+        for mode in [ValidationMode::Strict, ValidationMode::Permissive] {
+            let expected = success(RustEngine::new().validate(&schema, &policies, mode));
+            let actual = success(stub.validate(&schema, &policies, mode));
+            assert_eq!(actual.validation_passed(), expected.validation_passed());
+        }
+    }
+    let entities = entities();
+    assert!(success(stub.validate_entities(&schema, &entities)).validation_passed());
+    assert!(success(stub.validate_request(&schema, &request(1))).validation_passed());
+    let bad_entities = Entities::from_json_str(
+        r#"[{ "uid": { "type": "User", "id": "bob" }, "attrs": { "age": "old" }, "parents": [] }]"#,
+        None,
+    )
+    .unwrap();
+    assert!(!success(stub.validate_entities(&schema, &bad_entities)).validation_passed());
+}
@@ -24,6 +24,11 @@ use cedar_testing::integration_testing::{
     perform_integration_test_from_json_custom, resolve_integration_test_path,
 };
 
+use cedar_drt::dump::{
+    perform_dumped_test_from_json, ENTITY_VALIDATION_TEST_SUFFIX, LINKED_TEST_SUFFIX,
+    REQUEST_VALIDATION_TEST_SUFFIX, VALIDATION_TEST_SUFFIX,
+};
+use cedar_drt::external_engine::ExternalProcessEngine;
 use cedar_drt::CedarLeanEngine;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
@@ -53,8 +58,8 @@ pub fn get_integration_tests() -> impl Iterator<Item = PathBuf> {
         })
 }
 
-/// Pull out the relevant tests in `corpus_test_folder()`
-pub fn get_corpus_tests() -> impl Iterator<Item = PathBuf> {
+/// Names of all test case files in `corpus_test_folder()`
+fn corpus_test_files() -> impl Iterator<Item = (PathBuf, String)> {
     let tests_folder = resolve_integration_test_path(corpus_test_folder());
     WalkDir::new(&tests_folder)
         .into_iter()
@@ -62,16 +67,49 @@ pub fn get_corpus_tests() -> impl Iterator<Item = PathBuf> {
             e.expect("failed to access file in corpus_tests. Maybe you haven't unpacked `corpus-tests.tar.gz`")
                 .into_path()
         })
-        .filter(|p| {
+        .filter_map(|p| {
             let filename = p
                 .file_name()
                 .expect("didn't expect subdirectories in corpus-tests")
                 .to_str()
-                .expect("expected filenames to be valid UTF-8");
-            filename.ends_with(".json") && !filename.ends_with(".entities.json")
+                .expect("expected filenames to be valid UTF-8")
+                .to_string();
+            (filename.ends_with(".json") && !filename.ends_with(".entities.json"))
+                .then_some((p, filename))
         })
 }
 
+/// Whether `filename` is a test case with template links written by `dump`,
+/// or a validation test case written by `dump_validation`,
+/// `dump_entity_validation` or `dump_request_validation`, i.e., a test case
+/// not in the `JsonTest` format
+fn is_dumped_test(filename: &str) -> bool {
+    [
+        LINKED_TEST_SUFFIX,
+        VALIDATION_TEST_SUFFIX,
+        ENTITY_VALIDATION_TEST_SUFFIX,
+        REQUEST_VALIDATION_TEST_SUFFIX,
+    ]
+    .iter()
+    .any(|suffix| filename.ends_with(suffix))
+}
+
+/// Pull out the relevant tests in `corpus_test_folder()`, i.e., the test cases
+/// in the `JsonTest` format
+pub fn get_corpus_tests() -> impl Iterator<Item = PathBuf> {
+    corpus_test_files()
+        .filter(|(_, filename)| !is_dumped_test(filename))
+        .map(|(p, _)| p)
+}
+
+/// Pull out the test cases with template links and the validation tests in
+/// `corpus_test_folder()`
+pub fn get_corpus_dumped_tests() -> impl Iterator<Item = PathBuf> {
+    corpus_test_files()
+        .filter(|(_, filename)| is_dumped_test(filename))
+        .map(|(p, _)| p)
+}
+
 fn run_tests(custom_impl: &impl CedarTestImplementation, tests: impl Iterator<Item = PathBuf>) {
     for test_json in tests {
         perform_integration_test_from_json_custom(&test_json, custom_impl);
@@ -84,6 +122,9 @@ fn run_integration_tests(custom_impl: &impl CedarTestImplementation) {
 
 fn run_corpus_tests(custom_impl: &impl CedarTestImplementation) {
     run_tests(custom_impl, get_corpus_tests());
+    for test_json in get_corpus_dumped_tests() {
+        perform_dumped_test_from_json(&test_json, custom_impl);
+    }
 }
 
 #[test]
@@ -92,3 +133,10 @@ fn integration_tests_on_def_impl() {
     run_integration_tests(&lean_def_impl);
     run_corpus_tests(&lean_def_impl);
 }
+
+#[test]
+fn integration_tests_on_external_engine_stub() {
+    let stub = ExternalProcessEngine::spawn(env!("CARGO_BIN_EXE_external_engine_stub"), &[])
+        .expect("failed to start external engine stub");
+    run_integration_tests(&stub);
+}
@@ -13,9 +13,10 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use crate::smtlib::{CheckInputs, SmtLibEmitter};
 use crate::util::{AnalyzePolicyFindingsSer, OpenRequestEnv};
 use crate::{err::ExecError, util::RequestEnvSer};
-use cedar_lean_ffi::CedarLeanFfi;
+use cedar_lean_ffi::{CedarLeanFfi, SolverConfig};
 use cedar_policy::{Effect, Policy, PolicyId, PolicySet, RequestEnv, Schema};
 use itertools::Itertools;
 use prettytable::{Attr, Cell, Row, Table};
@@ -30,14 +31,17 @@ pub fn analyze_policyset(
     policy_set: PolicySet,
     schema: Schema,
     json_output: bool,
+    solver: SolverConfig,
+    emitter: &mut SmtLibEmitter,
 ) -> Result<(), ExecError> {
+    let lean_context = CedarLeanFfi::with_solver_config(solver);
     let mut policy_vacuity_results = HashMap::new();
 
     let req_envs = OpenRequestEnv::any().to_request_envs(&schema)?;
     let policies: Vec<&Policy> = policy_set.policies().collect();
 
     for policy in policies.iter() {
-        let pvr = vacuity_result(policy, &schema, &req_envs)?;
+        let pvr = vacuity_result(&lean_context, policy, &schema, &req_envs, emitter)?;
         policy_vacuity_results.insert(policy.id().clone(), pvr);
     }
 
@@ -53,7 +57,14 @@ pub fn analyze_policyset(
     let mut forbid_shadowed_by_forbid_findigns: HashMap<PolicyId, Vec<HashSet<PolicyId>>> =
         HashMap::new();
 
-    let policyset_vacuity_results = policyset_vacuous(&policy_set, &schema, &req_envs)?;
+    let policyset_vacuity_results = policyset_vacuous(
+        &lean_context,
+        &policy_set,
+        CheckInputs::Roles(&["policyset"]),
+        &schema,
+        &req_envs,
+        emitter,
+    )?;
 
     for [src_policy, tgt_policy] in policies.iter().array_combinations() {
         let svr = policy_vacuity_results
@@ -65,7 +76,14 @@ pub fn analyze_policyset(
         match (src_policy.effect(), tgt_policy.effect()) {
             (Effect::Permit, Effect::Permit) => {
                 let shadowing_results = compute_permit_shadowing_result(
-                    src_policy, svr, tgt_policy, tvr, &schema, &req_envs,
+                    &lean_context,
+                    src_policy,
+                    svr,
+                    tgt_policy,
+                    tvr,
+                    &schema,
+                    &req_envs,
+                    emitter,
                 )?;
                 update_findings(
                     src_policy.id(),
@@ -98,7 +116,14 @@ pub fn analyze_policyset(
             }
             (Effect::Permit, Effect::Forbid) => {
                 let override_results = compute_forbid_overrides_shadow_result(
-                    tgt_policy, tvr, src_policy, svr, &schema, &req_envs,
+                    &lean_context,
+                    tgt_policy,
+                    tvr,
+                    src_policy,
+                    svr,
+                    &schema,
+                    &req_envs,
+                    emitter,
                 )?;
                 update_findings(
                     src_policy.id(),
@@ -110,7 +135,14 @@ pub fn analyze_policyset(
             }
             (Effect::Forbid, Effect::Permit) => {
                 let override_results = compute_forbid_overrides_shadow_result(
-                    src_policy, svr, tgt_policy, tvr, &schema, &req_envs,
+                    &lean_context,
+                    src_policy,
+                    svr,
+                    tgt_policy,
+                    tvr,
+                    &schema,
+                    &req_envs,
+                    emitter,
                 )?;
                 update_findings(
                     tgt_policy.id(),
@@ -122,7 +154,14 @@ pub fn analyze_policyset(
             }
             (Effect::Forbid, Effect::Forbid) => {
                 let shadowing_results = compute_forbid_shadowing_result(
-                    src_policy, svr, tgt_policy, tvr, &schema, &req_envs,
+                    &lean_context,
+                    src_policy,
+                    svr,
+                    tgt_policy,
+                    tvr,
+                    &schema,
+                    &req_envs,
+                    emitter,
                 )?;
                 update_findings(
                     src_policy.id(),
@@ -450,17 +489,27 @@ fn vacous_finding_from_results(results: &[VacuityResult]) -> VacuityResult {
 
 /// Is a given PolicySet vacous (per request environment)
 fn policyset_vacuous(
+    lean_context: &CedarLeanFfi,
     policyset: &PolicySet,
+    inputs: CheckInputs<'_>,
     schema: &Schema,
     req_envs: &Vec<RequestEnv>,
+    emitter: &mut SmtLibEmitter,
 ) -> Result<Vec<VacuityResult>, ExecError> {
     let mut vr = Vec::new();
 
-    let lean_context = CedarLeanFfi::new();
     for req_env in req_envs {
+        emitter.emit("check-always-allows", inputs, req_env, || {
+            lean_context.smtlib_of_check_always_allows(policyset, schema, req_env)
+        })?;
         if lean_context.run_check_always_allows(policyset, schema, req_env)? {
             vr.push(VacuityResult::MatchesAll);
-        } else if lean_context.run_check_always_denies(policyset, schema, req_env)? {
+            continue;
+        }
+        emitter.emit("check-always-denies", inputs, req_env, || {
+            lean_context.smtlib_of_check_always_denies(policyset, schema, req_env)
+        })?;
+        if lean_context.run_check_always_denies(policyset, schema, req_env)? {
             vr.push(VacuityResult::MatchesNone);
         } else {
             vr.push(VacuityResult::MatchesSome);
@@ -471,9 +520,11 @@ fn policyset_vacuous(
 
 /// Auxillary function that computes the vacuitiness of a policy for each request environment
 fn vacuity_result(
+    lean_context: &CedarLeanFfi,
     policy: &Policy,
     schema: &Schema,
     req_envs: &Vec<RequestEnv>,
+    emitter: &mut SmtLibEmitter,
 ) -> Result<Vec<VacuityResult>, ExecError> {
     // turn forbid to permit to test if policy matches All, Some, or No requests
     // by checking if the permit variant allows All, None, or Some
@@ -484,7 +535,15 @@ fn vacuity_result(
         }
     })?;
 
-    policyset_vacuous(&pset, schema, req_envs)
+    let id = policy.id().to_string();
+    policyset_vacuous(
+        lean_context,
+        &pset,
+        CheckInputs::Policies(&[id.as_str()]),
+        schema,
+        req_envs,
+        emitter,
+    )
 }
 
 /// Represents if the Src Policy is shadowed by the Tgt Policy or vice versa
@@ -498,12 +557,14 @@ enum ShadowingResult {
 
 /// Compute Redudant and Shadowed relationship between src and tgt policies (per environment)
 fn compute_permit_shadowing_result(
+    lean_context: &CedarLeanFfi,
     src_policy: &Policy,
     src_vacuous_results: &Vec<VacuityResult>,
     tgt_policy: &Policy,
     tgt_vacuous_results: &Vec<VacuityResult>,
     schema: &Schema,
     req_envs: &Vec<RequestEnv>,
+    emitter: &mut SmtLibEmitter,
 ) -> Result<Vec<ShadowingResult>, ExecError> {
     let mut results = Vec::new();
     let src_pset = PolicySet::from_policies([src_policy.to_owned()]).map_err(|err| {
@@ -517,7 +578,9 @@ fn compute_permit_shadowing_result(
         }
     })?;
 
-    let lean_context = CedarLeanFfi::new();
+    let src_label = src_policy.id().to_string();
+    let tgt_label = tgt_policy.id().to_string();
+
     for ((src_vr, tgt_vr), req_env) in zip(zip(src_vacuous_results, tgt_vacuous_results), req_envs)
     {
         match (src_vr, tgt_vr) {
@@ -534,10 +597,24 @@ fn compute_permit_shadowing_result(
                 results.push(ShadowingResult::TgtShadowsSrc)
             }
             (VacuityResult::MatchesSome, VacuityResult::MatchesSome) => {
-                let src_implies_tgt =
-                    lean_context.run_check_implies(&src_pset, &tgt_pset, schema, req_env)?;
-                let tgt_implies_src =
-                    lean_context.run_check_implies(&tgt_pset, &src_pset, schema, req_env)?;
+                let src_implies_tgt = run_check_implies(
+                    lean_context,
+                    &src_pset,
+                    &tgt_pset,
+                    CheckInputs::Policies(&[src_label.as_str(), tgt_label.as_str()]),
+                    schema,
+                    req_env,
+                    emitter,
+                )?;
+                let tgt_implies_src = run_check_implies(
+                    lean_context,
+                    &tgt_pset,
+                    &src_pset,
+                    CheckInputs::Policies(&[tgt_label.as_str(), src_label.as_str()]),
+                    schema,
+                    req_env,
+                    emitter,
+                )?;
                 match (src_implies_tgt, tgt_implies_src) {
                     (true, true) => results.push(ShadowingResult::Equivalent),
                     (true, _) => results.push(ShadowingResult::TgtShadowsSrc),
@@ -559,12 +636,14 @@ enum OverrideResult {
 
 /// Determine if forbid policy overrides permit policy (per environment)
 fn compute_forbid_overrides_shadow_result(
+    lean_context: &CedarLeanFfi,
     forbid_policy: &Policy,
     forbid_vacuous_results: &Vec<VacuityResult>,
     permit_policy: &Policy,
     permit_vacuous_results: &Vec<VacuityResult>,
     schema: &Schema,
     req_envs: &Vec<RequestEnv>,
+    emitter: &mut SmtLibEmitter,
 ) -> Result<Vec<OverrideResult>, ExecError> {
     let mut results = Vec::new();
     let forbid_pset = PolicySet::from_policies([force_permit(forbid_policy)?]).map_err(|err| {
@@ -578,7 +657,9 @@ fn compute_forbid_overrides_shadow_result(
         }
     })?;
 
-    let lean_context = CedarLeanFfi::new();
+    let forbid_label = forbid_policy.id().to_string();
+    let permit_label = permit_policy.id().to_string();
+
     for ((forbid_vr, permit_vr), req_env) in zip(
         zip(forbid_vacuous_results, permit_vacuous_results),
         req_envs,
@@ -587,7 +668,15 @@ fn compute_forbid_overrides_shadow_result(
             (VacuityResult::MatchesNone, _) | (VacuityResult::MatchesAll, _) |                                          // forbid policy is vacous: does not apply or denies all
             (_, VacuityResult::MatchesNone) | (_, VacuityResult::MatchesAll) => results.push(OverrideResult::NoResult), // permit policy is vacous: does not apply or allows all (no need to check overriding)
             _ => {
-                if lean_context.run_check_implies(&permit_pset, &forbid_pset, schema, req_env)? {
+                if run_check_implies(
+                    lean_context,
+                    &permit_pset,
+                    &forbid_pset,
+                    CheckInputs::Policies(&[permit_label.as_str(), forbid_label.as_str()]),
+                    schema,
+                    req_env,
+                    emitter,
+                )? {
                     results.push(OverrideResult::Overrides); // Every request allowed by permit is denied by forbid
                 } else {
                     results.push(OverrideResult::NoResult);  // some request allowed by permit is not denies by forbid
@@ -600,12 +689,14 @@ fn compute_forbid_overrides_shadow_result(
 
 /// Compute Shadoing (and redudancy) relationship between src and tgt policies (per request environment)
 fn compute_forbid_shadowing_result(
+    lean_context: &CedarLeanFfi,
     src_policy: &Policy,
     src_vacuous_results: &Vec<VacuityResult>,
     tgt_policy: &Policy,
     tgt_vacuous_results: &Vec<VacuityResult>,
     schema: &Schema,
     req_envs: &Vec<RequestEnv>,
+    emitter: &mut SmtLibEmitter,
 ) -> Result<Vec<ShadowingResult>, ExecError> {
     let mut results = Vec::new();
     let src_pset = PolicySet::from_policies([force_permit(src_policy)?]).map_err(|err| {
@@ -618,7 +709,9 @@ fn compute_forbid_shadowing_result(
             error: Box::new(err),
         }
     })?;
-    let lean_context = CedarLeanFfi::new();
+    let src_label = src_policy.id().to_string();
+    let tgt_label = tgt_policy.id().to_string();
+
     for ((src_vr, tgt_vr), req_env) in zip(zip(src_vacuous_results, tgt_vacuous_results), req_envs)
     {
         // Forbid vacuity results are computed on them as if they were permit policies
@@ -636,10 +729,24 @@ fn compute_forbid_shadowing_result(
                 results.push(ShadowingResult::TgtShadowsSrc) // Tgt policy denies all requests, Src denies some
             }
             (VacuityResult::MatchesSome, VacuityResult::MatchesSome) => {
-                let src_implies_tgt =
-                    lean_context.run_check_implies(&src_pset, &tgt_pset, schema, req_env)?;
-                let tgt_implies_src =
-                    lean_context.run_check_implies(&tgt_pset, &src_pset, schema, req_env)?;
+                let src_implies_tgt = run_check_implies(
+                    lean_context,
+                    &src_pset,
+                    &tgt_pset,
+                    CheckInputs::Policies(&[src_label.as_str(), tgt_label.as_str()]),
+                    schema,
+                    req_env,
+                    emitter,
+                )?;
+                let tgt_implies_src = run_check_implies(
+                    lean_context,
+                    &tgt_pset,
+                    &src_pset,
+                    CheckInputs::Policies(&[tgt_label.as_str(), src_label.as_str()]),
+                    schema,
+                    req_env,
+                    emitter,
+                )?;
                 match (src_implies_tgt, tgt_implies_src) {
                     (true, true) => results.push(ShadowingResult::Equivalent), // Equivalent
                     (true, _) => results.push(ShadowingResult::TgtShadowsSrc), // Tgt denies strictly more than Src
@@ -652,6 +759,23 @@ fn compute_forbid_shadowing_result(
     Ok(results)
 }
 
+/// Run `check-implies` on the source and target PolicySets, after writing its SMT-LIB script
+/// (named after `inputs`) to `emitter`
+fn run_check_implies(
+    lean_context: &CedarLeanFfi,
+    src_pset: &PolicySet,
+    tgt_pset: &PolicySet,
+    inputs: CheckInputs<'_>,
+    schema: &Schema,
+    req_env: &RequestEnv,
+    emitter: &mut SmtLibEmitter,
+) -> Result<bool, ExecError> {
+    emitter.emit("check-implies", inputs, req_env, || {
+        lean_context.smtlib_of_check_implies(src_pset, tgt_pset, schema, req_env)
+    })?;
+    Ok(lean_context.run_check_implies(src_pset, tgt_pset, schema, req_env)?)
+}
+
 /// Converts a forbid policy into a permit policy
 fn force_permit(policy: &Policy) -> Result<Policy, ExecError> {
     let mut json = policy
@@ -749,16 +873,32 @@ pub fn compare_policysets(
     tgt_policyset: PolicySet,
     schema: Schema,
     json_output: bool,
+    solver: SolverConfig,
+    emitter: &mut SmtLibEmitter,
 ) -> Result<(), ExecError> {
     let req_envs = OpenRequestEnv::any().to_request_envs(&schema)?;
-    let lean_context = CedarLeanFfi::new();
+    let lean_context = CedarLeanFfi::with_solver_config(solver);
     let comparison_results: Vec<PolicySetComparisonResult> = req_envs
         .iter()
         .map(|req_env| -> Result<PolicySetComparisonResult, ExecError> {
-            let fwd_implies =
-                lean_context.run_check_implies(&src_policyset, &tgt_policyset, &schema, req_env)?;
-            let bwd_implies =
-                lean_context.run_check_implies(&tgt_policyset, &src_policyset, &schema, req_env)?;
+            let fwd_implies = run_check_implies(
+                &lean_context,
+                &src_policyset,
+                &tgt_policyset,
+                CheckInputs::Roles(&["source", "target"]),
+                &schema,
+                req_env,
+                emitter,
+            )?;
+            let bwd_implies = run_check_implies(
+                &lean_context,
+                &tgt_policyset,
+                &src_policyset,
+                CheckInputs::Roles(&["target", "source"]),
+                &schema,
+                req_env,
+                emitter,
+            )?;
             let status = match (fwd_implies, bwd_implies) {
                 (true, true) => PolicySetComparisonStatus::Equivalent,
                 (true, false) => PolicySetComparisonStatus::LessPermissive,
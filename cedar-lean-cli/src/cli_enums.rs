@@ -15,9 +15,11 @@
  */
 use crate::err::ExecError;
 use crate::util;
+use cedar_lean_ffi::SolverConfig;
 use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
 use serde::Serialize;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Args, Clone, Debug, Serialize)]
 pub(crate) struct PolicyAnalysisArgs {
@@ -61,7 +63,7 @@ pub(crate) struct ComparePolicySetAnalysisArgs {
 #[derive(Args, Clone, Debug, Serialize)]
 #[clap(next_help_heading = "Execution Modes")]
 pub(crate) struct Mode {
-    /// Run the SMT formula produced by the provided backend encoder via CVC5 [default]
+    /// Run the SMT formula produced by the provided backend encoder via the selected solver [default]
     #[arg(long, conflicts_with_all = ["print_smtlib"], global=true)]
     run_analysis: bool,
     /// Print the SMT formula produced by the provided backend
@@ -88,6 +90,45 @@ impl From<Mode> for ModeEnum {
     }
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, Serialize)]
+pub(crate) enum SolverKind {
+    Cvc5,
+    Z3,
+}
+
+#[derive(Args, Clone, Debug, Serialize)]
+#[clap(next_help_heading = "Solver Options")]
+pub(crate) struct SolverArgs {
+    /// The SMT solver used to run the analysis
+    #[arg(long, value_enum, default_value = "cvc5")]
+    solver: SolverKind,
+    /// Path to the solver executable [default: the value of the CVC5 or Z3 environment variable]
+    #[arg(long, value_name = "SOLVER_PATH")]
+    solver_path: Option<PathBuf>,
+    /// Per-query timeout in milliseconds, after which the solver gives up and returns unknown
+    #[arg(long, value_name = "MILLISECONDS")]
+    timeout: Option<u64>,
+}
+
+impl From<SolverArgs> for SolverConfig {
+    /// Convert from the `SolverArgs` struct which works well with clap to the
+    /// `SolverConfig` accepted by the Lean FFI
+    fn from(args: SolverArgs) -> Self {
+        let kind = match args.solver {
+            SolverKind::Cvc5 => cedar_lean_ffi::SolverKind::Cvc5,
+            SolverKind::Z3 => cedar_lean_ffi::SolverKind::Z3,
+        };
+        let mut config = SolverConfig::new(kind);
+        if let Some(path) = args.solver_path {
+            config = config.with_path(path);
+        }
+        if let Some(timeout) = args.timeout {
+            config = config.with_timeout(Duration::from_millis(timeout));
+        }
+        config
+    }
+}
+
 /// Need to refactor into a struct that has both options and make them conflict with each other...
 /// Then provide a translation into an Enum of this form for easier pattern matching.
 #[derive(Args, Clone, Debug, Serialize)]
@@ -176,6 +217,14 @@ impl From<RequestArgs> for RequestArgsEnum {
     }
 }
 
+#[derive(Args, Clone, Debug, Serialize)]
+#[clap(next_help_heading = "SMT-LIB Output")]
+pub(crate) struct SmtLibDumpArgs {
+    /// Write the SMT-LIB script of every check (one `.smt2` file per request environment) and a `manifest.json` to the given directory
+    #[arg(long, value_name = "DIR")]
+    pub(crate) emit_smtlib_dir: Option<PathBuf>,
+}
+
 #[derive(Args, Clone, Debug, Serialize)]
 #[clap(next_help_heading = "Request Environment Options")]
 #[serde(rename_all = "kebab-case")]
@@ -297,6 +346,10 @@ pub(crate) enum SymCCCommands {
         #[clap(flatten)]
         mode: Mode,
         #[clap(flatten)]
+        solver: SolverArgs,
+        #[clap(flatten)]
+        dump: SmtLibDumpArgs,
+        #[clap(flatten)]
         req_env: RequestEnvArgs,
     },
     /// Check if the provided PolicySet allows all authorization requests
@@ -306,6 +359,10 @@ pub(crate) enum SymCCCommands {
         #[clap(flatten)]
         mode: Mode,
         #[clap(flatten)]
+        solver: SolverArgs,
+        #[clap(flatten)]
+        dump: SmtLibDumpArgs,
+        #[clap(flatten)]
         req_env: RequestEnvArgs,
     },
     /// Check if the provided PolicySet denies all authorization requests
@@ -315,6 +372,10 @@ pub(crate) enum SymCCCommands {
         #[clap(flatten)]
         mode: Mode,
         #[clap(flatten)]
+        solver: SolverArgs,
+        #[clap(flatten)]
+        dump: SmtLibDumpArgs,
+        #[clap(flatten)]
         req_env: RequestEnvArgs,
     },
     /// Check if the source and target PolicySets are equivalent
@@ -324,6 +385,10 @@ pub(crate) enum SymCCCommands {
         #[clap(flatten)]
         mode: Mode,
         #[clap(flatten)]
+        solver: SolverArgs,
+        #[clap(flatten)]
+        dump: SmtLibDumpArgs,
+        #[clap(flatten)]
         req_env: RequestEnvArgs,
     },
     /// Check if the target PolicySet authorizes all requests that the source PolicySet authorizes
@@ -333,6 +398,10 @@ pub(crate) enum SymCCCommands {
         #[clap(flatten)]
         mode: Mode,
         #[clap(flatten)]
+        solver: SolverArgs,
+        #[clap(flatten)]
+        dump: SmtLibDumpArgs,
+        #[clap(flatten)]
         req_env: RequestEnvArgs,
     },
     /// Check if the source and target PolicySets are disjoint (there is no authorization request that both PolicySets allow)
@@ -342,6 +411,10 @@ pub(crate) enum SymCCCommands {
         #[clap(flatten)]
         mode: Mode,
         #[clap(flatten)]
+        solver: SolverArgs,
+        #[clap(flatten)]
+        dump: SmtLibDumpArgs,
+        #[clap(flatten)]
         req_env: RequestEnvArgs,
     },
 }
@@ -353,11 +426,52 @@ pub(crate) enum AnalysisCommands {
     Policies {
         #[clap(flatten)]
         args: PolicySetAnalysisArgs,
+        #[clap(flatten)]
+        solver: SolverArgs,
+        #[clap(flatten)]
+        dump: SmtLibDumpArgs,
     },
     /// Compare the source PolicySet against the target PolicySet
     Compare {
         #[clap(flatten)]
         args: ComparePolicySetAnalysisArgs,
+        #[clap(flatten)]
+        solver: SolverArgs,
+        #[clap(flatten)]
+        dump: SmtLibDumpArgs,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Subcommand)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum DiffTestCommands {
+    /// Compare authorization of a Request by cedar-policy and the Lean model
+    Authorize {
+        /// A file containing the PolicySet to authorize against
+        #[clap(required = true)]
+        policyset_file: PathBuf,
+        /// A file containing the entities relevant for authorization
+        #[clap(required = true)]
+        entities_file: PathBuf,
+        /// A file containing a Schema to parse entities with (the PolicySet is also validated against it)
+        schema_file: Option<PathBuf>,
+        #[clap(flatten)]
+        req_args: RequestArgs,
+    },
+    /// Compare validation of a PolicySet by cedar-policy and the Lean model
+    Validate {
+        /// A file containing the PolicySet to validate
+        #[clap(required = true)]
+        policyset_file: PathBuf,
+        /// A file containing the Schema to validate against
+        #[clap(required = true)]
+        schema_file: PathBuf,
+    },
+    /// Compare cedar-policy and the Lean model on an integration test (`JsonTest` format)
+    IntegrationTest {
+        /// A JSON file describing the test (policies, schema, entities, and requests)
+        #[clap(required = true)]
+        test_file: PathBuf,
     },
 }
 
@@ -384,6 +498,11 @@ pub(crate) enum Command {
         #[clap(subcommand)]
         command: SymCCCommands,
     },
+    /// Compare cedar-policy against the Lean model on the given inputs
+    DiffTest {
+        #[clap(subcommand)]
+        command: DiffTestCommands,
+    },
 }
 
 /// Command Line Interface for Cedar Lean
@@ -15,11 +15,13 @@
  */
 use crate::analysis;
 use crate::cli_enums::{
-    AnalysisCommands, CliArgs, Command, EvaluationCommands, ModeEnum, RequestArgsEnum,
-    SymCCCommands, ValidationCommands,
+    AnalysisCommands, CliArgs, Command, DiffTestCommands, EvaluationCommands, ModeEnum,
+    RequestArgsEnum, SymCCCommands, ValidationCommands,
 };
+use crate::diff_test;
 use crate::err::ExecError;
 use crate::evaluation;
+use crate::smtlib::SmtLibEmitter;
 use crate::symcc;
 use crate::util;
 use crate::util::OpenRequestEnv;
@@ -112,18 +114,39 @@ impl AnalysisCommands {
     /// Execute the task described by the analysis command
     fn exec(self) -> Result<(), ExecError> {
         match self {
-            Self::Policies { args } => {
+            Self::Policies { args, solver, dump } => {
                 let policyset = util::parse_policyset(&args.policyset_file)?;
                 let schema = util::parse_schema(&args.schema_file)?;
                 let json_output = args.json_output;
-                analysis::analyze_policyset(policyset, schema, json_output)
+                let mut emitter = SmtLibEmitter::new(dump.emit_smtlib_dir)?;
+                let result = analysis::analyze_policyset(
+                    policyset,
+                    schema,
+                    json_output,
+                    solver.into(),
+                    &mut emitter,
+                );
+                // Write the manifest even if the analysis failed part-way through
+                emitter.finish()?;
+                result
             }
-            Self::Compare { args } => {
+            Self::Compare { args, solver, dump } => {
                 let src_policyset = util::parse_policyset(&args.source_policyset_file)?;
                 let tgt_policyset = util::parse_policyset(&args.target_policyset_file)?;
                 let schema = util::parse_schema(&args.schema_file)?;
                 let json_output = args.json_output;
-                analysis::compare_policysets(src_policyset, tgt_policyset, schema, json_output)
+                let mut emitter = SmtLibEmitter::new(dump.emit_smtlib_dir)?;
+                let result = analysis::compare_policysets(
+                    src_policyset,
+                    tgt_policyset,
+                    schema,
+                    json_output,
+                    solver.into(),
+                    &mut emitter,
+                );
+                // Write the manifest even if the analysis failed part-way through
+                emitter.finish()?;
+                result
             }
         }
     }
@@ -136,14 +159,19 @@ impl SymCCCommands {
             Self::CheckNeverErrors {
                 args,
                 mode,
+                solver,
+                dump,
                 req_env,
             } => {
                 let policy = util::parse_policy(&args.policy_file)?;
                 let schema = util::parse_schema(&args.schema_file)?;
                 let req_env = OpenRequestEnv::from_request_args(req_env)?;
+                let mut emitter = SmtLibEmitter::new(dump.emit_smtlib_dir)?;
+                symcc::emit_check_never_errors(&policy, &schema, &req_env, &mut emitter)?;
+                emitter.finish()?;
                 match ModeEnum::from(mode) {
                     ModeEnum::RunAnalysis => {
-                        symcc::run_check_never_errors(policy, schema, &req_env)
+                        symcc::run_check_never_errors(policy, schema, &req_env, solver.into())
                     }
                     ModeEnum::PrintSMTLib => {
                         symcc::print_check_never_errors(policy, schema, &req_env)
@@ -153,14 +181,19 @@ impl SymCCCommands {
             Self::CheckAlwaysAllows {
                 args,
                 mode,
+                solver,
+                dump,
                 req_env,
             } => {
                 let policyset = util::parse_policyset(&args.policyset_file)?;
                 let schema = util::parse_schema(&args.schema_file)?;
                 let req_env = OpenRequestEnv::from_request_args(req_env)?;
+                let mut emitter = SmtLibEmitter::new(dump.emit_smtlib_dir)?;
+                symcc::emit_check_always_allows(&policyset, &schema, &req_env, &mut emitter)?;
+                emitter.finish()?;
                 match ModeEnum::from(mode) {
                     ModeEnum::RunAnalysis => {
-                        symcc::run_check_always_allows(policyset, schema, &req_env)
+                        symcc::run_check_always_allows(policyset, schema, &req_env, solver.into())
                     }
                     ModeEnum::PrintSMTLib => {
                         symcc::print_check_always_allows(policyset, schema, &req_env)
@@ -170,14 +203,19 @@ impl SymCCCommands {
             Self::CheckAlwaysDenies {
                 args,
                 mode,
+                solver,
+                dump,
                 req_env,
             } => {
                 let policyset = util::parse_policyset(&args.policyset_file)?;
                 let schema = util::parse_schema(&args.schema_file)?;
                 let req_env = OpenRequestEnv::from_request_args(req_env)?;
+                let mut emitter = SmtLibEmitter::new(dump.emit_smtlib_dir)?;
+                symcc::emit_check_always_denies(&policyset, &schema, &req_env, &mut emitter)?;
+                emitter.finish()?;
                 match ModeEnum::from(mode) {
                     ModeEnum::RunAnalysis => {
-                        symcc::run_check_always_denies(policyset, schema, &req_env)
+                        symcc::run_check_always_denies(policyset, schema, &req_env, solver.into())
                     }
                     ModeEnum::PrintSMTLib => {
                         symcc::print_check_always_denies(policyset, schema, &req_env)
@@ -187,16 +225,31 @@ impl SymCCCommands {
             Self::CheckEquivalent {
                 args,
                 mode,
+                solver,
+                dump,
                 req_env,
             } => {
                 let src_policyset = util::parse_policyset(&args.source_policyset_file)?;
                 let tgt_policyset = util::parse_policyset(&args.target_policyset_file)?;
                 let schema = util::parse_schema(&args.schema_file)?;
                 let req_env = OpenRequestEnv::from_request_args(req_env)?;
+                let mut emitter = SmtLibEmitter::new(dump.emit_smtlib_dir)?;
+                symcc::emit_check_equivalent(
+                    &src_policyset,
+                    &tgt_policyset,
+                    &schema,
+                    &req_env,
+                    &mut emitter,
+                )?;
+                emitter.finish()?;
                 match ModeEnum::from(mode) {
-                    ModeEnum::RunAnalysis => {
-                        symcc::run_check_equivalent(src_policyset, tgt_policyset, schema, &req_env)
-                    }
+                    ModeEnum::RunAnalysis => symcc::run_check_equivalent(
+                        src_policyset,
+                        tgt_policyset,
+                        schema,
+                        &req_env,
+                        solver.into(),
+                    ),
                     ModeEnum::PrintSMTLib => symcc::print_check_equivalent(
                         src_policyset,
                         tgt_policyset,
@@ -208,16 +261,31 @@ impl SymCCCommands {
             Self::CheckImplies {
                 args,
                 mode,
+                solver,
+                dump,
                 req_env,
             } => {
                 let src_policyset = util::parse_policyset(&args.source_policyset_file)?;
                 let tgt_policyset = util::parse_policyset(&args.target_policyset_file)?;
                 let schema = util::parse_schema(&args.schema_file)?;
                 let req_env = OpenRequestEnv::from_request_args(req_env)?;
+                let mut emitter = SmtLibEmitter::new(dump.emit_smtlib_dir)?;
+                symcc::emit_check_implies(
+                    &src_policyset,
+                    &tgt_policyset,
+                    &schema,
+                    &req_env,
+                    &mut emitter,
+                )?;
+                emitter.finish()?;
                 match ModeEnum::from(mode) {
-                    ModeEnum::RunAnalysis => {
-                        symcc::run_check_implies(src_policyset, tgt_policyset, schema, &req_env)
-                    }
+                    ModeEnum::RunAnalysis => symcc::run_check_implies(
+                        src_policyset,
+                        tgt_policyset,
+                        schema,
+                        &req_env,
+                        solver.into(),
+                    ),
                     ModeEnum::PrintSMTLib => {
                         symcc::print_check_implies(src_policyset, tgt_policyset, schema, &req_env)
                     }
@@ -226,16 +294,31 @@ impl SymCCCommands {
             Self::CheckDisjoint {
                 args,
                 mode,
+                solver,
+                dump,
                 req_env,
             } => {
                 let src_policyset = util::parse_policyset(&args.source_policyset_file)?;
                 let tgt_policyset = util::parse_policyset(&args.target_policyset_file)?;
                 let schema = util::parse_schema(&args.schema_file)?;
                 let req_env = OpenRequestEnv::from_request_args(req_env)?;
+                let mut emitter = SmtLibEmitter::new(dump.emit_smtlib_dir)?;
+                symcc::emit_check_disjoint(
+                    &src_policyset,
+                    &tgt_policyset,
+                    &schema,
+                    &req_env,
+                    &mut emitter,
+                )?;
+                emitter.finish()?;
                 match ModeEnum::from(mode) {
-                    ModeEnum::RunAnalysis => {
-                        symcc::run_check_disjoint(src_policyset, tgt_policyset, schema, &req_env)
-                    }
+                    ModeEnum::RunAnalysis => symcc::run_check_disjoint(
+                        src_policyset,
+                        tgt_policyset,
+                        schema,
+                        &req_env,
+                        solver.into(),
+                    ),
                     ModeEnum::PrintSMTLib => {
                         symcc::print_check_disjoint(src_policyset, tgt_policyset, schema, &req_env)
                     }
@@ -245,6 +328,37 @@ impl SymCCCommands {
     }
 }
 
+impl DiffTestCommands {
+    /// Execute the task described by the diff-test command
+    fn exec(self) -> Result<(), ExecError> {
+        match self {
+            Self::Authorize {
+                policyset_file,
+                entities_file,
+                schema_file,
+                req_args,
+            } => {
+                let policyset = util::parse_policyset(&policyset_file)?;
+                let schema = schema_file
+                    .map(|schema_file| util::parse_schema(&schema_file))
+                    .transpose()?;
+                let request = RequestArgsEnum::from(req_args).parse(schema.as_ref())?;
+                let entities = util::parse_entities(&entities_file, schema.as_ref())?;
+                diff_test::diff_test_authorize(&policyset, &entities, &request, schema.as_ref())
+            }
+            Self::Validate {
+                policyset_file,
+                schema_file,
+            } => {
+                let policyset = util::parse_policyset(&policyset_file)?;
+                let schema = util::parse_schema(&schema_file)?;
+                diff_test::diff_test_validate(&policyset, &schema)
+            }
+            Self::IntegrationTest { test_file } => diff_test::diff_test_integration(&test_file),
+        }
+    }
+}
+
 impl CliArgs {
     /// Execute the task described by the command-line arguments
     pub fn exec(self) -> Result<(), ExecError> {
@@ -253,6 +367,7 @@ impl CliArgs {
             Command::Evaluate { command } => command.exec(),
             Command::Validate { command } => command.exec(),
             Command::Symcc { command } => command.exec(),
+            Command::DiffTest { command } => command.exec(),
         }
     }
 }
@@ -0,0 +1,483 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Differential testing of user-provided inputs: runs both `cedar-policy` and
+//! the Lean formalization on the same inputs and reports any disagreement
+//! between the two, rather than panicking like the DRT fuzz targets.
+
+use crate::err::{ContentType, ExecError, RequestElement};
+use crate::util;
+use cedar_lean_ffi::{AuthorizationResponse, CedarLeanFfi, ValidationResponse};
+use cedar_policy::{
+    AuthorizationError, Authorizer, Context, Decision, Entities, EntityUid, PolicyId, PolicySet,
+    Request, Response, Schema, ValidationMode, Validator,
+};
+use cedar_testing::integration_testing::{JsonRequest, JsonTest};
+use itertools::Itertools;
+use std::collections::BTreeSet;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Authorization outcome of a single engine, normalized so that the Rust and
+/// Lean results can be compared directly. Erroring policies are compared by
+/// policy id only, since the Lean engine does not report error details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AuthOutcome {
+    decision: Decision,
+    reason: BTreeSet<PolicyId>,
+    errors: BTreeSet<PolicyId>,
+}
+
+impl AuthOutcome {
+    fn from_rust(response: &Response) -> Self {
+        Self {
+            decision: response.decision(),
+            reason: response.diagnostics().reason().cloned().collect(),
+            errors: response
+                .diagnostics()
+                .errors()
+                .map(|e| match e {
+                    AuthorizationError::PolicyEvaluationError(e) => e.policy_id().clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn from_lean(response: &AuthorizationResponse) -> Self {
+        Self {
+            decision: response.decision(),
+            reason: response.determining_policies().iter().cloned().collect(),
+            errors: response.erroring_policies().iter().cloned().collect(),
+        }
+    }
+
+    fn from_expected(request: &JsonRequest) -> Self {
+        Self {
+            decision: request.decision,
+            reason: request.reason.iter().cloned().collect(),
+            errors: request.errors.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Pretty print a set of policy ids as `[p0, p1]`
+fn fmt_ids(ids: &BTreeSet<PolicyId>) -> String {
+    format!("[{}]", ids.iter().join(", "))
+}
+
+/// A single disagreement found while differentially testing
+enum Discrepancy {
+    /// The two engines returned different authorization responses
+    Authorization {
+        label: String,
+        request: String,
+        rust: AuthOutcome,
+        lean: AuthOutcome,
+    },
+    /// `cedar-policy` validated the policy set but the Lean validator did not
+    Validation {
+        rust_warnings: Vec<String>,
+        lean_error: String,
+    },
+    /// The Lean engine failed to produce a result at all
+    LeanFailure { label: String, error: String },
+    /// Both engines agree with each other, but not with the expected output
+    /// recorded in an integration test
+    UnexpectedAuthorization {
+        label: String,
+        request: String,
+        expected: AuthOutcome,
+        actual: AuthOutcome,
+    },
+    /// Both engines agree on the validation result, but it does not match
+    /// `should_validate` in an integration test
+    UnexpectedValidation { expected: bool, actual: bool },
+}
+
+impl std::fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Authorization {
+                label,
+                request,
+                rust,
+                lean,
+            } => {
+                writeln!(f, "Authorization mismatch for {label}")?;
+                writeln!(f, "  request:  {request}")?;
+                writeln!(
+                    f,
+                    "  decision: rust = {:?}, lean = {:?}",
+                    rust.decision, lean.decision
+                )?;
+                writeln!(
+                    f,
+                    "  reason:   rust = {}, lean = {}",
+                    fmt_ids(&rust.reason),
+                    fmt_ids(&lean.reason)
+                )?;
+                write!(
+                    f,
+                    "  errors:   rust = {}, lean = {}",
+                    fmt_ids(&rust.errors),
+                    fmt_ids(&lean.errors)
+                )
+            }
+            Self::Validation {
+                rust_warnings,
+                lean_error,
+            } => {
+                writeln!(f, "Validation mismatch")?;
+                writeln!(f, "  rust: passed")?;
+                if !rust_warnings.is_empty() {
+                    writeln!(f, "  rust warnings: {}", rust_warnings.iter().join("; "))?;
+                }
+                write!(f, "  lean: failed with {lean_error}")
+            }
+            Self::LeanFailure { label, error } => {
+                writeln!(f, "Lean engine failed on {label}")?;
+                write!(f, "  error: {error}")
+            }
+            Self::UnexpectedAuthorization {
+                label,
+                request,
+                expected,
+                actual,
+            } => {
+                writeln!(
+                    f,
+                    "Both engines disagree with the expected result for {label}"
+                )?;
+                writeln!(f, "  request:  {request}")?;
+                writeln!(
+                    f,
+                    "  decision: expected = {:?}, actual = {:?}",
+                    expected.decision, actual.decision
+                )?;
+                writeln!(
+                    f,
+                    "  reason:   expected = {}, actual = {}",
+                    fmt_ids(&expected.reason),
+                    fmt_ids(&actual.reason)
+                )?;
+                write!(
+                    f,
+                    "  errors:   expected = {}, actual = {}",
+                    fmt_ids(&expected.errors),
+                    fmt_ids(&actual.errors)
+                )
+            }
+            Self::UnexpectedValidation { expected, actual } => {
+                writeln!(
+                    f,
+                    "Both engines disagree with the expected validation result"
+                )?;
+                write!(
+                    f,
+                    "  should_validate: expected = {expected}, actual = {actual}"
+                )
+            }
+        }
+    }
+}
+
+/// Collects the results of the individual comparisons made during a
+/// `diff-test` run and prints them as a readable report
+struct DiffReport {
+    lean_context: CedarLeanFfi,
+    checks: usize,
+    discrepancies: Vec<Discrepancy>,
+}
+
+impl DiffReport {
+    fn new() -> Self {
+        Self {
+            lean_context: CedarLeanFfi::new(),
+            checks: 0,
+            discrepancies: Vec::new(),
+        }
+    }
+
+    /// Compare `cedar-policy` and Lean authorization on a single request.
+    /// Returns the response that the two engines agree on, if they agree.
+    fn compare_authorization(
+        &mut self,
+        label: &str,
+        policyset: &PolicySet,
+        entities: &Entities,
+        request: &Request,
+    ) -> Option<AuthOutcome> {
+        self.checks += 1;
+        let rust =
+            AuthOutcome::from_rust(&Authorizer::new().is_authorized(request, policyset, entities));
+        match self
+            .lean_context
+            .is_authorized(policyset, entities, request)
+        {
+            Ok(lean) => {
+                let lean = AuthOutcome::from_lean(&lean);
+                if rust == lean {
+                    println!("{label}: engines agree ({:?})", rust.decision);
+                    Some(rust)
+                } else {
+                    println!("{label}: MISMATCH");
+                    self.discrepancies.push(Discrepancy::Authorization {
+                        label: label.to_string(),
+                        request: request.to_string(),
+                        rust,
+                        lean,
+                    });
+                    None
+                }
+            }
+            Err(err) => {
+                println!("{label}: LEAN FAILURE");
+                self.discrepancies.push(Discrepancy::LeanFailure {
+                    label: label.to_string(),
+                    error: err.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Compare `cedar-policy` and Lean (strict) validation of a policy set.
+    /// Like `run_val_test` in `cedar-drt`, only a policy set that passes Rust
+    /// validation but fails Lean validation counts as a mismatch.
+    /// Returns `cedar-policy`'s result unless there is a mismatch or Lean fails.
+    fn compare_validation(&mut self, policyset: &PolicySet, schema: &Schema) -> Option<bool> {
+        self.checks += 1;
+        let rust = Validator::new(schema.clone()).validate(policyset, ValidationMode::Strict);
+        let rust_passed = rust.validation_passed();
+        match self
+            .lean_context
+            .validate(policyset, schema, &ValidationMode::Strict)
+        {
+            // The Lean validator reports `impossiblePolicy` as an error, while
+            // `cedar-policy` reports it as a warning
+            Ok(ValidationResponse::Error(err)) if rust_passed && err != "impossiblePolicy" => {
+                println!("Validation: MISMATCH");
+                self.discrepancies.push(Discrepancy::Validation {
+                    rust_warnings: rust
+                        .validation_warnings()
+                        .map(ToString::to_string)
+                        .collect(),
+                    lean_error: err,
+                });
+                None
+            }
+            // Lean may accept policy sets that `cedar-policy` rejects
+            Ok(ValidationResponse::Ok(())) if !rust_passed => {
+                println!(
+                    "Validation: not compared (AgreeOnValid): cedar-policy failed, Lean passed"
+                );
+                Some(rust_passed)
+            }
+            Ok(_) => {
+                println!(
+                    "Validation: engines agree ({})",
+                    if rust_passed { "passed" } else { "failed" }
+                );
+                Some(rust_passed)
+            }
+            Err(err) => {
+                println!("Validation: LEAN FAILURE");
+                self.discrepancies.push(Discrepancy::LeanFailure {
+                    label: "validation".to_string(),
+                    error: err.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Print the discrepancy report. Returns an error if any discrepancy was
+    /// found so that the CLI exits with a non-zero status.
+    fn finish(self) -> Result<(), ExecError> {
+        println!();
+        if self.discrepancies.is_empty() {
+            println!(
+                "No discrepancies found between cedar-policy and the Lean model ({} checks)",
+                self.checks
+            );
+            return Ok(());
+        }
+        println!(
+            "Found {} discrepancies ({} checks):",
+            self.discrepancies.len(),
+            self.checks
+        );
+        for (i, discrepancy) in self.discrepancies.iter().enumerate() {
+            println!();
+            println!("[{}] {discrepancy}", i + 1);
+        }
+        Err(ExecError::DiffTestMismatch {
+            count: self.discrepancies.len(),
+        })
+    }
+}
+
+/// Differentially test authorization of a single request, additionally
+/// comparing validation of the policy set if a schema was provided
+pub fn diff_test_authorize(
+    policyset: &PolicySet,
+    entities: &Entities,
+    request: &Request,
+    schema: Option<&Schema>,
+) -> Result<(), ExecError> {
+    let mut report = DiffReport::new();
+    if let Some(schema) = schema {
+        report.compare_validation(policyset, schema);
+    }
+    report.compare_authorization("Request", policyset, entities, request);
+    report.finish()
+}
+
+/// Differentially test validation of a policy set against a schema
+pub fn diff_test_validate(policyset: &PolicySet, schema: &Schema) -> Result<(), ExecError> {
+    let mut report = DiffReport::new();
+    report.compare_validation(policyset, schema);
+    report.finish()
+}
+
+/// Differentially test an integration test in the `cedar-testing` `JsonTest`
+/// format. Besides comparing the two engines with each other, results that both
+/// engines agree on are checked against the expectations recorded in the test.
+pub fn diff_test_integration(test_file: &PathBuf) -> Result<(), ExecError> {
+    let test = parse_json_test(test_file)?;
+    let base_dir = test_file.parent().unwrap_or(Path::new("."));
+    let policyset = parse_policyset_from_test(&resolve_test_path(base_dir, &test.policies))?;
+    let schema = util::parse_schema(&resolve_test_path(base_dir, &test.schema))?;
+    let entities =
+        util::parse_entities(&resolve_test_path(base_dir, &test.entities), Some(&schema))?;
+
+    let mut report = DiffReport::new();
+    if let Some(passed) = report.compare_validation(&policyset, &schema) {
+        if passed != test.should_validate {
+            report
+                .discrepancies
+                .push(Discrepancy::UnexpectedValidation {
+                    expected: test.should_validate,
+                    actual: passed,
+                });
+        }
+    }
+    for (i, json_request) in test.requests.iter().enumerate() {
+        let label = format!("Request {i} ({})", json_request.description);
+        let request = request_from_json_test(json_request, &schema)?;
+        if let Some(actual) = report.compare_authorization(&label, &policyset, &entities, &request)
+        {
+            let expected = AuthOutcome::from_expected(json_request);
+            if actual != expected {
+                report
+                    .discrepancies
+                    .push(Discrepancy::UnexpectedAuthorization {
+                        label,
+                        request: request.to_string(),
+                        expected,
+                        actual,
+                    });
+            }
+        }
+    }
+    report.finish()
+}
+
+/// Parse a file in the `JsonTest` integration test format
+fn parse_json_test(test_file: &PathBuf) -> Result<JsonTest, ExecError> {
+    match read_to_string(test_file) {
+        Ok(json_str) => serde_json::from_str(&json_str).map_err(|e| ExecError::ParseError {
+            content_type: ContentType::IntegrationTest,
+            file_name: test_file.to_path_buf(),
+            error: Box::new(e),
+        }),
+        Err(e) => Err(ExecError::FileReadError {
+            content_type: ContentType::IntegrationTest,
+            file_name: test_file.to_path_buf(),
+            error: Box::new(e),
+        }),
+    }
+}
+
+/// Parse the policies of a `JsonTest`. Unlike `util::parse_policyset`, this
+/// keeps the default policy ids (`policy0`, `policy1`, ...) since the expected
+/// `reason` and `errors` in the test refer to them.
+fn parse_policyset_from_test(fname: &PathBuf) -> Result<PolicySet, ExecError> {
+    match read_to_string(fname) {
+        Ok(policyset_text) => {
+            PolicySet::from_str(&policyset_text).map_err(|e| ExecError::ParseError {
+                content_type: ContentType::PolicySet,
+                file_name: fname.to_path_buf(),
+                error: Box::new(e),
+            })
+        }
+        Err(e) => Err(ExecError::FileReadError {
+            content_type: ContentType::PolicySet,
+            file_name: fname.to_path_buf(),
+            error: Box::new(e),
+        }),
+    }
+}
+
+/// Paths in a `JsonTest` are relative to the directory the tests are run
+/// from. Use the path as-is if it exists, and otherwise interpret it relative
+/// to the directory containing the test file.
+fn resolve_test_path(base_dir: &Path, path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_absolute() || path.exists() {
+        path
+    } else {
+        base_dir.join(path)
+    }
+}
+
+/// Auxillary function that converts the JSON representation of an entity uid
+/// in a `JsonRequest` into a Cedar EntityUid
+fn entity_uid_from_json_test(
+    v: &serde_json::Value,
+    element: RequestElement,
+) -> Result<EntityUid, ExecError> {
+    EntityUid::from_json(v.clone()).map_err(|e| ExecError::RequestError {
+        element,
+        input_str: v.to_string(),
+        error: Box::new(e),
+    })
+}
+
+/// Auxillary function used to convert a `JsonRequest` into a Cedar Request,
+/// validating the request against `schema` if the test asks for it
+fn request_from_json_test(
+    json_request: &JsonRequest,
+    schema: &Schema,
+) -> Result<Request, ExecError> {
+    let principal = entity_uid_from_json_test(&json_request.principal, RequestElement::Principal)?;
+    let action = entity_uid_from_json_test(&json_request.action, RequestElement::Action)?;
+    let resource = entity_uid_from_json_test(&json_request.resource, RequestElement::Resource)?;
+    let context = Context::from_json_value(json_request.context.clone(), Some((schema, &action)))
+        .map_err(|e| ExecError::RequestError {
+        element: RequestElement::Context,
+        input_str: json_request.context.to_string(),
+        error: Box::new(e),
+    })?;
+    let schema = if json_request.validate_request {
+        Some(schema)
+    } else {
+        None
+    };
+    Request::new(principal, action, resource, context, schema)
+        .map_err(|e| ExecError::RequestValidationError { error: Box::new(e) })
+}
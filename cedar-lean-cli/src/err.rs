@@ -23,6 +23,7 @@ pub enum ContentType {
     Context,
     Entities,
     Expression,
+    IntegrationTest,
     Policy,
     PolicySet,
     Request,
@@ -99,6 +100,13 @@ pub enum ExecError {
         action_name: String,
         resource_type: String,
     },
+    #[error("Error writing SMT-LIB output to {file_name} : {error}")]
+    SmtLibWriteError {
+        file_name: PathBuf,
+        error: std::io::Error,
+    },
+    #[error("Found {count} discrepancies between cedar-policy and the Lean model")]
+    DiffTestMismatch { count: usize },
     #[error(transparent)]
     LeanFFIError(#[from] cedar_lean_ffi::FfiError),
 }
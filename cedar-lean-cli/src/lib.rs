@@ -17,8 +17,10 @@
 mod analysis;
 mod cli_enums;
 mod cli_exec;
+mod diff_test;
 mod err;
 mod evaluation;
+mod smtlib;
 mod symcc;
 mod util;
 mod validation;
@@ -0,0 +1,180 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use crate::err::ExecError;
+use crate::util::RequestEnvSer;
+use cedar_lean_ffi::FfiError;
+use cedar_policy::RequestEnv;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest written next to the emitted SMT-LIB scripts
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Subdirectory of the scripts whose inputs are individual policies (see [`CheckInputs::Policies`])
+const POLICIES_DIR_NAME: &str = "policies";
+
+/// The inputs of a check, used to name its script
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CheckInputs<'a> {
+    /// The role of each PolicySet in the command (e.g., `source`)
+    Roles(&'a [&'a str]),
+    /// The ids of individual policies. Since a policy id may be any string (including a role),
+    /// these scripts are written to their own subdirectory.
+    Policies(&'a [&'a str]),
+}
+
+/// Manifest entry mapping an emitted `.smt2` file to the check that produced it
+#[derive(Debug, Clone, Serialize)]
+struct ManifestEntry {
+    /// Path of the `.smt2` file (relative to the output directory)
+    file: String,
+    /// The check encoded by the script (e.g., `check-implies`)
+    check: String,
+    /// The inputs of the check: the role of each PolicySet (e.g., `source`), or policy ids
+    /// for files in the `policies` subdirectory
+    inputs: Vec<String>,
+    /// The request environment the check was encoded for
+    request_env: RequestEnvSer,
+}
+
+/// Writes the SMT-LIB script of every check (per request environment) to its own `.smt2`
+/// file within a directory, along with a `manifest.json` mapping each file to its check.
+///
+/// File names are derived from the check, its inputs, and the request environment, so
+/// running the same command twice produces the same files. A disabled emitter (no output
+/// directory) writes nothing and never calls into the Lean backend.
+pub(crate) struct SmtLibEmitter {
+    dir: Option<PathBuf>,
+    entries: Vec<ManifestEntry>,
+    file_names: HashSet<String>,
+}
+
+impl SmtLibEmitter {
+    /// Create an emitter writing to `dir` (creating it if needed), or a disabled emitter if `dir` is `None`
+    pub(crate) fn new(dir: Option<PathBuf>) -> Result<Self, ExecError> {
+        if let Some(dir) = &dir {
+            std::fs::create_dir_all(dir).map_err(|error| ExecError::SmtLibWriteError {
+                file_name: dir.clone(),
+                error,
+            })?;
+        }
+        Ok(Self {
+            dir,
+            entries: Vec::new(),
+            file_names: HashSet::new(),
+        })
+    }
+
+    /// Whether scripts are written anywhere
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Write the SMT-LIB script of `check` on `inputs` for `req_env`.
+    /// `script` is only invoked if the emitter is enabled.
+    pub(crate) fn emit<F>(
+        &mut self,
+        check: &str,
+        inputs: CheckInputs<'_>,
+        req_env: &RequestEnv,
+        script: F,
+    ) -> Result<(), ExecError>
+    where
+        F: FnOnce() -> Result<String, FfiError>,
+    {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let script = script()?;
+        let (subdir, inputs) = match inputs {
+            CheckInputs::Roles(roles) => (None, roles),
+            CheckInputs::Policies(ids) => (Some(POLICIES_DIR_NAME), ids),
+        };
+
+        // Components are sanitized to `[A-Za-z0-9_-]` so `.` unambiguously separates them
+        let stem = std::iter::once(check.to_string())
+            .chain(inputs.iter().map(|input| input.to_string()))
+            .chain([
+                req_env.principal().to_string(),
+                req_env.action().to_string(),
+                req_env.resource().to_string(),
+            ])
+            .map(|component| sanitize(&component))
+            .collect::<Vec<_>>()
+            .join(".");
+        let prefix = match subdir {
+            Some(subdir) => {
+                let subdir_path = dir.join(subdir);
+                std::fs::create_dir_all(&subdir_path).map_err(|error| {
+                    ExecError::SmtLibWriteError {
+                        file_name: subdir_path,
+                        error,
+                    }
+                })?;
+                format!("{subdir}/")
+            }
+            None => String::new(),
+        };
+        let mut file = format!("{prefix}{stem}.smt2");
+        let mut suffix = 1;
+        while !self.file_names.insert(file.clone()) {
+            file = format!("{prefix}{stem}-{suffix}.smt2");
+            suffix += 1;
+        }
+
+        write_file(&dir.join(&file), &script)?;
+        self.entries.push(ManifestEntry {
+            file,
+            check: check.to_string(),
+            inputs: inputs.iter().map(|input| input.to_string()).collect(),
+            request_env: RequestEnvSer::new(req_env),
+        });
+        Ok(())
+    }
+
+    /// Write the manifest of all scripts emitted so far (sorted by file path)
+    pub(crate) fn finish(mut self) -> Result<(), ExecError> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        self.entries.sort_by(|e1, e2| e1.file.cmp(&e2.file));
+        let manifest =
+            serde_json::to_string_pretty(&self.entries).expect("Failed to serialize manifest");
+        write_file(&dir.join(MANIFEST_FILE_NAME), &manifest)
+    }
+}
+
+/// Replace every character that is not an ASCII alphanumeric, `-`, or `_` by `_`
+fn sanitize(component: &str) -> String {
+    component
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), ExecError> {
+    std::fs::write(path, contents).map_err(|error| ExecError::SmtLibWriteError {
+        file_name: path.to_path_buf(),
+        error,
+    })
+}
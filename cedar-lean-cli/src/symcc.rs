@@ -14,8 +14,9 @@
  * limitations under the License.
  */
 use crate::err::ExecError;
+use crate::smtlib::{CheckInputs, SmtLibEmitter};
 use crate::util::{OpenRequestEnv, ReqEnv};
-use cedar_lean_ffi::CedarLeanFfi;
+use cedar_lean_ffi::{CedarLeanFfi, SolverConfig};
 use cedar_policy::{Policy, PolicySet, RequestEnv, Schema};
 use std::iter::zip;
 
@@ -24,8 +25,9 @@ pub fn run_check_never_errors(
     policy: Policy,
     schema: Schema,
     request_env: &OpenRequestEnv,
+    solver: SolverConfig,
 ) -> Result<(), ExecError> {
-    let lean_context = CedarLeanFfi::new();
+    let lean_context = CedarLeanFfi::with_solver_config(solver);
     let req_envs = request_env.to_request_envs(&schema)?;
     let mut results = Vec::new();
     for req_env in req_envs.iter() {
@@ -40,8 +42,9 @@ pub fn run_check_always_allows(
     policyset: PolicySet,
     schema: Schema,
     request_env: &OpenRequestEnv,
+    solver: SolverConfig,
 ) -> Result<(), ExecError> {
-    let lean_context = CedarLeanFfi::new();
+    let lean_context = CedarLeanFfi::with_solver_config(solver);
     let req_envs = request_env.to_request_envs(&schema)?;
     let mut results = Vec::new();
     for req_env in req_envs.iter() {
@@ -56,8 +59,9 @@ pub fn run_check_always_denies(
     policyset: PolicySet,
     schema: Schema,
     request_env: &OpenRequestEnv,
+    solver: SolverConfig,
 ) -> Result<(), ExecError> {
-    let lean_context = CedarLeanFfi::new();
+    let lean_context = CedarLeanFfi::with_solver_config(solver);
     let req_envs = request_env.to_request_envs(&schema)?;
     let mut results = Vec::new();
     for req_env in req_envs.iter() {
@@ -73,8 +77,9 @@ pub fn run_check_equivalent(
     tgt_policyset: PolicySet,
     schema: Schema,
     request_env: &OpenRequestEnv,
+    solver: SolverConfig,
 ) -> Result<(), ExecError> {
-    let lean_context = CedarLeanFfi::new();
+    let lean_context = CedarLeanFfi::with_solver_config(solver);
     let req_envs = request_env.to_request_envs(&schema)?;
     let mut results = Vec::new();
     for req_env in req_envs.iter() {
@@ -96,8 +101,9 @@ pub fn run_check_implies(
     tgt_policyset: PolicySet,
     schema: Schema,
     request_env: &OpenRequestEnv,
+    solver: SolverConfig,
 ) -> Result<(), ExecError> {
-    let lean_context = CedarLeanFfi::new();
+    let lean_context = CedarLeanFfi::with_solver_config(solver);
     let req_envs = request_env.to_request_envs(&schema)?;
     let mut results = Vec::new();
     for req_env in req_envs.iter() {
@@ -117,8 +123,9 @@ pub fn run_check_disjoint(
     tgt_policyset: PolicySet,
     schema: Schema,
     request_env: &OpenRequestEnv,
+    solver: SolverConfig,
 ) -> Result<(), ExecError> {
-    let lean_context = CedarLeanFfi::new();
+    let lean_context = CedarLeanFfi::with_solver_config(solver);
     let req_envs = request_env.to_request_envs(&schema)?;
     let mut results = Vec::new();
     for req_env in req_envs.iter() {
@@ -262,6 +269,148 @@ pub fn print_check_disjoint(
     Ok(())
 }
 
+/// Writes the SMTLib script produced by the lean backend for analysis `check-never-errors` to the emitter's directory
+pub fn emit_check_never_errors(
+    policy: &Policy,
+    schema: &Schema,
+    request_env: &OpenRequestEnv,
+    emitter: &mut SmtLibEmitter,
+) -> Result<(), ExecError> {
+    if !emitter.is_enabled() {
+        return Ok(());
+    }
+    let lean_context = CedarLeanFfi::new();
+    for req_env in request_env.to_request_envs(schema)?.iter() {
+        emitter.emit(
+            "check-never-errors",
+            CheckInputs::Roles(&["policy"]),
+            req_env,
+            || lean_context.smtlib_of_check_never_errors(policy, schema, req_env),
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the SMTLib script produced by the lean backend for analysis `check-always-allows` to the emitter's directory
+pub fn emit_check_always_allows(
+    policyset: &PolicySet,
+    schema: &Schema,
+    request_env: &OpenRequestEnv,
+    emitter: &mut SmtLibEmitter,
+) -> Result<(), ExecError> {
+    if !emitter.is_enabled() {
+        return Ok(());
+    }
+    let lean_context = CedarLeanFfi::new();
+    for req_env in request_env.to_request_envs(schema)?.iter() {
+        emitter.emit(
+            "check-always-allows",
+            CheckInputs::Roles(&["policyset"]),
+            req_env,
+            || lean_context.smtlib_of_check_always_allows(policyset, schema, req_env),
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the SMTLib script produced by the lean backend for analysis `check-always-denies` to the emitter's directory
+pub fn emit_check_always_denies(
+    policyset: &PolicySet,
+    schema: &Schema,
+    request_env: &OpenRequestEnv,
+    emitter: &mut SmtLibEmitter,
+) -> Result<(), ExecError> {
+    if !emitter.is_enabled() {
+        return Ok(());
+    }
+    let lean_context = CedarLeanFfi::new();
+    for req_env in request_env.to_request_envs(schema)?.iter() {
+        emitter.emit(
+            "check-always-denies",
+            CheckInputs::Roles(&["policyset"]),
+            req_env,
+            || lean_context.smtlib_of_check_always_denies(policyset, schema, req_env),
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the SMTLib script produced by the lean backend for analysis `check-equivalent` to the emitter's directory
+pub fn emit_check_equivalent(
+    src_policyset: &PolicySet,
+    tgt_policyset: &PolicySet,
+    schema: &Schema,
+    request_env: &OpenRequestEnv,
+    emitter: &mut SmtLibEmitter,
+) -> Result<(), ExecError> {
+    if !emitter.is_enabled() {
+        return Ok(());
+    }
+    let lean_context = CedarLeanFfi::new();
+    for req_env in request_env.to_request_envs(schema)?.iter() {
+        emitter.emit(
+            "check-equivalent",
+            CheckInputs::Roles(&["source", "target"]),
+            req_env,
+            || {
+                lean_context.smtlib_of_check_equivalent(
+                    src_policyset,
+                    tgt_policyset,
+                    schema,
+                    req_env,
+                )
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the SMTLib script produced by the lean backend for analysis `check-implies` to the emitter's directory
+pub fn emit_check_implies(
+    src_policyset: &PolicySet,
+    tgt_policyset: &PolicySet,
+    schema: &Schema,
+    request_env: &OpenRequestEnv,
+    emitter: &mut SmtLibEmitter,
+) -> Result<(), ExecError> {
+    if !emitter.is_enabled() {
+        return Ok(());
+    }
+    let lean_context = CedarLeanFfi::new();
+    for req_env in request_env.to_request_envs(schema)?.iter() {
+        emitter.emit(
+            "check-implies",
+            CheckInputs::Roles(&["source", "target"]),
+            req_env,
+            || lean_context.smtlib_of_check_implies(src_policyset, tgt_policyset, schema, req_env),
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the SMTLib script produced by the lean backend for analysis `check-disjoint` to the emitter's directory
+pub fn emit_check_disjoint(
+    src_policyset: &PolicySet,
+    tgt_policyset: &PolicySet,
+    schema: &Schema,
+    request_env: &OpenRequestEnv,
+    emitter: &mut SmtLibEmitter,
+) -> Result<(), ExecError> {
+    if !emitter.is_enabled() {
+        return Ok(());
+    }
+    let lean_context = CedarLeanFfi::new();
+    for req_env in request_env.to_request_envs(schema)?.iter() {
+        emitter.emit(
+            "check-disjoint",
+            CheckInputs::Roles(&["source", "target"]),
+            req_env,
+            || lean_context.smtlib_of_check_disjoint(src_policyset, tgt_policyset, schema, req_env),
+        )?;
+    }
+    Ok(())
+}
+
 /***************************************************************************************************
  * Functions to pretty print results
  ***************************************************************************************************/
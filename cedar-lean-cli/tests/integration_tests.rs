@@ -930,3 +930,83 @@ fn test_analyze_compare_tabular_demo_4_3() {
         false,
     )
 }
+
+/***************************************** Tests for SMT-LIB Output *****************************************/
+#[test]
+fn test_analyze_compare_emit_smtlib_dir() {
+    let base_path = PathBuf::from("examples/analyze/view_box");
+    let out_dir = std::env::temp_dir().join("cedar-lean-cli-emit-smtlib-compare");
+    let _ = std::fs::remove_dir_all(&out_dir);
+
+    let output = Command::new("cedar-lean-cli")
+        .arg("analyze")
+        .arg("compare")
+        .arg(base_path.join("policies6.cedar"))
+        .arg(base_path.join("policies7.cedar"))
+        .arg(base_path.join("policies.cedarschema"))
+        .arg("--emit-smtlib-dir")
+        .arg(&out_dir)
+        .output()
+        .expect("Failed to run cedar-lean-cli");
+
+    // Emitting SMT-LIB scripts does not change the findings
+    check_output(
+        output,
+        base_path.join("outputs/tabular/compare_basic_6_7.out"),
+        false,
+    );
+
+    let manifest: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(out_dir.join("manifest.json")).expect("Failed to read manifest"),
+    )
+    .expect("Manifest is not valid JSON");
+    let entries = manifest.as_array().expect("Manifest is not a JSON array");
+    assert!(!entries.is_empty(), "No SMT-LIB scripts were emitted");
+    for entry in entries {
+        assert_eq!(entry["check"], "check-implies");
+        let file = entry["file"].as_str().expect("Manifest entry has no file");
+        let script = std::fs::read_to_string(out_dir.join(file)).expect("Failed to read script");
+        // Trivially decided checks produce an empty script; all others start by resetting the solver
+        assert!(
+            script.is_empty() || script.starts_with("(reset)"),
+            "{file} is not an SMT-LIB script"
+        );
+    }
+}
+
+/***************************************** Tests for Diff Testing *****************************************/
+#[test]
+fn test_diff_test_authorize() {
+    let base_path = PathBuf::from("examples/diff_test");
+
+    let output = Command::new("cedar-lean-cli")
+        .arg("diff-test")
+        .arg("authorize")
+        .arg(base_path.join("policies.cedar"))
+        .arg(base_path.join("entities.json"))
+        .arg(base_path.join("policies.cedarschema"))
+        .arg("--request-file")
+        .arg(base_path.join("request.json"))
+        .output()
+        .expect("Failed to run cedar-lean-cli");
+
+    check_output(output, base_path.join("outputs/authorize.out"), false)
+}
+
+#[test]
+fn test_diff_test_integration_test() {
+    let base_path = PathBuf::from("examples/diff_test");
+
+    let output = Command::new("cedar-lean-cli")
+        .arg("diff-test")
+        .arg("integration-test")
+        .arg(base_path.join("test.json"))
+        .output()
+        .expect("Failed to run cedar-lean-cli");
+
+    check_output(
+        output,
+        base_path.join("outputs/integration_test.out"),
+        false,
+    )
+}
@@ -1,11 +1,15 @@
-use cedar_policy::{Decision, EntityId, EntityTypeName, EntityUid, PolicyId};
+use cedar_policy::{
+    Context, Decision, Entities, Entity, EntityId, EntityTypeName, EntityUid, Expression, Policy,
+    PolicyId, Request, RestrictedExpression, Schema,
+};
 use num_bigint::ParseBigIntError;
-use serde::{Deserialize, Deserializer};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
 use smol_str::SmolStr;
 use thiserror::Error;
 
 use std::char::CharTryFromError;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -34,13 +38,16 @@ pub(crate) enum ResultDef<T> {
     /// Failure case
     #[serde(rename = "error")]
     Error(String),
+    /// The SMT solver returned `unknown` (only produced by functions that call the solver)
+    #[serde(rename = "unknown")]
+    Unknown(String),
 }
 
 impl<T> ResultDef<T> {
     pub fn to_result(def: ResultDef<T>) -> Result<T, String> {
         match def {
             ResultDef::Ok(t) => Ok(t),
-            ResultDef::Error(s) => Err(s),
+            ResultDef::Error(s) | ResultDef::Unknown(s) => Err(s),
         }
     }
 }
@@ -117,8 +124,93 @@ where
     Ok(EntityUid::from_type_name_and_id(euid_def.ty, eid))
 }
 
+// Helper function to deserialize a Lean `Pattern`, failing on characters that
+// are not Unicode scalar values
+fn deserialize_pattern<'de, D>(
+    deserializer: D,
+) -> Result<Vec<cedar_policy_core::ast::PatternElem>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<PatElem>::deserialize(deserializer)?
+        .into_iter()
+        .map(|elem| {
+            cedar_policy_core::ast::PatternElem::try_from(elem).map_err(serde::de::Error::custom)
+        })
+        .collect()
+}
+
 /********************************** Publicly Exported Types **********************************/
 
+/// The SMT solvers supported by the Lean backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SolverKind {
+    #[default]
+    Cvc5,
+    Z3,
+}
+
+/// Configuration of the SMT solver that the Lean backend spawns to discharge
+/// `run_check_*` queries. By default, CVC5 is used and its executable is read
+/// from the `CVC5` environment variable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolverConfig {
+    kind: SolverKind,
+    path: Option<PathBuf>,
+    options: Vec<String>,
+    timeout: Option<std::time::Duration>,
+}
+
+impl SolverConfig {
+    /// Use the solver `kind`, whose executable is read from the `CVC5` or `Z3`
+    /// environment variable unless a path is set with `with_path`
+    pub fn new(kind: SolverKind) -> Self {
+        Self {
+            kind,
+            ..Self::default()
+        }
+    }
+
+    /// Use the solver executable at `path`
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Pass `options` to the solver in addition to the default arguments
+    pub fn with_options(mut self, options: impl IntoIterator<Item = String>) -> Self {
+        self.options = options.into_iter().collect();
+        self
+    }
+
+    /// Give up on each query after `timeout`, in which case the Lean backend
+    /// returns `FfiError::SolverUnknown`. Sub-millisecond precision is ignored.
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The kind of solver
+    pub fn kind(&self) -> SolverKind {
+        self.kind
+    }
+
+    /// The path to the solver executable, if set
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The extra options passed to the solver
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    /// The per-query timeout, if set
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        self.timeout
+    }
+}
+
 #[derive(Debug)]
 pub struct TimedResult<T> {
     pub(crate) result: T,
@@ -884,6 +976,160 @@ impl From<cedar_policy_symcc::term::Term> for Term {
     }
 }
 
+/********************************** SymCC Counterexamples **********************************/
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PrimDef {
+    Bool(bool),
+    Int(i64),
+    String(String),
+    #[serde(rename = "entityUID")]
+    EntityUid(#[serde(deserialize_with = "deserialize_entity_uid")] EntityUid),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ValueDef {
+    Prim { p: PrimDef },
+    Set { s: Vec<ValueDef> },
+    Record { m: Vec<(String, ValueDef)> },
+    Ext { x: Ext },
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RequestDef {
+    #[serde(deserialize_with = "deserialize_entity_uid")]
+    principal: EntityUid,
+    #[serde(deserialize_with = "deserialize_entity_uid")]
+    action: EntityUid,
+    #[serde(deserialize_with = "deserialize_entity_uid")]
+    resource: EntityUid,
+    context: Vec<(String, ValueDef)>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EntityDataDef {
+    attrs: Vec<(String, ValueDef)>,
+    ancestors: Vec<EntityUidDef>,
+    tags: Vec<(String, ValueDef)>,
+}
+
+impl From<EntityUidDef> for EntityUid {
+    fn from(def: EntityUidDef) -> Self {
+        Self::from_type_name_and_id(def.ty, EntityId::new(def.eid))
+    }
+}
+
+impl ValueDef {
+    fn into_restricted_expr(self) -> Result<RestrictedExpression, FfiError> {
+        Ok(match self {
+            Self::Prim {
+                p: PrimDef::Bool(b),
+            } => RestrictedExpression::new_bool(b),
+            Self::Prim { p: PrimDef::Int(i) } => RestrictedExpression::new_long(i),
+            Self::Prim {
+                p: PrimDef::String(s),
+            } => RestrictedExpression::new_string(s),
+            Self::Prim {
+                p: PrimDef::EntityUid(uid),
+            } => RestrictedExpression::new_entity_uid(uid),
+            Self::Set { s } => RestrictedExpression::new_set(
+                s.into_iter()
+                    .map(Self::into_restricted_expr)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            Self::Record { m } => RestrictedExpression::new_record(restricted_exprs_of_map(m)?)
+                .map_err(|err| FfiError::LeanDeserializationError(err.to_string()))?,
+            Self::Ext {
+                x: Ext::Decimal { d },
+            } => {
+                // Decimals are represented as the number of ten-thousandths
+                let sign = if d.0 < 0 { "-" } else { "" };
+                let abs = d.0.unsigned_abs();
+                RestrictedExpression::new_decimal(format!(
+                    "{sign}{}.{:04}",
+                    abs / 10000,
+                    abs % 10000
+                ))
+            }
+            Self::Ext {
+                x: Ext::Ipaddr { ip },
+            } => RestrictedExpression::new_ip(ip_literal(ip)?),
+            Self::Ext {
+                x: Ext::Datetime { dt },
+            } => RestrictedExpression::new_datetime(datetime_literal(dt.val)?),
+            Self::Ext {
+                x: Ext::Duration { dur },
+            } => RestrictedExpression::new_duration(format!("{}ms", dur.val)),
+        })
+    }
+}
+
+fn restricted_exprs_of_map(
+    m: Vec<(String, ValueDef)>,
+) -> Result<Vec<(String, RestrictedExpression)>, FfiError> {
+    m.into_iter()
+        .map(|(k, v)| Ok((k, v.into_restricted_expr()?)))
+        .collect()
+}
+
+/// Render an IP address as a Cedar `ip` literal. IPv6 addresses are rendered
+/// without `::` compression so that no address is printed in dotted IPv4 form.
+fn ip_literal(ip: IpAddr) -> Result<String, FfiError> {
+    let parse = |bv: &Bitvec| {
+        bv.val
+            .parse::<u128>()
+            .map_err(|err| FfiError::LeanDeserializationError(err.to_string()))
+    };
+    let (addr, cidr) = match ip {
+        IpAddr::V4(cidr) => (
+            std::net::Ipv4Addr::from(parse(&cidr.addr)? as u32).to_string(),
+            cidr,
+        ),
+        IpAddr::V6(cidr) => (
+            std::net::Ipv6Addr::from(parse(&cidr.addr)?)
+                .segments()
+                .map(|segment| format!("{segment:x}"))
+                .join(":"),
+            cidr,
+        ),
+    };
+    Ok(match &cidr.prefix {
+        Some(prefix) => format!("{addr}/{}", parse(prefix)?),
+        None => addr,
+    })
+}
+
+/// Render the number of milliseconds since the Unix epoch as a Cedar `datetime` literal
+fn datetime_literal(ms: i64) -> Result<String, FfiError> {
+    let days = ms.div_euclid(86_400_000);
+    let ms_of_day = ms.rem_euclid(86_400_000);
+    // Convert days since the epoch to a proleptic Gregorian date
+    // (http://howardhinnant.github.io/date_algorithms.html#civil_from_days)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    if !(0..=9999).contains(&year) {
+        return Err(FfiError::LeanDeserializationError(format!(
+            "datetime {ms} is not representable as a Cedar datetime literal"
+        )));
+    }
+    Ok(format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    ))
+}
+
 /// Represent a counterexample
 #[derive(Debug, Deserialize)]
 pub struct Env {
@@ -893,6 +1139,559 @@ pub struct Env {
     pub entities: serde_json::Value,
 }
 
+impl Env {
+    /// Convert the counterexample's request into a Cedar `Request` (without schema validation)
+    pub fn to_request(&self) -> Result<Request, FfiError> {
+        request_of_lean_json(self.request.clone())
+    }
+
+    /// Convert the counterexample's entities into Cedar `Entities` (without schema validation)
+    pub fn to_entities(&self) -> Result<Entities, FfiError> {
+        entities_of_lean_json(self.entities.clone())
+    }
+}
+
+fn from_lean_json<T: DeserializeOwned>(json: serde_json::Value) -> Result<T, FfiError> {
+    serde_json::from_value(json).map_err(|err| FfiError::LeanDeserializationError(err.to_string()))
+}
+
+/// Convert a Lean `Request` (as JSON) into a Cedar `Request` (without schema validation)
+pub(crate) fn request_of_lean_json(json: serde_json::Value) -> Result<Request, FfiError> {
+    let request: RequestDef = from_lean_json(json)?;
+    let context = Context::from_pairs(restricted_exprs_of_map(request.context)?)
+        .map_err(|err| FfiError::LeanDeserializationError(err.to_string()))?;
+    Request::new(
+        request.principal,
+        request.action,
+        request.resource,
+        context,
+        None,
+    )
+    .map_err(|err| FfiError::LeanDeserializationError(err.to_string()))
+}
+
+/// Convert Lean `Entities` (as JSON) into Cedar `Entities` (without schema validation)
+pub(crate) fn entities_of_lean_json(json: serde_json::Value) -> Result<Entities, FfiError> {
+    let entities: Vec<(EntityUidDef, EntityDataDef)> = from_lean_json(json)?;
+    let entities = entities
+        .into_iter()
+        .map(|(uid, data)| {
+            Entity::new_with_tags(
+                uid.into(),
+                restricted_exprs_of_map(data.attrs)?,
+                data.ancestors.into_iter().map(EntityUid::from),
+                restricted_exprs_of_map(data.tags)?,
+            )
+            .map_err(|err| FfiError::LeanDeserializationError(err.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Entities::from_entities(entities, None)
+        .map_err(|err| FfiError::LeanDeserializationError(err.to_string()))
+}
+
+/********************************** Decoded Protobuf Values **********************************/
+
+// Lean ASTs are converted back to Cedar values by rendering them as Cedar
+// syntax (policies and expressions) or in the Cedar JSON schema format
+// (schemas), and parsing the result with the Rust implementation.
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EntityTypeNameDef(
+    #[serde(deserialize_with = "deserialize_entity_type_name")] EntityTypeName,
+);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum VarDef {
+    Principal,
+    Action,
+    Resource,
+    Context,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum UnaryOpDef {
+    Not,
+    Neg,
+    IsEmpty,
+    Like {
+        #[serde(deserialize_with = "deserialize_pattern")]
+        p: Vec<cedar_policy_core::ast::PatternElem>,
+    },
+    Is {
+        ety: EntityTypeNameDef,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BinaryOpDef {
+    Eq,
+    Mem,
+    HasTag,
+    GetTag,
+    Less,
+    LessEq,
+    Add,
+    Sub,
+    Mul,
+    Contains,
+    ContainsAll,
+    ContainsAny,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ExprDef {
+    Lit {
+        p: PrimDef,
+    },
+    Var {
+        v: VarDef,
+    },
+    #[serde(rename_all = "camelCase")]
+    Ite {
+        cond: Box<ExprDef>,
+        then_expr: Box<ExprDef>,
+        else_expr: Box<ExprDef>,
+    },
+    And {
+        a: Box<ExprDef>,
+        b: Box<ExprDef>,
+    },
+    Or {
+        a: Box<ExprDef>,
+        b: Box<ExprDef>,
+    },
+    UnaryApp {
+        op: UnaryOpDef,
+        expr: Box<ExprDef>,
+    },
+    BinaryApp {
+        op: BinaryOpDef,
+        a: Box<ExprDef>,
+        b: Box<ExprDef>,
+    },
+    GetAttr {
+        expr: Box<ExprDef>,
+        attr: String,
+    },
+    HasAttr {
+        expr: Box<ExprDef>,
+        attr: String,
+    },
+    Set {
+        ls: Vec<ExprDef>,
+    },
+    Record {
+        map: Vec<(String, ExprDef)>,
+    },
+    Call {
+        xfn: String,
+        args: Vec<ExprDef>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum EffectDef {
+    Permit,
+    Forbid,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ScopeDef {
+    Any,
+    Eq {
+        #[serde(deserialize_with = "deserialize_entity_uid")]
+        entity: EntityUid,
+    },
+    Mem {
+        #[serde(deserialize_with = "deserialize_entity_uid")]
+        entity: EntityUid,
+    },
+    Is {
+        ety: EntityTypeNameDef,
+    },
+    IsMem {
+        ety: EntityTypeNameDef,
+        #[serde(deserialize_with = "deserialize_entity_uid")]
+        entity: EntityUid,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PrincipalScopeDef {
+    PrincipalScope { scope: ScopeDef },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ResourceScopeDef {
+    ResourceScope { scope: ScopeDef },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ActionScopeDef {
+    ActionScope { scope: ScopeDef },
+    ActionInAny { ls: Vec<EntityUidDef> },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ConditionKindDef {
+    When,
+    Unless,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ConditionDef {
+    kind: ConditionKindDef,
+    body: ExprDef,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PolicyDef {
+    id: String,
+    effect: EffectDef,
+    principal_scope: PrincipalScopeDef,
+    action_scope: ActionScopeDef,
+    resource_scope: ResourceScopeDef,
+    condition: Vec<ConditionDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum QualifiedDef {
+    Optional { a: CedarTypeDef },
+    Required { a: CedarTypeDef },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CedarTypeDef {
+    // Decoded schemas only contain `anyBool`
+    Bool { bty: serde::de::IgnoredAny },
+    Int,
+    String,
+    Entity { ety: EntityTypeNameDef },
+    Set { ty: Box<CedarTypeDef> },
+    Record { rty: Vec<(String, QualifiedDef)> },
+    Ext { xty: ExtType },
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct StandardSchemaEntryDef {
+    ancestors: Vec<EntityTypeNameDef>,
+    attrs: Vec<(String, QualifiedDef)>,
+    #[serde(default)]
+    tags: Option<CedarTypeDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum EntitySchemaEntryDef {
+    Standard { ty: StandardSchemaEntryDef },
+    Enum { eids: Vec<String> },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActionSchemaEntryDef {
+    applies_to_principal: Vec<EntityTypeNameDef>,
+    applies_to_resource: Vec<EntityTypeNameDef>,
+    ancestors: Vec<EntityUidDef>,
+    context: Vec<(String, QualifiedDef)>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SchemaDef {
+    ets: Vec<(EntityTypeNameDef, EntitySchemaEntryDef)>,
+    acts: Vec<(EntityUidDef, ActionSchemaEntryDef)>,
+}
+
+/// Write `items` separated by `, `
+fn write_comma_separated<T: std::fmt::Display>(
+    f: &mut std::fmt::Formatter<'_>,
+    items: impl IntoIterator<Item = T>,
+) -> std::fmt::Result {
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for PrimDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Int(i) => write!(f, "{i}"),
+            Self::String(s) => write!(f, "\"{}\"", s.escape_debug()),
+            Self::EntityUid(uid) => write!(f, "{uid}"),
+        }
+    }
+}
+
+/// Renders a Lean `Expr` in fully parenthesized Cedar syntax
+impl std::fmt::Display for ExprDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lit { p } => write!(f, "{p}"),
+            Self::Var { v } => match v {
+                VarDef::Principal => write!(f, "principal"),
+                VarDef::Action => write!(f, "action"),
+                VarDef::Resource => write!(f, "resource"),
+                VarDef::Context => write!(f, "context"),
+            },
+            Self::Ite {
+                cond,
+                then_expr,
+                else_expr,
+            } => write!(f, "(if {cond} then {then_expr} else {else_expr})"),
+            Self::And { a, b } => write!(f, "({a} && {b})"),
+            Self::Or { a, b } => write!(f, "({a} || {b})"),
+            Self::UnaryApp { op, expr } => match op {
+                UnaryOpDef::Not => write!(f, "(!{expr})"),
+                UnaryOpDef::Neg => write!(f, "(-({expr}))"),
+                UnaryOpDef::IsEmpty => write!(f, "({expr}).isEmpty()"),
+                UnaryOpDef::Like { p } => {
+                    let pattern = cedar_policy_core::ast::Pattern::from_iter(p.iter().cloned());
+                    write!(f, "({expr} like \"{pattern}\")")
+                }
+                UnaryOpDef::Is { ety } => write!(f, "({expr} is {})", ety.0),
+            },
+            Self::BinaryApp { op, a, b } => match op {
+                BinaryOpDef::Eq => write!(f, "({a} == {b})"),
+                BinaryOpDef::Mem => write!(f, "({a} in {b})"),
+                BinaryOpDef::Less => write!(f, "({a} < {b})"),
+                BinaryOpDef::LessEq => write!(f, "({a} <= {b})"),
+                BinaryOpDef::Add => write!(f, "({a} + {b})"),
+                BinaryOpDef::Sub => write!(f, "({a} - {b})"),
+                BinaryOpDef::Mul => write!(f, "({a} * {b})"),
+                BinaryOpDef::HasTag => write!(f, "({a}).hasTag({b})"),
+                BinaryOpDef::GetTag => write!(f, "({a}).getTag({b})"),
+                BinaryOpDef::Contains => write!(f, "({a}).contains({b})"),
+                BinaryOpDef::ContainsAll => write!(f, "({a}).containsAll({b})"),
+                BinaryOpDef::ContainsAny => write!(f, "({a}).containsAny({b})"),
+            },
+            Self::GetAttr { expr, attr } => write!(f, "({expr})[\"{}\"]", attr.escape_debug()),
+            Self::HasAttr { expr, attr } => write!(f, "({expr} has \"{}\")", attr.escape_debug()),
+            Self::Set { ls } => {
+                write!(f, "[")?;
+                write_comma_separated(f, ls)?;
+                write!(f, "]")
+            }
+            Self::Record { map } => {
+                write!(f, "{{")?;
+                write_comma_separated(
+                    f,
+                    map.iter()
+                        .map(|(k, v)| format!("\"{}\": {v}", k.escape_debug())),
+                )?;
+                write!(f, "}}")
+            }
+            Self::Call { xfn, args } => match (xfn.as_str(), args.split_first()) {
+                // Extension constructors are functions; all other extension functions are methods
+                ("decimal" | "ip" | "datetime" | "duration", _) | (_, None) => {
+                    write!(f, "{xfn}(")?;
+                    write_comma_separated(f, args)?;
+                    write!(f, ")")
+                }
+                (_, Some((receiver, rest))) => {
+                    write!(f, "({receiver}).{xfn}(")?;
+                    write_comma_separated(f, rest)?;
+                    write!(f, ")")
+                }
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ScopeDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => Ok(()),
+            Self::Eq { entity } => write!(f, " == {entity}"),
+            Self::Mem { entity } => write!(f, " in {entity}"),
+            Self::Is { ety } => write!(f, " is {}", ety.0),
+            Self::IsMem { ety, entity } => write!(f, " is {} in {entity}", ety.0),
+        }
+    }
+}
+
+/// Renders a Lean `Policy` in Cedar syntax (Lean policies have no annotations)
+impl std::fmt::Display for PolicyDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let PrincipalScopeDef::PrincipalScope { scope: principal } = &self.principal_scope;
+        let ResourceScopeDef::ResourceScope { scope: resource } = &self.resource_scope;
+        match self.effect {
+            EffectDef::Permit => write!(f, "permit")?,
+            EffectDef::Forbid => write!(f, "forbid")?,
+        }
+        write!(f, "(principal{principal}, action")?;
+        match &self.action_scope {
+            ActionScopeDef::ActionScope { scope } => write!(f, "{scope}")?,
+            ActionScopeDef::ActionInAny { ls } => {
+                write!(f, " in [")?;
+                write_comma_separated(
+                    f,
+                    ls.iter().map(|uid| {
+                        EntityUid::from_type_name_and_id(uid.ty.clone(), EntityId::new(&uid.eid))
+                    }),
+                )?;
+                write!(f, "]")?;
+            }
+        }
+        write!(f, ", resource{resource})")?;
+        for condition in &self.condition {
+            match condition.kind {
+                ConditionKindDef::When => write!(f, " when")?,
+                ConditionKindDef::Unless => write!(f, " unless")?,
+            }
+            write!(f, " {{ {} }}", condition.body)?;
+        }
+        write!(f, ";")
+    }
+}
+
+impl CedarTypeDef {
+    /// The type in the Cedar JSON schema format
+    fn to_json_schema(&self) -> serde_json::Value {
+        match self {
+            Self::Bool { .. } => serde_json::json!({"type": "Boolean"}),
+            Self::Int => serde_json::json!({"type": "Long"}),
+            Self::String => serde_json::json!({"type": "String"}),
+            Self::Entity { ety } => {
+                serde_json::json!({"type": "Entity", "name": ety.0.to_string()})
+            }
+            Self::Set { ty } => serde_json::json!({"type": "Set", "element": ty.to_json_schema()}),
+            Self::Record { rty } => record_type_json_schema(rty),
+            Self::Ext { xty } => serde_json::json!({
+                "type": "Extension",
+                "name": match xty {
+                    ExtType::IpAddr => "ipaddr",
+                    ExtType::Decimal => "decimal",
+                    ExtType::Datetime => "datetime",
+                    ExtType::Duration => "duration",
+                },
+            }),
+        }
+    }
+}
+
+/// A record type in the Cedar JSON schema format
+fn record_type_json_schema(rty: &[(String, QualifiedDef)]) -> serde_json::Value {
+    let attributes = rty
+        .iter()
+        .map(|(attr, qty)| {
+            let (ty, required) = match qty {
+                QualifiedDef::Optional { a } => (a, false),
+                QualifiedDef::Required { a } => (a, true),
+            };
+            let mut ty = ty.to_json_schema();
+            ty["required"] = required.into();
+            (attr.clone(), ty)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    serde_json::json!({"type": "Record", "attributes": attributes})
+}
+
+impl SchemaDef {
+    /// The schema in the Cedar JSON schema format. Entity types and actions are
+    /// grouped by namespace, and all references to entity types are fully qualified.
+    fn to_json_schema(&self) -> serde_json::Value {
+        fn namespace<'a>(
+            namespaces: &'a mut serde_json::Map<String, serde_json::Value>,
+            name: &EntityTypeName,
+        ) -> &'a mut serde_json::Value {
+            namespaces
+                .entry(name.namespace())
+                .or_insert_with(|| serde_json::json!({"entityTypes": {}, "actions": {}}))
+        }
+
+        let mut namespaces = serde_json::Map::new();
+        for (EntityTypeNameDef(name), entry) in &self.ets {
+            let json = match entry {
+                EntitySchemaEntryDef::Standard { ty } => {
+                    let mut json = serde_json::json!({
+                        "memberOfTypes": ty
+                            .ancestors
+                            .iter()
+                            .map(|ety| ety.0.to_string())
+                            .collect::<Vec<_>>(),
+                        "shape": record_type_json_schema(&ty.attrs),
+                    });
+                    if let Some(tags) = &ty.tags {
+                        json["tags"] = tags.to_json_schema();
+                    }
+                    json
+                }
+                EntitySchemaEntryDef::Enum { eids } => serde_json::json!({ "enum": eids }),
+            };
+            namespace(&mut namespaces, name)["entityTypes"][name.basename()] = json;
+        }
+        for (uid, entry) in &self.acts {
+            let json = serde_json::json!({
+                "appliesTo": {
+                    "principalTypes": entry
+                        .applies_to_principal
+                        .iter()
+                        .map(|ety| ety.0.to_string())
+                        .collect::<Vec<_>>(),
+                    "resourceTypes": entry
+                        .applies_to_resource
+                        .iter()
+                        .map(|ety| ety.0.to_string())
+                        .collect::<Vec<_>>(),
+                    "context": record_type_json_schema(&entry.context),
+                },
+                "memberOf": entry
+                    .ancestors
+                    .iter()
+                    .map(|ancestor| serde_json::json!({"type": ancestor.ty.to_string(), "id": ancestor.eid}))
+                    .collect::<Vec<_>>(),
+            });
+            namespace(&mut namespaces, &uid.ty)["actions"][uid.eid.as_str()] = json;
+        }
+        serde_json::Value::Object(namespaces)
+    }
+}
+
+/// Convert a Lean `Expr` (as JSON) into a Cedar `Expression`
+pub(crate) fn expression_of_lean_json(json: serde_json::Value) -> Result<Expression, FfiError> {
+    let expr: ExprDef = from_lean_json(json)?;
+    let src = expr.to_string();
+    Expression::from_str(&src).map_err(|err| {
+        FfiError::LeanDeserializationError(format!("{err}\nrendered expression was: {src}"))
+    })
+}
+
+/// Convert a Lean `Policy` (as JSON) into a Cedar `Policy`
+pub(crate) fn policy_of_lean_json(json: serde_json::Value) -> Result<Policy, FfiError> {
+    let policy: PolicyDef = from_lean_json(json)?;
+    let src = policy.to_string();
+    Policy::parse(Some(PolicyId::new(&policy.id)), &src).map_err(|err| {
+        FfiError::LeanDeserializationError(format!("{err}\nrendered policy was: {src}"))
+    })
+}
+
+/// Convert a Lean `Schema` (as JSON) into a Cedar `Schema`
+pub(crate) fn schema_of_lean_json(json: serde_json::Value) -> Result<Schema, FfiError> {
+    let schema: SchemaDef = from_lean_json(json)?;
+    let json = schema.to_json_schema();
+    Schema::from_json_value(json.clone()).map_err(|err| {
+        FfiError::LeanDeserializationError(format!("{err}\nrendered schema was: {json}"))
+    })
+}
+
 #[cfg(test)]
 mod deserialization {
     use crate::Bitvec;
@@ -932,6 +1731,164 @@ mod deserialization {
         let _: Vec<crate::Term> =
             serde_json::from_value(json).expect("deserialization should succeed");
     }
+
+    #[test]
+    fn env() {
+        use cedar_policy::{Authorizer, Decision, PolicySet};
+        use std::str::FromStr;
+
+        let uid =
+            |id: &str, eid: &str| serde_json::json!({"ty": {"path": ["N"], "id": id}, "eid": eid});
+        let json = serde_json::json!({
+            "request": {
+                "principal": uid("User", "alice"),
+                "action": uid("Action", "view"),
+                "resource": uid("Photo", ""),
+                "context": [
+                    ["d", {"ext": {"x": {"decimal": {"d": -12345}}}}],
+                    ["ip", {"ext": {"x": {"ipaddr": {"ip": {"V4": {"addr": {"size": 32, "value": "3232235777"}, "pre": {"size": 5, "value": "24"}}}}}}}],
+                    ["dt", {"ext": {"x": {"datetime": {"dt": {"val": 1729000000123i64}}}}}],
+                    ["dur", {"ext": {"x": {"duration": {"dur": {"val": -1500}}}}}]
+                ]
+            },
+            "entities": [
+                [uid("User", "alice"), {
+                    "attrs": [
+                        ["age", {"prim": {"p": {"int": 42}}}],
+                        ["tags", {"set": {"s": [{"prim": {"p": {"string": "a"}}}]}}],
+                        ["r", {"record": {"m": [["b", {"prim": {"p": {"bool": true}}}]]}}]
+                    ],
+                    "ancestors": [uid("Group", "g")],
+                    "tags": [["t", {"prim": {"p": {"entityUID": uid("Group", "g")}}}]]
+                }],
+                [uid("Group", "g"), {"attrs": [], "ancestors": [], "tags": []}]
+            ]
+        });
+        let env: crate::Env = serde_json::from_value(json).expect("deserialization should succeed");
+        let request = env.to_request().expect("conversion should succeed");
+        let entities = env.to_entities().expect("conversion should succeed");
+        let policies = PolicySet::from_str(
+            r#"permit(principal in N::Group::"g", action, resource) when {
+                principal.age == 42 && principal.tags.contains("a") && principal.r.b &&
+                principal.getTag("t") == N::Group::"g" &&
+                context.d == decimal("-1.2345") &&
+                context.ip == ip("192.168.1.1/24") &&
+                context.dt == datetime("2024-10-15T13:46:40.123Z") &&
+                context.dur == duration("-1500ms")
+            };"#,
+        )
+        .unwrap();
+        let response = Authorizer::new().is_authorized(&request, &policies, &entities);
+        assert_eq!(response.decision(), Decision::Allow);
+    }
+
+    #[test]
+    fn decoded_values() {
+        use cedar_policy::{Expression, Policy, PolicyId, Schema};
+        use std::str::FromStr;
+
+        let name = |path: &[&str], id: &str| serde_json::json!({"path": path, "id": id});
+        let uid = |id: &str, eid: &str| serde_json::json!({"ty": name(&["N"], id), "eid": eid});
+        let var = |v: &str| serde_json::json!({"var": {"v": v}});
+        let string = |s: &str| serde_json::json!({"lit": {"p": {"string": s}}});
+
+        let expr = serde_json::json!({"and": {
+            "a": {"unaryApp": {"op": {"like": {"p": ["star", {"justChar": {"c": 42}}, {"justChar": {"c": 34}}]}}, "expr": string("a\"*")}},
+            "b": {"ite": {
+                "cond": {"hasAttr": {"expr": var("context"), "attr": "a b"}},
+                "thenExpr": {"call": {"xfn": "isInRange", "args": [
+                    {"call": {"xfn": "ip", "args": [string("10.0.0.1")]}},
+                    {"call": {"xfn": "ip", "args": [string("10.0.0.0/8")]}}
+                ]}},
+                "elseExpr": {"binaryApp": {"op": "less",
+                    "a": {"unaryApp": {"op": "neg", "expr": {"lit": {"p": {"int": -3}}}}},
+                    "b": {"getAttr": {"expr": {"record": {"map": [["x", {"set": {"ls": []}}]]}}, "attr": "x"}}
+                }}
+            }}
+        }});
+        assert_eq!(
+            crate::datatypes::expression_of_lean_json(expr)
+                .expect("conversion should succeed")
+                .to_string(),
+            Expression::from_str(
+                r#""a\"*" like "*\*\"" && (if context has "a b" then ip("10.0.0.1").isInRange(ip("10.0.0.0/8")) else -(-3) < {"x": []}.x)"#
+            )
+            .unwrap()
+            .to_string()
+        );
+
+        let policy = serde_json::json!({
+            "id": "p0",
+            "effect": "forbid",
+            "principalScope": {"principalScope": {"scope": {"isMem": {"ety": name(&["N"], "User"), "entity": uid("Group", "g")}}}},
+            "actionScope": {"actionInAny": {"ls": [uid("Action", "view"), uid("Action", "edit")]}},
+            "resourceScope": {"resourceScope": {"scope": "any"}},
+            "condition": [
+                {"kind": "when", "body": {"binaryApp": {"op": "hasTag", "a": var("resource"), "b": string("t")}}},
+                {"kind": "unless", "body": {"unaryApp": {"op": {"is": {"ety": name(&["N"], "Photo")}}, "expr": var("resource")}}}
+            ]
+        });
+        let policy =
+            crate::datatypes::policy_of_lean_json(policy).expect("conversion should succeed");
+        assert_eq!(policy.id(), &PolicyId::new("p0"));
+        // `Policy::to_string` prints the source text, so compare the JSON representations
+        assert_eq!(
+            policy.to_json().unwrap(),
+            Policy::from_str(
+                r#"forbid(principal is N::User in N::Group::"g", action in [N::Action::"view", N::Action::"edit"], resource)
+                when { resource.hasTag("t") } unless { resource is N::Photo };"#
+            )
+            .unwrap()
+            .to_json()
+            .unwrap()
+        );
+
+        let required = |ty: serde_json::Value| serde_json::json!({"required": {"a": ty}});
+        let schema = serde_json::json!({
+            "ets": [
+                [name(&["N"], "User"), {"standard": {"ty": {
+                    "ancestors": [name(&["N"], "Group")],
+                    "attrs": [["age", {"optional": {"a": "int"}}], ["ip", required(serde_json::json!({"ext": {"xty": "ipAddr"}}))]],
+                    "tags": {"set": {"ty": {"bool": {"bty": "anyBool"}}}}
+                }}}],
+                [name(&["N"], "Group"), {"standard": {"ty": {"ancestors": [], "attrs": []}}}],
+                [name(&[], "Color"), {"enum": {"eids": ["red", "blue"]}}]
+            ],
+            "acts": [
+                [uid("Action", "view"), {
+                    "appliesToPrincipal": [name(&["N"], "User")],
+                    "appliesToResource": [name(&[], "Color")],
+                    "ancestors": [],
+                    "context": [["r", required(serde_json::json!({"record": {"rty": [["u", required(serde_json::json!({"entity": {"ety": name(&["N"], "User")}}))]]}}))]]
+                }]
+            ]
+        });
+        let schema =
+            crate::datatypes::schema_of_lean_json(schema).expect("conversion should succeed");
+        let (expected, _) = Schema::from_cedarschema_str(
+            r#"
+            entity Color enum ["red", "blue"];
+            namespace N {
+                entity Group;
+                entity User in [Group] { age?: Long, ip: ipaddr } tags Set<Bool>;
+                action view appliesTo { principal: User, resource: Color, context: { r: { u: User } } };
+            }"#,
+        )
+        .unwrap();
+        let envs = |schema: &Schema| {
+            schema
+                .request_envs()
+                .map(|env| {
+                    (
+                        env.principal().to_string(),
+                        env.action().to_string(),
+                        env.resource().to_string(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(envs(&schema), envs(&expected));
+    }
 }
 
 #[cfg(test)]
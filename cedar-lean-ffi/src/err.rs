@@ -21,4 +21,6 @@ pub enum FfiError {
     LeanDeserializationError(String),
     #[error("Error occured in Lean backend : {0}")]
     LeanBackendError(String),
+    #[error("SMT solver returned unknown (e.g., because it timed out) : {0}")]
+    SolverUnknown(String),
 }
@@ -14,8 +14,8 @@
  * limitations under the License.
  */
 use crate::datatypes::{
-    AuthorizationResponse, AuthorizationResponseInner, Env, ResultDef, Term, TimedDef, TimedResult,
-    ValidationResponse,
+    self, AuthorizationResponse, AuthorizationResponseInner, Env, ResultDef, SolverConfig, Term,
+    TimedDef, TimedResult, ValidationResponse,
 };
 use crate::err::FfiError;
 use crate::messages::*;
@@ -80,6 +80,12 @@ extern "C" {
     fn validateEntities(req: *mut lean_object) -> *mut lean_object;
     fn validateRequest(req: *mut lean_object) -> *mut lean_object;
 
+    fn decodePolicy(req: *mut lean_object) -> *mut lean_object;
+    fn decodeExpr(req: *mut lean_object) -> *mut lean_object;
+    fn decodeEntities(req: *mut lean_object) -> *mut lean_object;
+    fn decodeRequest(req: *mut lean_object) -> *mut lean_object;
+    fn decodeSchema(req: *mut lean_object) -> *mut lean_object;
+
     fn runCheckAsserts(asserts: *mut lean_object) -> *mut lean_object;
     fn printCheckAsserts(asserts: *mut lean_object) -> *mut lean_object;
     fn smtLibOfCheckAsserts(asserts: *mut lean_object) -> *mut lean_object;
@@ -106,7 +112,10 @@ static START: Once = Once::new();
 
 #[derive(Default)]
 /// A struct which will initialize the lean backend (and initialize a thread running the lean runtime)
-pub struct CedarLeanFfi {}
+pub struct CedarLeanFfi {
+    /// The SMT solver used by the `run_check_*` functions
+    solver: SolverConfig,
+}
 
 /// Safe wrapper around `*mut lean_object`, which ensures that the Lean object
 /// is freed when this Rust value is dropped. Only use this if the Rust code is
@@ -196,6 +205,27 @@ unsafe fn call_lean_ffi_takes_protobuf(
     call_lean_ffi_function(func, arg)
 }
 
+/// Deserialize the response of a Lean `decode*` function and convert the
+/// decoded Lean value (as JSON) with `convert`
+fn convert_decoded<T>(
+    response: OwnedLeanObject,
+    convert: impl FnOnce(serde_json::Value) -> Result<T, FfiError>,
+) -> Result<TimedResult<T>, FfiError> {
+    let decoded: ResultDef<TimedDef<serde_json::Value>> = response.deserialize_into()?;
+    match decoded {
+        ResultDef::Ok(res) => {
+            let res = TimedResult::from_def(res);
+            let duration = res.duration();
+            Ok(TimedResult {
+                result: convert(res.take_result())?,
+                duration,
+            })
+        }
+        ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+        ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
+    }
+}
+
 /// A macro which converts symcc-request to protobuf, calls the lean code, then deserializes the output
 macro_rules! checkPolicy_func {
     // Pattern for function identifier
@@ -216,11 +246,12 @@ macro_rules! checkPolicy_func {
         ) -> Result<TimedResult<$ret_ty>, FfiError> {
             let response = unsafe { call_lean_ffi_takes_protobuf(
                 $lean_func_name,
-                &proto::CheckPolicyRequest::new(policy, schema, request_env),
+                &proto::CheckPolicyRequest::new(policy, schema, request_env, &self.solver),
             )};
             match response.deserialize_into()? {
                 ResultDef::Ok(t) => Ok(TimedResult::from_def(t).transform($transform)),
                 ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+                ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
             }
         }
         pub fn $untimed_func_name(
@@ -256,11 +287,12 @@ macro_rules! checkPolicySet_func {
         ) -> Result<TimedResult<$ret_ty>, FfiError> {
             let response = unsafe { call_lean_ffi_takes_protobuf(
                 $lean_func_name,
-                &proto::CheckPolicySetRequest::new(policyset, schema, request_env),
+                &proto::CheckPolicySetRequest::new(policyset, schema, request_env, &self.solver),
             )};
             match response.deserialize_into()? {
                 ResultDef::Ok(t) => Ok(TimedResult::from_def(t).transform($transform)),
                 ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+                ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
             }
         }
         pub fn $untimed_func_name(
@@ -302,11 +334,13 @@ macro_rules! comparePolicySet_func {
                     tgt_policyset,
                     schema,
                     request_env,
+                    &self.solver,
                 ),
             )};
             match response.deserialize_into()? {
                 ResultDef::Ok(t) => Ok(TimedResult::from_def(t).transform($transform)),
                 ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+                ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
             }
         }
         pub fn $untimed_func_name(
@@ -342,11 +376,12 @@ macro_rules! checkAsserts_func {
         ) -> Result<TimedResult<$ret_ty>, FfiError> {
             let response = unsafe { call_lean_ffi_takes_protobuf(
                 $lean_func_name,
-                &proto::CheckAssertsRequest::new(asserts, schema, request_env),
+                &proto::CheckAssertsRequest::new(asserts, schema, request_env, &self.solver),
             )};
             match response.deserialize_into()? {
                 ResultDef::Ok(t) => Ok(TimedResult::from_def(t).transform($transform)),
                 ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+                ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
             }
         }
         pub fn $untimed_func_name(
@@ -382,12 +417,28 @@ impl CedarLeanFfi {
             };
         });
         unsafe { lean_initialize_thread() };
-        Self {}
+        Self {
+            solver: SolverConfig::default(),
+        }
+    }
+
+    /// Like `new`, but the `run_check_*` functions use the SMT solver described by `solver`
+    /// WARNING: we can only have one Lean thread
+    pub fn with_solver_config(solver: SolverConfig) -> Self {
+        let mut ffi = Self::new();
+        ffi.solver = solver;
+        ffi
+    }
+
+    /// The configuration of the SMT solver used by the `run_check_*` functions
+    pub fn solver_config(&self) -> &SolverConfig {
+        &self.solver
     }
 
     // Adds each of the run_(symcc-command) to call the corresponding lean function
     // returns true if the check definitely holds and false if it definitely doesn't
-    // returns an error if the lean could not successfully run the solver or if the solver returned unknown
+    // returns `FfiError::SolverUnknown` if the solver returned unknown (e.g., because it timed out),
+    // and `FfiError::LeanBackendError` if lean could not successfully run the solver
     checkPolicy_func!(
         run_check_never_errors_timed,
         run_check_never_errors,
@@ -725,6 +776,7 @@ impl CedarLeanFfi {
                 Ok(TimedResult::from_def(tdef))
             }
             ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+            ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
         }
     }
     pub fn is_authorized(
@@ -754,6 +806,7 @@ impl CedarLeanFfi {
         match response.deserialize_into()? {
             ResultDef::Ok(t) => Ok(TimedResult::from_def(t)),
             ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+            ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
         }
     }
     pub fn print_evaluation(
@@ -789,6 +842,7 @@ impl CedarLeanFfi {
         match response.deserialize_into()? {
             ResultDef::Ok(are_eq) => Ok(TimedResult::from_def(are_eq)),
             ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+            ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
         }
     }
     pub fn check_evaluate(
@@ -819,6 +873,7 @@ impl CedarLeanFfi {
         match response.deserialize_into()? {
             ResultDef::Ok(res) => Ok(TimedResult::from_def(res)),
             ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+            ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
         }
     }
     pub fn validate(
@@ -846,6 +901,7 @@ impl CedarLeanFfi {
         match response.deserialize_into()? {
             ResultDef::Ok(res) => Ok(TimedResult::from_def(res)),
             ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+            ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
         }
     }
     pub fn level_validate(
@@ -874,6 +930,7 @@ impl CedarLeanFfi {
         match response.deserialize_into()? {
             ResultDef::Ok(res) => Ok(TimedResult::from_def(res)),
             ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+            ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
         }
     }
     pub fn validate_entities(
@@ -901,6 +958,7 @@ impl CedarLeanFfi {
         match response.deserialize_into()? {
             ResultDef::Ok(res) => Ok(TimedResult::from_def(res)),
             ResultDef::Error(s) => Err(FfiError::LeanBackendError(s)),
+            ResultDef::Unknown(s) => Err(FfiError::SolverUnknown(s)),
         }
     }
     pub fn validate_request(
@@ -910,6 +968,83 @@ impl CedarLeanFfi {
     ) -> Result<ValidationResponse, FfiError> {
         Ok(self.validate_request_timed(schema, request)?.take_result())
     }
+
+    /// Calls the lean backend to decode the protobuf encoding of `policy`, and
+    /// converts the decoded Lean policy back into a Cedar `Policy`
+    pub fn decode_policy_timed(&self, policy: &Policy) -> Result<TimedResult<Policy>, FfiError> {
+        let response =
+            unsafe { call_lean_ffi_takes_protobuf(decodePolicy, &proto::Policy::from(policy)) };
+        convert_decoded(response, datatypes::policy_of_lean_json)
+    }
+    pub fn decode_policy(&self, policy: &Policy) -> Result<Policy, FfiError> {
+        Ok(self.decode_policy_timed(policy)?.take_result())
+    }
+
+    /// Calls the lean backend to decode the protobuf encoding of `expr`, and
+    /// converts the decoded Lean expression back into a Cedar `Expression`
+    pub fn decode_expr_timed(
+        &self,
+        expr: &Expression,
+    ) -> Result<TimedResult<Expression>, FfiError> {
+        let response = unsafe {
+            call_lean_ffi_takes_protobuf(decodeExpr, &cedar_policy::proto::models::Expr::from(expr))
+        };
+        convert_decoded(response, datatypes::expression_of_lean_json)
+    }
+    pub fn decode_expr(&self, expr: &Expression) -> Result<Expression, FfiError> {
+        Ok(self.decode_expr_timed(expr)?.take_result())
+    }
+
+    /// Calls the lean backend to decode the protobuf encoding of `entities`, and
+    /// converts the decoded Lean entities back into Cedar `Entities`
+    pub fn decode_entities_timed(
+        &self,
+        entities: &Entities,
+    ) -> Result<TimedResult<Entities>, FfiError> {
+        let response = unsafe {
+            call_lean_ffi_takes_protobuf(
+                decodeEntities,
+                &cedar_policy::proto::models::Entities::from(entities),
+            )
+        };
+        convert_decoded(response, datatypes::entities_of_lean_json)
+    }
+    pub fn decode_entities(&self, entities: &Entities) -> Result<Entities, FfiError> {
+        Ok(self.decode_entities_timed(entities)?.take_result())
+    }
+
+    /// Calls the lean backend to decode the protobuf encoding of `request`, and
+    /// converts the decoded Lean request back into a Cedar `Request`
+    pub fn decode_request_timed(
+        &self,
+        request: &Request,
+    ) -> Result<TimedResult<Request>, FfiError> {
+        let response = unsafe {
+            call_lean_ffi_takes_protobuf(
+                decodeRequest,
+                &cedar_policy::proto::models::Request::from(request),
+            )
+        };
+        convert_decoded(response, datatypes::request_of_lean_json)
+    }
+    pub fn decode_request(&self, request: &Request) -> Result<Request, FfiError> {
+        Ok(self.decode_request_timed(request)?.take_result())
+    }
+
+    /// Calls the lean backend to decode the protobuf encoding of `schema`, and
+    /// converts the decoded Lean schema back into a Cedar `Schema`
+    pub fn decode_schema_timed(&self, schema: &Schema) -> Result<TimedResult<Schema>, FfiError> {
+        let response = unsafe {
+            call_lean_ffi_takes_protobuf(
+                decodeSchema,
+                &cedar_policy::proto::models::Schema::from(schema),
+            )
+        };
+        convert_decoded(response, datatypes::schema_of_lean_json)
+    }
+    pub fn decode_schema(&self, schema: &Schema) -> Result<Schema, FfiError> {
+        Ok(self.decode_schema_timed(schema)?.take_result())
+    }
 }
 
 /// uninitialize lean thread when done
@@ -937,6 +1072,8 @@ mod test {
     };
     use cool_asserts::assert_matches;
 
+    use crate::datatypes::SolverKind;
+
     use std::collections::HashSet;
     use std::str::FromStr;
 
@@ -1015,6 +1152,29 @@ mod test {
             );
     }
 
+    #[test]
+    fn test_solver_config() {
+        let pset =
+            PolicySet::from_str("permit(principal, action, resource) when { resource.private };")
+                .expect("Failed to parse policy set");
+        let schema = example_schema();
+        let req_env = request_env("Identity", "Action::\"view\"", "Thing");
+
+        let solver = SolverConfig::new(SolverKind::Z3).with_path("/nonexistent/solver");
+        let ffi = CedarLeanFfi::with_solver_config(solver.clone());
+        assert_eq!(ffi.solver_config(), &solver);
+
+        // The configured executable is used instead of the one in the environment
+        assert_matches!(
+            ffi.run_check_always_allows(&pset, &schema, &req_env),
+            Err(FfiError::LeanBackendError(_))
+        );
+
+        // Printing the SMTLib encoding does not spawn a solver
+        ffi.smtlib_of_check_always_allows(&pset, &schema, &req_env)
+            .expect("Lean call unexpectedly failed for smtlib_of_check_always_allows");
+    }
+
     #[test]
     fn test_check_always_allows() {
         let always_allows_pset = PolicySet::from_str("permit(principal, action, resource);")
@@ -1473,6 +1633,71 @@ mod test {
         assert_eq!(res, ValidationResponse::Ok(()));
     }
 
+    #[test]
+    fn test_decode() {
+        let ffi = CedarLeanFfi::new();
+
+        let schema = example_schema();
+        let decoded = ffi
+            .decode_schema(&schema)
+            .expect("Lean call unexpectedly failed for decode_schema");
+        let envs = |schema: &Schema| {
+            let mut envs = schema
+                .request_envs()
+                .map(|env| {
+                    (
+                        env.principal().to_string(),
+                        env.action().to_string(),
+                        env.resource().to_string(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            envs.sort();
+            envs
+        };
+        assert_eq!(envs(&decoded), envs(&schema));
+
+        let policy = Policy::from_str(
+            r#"permit(principal == Identity::"Alice", action in [Action::"view"], resource)
+            when { context.x like "a*b" && [1, -2].contains(resource.n) };"#,
+        )
+        .unwrap();
+        let decoded = ffi
+            .decode_policy(&policy)
+            .expect("Lean call unexpectedly failed for decode_policy");
+        assert_eq!(decoded.to_json().unwrap(), policy.to_json().unwrap());
+
+        let expr = Expression::from_str(r#"ip("10.0.0.1").isInRange(ip("10.0.0.0/8"))"#).unwrap();
+        let decoded = ffi
+            .decode_expr(&expr)
+            .expect("Lean call unexpectedly failed for decode_expr");
+        assert_eq!(decoded.to_string(), expr.to_string());
+
+        let req = request(
+            "Identity::\"Alice\"",
+            "Action::\"view\"",
+            "Thing::\"thing1\"",
+        );
+        let decoded = ffi
+            .decode_request(&req)
+            .expect("Lean call unexpectedly failed for decode_request");
+        assert_eq!(decoded.principal(), req.principal());
+        assert_eq!(decoded.action(), req.action());
+        assert_eq!(decoded.resource(), req.resource());
+
+        let entities = Entities::from_entities(
+            vec![Entity::with_uid(
+                EntityUid::from_str("Account::\"account\"").unwrap(),
+            )],
+            None,
+        )
+        .unwrap();
+        let decoded = ffi
+            .decode_entities(&entities)
+            .expect("Lean call unexpectedly failed for decode_entities");
+        assert_eq!(decoded, entities);
+    }
+
     #[test]
     fn test_cex() {
         let schema = Schema::from_str(
@@ -19,7 +19,11 @@ mod err;
 mod lean_ffi;
 mod messages;
 
-pub use datatypes::{AuthorizationResponse, TimedResult, ValidationResponse};
+pub use datatypes::{
+    AuthorizationResponse, SolverConfig, SolverKind, TimedResult, ValidationResponse,
+};
+// SymCC counterexamples
+pub use datatypes::Env;
 // Term and associated types
 pub use datatypes::{
     Bitvec, Cidr, Datetime, Decimal, Duration, Ext, ExtOp, ExtType, IpAddr, Op, Term, TermPrim,
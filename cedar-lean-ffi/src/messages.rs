@@ -50,6 +50,32 @@ impl From<&RequestEnv> for proto::RequestEnv {
     }
 }
 
+/// Serialize a SolverConfig to a Protobuf message
+impl From<&datatypes::SolverConfig> for proto::SolverConfig {
+    fn from(solver: &datatypes::SolverConfig) -> Self {
+        let kind = match solver.kind() {
+            datatypes::SolverKind::Cvc5 => proto::solver_config::SolverKind::Cvc5,
+            datatypes::SolverKind::Z3 => proto::solver_config::SolverKind::Z3,
+        };
+        Self {
+            kind: kind.into(),
+            path: solver
+                .path()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            options: solver.options().to_vec(),
+            timeout_ms: solver
+                .timeout()
+                .map(|timeout| {
+                    u32::try_from(timeout.as_millis())
+                        .unwrap_or(u32::MAX)
+                        .max(1)
+                })
+                .unwrap_or(0),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct CheckPolicyRequest {
     pub(crate) policy: Policy,
@@ -58,11 +84,17 @@ pub(crate) struct CheckPolicyRequest {
 }
 
 impl proto::CheckPolicyRequest {
-    pub(crate) fn new(policy: &Policy, schema: &Schema, request: &RequestEnv) -> Self {
+    pub(crate) fn new(
+        policy: &Policy,
+        schema: &Schema,
+        request: &RequestEnv,
+        solver: &datatypes::SolverConfig,
+    ) -> Self {
         Self {
             policy: Some(proto::Policy::from(policy)),
             schema: Some(cedar_policy::proto::models::Schema::from(schema)),
             request: Some(proto::RequestEnv::from(request)),
+            solver: Some(proto::SolverConfig::from(solver)),
         }
     }
 }
@@ -70,7 +102,12 @@ impl proto::CheckPolicyRequest {
 /// Serialize the symcc request arguments to a ProtoBuf message
 impl From<&CheckPolicyRequest> for proto::CheckPolicyRequest {
     fn from(req: &CheckPolicyRequest) -> Self {
-        Self::new(&req.policy, &req.schema, &req.request)
+        Self::new(
+            &req.policy,
+            &req.schema,
+            &req.request,
+            &datatypes::SolverConfig::default(),
+        )
     }
 }
 
@@ -82,11 +119,17 @@ pub(crate) struct CheckPolicySetRequest {
 }
 
 impl proto::CheckPolicySetRequest {
-    pub(crate) fn new(policyset: &PolicySet, schema: &Schema, request: &RequestEnv) -> Self {
+    pub(crate) fn new(
+        policyset: &PolicySet,
+        schema: &Schema,
+        request: &RequestEnv,
+        solver: &datatypes::SolverConfig,
+    ) -> Self {
         Self {
             policy_set: Some(cedar_policy::proto::models::PolicySet::from(policyset)),
             schema: Some(cedar_policy::proto::models::Schema::from(schema)),
             request: Some(proto::RequestEnv::from(request)),
+            solver: Some(proto::SolverConfig::from(solver)),
         }
     }
 }
@@ -94,7 +137,12 @@ impl proto::CheckPolicySetRequest {
 /// Serialize the symcc request arguments to a ProtoBuf message
 impl From<&CheckPolicySetRequest> for proto::CheckPolicySetRequest {
     fn from(req: &CheckPolicySetRequest) -> Self {
-        Self::new(&req.policyset, &req.schema, &req.request)
+        Self::new(
+            &req.policyset,
+            &req.schema,
+            &req.request,
+            &datatypes::SolverConfig::default(),
+        )
     }
 }
 
@@ -112,12 +160,14 @@ impl proto::ComparePolicySetsRequest {
         tgt_policyset: &PolicySet,
         schema: &Schema,
         request: &RequestEnv,
+        solver: &datatypes::SolverConfig,
     ) -> Self {
         Self {
             src_policy_set: Some(cedar_policy::proto::models::PolicySet::from(src_policyset)),
             tgt_policy_set: Some(cedar_policy::proto::models::PolicySet::from(tgt_policyset)),
             schema: Some(cedar_policy::proto::models::Schema::from(schema)),
             request: Some(proto::RequestEnv::from(request)),
+            solver: Some(proto::SolverConfig::from(solver)),
         }
     }
 }
@@ -130,6 +180,7 @@ impl From<&ComparePolicySetsRequest> for proto::ComparePolicySetsRequest {
             &req.tgt_policyset,
             &req.schema,
             &req.request,
+            &datatypes::SolverConfig::default(),
         )
     }
 }
@@ -578,11 +629,13 @@ impl proto::CheckAssertsRequest {
         asserts: &Vec<datatypes::Term>,
         schema: &Schema,
         request: &RequestEnv,
+        solver: &datatypes::SolverConfig,
     ) -> Self {
         Self {
             asserts: Some(proto::Asserts::new(asserts)),
             schema: Some(cedar_policy::proto::models::Schema::from(schema)),
             request: Some(proto::RequestEnv::from(request)),
+            solver: Some(proto::SolverConfig::from(solver)),
         }
     }
 }
@@ -695,7 +748,12 @@ mod test {
             resource_type.clone(),
         );
 
-        let check_policy_pre_proto = proto::CheckPolicyRequest::new(&policy, &schema, &request_env);
+        let check_policy_pre_proto = proto::CheckPolicyRequest::new(
+            &policy,
+            &schema,
+            &request_env,
+            &datatypes::SolverConfig::default(),
+        );
         let check_policy_bytes = check_policy_pre_proto.encode_to_vec();
         let check_policy_proto = proto::CheckPolicyRequest::decode(&check_policy_bytes[..])
             .expect("Failed to decode protobuf CheckPolicyReuqest");
@@ -754,8 +812,12 @@ mod test {
             resource_type.clone(),
         );
 
-        let check_policyset_pre_proto =
-            proto::CheckPolicySetRequest::new(&policyset, &schema, &request_env);
+        let check_policyset_pre_proto = proto::CheckPolicySetRequest::new(
+            &policyset,
+            &schema,
+            &request_env,
+            &datatypes::SolverConfig::default(),
+        );
         let check_policyset_bytes = check_policyset_pre_proto.encode_to_vec();
         let check_policyset_proto =
             proto::CheckPolicySetRequest::decode(&check_policyset_bytes[..])
@@ -821,6 +883,7 @@ mod test {
             &tgt_policyset,
             &schema,
             &request_env,
+            &datatypes::SolverConfig::default(),
         );
         let compare_policyset_bytes = compare_policyset_pre_proto.encode_to_vec();
         let compare_policyset_proto =
@@ -1083,4 +1146,33 @@ mod test {
         assert_eq!(request.action(), rt_request.action());
         assert_eq!(request.resource(), rt_request.resource());
     }
+
+    #[test]
+    fn convert_proto_solver_config() {
+        let default_proto = proto::SolverConfig::from(&datatypes::SolverConfig::default());
+        assert_eq!(default_proto.kind(), proto::solver_config::SolverKind::Cvc5);
+        assert_eq!(default_proto.path, "");
+        assert!(default_proto.options.is_empty());
+        assert_eq!(default_proto.timeout_ms, 0);
+
+        let config = datatypes::SolverConfig::new(datatypes::SolverKind::Z3)
+            .with_path("/usr/bin/z3")
+            .with_options(["smt.random_seed=1".to_string()])
+            .with_timeout(std::time::Duration::from_secs(2));
+        let config_pre_proto = proto::SolverConfig::from(&config);
+        let config_bytes = config_pre_proto.encode_to_vec();
+        let config_proto = proto::SolverConfig::decode(&config_bytes[..])
+            .expect("Failed to decode protobuf solver config");
+        assert_eq!(config_pre_proto, config_proto);
+
+        assert_eq!(config_proto.kind(), proto::solver_config::SolverKind::Z3);
+        assert_eq!(config_proto.path, "/usr/bin/z3");
+        assert_eq!(config_proto.options, vec!["smt.random_seed=1".to_string()]);
+        assert_eq!(config_proto.timeout_ms, 2000);
+
+        // A nonzero timeout must not round down to 0, which means "no timeout"
+        let config =
+            datatypes::SolverConfig::default().with_timeout(std::time::Duration::from_micros(10));
+        assert_eq!(proto::SolverConfig::from(&config).timeout_ms, 1);
+    }
 }
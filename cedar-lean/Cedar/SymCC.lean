@@ -77,6 +77,26 @@ by the compiler, and enforced by the typechecker through policy transformation.
 def wellTypedPolicies (ps : Policies) (Γ : Cedar.Validation.TypeEnv) : Option Policies :=
   ps.mapM (wellTypedPolicy · Γ)
 
+/--
+The message of the error thrown by `checkSat` and `checkUnsat` when the solver
+returns `unknown`, e.g., because the query exceeded the solver's time limit.
+-/
+def solverUnknownMsg : String := "Solver returned unknown."
+
+/--
+The error thrown by `checkSat` and `checkUnsat` when the solver returns
+`unknown`.
+-/
+def solverUnknownError : IO.Error := .userError solverUnknownMsg
+
+/--
+Returns true iff `e` is `solverUnknownError`, i.e., the solver returned
+`unknown` rather than failing.
+-/
+def isSolverUnknown : IO.Error → Bool
+  | .userError msg => msg == solverUnknownMsg
+  | _              => false
+
 ----- Slow verification checks that extract models -----
 
 /--
@@ -109,7 +129,7 @@ def checkSat (vc : SymEnv → Result Asserts) (εnv : SymEnv) : SolverM (Option
               throw (IO.userError s!"Model violates assertion {reprStr t}: {model}")
           pure (.some I)
         | .error msg => throw (IO.userError s!"Model decoding failed: {msg}\n {model}")
-      | .unknown => throw (IO.userError s!"Solver returned unknown.")
+      | .unknown => throw solverUnknownError
   | .error err =>
     throw (IO.userError s!"SymCC failed: {reprStr err}.")
 
@@ -208,7 +228,7 @@ def checkUnsat (vc : SymEnv → Result Asserts) (εnv : SymEnv) : SolverM Bool :
       match (← Solver.checkSat) with
       | .unsat   => pure true
       | .sat     => pure false
-      | .unknown => throw (IO.userError s!"Solver returned unknown.")
+      | .unknown => throw solverUnknownError
   | .error err =>
     throw (IO.userError s!"SymCC failed: {reprStr err}.")
 
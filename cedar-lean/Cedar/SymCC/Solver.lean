@@ -22,9 +22,12 @@ process. Callers communicate with the solver by issuing commands with
 s-expressions encoded as strings. The interface is based on
 [lean-smt](https://github.com/ufmg-smite/lean-smt/).
 
-Currently, we support only CVC5. The function `cvc5` creates a fresh CVC5 solver
-process. This function assumes that the environment variable `CVC5` contains the
-absolute path to the CVC5 executable.
+We support CVC5 and Z3. The functions `cvc5` and `z3` create a fresh solver
+process, assuming that the environment variable `CVC5` (respectively, `Z3`)
+contains the absolute path to the solver executable. The function `ofConfig`
+creates a solver process as described by a `SolverConfig`, which can also
+specify the executable path, extra command-line options, and a per-query
+timeout.
 -/
 
 namespace Cedar.SymCC
@@ -57,6 +60,26 @@ structure Solver where
 
 abbrev SolverM (α) := ReaderT Solver IO α
 
+/-- The SMT solvers that can be spawned by `Solver.ofConfig`. -/
+inductive SolverKind where
+  | cvc5
+  | z3
+deriving DecidableEq, Repr, Inhabited
+
+/--
+  Describes how to spawn an SMT solver process. If `path` is `none`, the
+  executable is read from the `CVC5` or `Z3` environment variable, depending on
+  `kind`. The `options` are passed to the executable after the default
+  arguments. If `timeout` is `some ms`, the solver gives up on each `checkSat`
+  query after `ms` milliseconds and returns `Decision.unknown`.
+-/
+structure SolverConfig where
+  kind    : SolverKind := .cvc5
+  path    : Option String := .none
+  options : Array String := #[]
+  timeout : Option Nat := .none
+deriving Repr, Inhabited
+
 def SolverM.run (solver : Solver) (x : SolverM α) : IO α := ReaderT.run x solver
 
 namespace Solver
@@ -76,14 +99,41 @@ def spawn (path : String) (args : Array String) : IO Solver := do
   }
   return ⟨IO.FS.Stream.ofHandle proc.stdin, IO.FS.Stream.ofHandle proc.stdout⟩
 
+private def defaultArgs : SolverKind → Option Nat → Array String
+  | .cvc5, timeout => #["--quiet", "--lang", "smt"] ++ (timeout.map (s!"--tlimit-per={·}")).toArray
+  | .z3,   timeout => #["-in", "-smt2"] ++ (timeout.map (s!"-t:{·}")).toArray
+
+private def envVar : SolverKind → String
+  | .cvc5 => "CVC5"
+  | .z3   => "Z3"
+
+/--
+  Returns a Solver as described by the configuration `cfg`. If `cfg.path` is
+  not given, the executable is read from the environment variable "CVC5" or
+  "Z3", depending on `cfg.kind`.
+-/
+def ofConfig (cfg : SolverConfig) : IO Solver := do
+  let path ← match cfg.path with
+    | .some path => pure path
+    | .none      =>
+      match (← IO.getEnv (envVar cfg.kind)) with
+      | .some path => pure path
+      | .none      => throw (IO.userError s!"{envVar cfg.kind} environment variable not defined.")
+  spawn path (defaultArgs cfg.kind cfg.timeout ++ cfg.options)
+
 /--
   Returns an instance of the CVC5 solver that is backed by the executable
   specified in the environment variable "CVC5".
 -/
-def cvc5 : IO Solver := do
-  match (← IO.getEnv "CVC5") with
-  | .some path => spawn path ["--quiet", "--lang", "smt"].toArray
-  | .none      => throw (IO.userError "CVC5 environment variable not defined.")
+def cvc5 : IO Solver :=
+  ofConfig { kind := .cvc5 }
+
+/--
+  Returns an instance of the Z3 solver that is backed by the executable
+  specified in the environment variable "Z3".
+-/
+def z3 : IO Solver :=
+  ofConfig { kind := .z3 }
 
 /--
   Returns a solver that writes all issued commands to the given stream `s`.
@@ -66,6 +66,20 @@ unsafe def runFfiM {α : Type} [Lean.ToJson α] (m : FfiM α) : String :=
   | .ok (.error s) => toString (Lean.toJson ((.error s) : Except String α))
   | .ok (.ok r) => toString (Lean.toJson (.ok r : Except String α))
 
+/--
+  Like `runFfiM`, but for computations that call an SMT solver. If the solver
+  returned `unknown` (e.g., because it reached its time limit), i.e., `m` threw
+  `solverUnknownError`, the result is encoded as `{"unknown": err_message}`
+  rather than as an error.
+-/
+unsafe def runSolverFfiM {α : Type} [Lean.ToJson α] (m : FfiM α) : String :=
+  match unsafeIO m with
+  | .error e =>
+    if isSolverUnknown e then toString (Lean.Json.mkObj [("unknown", Lean.Json.str (toString e))])
+    else toString (Lean.toJson ((.error s!"IO error: {e}") : Except String α))
+  | .ok (.error s) => toString (Lean.toJson ((.error s) : Except String α))
+  | .ok (.ok r) => toString (Lean.toJson (.ok r : Except String α))
+
 --------------------------------- Cedar Evaluation / Validation ---------------------------------
 
 /--
@@ -153,19 +167,80 @@ unsafe def runFfiM {α : Type} [Lean.ToJson α] (m : FfiM α) : String :=
     let v ← (@Message.interpret? RequestValidationRequest) req |>.mapError (s!"failed to parse input: {·}")
     runAndTime (λ () => validateRequest v.schema v.request)
 
+--------------------------------------- Protobuf Decoding ---------------------------------------
+
+/-
+  The functions below decode a single protobuf message and return the decoded
+  Lean value as JSON, so that callers can check that the Lean decoders agree with
+  the Rust encoders.
+-/
+
+/--
+  `req`: binary protobuf for a `Policy` (a template and its link)
+
+  returns a string containing JSON
+-/
+@[export decodePolicy] unsafe def decodePolicyFFI (req : ByteArray) : String :=
+  runFfiM do
+    let p ← (@Message.interpret? Cedar.SymCC.Proto.Policy) req |>.mapError (s!"failed to parse input: {·}")
+    let p ← p.toPolicy
+    runAndTime (λ () => p)
+
+/--
+  `req`: binary protobuf for an `Expr`
+
+  returns a string containing JSON
+-/
+@[export decodeExpr] unsafe def decodeExprFFI (req : ByteArray) : String :=
+  runFfiM do
+    let e ← (@Message.interpret? Expr) req |>.mapError (s!"failed to parse input: {·}")
+    runAndTime (λ () => e)
+
+/--
+  `req`: binary protobuf for an `Entities`
+
+  returns a string containing JSON
+-/
+@[export decodeEntities] unsafe def decodeEntitiesFFI (req : ByteArray) : String :=
+  runFfiM do
+    let es ← (@Message.interpret? Cedar.Spec.Proto.Entities) req |>.mapError (s!"failed to parse input: {·}")
+    runAndTime (λ () => es.toEntities)
+
+/--
+  `req`: binary protobuf for a `Request`
+
+  returns a string containing JSON
+-/
+@[export decodeRequest] unsafe def decodeRequestFFI (req : ByteArray) : String :=
+  runFfiM do
+    let r ← (@Message.interpret? Cedar.Spec.Proto.Request) req |>.mapError (s!"failed to parse input: {·}")
+    let r ← r.toRequest
+    runAndTime (λ () => r)
+
+/--
+  `req`: binary protobuf for a `Schema`
+
+  returns a string containing JSON
+-/
+@[export decodeSchema] unsafe def decodeSchemaFFI (req : ByteArray) : String :=
+  runFfiM do
+    let s ← (@Message.interpret? Cedar.Validation.Proto.Schema) req |>.mapError (s!"failed to parse input: {·}")
+    let s ← s.toSchema
+    runAndTime (λ () => s)
+
 ------------------------------------ Cedar Symbolic Compiler ------------------------------------
 
 /--
   `req`: binary protobuf for an `CheckPolicyRequest`
   `return_original`: return the deserialized policy when enabled, as opposed to the one generated by the validator
 
-  Upon success returns a well-typed policy and symbolic environment corresponding to the request `req`
+  Upon success returns a well-typed policy, symbolic environment, and solver configuration corresponding to the request `req`
   Returns a failure if
   1.) Protobuf message could not be parsed
   2.) The requestEnv of `req` is not consistent with the schema of `req`
   3.) The policy of `req` is not well-typed for the requestEnv of `req`
 -/
-def parseCheckPolicyReq (req : ByteArray) (return_original: Bool) : Except String (Cedar.Spec.Policy × SymEnv) := do
+def parseCheckPolicyReq (req : ByteArray) (return_original: Bool) : Except String (Cedar.Spec.Policy × SymEnv × SolverConfig) := do
   let req ← (@Message.interpret? CheckPolicyRequest) req |>.mapError (s!"failed to parse input: {·}")
   let policy := req.policy
   let schema := req.schema
@@ -177,19 +252,19 @@ def parseCheckPolicyReq (req : ByteArray) (return_original: Bool) : Except Strin
   let well_typed_policy ← match wellTypedPolicy policy env with
     | none => .error s!"failed to validate policy for requestEnv (PrincipalType: {request.principal}, ActionName: {request.action}, ResourceType: {request.resource})"
     | some policy => .ok policy
-  return (if return_original then policy else well_typed_policy, SymEnv.ofTypeEnv env)
+  return (if return_original then policy else well_typed_policy, SymEnv.ofTypeEnv env, req.solver)
 
 /--
   `req`: binary protobuf for an `CheckPolicySetRequest`
   `return_original`: return the deserialized policy set when enabled, as opposed to the one generated by the validator
 
-  Upon success returns a list of well-typed policies and symbolic environment corresponding to the request `req`
+  Upon success returns a list of well-typed policies, symbolic environment, and solver configuration corresponding to the request `req`
   Returns a failure if
   1.) Protobuf message could not be parsed
   2.) The requestEnv of `req` is not consistent with the schema of `req`
   3.) Any policy of the policySet of `req` is not well-typed for the requestEnv of `req`
 -/
-def parseCheckPoliciesReq (req : ByteArray) (return_original: Bool) : Except String (Policies × SymEnv) := do
+def parseCheckPoliciesReq (req : ByteArray) (return_original: Bool) : Except String (Policies × SymEnv × SolverConfig) := do
   let req ← (@Message.interpret? CheckPolicySetRequest) req |>.mapError (s!"failed to parse input: {·}")
   let policySet := req.policySet
   let schema := req.schema
@@ -200,19 +275,19 @@ def parseCheckPoliciesReq (req : ByteArray) (return_original: Bool) : Except Str
   let well_typed_policies ← match wellTypedPolicies policySet env with
     | none => .error s!"failed to validate policy for requestEnv (PrincipalType: {request.principal}, ActionName: {request.action}, ResourceType: {request.resource})"
     | some policies => .ok policies
-  return (if return_original then policySet else well_typed_policies, SymEnv.ofTypeEnv env)
+  return (if return_original then policySet else well_typed_policies, SymEnv.ofTypeEnv env, req.solver)
 
 /--
   `req`: binary protobuf for an `CheckPolicySetRequest`
   `return_original`: return the deserialized policy sets when enabled, as opposed to the ones generated by the validator
 
-  Upon success returns a list of well-typed policies and symbolic environment corresponding to the request `req`
+  Upon success returns a list of well-typed policies, symbolic environment, and solver configuration corresponding to the request `req`
   Returns a failure if
   1.) Protobuf message could not be parsed
   2.) The requestEnv of `req` is not consistent with the schema of `req`
   3.) Any policy of the source or target PolicySets of `req` is not well-typed for the requestEnv of `req`
 -/
-def parseComparePolicySetsReq (req : ByteArray) (return_original: Bool) : Except String (Policies × Policies × SymEnv) := do
+def parseComparePolicySetsReq (req : ByteArray) (return_original: Bool) : Except String (Policies × Policies × SymEnv × SolverConfig) := do
   let req ← (@Message.interpret? ComparePolicySetsRequest) req |>.mapError (s!"failed to parse input: {·}")
   let srcPolicySet := req.srcPolicySet
   let tgtPolicySet := req.tgtPolicySet
@@ -224,10 +299,10 @@ def parseComparePolicySetsReq (req : ByteArray) (return_original: Bool) : Except
   let (well_typed_src_policies, well_typed_tgt_policies) ← match wellTypedPolicies srcPolicySet env, wellTypedPolicies tgtPolicySet env with
     | none, _ | _, none => .error s!"failed to validate policy for requestEnv (PrincipalType: {request.principal}, ActionName: {request.action}, ResourceType: {request.resource})"
     | some src, some tgt => .ok (src, tgt)
-  return if return_original then (srcPolicySet, tgtPolicySet, SymEnv.ofTypeEnv env) else (well_typed_src_policies, well_typed_tgt_policies, SymEnv.ofTypeEnv env)
+  return if return_original then (srcPolicySet, tgtPolicySet, SymEnv.ofTypeEnv env, req.solver) else (well_typed_src_policies, well_typed_tgt_policies, SymEnv.ofTypeEnv env, req.solver)
 
 
-def parseCheckAssertsReq (proto : ByteArray) : Except String (Cedar.SymCC.Asserts × SymEnv) := do
+def parseCheckAssertsReq (proto : ByteArray) : Except String (Cedar.SymCC.Asserts × SymEnv × SolverConfig) := do
   let req ← (@Message.interpret? CheckAssertsRequest) proto |>.mapError (s!"failed to parse input: {·}")
   let asserts := req.asserts
   let schema := req.schema
@@ -235,7 +310,7 @@ def parseCheckAssertsReq (proto : ByteArray) : Except String (Cedar.SymCC.Assert
   let env ← match schema.environment? request.principal request.resource request.action with
     | none => .error s!"failed to get environment from requestEnv (PrincipalType: {request.principal}, ActionName: {request.action}, ResourceType: {request.resource})"
     | some env => .ok env
-  return (asserts, SymEnv.ofTypeEnv env)
+  return (asserts, SymEnv.ofTypeEnv env, req.solver)
 
 /--
   Run `solver` on `vcs` without exposing the IO monad to the calling code
@@ -246,8 +321,13 @@ private def safeSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except
 @[implemented_by safeSolve]
 opaque solve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except String α)
 
+/--
+  Like `solve`, but also returns the time taken by `vcs`. The solver is spawned
+  before starting the timer, so the duration only covers encoding and solving.
+-/
 private def safeTimedSolve {α} (solver: IO Solver) (vcs : SolverM α) : IO (Except String (Timed α)) := do
-  let result ← runAndTimeIO (solve solver vcs)
+  let solver ← solver
+  let result ← runAndTimeIO (solve (pure solver) vcs)
   return match result.data with
   | .ok res => .ok ( { data := res, duration := result.duration })
   | .error s => .error s
@@ -262,11 +342,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := true, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := false, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckNeverErrors] unsafe def runCheckNeverErrors (req : ByteArray) : String :=
-  runFfiM do
-    let (policy, εnv) ← parseCheckPolicyReq req false
-    timedSolve Solver.cvc5 (checkNeverErrors policy εnv)
+  runSolverFfiM do
+    let (policy, εnv, solver) ← parseCheckPolicyReq req false
+    timedSolve (Solver.ofConfig solver) (checkNeverErrors policy εnv)
 
 /--
   `req`: binary protobuf for an `CheckPolicyRequest`
@@ -275,11 +356,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := null, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := {request: ..., entities: ...}, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckNeverErrorsWithCex] unsafe def runCheckNeverErrorsWithCex (req : ByteArray) : String :=
-  runFfiM do
-    let (policy, εnv) ← parseCheckPolicyReq req false
-    timedSolve Solver.cvc5 (neverErrors? policy εnv)
+  runSolverFfiM do
+    let (policy, εnv, solver) ← parseCheckPolicyReq req false
+    timedSolve (Solver.ofConfig solver) (neverErrors? policy εnv)
 
 /--
   `req`: binary protobuf for an `CheckPolicySetRequest`
@@ -288,11 +370,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := true, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := false, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckAlwaysAllows] unsafe def runCheckAlwaysAllows (req : ByteArray) : String :=
-  runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
-    timedSolve Solver.cvc5 (checkAlwaysAllows policies εnv)
+  runSolverFfiM do
+    let (policies, εnv, solver) ← parseCheckPoliciesReq req false
+    timedSolve (Solver.ofConfig solver) (checkAlwaysAllows policies εnv)
 
 /--
   `req`: binary protobuf for an `CheckPolicySetRequest`
@@ -301,11 +384,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := null, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := {request: ..., entities: ...}, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckAlwaysAllowsWithCex] unsafe def runCheckAlwaysAllowsWithCex (req : ByteArray) : String :=
-  runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
-    timedSolve Solver.cvc5 (alwaysAllows? policies εnv)
+  runSolverFfiM do
+    let (policies, εnv, solver) ← parseCheckPoliciesReq req false
+    timedSolve (Solver.ofConfig solver) (alwaysAllows? policies εnv)
 
 /--
   `req`: binary protobuf for an `CheckPolicySetRequest`
@@ -314,11 +398,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := true, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := false, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckAlwaysDenies] unsafe def runCheckAlwaysDenies (req : ByteArray) : String :=
-  runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
-    timedSolve Solver.cvc5 (checkAlwaysDenies policies εnv)
+  runSolverFfiM do
+    let (policies, εnv, solver) ← parseCheckPoliciesReq req false
+    timedSolve (Solver.ofConfig solver) (checkAlwaysDenies policies εnv)
 
 /--
   `req`: binary protobuf for an `CheckPolicySetRequest`
@@ -327,11 +412,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := null, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := {request: ..., entities: ...}, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckAlwaysDeniesWithCex] unsafe def runCheckAlwaysDeniesWithCex (req : ByteArray) : String :=
-  runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
-    timedSolve Solver.cvc5 (alwaysDenies? policies εnv)
+  runSolverFfiM do
+    let (policies, εnv, solver) ← parseCheckPoliciesReq req false
+    timedSolve (Solver.ofConfig solver) (alwaysDenies? policies εnv)
 
 /--
   `req`: binary protobuf for an `ComparePolicySetsRequest`
@@ -340,11 +426,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := true, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := false, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckEquivalent] unsafe def runCheckEquivalent (req : ByteArray) : String :=
-  runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
-    timedSolve Solver.cvc5 (checkEquivalent srcPolicies tgtPolicies εnv)
+  runSolverFfiM do
+    let (srcPolicies, tgtPolicies, εnv, solver) ← parseComparePolicySetsReq req false
+    timedSolve (Solver.ofConfig solver) (checkEquivalent srcPolicies tgtPolicies εnv)
 
 /--
   `req`: binary protobuf for an `ComparePolicySetsRequest`
@@ -353,11 +440,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := null, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := {request: ..., entities: ...}, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckEquivalentWithCex] unsafe def runCheckEquivalentWithCex (req : ByteArray) : String :=
-  runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
-    timedSolve Solver.cvc5 (equivalent? srcPolicies tgtPolicies εnv)
+  runSolverFfiM do
+    let (srcPolicies, tgtPolicies, εnv, solver) ← parseComparePolicySetsReq req false
+    timedSolve (Solver.ofConfig solver) (equivalent? srcPolicies tgtPolicies εnv)
 
 /--
   `req`: binary protobuf for an `ComparePolicySetsRequest`
@@ -366,11 +454,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := true, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := false, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckImplies] unsafe def runCheckImplies (req : ByteArray) : String :=
-  runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
-    timedSolve Solver.cvc5 (checkImplies srcPolicies tgtPolicies εnv)
+  runSolverFfiM do
+    let (srcPolicies, tgtPolicies, εnv, solver) ← parseComparePolicySetsReq req false
+    timedSolve (Solver.ofConfig solver) (checkImplies srcPolicies tgtPolicies εnv)
 
 /--
   `req`: binary protobuf for an `ComparePolicySetsRequest`
@@ -379,11 +468,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := null, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := {request: ..., entities: ...}, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckImpliesWithCex] unsafe def runCheckImpliesWithCex (req : ByteArray) : String :=
-  runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
-    timedSolve Solver.cvc5 (implies? srcPolicies tgtPolicies εnv)
+  runSolverFfiM do
+    let (srcPolicies, tgtPolicies, εnv, solver) ← parseComparePolicySetsReq req false
+    timedSolve (Solver.ofConfig solver) (implies? srcPolicies tgtPolicies εnv)
 
 /--
   `req`: binary protobuf for an `ComparePolicySetsRequest`
@@ -392,11 +482,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := true, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := false, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckDisjoint] unsafe def runCheckDisjoint (req : ByteArray) : String :=
-  runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
-    timedSolve Solver.cvc5 (checkDisjoint srcPolicies tgtPolicies εnv)
+  runSolverFfiM do
+    let (srcPolicies, tgtPolicies, εnv, solver) ← parseComparePolicySetsReq req false
+    timedSolve (Solver.ofConfig solver) (checkDisjoint srcPolicies tgtPolicies εnv)
 
 /--
   `req`: binary protobuf for an `ComparePolicySetsRequest`
@@ -405,11 +496,12 @@ opaque timedSolve {α} (solver : IO Solver) (vcs : SolverM α) : IO (Except Stri
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := null, duration := <encode+solve_time> } if the solver could prove `req` holds
   3.) .ok { data := {request: ..., entities: ...}, duration := <encode+solve_time> } if the solver could prove `req` does not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckDisjointWithCex] unsafe def runCheckDisjointWithCex (req : ByteArray) : String :=
-  runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
-    timedSolve Solver.cvc5 (disjoint? srcPolicies tgtPolicies εnv)
+  runSolverFfiM do
+    let (srcPolicies, tgtPolicies, εnv, solver) ← parseComparePolicySetsReq req false
+    timedSolve (Solver.ofConfig solver) (disjoint? srcPolicies tgtPolicies εnv)
 
 /--
   Auxillary function that encodes and runs the solver on the generated VCs. Useful for
@@ -440,7 +532,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export printCheckNeverErrors] unsafe def printCheckNeverErrors (req : ByteArray) : String :=
   runFfiM do
-    let (policy, εnv) ← parseCheckPolicyReq req false
+    let (policy, εnv, _) ← parseCheckPolicyReq req false
     let stdOut ← IO.getStdout
     let solver ← Solver.streamWriter stdOut
     let vcs := ignoreOutput (verifyNeverErrors policy) εnv
@@ -455,7 +547,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export printCheckAlwaysAllows] unsafe def printCheckAlwaysAllows (req : ByteArray) : String :=
   runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
+    let (policies, εnv, _) ← parseCheckPoliciesReq req false
     let stdOut ← IO.getStdout
     let solver ← Solver.streamWriter stdOut
     let vcs := ignoreOutput (verifyAlwaysAllows policies) εnv
@@ -470,7 +562,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export printCheckAlwaysDenies] unsafe def printCheckAlwaysDenies (req : ByteArray) : String :=
   runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
+    let (policies, εnv, _) ← parseCheckPoliciesReq req false
     let stdOut ← IO.getStdout
     let solver ← Solver.streamWriter stdOut
     let vcs := ignoreOutput (verifyAlwaysDenies policies) εnv
@@ -485,7 +577,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export printCheckEquivalent] unsafe def printCheckEquivalent (req : ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     let stdOut ← IO.getStdout
     let solver ← Solver.streamWriter stdOut
     let vcs := ignoreOutput (verifyEquivalent srcPolicies tgtPolicies) εnv
@@ -500,7 +592,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export printCheckImplies] unsafe def printCheckImplies (req : ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     let stdOut ← IO.getStdout
     let solver ← Solver.streamWriter stdOut
     let vcs := ignoreOutput (verifyImplies srcPolicies tgtPolicies) εnv
@@ -515,7 +607,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export printCheckDisjoint] unsafe def printCheckDisjoint (req : ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     let stdOut ← IO.getStdout
     let solver ← Solver.streamWriter stdOut
     let vcs := ignoreOutput (verifyDisjoint srcPolicies tgtPolicies) εnv
@@ -528,11 +620,12 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
   1.) .error err_message if there was in error in parsing or running the solver
   2.) .ok { data := true, duration := <solve_time> } if the solver could prove `asserts` hold
   3.) .ok { data := false, duration := <solve_time> } if the solver could prove `asserts` do not hold
+  4.) .unknown err_message if the solver returned unknown, e.g., because it timed out
 -/
 @[export runCheckAsserts] unsafe def runCheckAsserts (req: ByteArray) : String :=
-  runFfiM do
-    let (asserts, εnv) ← parseCheckAssertsReq req
-    timedSolve Solver.cvc5 (checkUnsat (λ _ => .ok asserts) εnv)
+  runSolverFfiM do
+    let (asserts, εnv, solver) ← parseCheckAssertsReq req
+    timedSolve (Solver.ofConfig solver) (checkUnsat (λ _ => .ok asserts) εnv)
 
 /--
   `req`: binary protobuf for a `CheckAsserts`
@@ -543,7 +636,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export printCheckAsserts] unsafe def printCheckAsserts (req: ByteArray) : String :=
   runFfiM do
-    let (asserts, εnv) ← parseCheckAssertsReq req
+    let (asserts, εnv, _) ← parseCheckAssertsReq req
     let stdOut ← IO.getStdout
     let solver ← Solver.streamWriter stdOut
     timedSolve (pure solver) (ignoreOutput (λ _ => .ok asserts) εnv)
@@ -558,7 +651,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export smtLibOfCheckAsserts] unsafe def smtLibOfCheckAsserts (req: ByteArray) : String :=
   runFfiM do
-    let (asserts, εnv) ← parseCheckAssertsReq req
+    let (asserts, εnv, _) ← parseCheckAssertsReq req
     let buffer ← IO.mkRef ⟨ByteArray.empty, 0⟩
     let solver ← Solver.bufferWriter buffer
     let r ← timedSolve (pure solver) (ignoreOutput (fun _ => .ok asserts) εnv)
@@ -573,7 +666,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckNeverErrors] unsafe def assertsOfCheckNeverErrors (req: ByteArray) : String :=
   runFfiM do
-    let (policy, εnv) ← parseCheckPolicyReq req false
+    let (policy, εnv, _) ← parseCheckPolicyReq req false
     runAndTime (λ () => verifyNeverErrors policy εnv)
 
 /--
@@ -583,7 +676,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckNeverErrorsOnOriginal] unsafe def assertsOfCheckNeverErrorsOnOriginal (req: ByteArray) : String :=
   runFfiM do
-    let (policy, εnv) ← parseCheckPolicyReq req true
+    let (policy, εnv, _) ← parseCheckPolicyReq req true
     runAndTime (λ () => verifyNeverErrors policy εnv)
 
 /--
@@ -593,7 +686,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckAlwaysAllows] unsafe def assertsOfCheckAlwaysAllows (req: ByteArray) : String :=
   runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
+    let (policies, εnv, _) ← parseCheckPoliciesReq req false
     runAndTime (λ () => verifyAlwaysAllows policies εnv)
 
 /--
@@ -603,7 +696,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckAlwaysAllowsOnOriginal] unsafe def assertsOfCheckAlwaysAllowsOnOriginal (req: ByteArray) : String :=
   runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req true
+    let (policies, εnv, _) ← parseCheckPoliciesReq req true
     runAndTime (λ () => verifyAlwaysAllows policies εnv)
 
 /--
@@ -613,7 +706,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckAlwaysDenies] unsafe def assertsOfCheckAlwaysDenies (req: ByteArray) : String :=
   runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
+    let (policies, εnv, _) ← parseCheckPoliciesReq req false
     runAndTime (λ () => verifyAlwaysDenies policies εnv)
 
 /--
@@ -623,7 +716,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckAlwaysDeniesOnOriginal] unsafe def assertsOfCheckAlwaysDeniesOnOriginal (req: ByteArray) : String :=
   runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req true
+    let (policies, εnv, _) ← parseCheckPoliciesReq req true
     runAndTime (λ () => verifyAlwaysDenies policies εnv)
 
 /--
@@ -633,7 +726,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckEquivalent] unsafe def assertsOfCheckEquivalent (req: ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     runAndTime (λ () => verifyEquivalent srcPolicies tgtPolicies εnv)
 
 /--
@@ -643,7 +736,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckEquivalentOnOriginal] unsafe def assertsOfCheckEquivalentOnOriginal (req: ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req true
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req true
     runAndTime (λ () => verifyEquivalent srcPolicies tgtPolicies εnv)
 
 /--
@@ -653,7 +746,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckImplies] unsafe def assertsOfCheckImplies (req: ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     runAndTime (λ () => verifyImplies srcPolicies tgtPolicies εnv)
 
 /--
@@ -663,7 +756,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckImpliesOnOriginal] unsafe def assertsOfCheckImpliesOnOriginal (req: ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req true
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req true
     runAndTime (λ () => verifyImplies srcPolicies tgtPolicies εnv)
 
 /--
@@ -673,7 +766,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckDisjoint] unsafe def assertsOfCheckDisjoint (req: ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     runAndTime (λ () => verifyDisjoint srcPolicies tgtPolicies εnv)
 
 /--
@@ -683,7 +776,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export assertsOfCheckDisjointOnOriginal] unsafe def assertsOfCheckDisjointOnOriginal (req: ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req true
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req true
     runAndTime (λ () => verifyDisjoint srcPolicies tgtPolicies εnv)
 
 /--
@@ -696,7 +789,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export smtLibOfCheckNeverErrors] unsafe def smtLibOfCheckNeverErrors (req : ByteArray) : String :=
   runFfiM do
-    let (policy, εnv) ← parseCheckPolicyReq req false
+    let (policy, εnv, _) ← parseCheckPolicyReq req false
     let buffer ← IO.mkRef ⟨ByteArray.empty, 0⟩
     let solver ← Solver.bufferWriter buffer
     let vcs := ignoreOutput (verifyNeverErrors policy) εnv
@@ -715,7 +808,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export smtLibOfCheckAlwaysAllows] unsafe def smtLibOfCheckAlwaysAllows (req : ByteArray) : String :=
   runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
+    let (policies, εnv, _) ← parseCheckPoliciesReq req false
     let buffer ← IO.mkRef ⟨ByteArray.empty, 0⟩
     let solver ← Solver.bufferWriter buffer
     let vcs := ignoreOutput (verifyAlwaysAllows policies) εnv
@@ -734,7 +827,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export smtLibOfCheckAlwaysDenies] unsafe def smtLibOfCheckAlwaysDenies (req : ByteArray) : String :=
   runFfiM do
-    let (policies, εnv) ← parseCheckPoliciesReq req false
+    let (policies, εnv, _) ← parseCheckPoliciesReq req false
     let buffer ← IO.mkRef ⟨ByteArray.empty, 0⟩
     let solver ← Solver.bufferWriter buffer
     let vcs := ignoreOutput (verifyAlwaysDenies policies) εnv
@@ -753,7 +846,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export smtLibOfCheckEquivalent] unsafe def smtLibOfCheckEquivalent (req : ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     let buffer ← IO.mkRef ⟨ByteArray.empty, 0⟩
     let solver ← Solver.bufferWriter buffer
     let vcs := ignoreOutput (verifyEquivalent srcPolicies tgtPolicies) εnv
@@ -772,7 +865,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export smtLibOfCheckImplies] unsafe def smtLibOfCheckImplies (req : ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     let buffer ← IO.mkRef ⟨ByteArray.empty, 0⟩
     let solver ← Solver.bufferWriter buffer
     let vcs := ignoreOutput (verifyImplies srcPolicies tgtPolicies) εnv
@@ -791,7 +884,7 @@ private def ignoreOutput (vc : SymEnv → Cedar.SymCC.Result Cedar.SymCC.Asserts
 -/
 @[export smtLibOfCheckDisjoint] unsafe def smtLibOfCheckDisjoint (req : ByteArray) : String :=
   runFfiM do
-    let (srcPolicies, tgtPolicies, εnv) ← parseComparePolicySetsReq req false
+    let (srcPolicies, tgtPolicies, εnv, _) ← parseComparePolicySetsReq req false
     let buffer ← IO.mkRef ⟨ByteArray.empty, 0⟩
     let solver ← Solver.bufferWriter buffer
     let vcs := ignoreOutput (verifyDisjoint srcPolicies tgtPolicies) εnv
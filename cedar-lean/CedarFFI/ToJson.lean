@@ -2,6 +2,7 @@ import Lean.Data.Json.FromToJson
 
 import Cedar.Spec
 import Cedar.SymCC
+import Cedar.Validation
 import Cedar.Data
 
 namespace CedarFFI
@@ -193,4 +194,26 @@ deriving instance Lean.ToJson for Entities
 /- Serializing `Env` -/
 deriving instance Lean.ToJson for Env
 
+/- Serializing decoded `Expr`, `Policy`, and `Schema` -/
+deriving instance Lean.ToJson for Cedar.Spec.Var
+deriving instance Lean.ToJson for Cedar.Spec.UnaryOp
+deriving instance Lean.ToJson for Cedar.Spec.BinaryOp
+deriving instance Lean.ToJson for Cedar.Spec.ExtFun
+deriving instance Lean.ToJson for Cedar.Spec.Expr
+deriving instance Lean.ToJson for Cedar.Spec.Effect
+deriving instance Lean.ToJson for Cedar.Spec.Scope
+deriving instance Lean.ToJson for Cedar.Spec.PrincipalScope
+deriving instance Lean.ToJson for Cedar.Spec.ResourceScope
+deriving instance Lean.ToJson for Cedar.Spec.ActionScope
+deriving instance Lean.ToJson for Cedar.Spec.ConditionKind
+deriving instance Lean.ToJson for Cedar.Spec.Condition
+deriving instance Lean.ToJson for Cedar.Spec.Policy
+deriving instance Lean.ToJson for Cedar.Validation.BoolType
+deriving instance Lean.ToJson for Cedar.Validation.Qualified
+deriving instance Lean.ToJson for Cedar.Validation.CedarType
+deriving instance Lean.ToJson for Cedar.Validation.StandardSchemaEntry
+deriving instance Lean.ToJson for Cedar.Validation.EntitySchemaEntry
+deriving instance Lean.ToJson for Cedar.Validation.ActionSchemaEntry
+deriving instance Lean.ToJson for Cedar.Validation.Schema
+
 end CedarFFI
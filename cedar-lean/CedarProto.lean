@@ -32,6 +32,7 @@ import CedarProto.PrincipalOrResourceConstraint
 import CedarProto.Request
 import CedarProto.RequestValidationRequest
 import CedarProto.Schema
+import CedarProto.SolverConfig
 import CedarProto.SymCCRequest
 import CedarProto.TemplateBody
 import CedarProto.Term
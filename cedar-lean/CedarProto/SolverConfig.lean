@@ -0,0 +1,94 @@
+/-
+ Copyright Cedar Contributors
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+-/
+
+import Cedar.SymCC.Solver
+import Protobuf.Enum
+import Protobuf.Message
+import Protobuf.String
+import Protobuf.Structure
+
+open Proto
+
+namespace Cedar.SymCC
+
+namespace SolverKind
+
+def fromInt (n : Int) : Except String SolverKind :=
+  match n with
+  | 0 => .ok .cvc5
+  | 1 => .ok .z3
+  | n => .error s!"Field {n} does not exist in enum"
+
+instance : ProtoEnum SolverKind := {
+  fromInt := fromInt
+}
+
+end SolverKind
+
+end Cedar.SymCC
+
+namespace Cedar.SymCC.Proto
+
+-- Note: proto3 has no optional scalars, so an empty `path` and a zero
+-- `timeoutMs` stand for "not set" and are converted to `none` in `toSolverConfig`.
+structure SolverConfig where
+  kind : SolverKind
+  path : String
+  options : Repeated String
+  timeoutMs : UInt32
+deriving Inhabited
+
+namespace SolverConfig
+
+instance : Message SolverConfig where
+  parseField (t : Proto.Tag) := do
+    match t.fieldNum with
+    | 1 => parseFieldElement t kind (update kind)
+    | 2 => parseFieldElement t path (update path)
+    | 3 => parseFieldElement t options (update options)
+    | 4 => parseFieldElement t timeoutMs (update timeoutMs)
+    | _ => let _ ← t.wireType.skip ; pure ignore
+
+  merge x y := {
+    kind      := Field.merge x.kind      y.kind
+    path      := Field.merge x.path      y.path
+    options   := Field.merge x.options   y.options
+    timeoutMs := Field.merge x.timeoutMs y.timeoutMs
+  }
+
+def toSolverConfig (cfg : SolverConfig) : Cedar.SymCC.SolverConfig := {
+  kind    := cfg.kind
+  path    := if cfg.path.isEmpty then .none else .some cfg.path
+  options := cfg.options
+  timeout := if cfg.timeoutMs == 0 then .none else .some cfg.timeoutMs.toNat
+}
+
+end SolverConfig
+end Cedar.SymCC.Proto
+
+namespace Cedar.SymCC
+
+def SolverConfig.merge (x y : SolverConfig) : SolverConfig := {
+  kind    := y.kind
+  path    := y.path <|> x.path
+  options := x.options ++ y.options
+  timeout := y.timeout <|> x.timeout
+}
+
+instance : Field SolverConfig :=
+  Field.fromInterField Proto.SolverConfig.toSolverConfig SolverConfig.merge
+
+end Cedar.SymCC
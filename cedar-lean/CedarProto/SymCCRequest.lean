@@ -24,6 +24,7 @@ import CedarProto.Policy
 import CedarProto.PolicySet
 import CedarProto.RequestEnv
 import CedarProto.Schema
+import CedarProto.SolverConfig
 
 open Proto
 
@@ -100,6 +101,7 @@ structure CheckPolicyRequest where
   policy  : Spec.Policy
   schema  : Validation.Schema
   request : Validation.Proto.RequestEnv
+  solver  : Cedar.SymCC.SolverConfig
 deriving Inhabited
 
 namespace CheckPolicyRequest
@@ -110,12 +112,14 @@ instance : Message CheckPolicyRequest where
     | 1 => parseFieldElement t policy (update policy)
     | 2 => parseFieldElement t schema (update schema)
     | 3 => parseFieldElement t request (update request)
+    | 4 => parseFieldElement t solver (update solver)
     | _ => let _ ← t.wireType.skip ; pure ignore
 
   merge x y := {
     policy  := Field.merge x.policy y.policy
     schema  := Field.merge x.schema y.schema
     request := Field.merge x.request y.request
+    solver  := Field.merge x.solver y.solver
   }
 
 end CheckPolicyRequest
@@ -124,6 +128,7 @@ structure CheckPolicySetRequest where
   policySet : Spec.Policies
   schema : Validation.Schema
   request : Validation.Proto.RequestEnv
+  solver : Cedar.SymCC.SolverConfig
 deriving Inhabited
 
 namespace CheckPolicySetRequest
@@ -134,12 +139,14 @@ instance : Message CheckPolicySetRequest where
     | 1 => parseFieldElement t policySet (update policySet)
     | 2 => parseFieldElement t schema (update schema)
     | 3 => parseFieldElement t request (update request)
+    | 4 => parseFieldElement t solver (update solver)
     | _ => let _ ← t.wireType.skip ; pure ignore
 
   merge x y := {
     policySet := Field.merge x.policySet y.policySet
     schema := Field.merge x.schema y.schema
     request := Field.merge x.request y.request
+    solver := Field.merge x.solver y.solver
   }
 
 end CheckPolicySetRequest
@@ -149,6 +156,7 @@ structure ComparePolicySetsRequest where
   tgtPolicySet : Spec.Policies
   schema : Validation.Schema
   request : Validation.Proto.RequestEnv
+  solver : Cedar.SymCC.SolverConfig
 deriving Inhabited
 
 namespace ComparePolicySetsRequest
@@ -160,6 +168,7 @@ instance : Message ComparePolicySetsRequest where
     | 2 => parseFieldElement t tgtPolicySet (update tgtPolicySet)
     | 3 => parseFieldElement t schema (update schema)
     | 4 => parseFieldElement t request (update request)
+    | 5 => parseFieldElement t solver (update solver)
     | _ => let _ ← t.wireType.skip ; pure ignore
 
   merge x y := {
@@ -167,6 +176,7 @@ instance : Message ComparePolicySetsRequest where
     tgtPolicySet := Field.merge x.tgtPolicySet y.tgtPolicySet
     schema := Field.merge x.schema y.schema
     request := Field.merge x.request y.request
+    solver := Field.merge x.solver y.solver
   }
 
 end ComparePolicySetsRequest
@@ -175,6 +185,7 @@ structure CheckAssertsRequest where
   asserts : Cedar.SymCC.Asserts
   schema : Validation.Schema
   request : Validation.Proto.RequestEnv
+  solver : Cedar.SymCC.SolverConfig
 deriving Inhabited
 
 namespace CheckAssertsRequest
@@ -191,12 +202,14 @@ instance : Message CheckAssertsRequest where
     | 1 => parseFieldElement t asserts (update asserts)
     | 2 => parseFieldElement t schema (update schema)
     | 3 => parseFieldElement t request (update request)
+    | 4 => parseFieldElement t solver (update solver)
     | _ => t.wireType.skip ; pure ignore
 
   merge x y := {
     asserts := Field.merge x.asserts y.asserts
     schema := Field.merge x.schema y.schema
     request := Field.merge x.request y.request
+    solver := Field.merge x.solver y.solver
   }
 
 end CheckAssertsRequest
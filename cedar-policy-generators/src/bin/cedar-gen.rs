@@ -0,0 +1,294 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generate a schema, policy set, entity store and requests from a seed.
+//!
+//! Usage: `cargo run --features cedar-policy --bin cedar-gen -- --seed 42 --out-dir out`
+//!
+//! Writes `<name>.cedarschema`, `<name>.cedar`, `<name>.entities.json` and
+//! `<name>.requests.json` to the output directory. The same seed, settings and
+//! version of this crate always give the same files.
+
+use anyhow::{anyhow, Context as _};
+use cedar_policy::{Entities, Policy, PolicyId, PolicySet, Request, SchemaFragment};
+use cedar_policy_core::entities::TypeAndId;
+use cedar_policy_generators::{
+    schema::Schema,
+    settings::{ABACSettings, ExprWeights},
+};
+use clap::Parser;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Number of times to try generating a workload before giving up
+const MAX_ATTEMPTS: usize = 100;
+
+/// Generate a Cedar schema, policy set, entity store and requests from a seed
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Seed for the pseudorandom generation
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// TOML file of generator settings, named as the fields of `ABACSettings`.
+    /// Flags take precedence over the file.
+    #[arg(long)]
+    settings: Option<PathBuf>,
+    #[command(flatten)]
+    overrides: SettingsOverrides,
+    /// Number of policies to generate
+    #[arg(long, default_value_t = 4)]
+    policies: usize,
+    /// Number of requests to generate
+    #[arg(long, default_value_t = 8)]
+    requests: usize,
+    /// Number of pseudorandom bytes each generation attempt draws from
+    #[arg(long, default_value_t = 1 << 16)]
+    bytes: usize,
+    /// Directory to write the generated files to
+    #[arg(long, default_value = ".")]
+    out_dir: PathBuf,
+    /// Basename of the generated files
+    #[arg(long, default_value = "generated")]
+    name: String,
+}
+
+/// Generator settings, each of them optional, as given by flags or a TOML file
+#[derive(Debug, Clone, Default, clap::Args, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SettingsOverrides {
+    /// Generate well-typed entities, policies and requests [default: true]
+    #[arg(long)]
+    match_types: Option<bool>,
+    /// Generate extension values and function calls [default: true]
+    #[arg(long)]
+    enable_extensions: Option<bool>,
+    /// Maximum depth of expressions and types [default: 3]
+    #[arg(long)]
+    max_depth: Option<usize>,
+    /// Maximum width of expressions and types [default: 3]
+    #[arg(long)]
+    max_width: Option<usize>,
+    /// Allow `additionalAttributes` in the schema [default: false]
+    #[arg(long)]
+    enable_additional_attributes: Option<bool>,
+    /// Generate `like` expressions [default: true]
+    #[arg(long)]
+    enable_like: Option<bool>,
+    /// Generate action groups and entity attributes [default: true]
+    #[arg(long)]
+    enable_action_groups_and_attrs: Option<bool>,
+    /// Generate calls to unknown extension functions, which the parser rejects
+    /// [default: false]
+    #[arg(long)]
+    enable_arbitrary_func_call: Option<bool>,
+    /// Generate unknowns [default: false]
+    #[arg(long)]
+    enable_unknowns: Option<bool>,
+    /// Generate `action in` constraints [default: true]
+    #[arg(long)]
+    enable_action_in_constraints: Option<bool>,
+    /// Maximum number of request environments per action [default: 128]
+    #[arg(long)]
+    per_action_request_env_limit: Option<usize>,
+    /// Maximum number of request environments [default: 1024]
+    #[arg(long)]
+    total_action_request_env_limit: Option<usize>,
+    /// Weights of the forms of expressions in policies: `default`,
+    /// `arithmetic-heavy`, `hierarchy-heavy` or `extension-heavy`
+    /// [default: default]
+    #[arg(long)]
+    expr_weights: Option<ExprWeights>,
+}
+
+impl SettingsOverrides {
+    /// Take the settings not given here from `fallback`
+    fn or(self, fallback: Self) -> Self {
+        Self {
+            match_types: self.match_types.or(fallback.match_types),
+            enable_extensions: self.enable_extensions.or(fallback.enable_extensions),
+            max_depth: self.max_depth.or(fallback.max_depth),
+            max_width: self.max_width.or(fallback.max_width),
+            enable_additional_attributes: self
+                .enable_additional_attributes
+                .or(fallback.enable_additional_attributes),
+            enable_like: self.enable_like.or(fallback.enable_like),
+            enable_action_groups_and_attrs: self
+                .enable_action_groups_and_attrs
+                .or(fallback.enable_action_groups_and_attrs),
+            enable_arbitrary_func_call: self
+                .enable_arbitrary_func_call
+                .or(fallback.enable_arbitrary_func_call),
+            enable_unknowns: self.enable_unknowns.or(fallback.enable_unknowns),
+            enable_action_in_constraints: self
+                .enable_action_in_constraints
+                .or(fallback.enable_action_in_constraints),
+            per_action_request_env_limit: self
+                .per_action_request_env_limit
+                .or(fallback.per_action_request_env_limit),
+            total_action_request_env_limit: self
+                .total_action_request_env_limit
+                .or(fallback.total_action_request_env_limit),
+            expr_weights: self.expr_weights.or(fallback.expr_weights),
+        }
+    }
+
+    fn into_settings(self) -> ABACSettings {
+        ABACSettings {
+            match_types: self.match_types.unwrap_or(true),
+            enable_extensions: self.enable_extensions.unwrap_or(true),
+            max_depth: self.max_depth.unwrap_or(3),
+            max_width: self.max_width.unwrap_or(3),
+            enable_additional_attributes: self.enable_additional_attributes.unwrap_or(false),
+            enable_like: self.enable_like.unwrap_or(true),
+            enable_action_groups_and_attrs: self.enable_action_groups_and_attrs.unwrap_or(true),
+            enable_arbitrary_func_call: self.enable_arbitrary_func_call.unwrap_or(false),
+            enable_unknowns: self.enable_unknowns.unwrap_or(false),
+            enable_action_in_constraints: self.enable_action_in_constraints.unwrap_or(true),
+            per_action_request_env_limit: self
+                .per_action_request_env_limit
+                .unwrap_or(ABACSettings::default_per_action_request_env_limit()),
+            total_action_request_env_limit: self
+                .total_action_request_env_limit
+                .unwrap_or(ABACSettings::default_total_action_request_env_limit()),
+            expr_weights: self
+                .expr_weights
+                .unwrap_or(ABACSettings::default_expr_weights()),
+        }
+    }
+}
+
+/// A generated schema, with policies, entities and requests conforming to it
+#[derive(Debug)]
+struct Workload {
+    schema: SchemaFragment,
+    policies: PolicySet,
+    entities: Entities,
+    requests: Vec<Request>,
+}
+
+impl Workload {
+    fn generate(
+        settings: &ABACSettings,
+        args: &Args,
+        u: &mut arbitrary::Unstructured<'_>,
+    ) -> anyhow::Result<Self> {
+        let schema = Schema::arbitrary(settings.clone(), u)?;
+        let hierarchy = schema.arbitrary_hierarchy(u)?;
+        let mut policies = PolicySet::new();
+        for i in 0..args.policies {
+            let policy = Policy::from(schema.arbitrary_policy(&hierarchy, u)?);
+            policies.add(policy.new_id(PolicyId::new(format!("policy{i}"))))?;
+        }
+        let requests = (0..args.requests)
+            .map(|_| Ok(Request::from(schema.arbitrary_request(&hierarchy, u)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let entities = Entities::try_from(hierarchy).map_err(|e| anyhow!(e))?;
+        let schema = SchemaFragment::try_from(schema)?;
+        // Only keep workloads whose schema and entities are valid
+        let validated = cedar_policy::Schema::from_schema_fragments([schema.clone()])?;
+        let entities = Entities::from_entities(entities.iter().cloned(), Some(&validated))?;
+        Ok(Self {
+            schema,
+            policies,
+            entities,
+            requests,
+        })
+    }
+
+    fn write(&self, dir: &Path, name: &str) -> anyhow::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let path = |ext: &str| dir.join(format!("{name}.{ext}"));
+        std::fs::write(path("cedarschema"), self.schema.to_cedarschema()?)?;
+        std::fs::write(path("cedar"), self.policies.to_string())?;
+        self.entities
+            .write_to_json(std::fs::File::create(path("entities.json"))?)?;
+        let requests = self
+            .requests
+            .iter()
+            .map(request_to_json)
+            .collect::<Vec<_>>();
+        std::fs::write(
+            path("requests.json"),
+            serde_json::to_string_pretty(&requests)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Encode `request` as JSON, in the format of the integration tests
+fn request_to_json(request: &Request) -> Value {
+    let uid = |uid: Option<&cedar_policy::EntityUid>| {
+        let uid = uid.expect("generated requests should be concrete");
+        serde_json::to_value(TypeAndId::from(uid.as_ref())).expect("failed to serialize euid")
+    };
+    let context = request
+        .context()
+        .expect("generated requests should be concrete")
+        .clone()
+        .into_iter()
+        .map(|(k, v)| {
+            let v = v
+                .as_ref()
+                .to_natural_json()
+                .expect("failed to serialize context");
+            (k, v)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    json!({
+        "principal": uid(request.principal()),
+        "action": uid(request.action()),
+        "resource": uid(request.resource()),
+        "context": context,
+    })
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let file_settings = match &args.settings {
+        Some(path) => {
+            let file = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            toml::from_str(&file).with_context(|| format!("failed to parse {}", path.display()))?
+        }
+        None => SettingsOverrides::default(),
+    };
+    let settings = args.overrides.clone().or(file_settings).into_settings();
+
+    // Each failed attempt draws fresh bytes from the same seeded generator, so
+    // the output only depends on the seed and the settings
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let mut bytes = vec![0; args.bytes];
+    for attempt in 1..=MAX_ATTEMPTS {
+        rng.fill_bytes(&mut bytes);
+        let mut u = arbitrary::Unstructured::new(&bytes);
+        match Workload::generate(&settings, &args, &mut u) {
+            Ok(workload) => {
+                workload.write(&args.out_dir, &args.name)?;
+                eprintln!(
+                    "wrote {} to {} (attempt {attempt})",
+                    args.name,
+                    args.out_dir.display()
+                );
+                return Ok(());
+            }
+            Err(err) => eprintln!("attempt {attempt} failed: {err}"),
+        }
+    }
+    Err(anyhow!("no workload generated in {MAX_ATTEMPTS} attempts"))
+}
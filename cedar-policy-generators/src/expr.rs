@@ -20,7 +20,7 @@ use crate::abac::{
 use crate::collections::HashMap;
 use crate::err::{while_doing, Error, Result};
 use crate::hierarchy::{generate_uid_with_type, Hierarchy};
-use crate::schema::{attrs_from_attrs_or_context, uid_for_action_name, Schema};
+use crate::schema::{attrs_from_attrs_or_context, Schema};
 use crate::settings::ABACSettings;
 use crate::size_hint_utils::{size_hint_for_choose, size_hint_for_range, size_hint_for_ratio};
 use crate::{accum, gen, gen_inner, uniform};
@@ -64,6 +64,7 @@ impl ExprGenerator<'_> {
             // no recursion allowed: just generate a literal
             self.generate_literal_or_var(u)
         } else {
+            let weights = self.settings.expr_weights;
             gen!(u,
             2 => {
                 // a literal or variable
@@ -83,71 +84,71 @@ impl ExprGenerator<'_> {
             1 => {
                 // any other expression
                 gen!(u,
-                    2 => Ok(ast::Expr::ite(
+                    2 * weights.conditionals => Ok(ast::Expr::ite(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    2 => Ok(ast::Expr::and(
+                    2 * weights.conditionals => Ok(ast::Expr::and(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    2 => Ok(ast::Expr::or(
+                    2 * weights.conditionals => Ok(ast::Expr::or(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::less(
+                    weights.arithmetic => Ok(ast::Expr::less(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::lesseq(
+                    weights.arithmetic => Ok(ast::Expr::lesseq(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::greater(
+                    weights.arithmetic => Ok(ast::Expr::greater(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::greatereq(
+                    weights.arithmetic => Ok(ast::Expr::greatereq(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::add(
+                    weights.arithmetic => Ok(ast::Expr::add(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::sub(
+                    weights.arithmetic => Ok(ast::Expr::sub(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::mul(
+                    weights.arithmetic => Ok(ast::Expr::mul(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => {
+                    weights.arithmetic => {
                         // negation expression
                         Ok(ast::Expr::neg(self.generate_expr(max_depth - 1, u)?))
                     },
-                    6 => Ok(ast::Expr::is_in(
+                    6 * weights.hierarchy => Ok(ast::Expr::is_in(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::contains(
+                    weights.sets => Ok(ast::Expr::contains(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::contains_all(
+                    weights.sets => Ok(ast::Expr::contains_all(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::contains_any(
+                    weights.sets => Ok(ast::Expr::contains_any(
                         self.generate_expr(max_depth - 1, u)?,
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    1 => Ok(ast::Expr::is_empty(
+                    weights.sets => Ok(ast::Expr::is_empty(
                         self.generate_expr(max_depth - 1, u)?,
                     )),
-                    2 => {
+                    2 * weights.like => {
                         if self.settings.enable_like {
                             Ok(ast::Expr::like(
                                 self.generate_expr(max_depth - 1, u)?,
@@ -157,13 +158,13 @@ impl ExprGenerator<'_> {
                             Err(Error::LikeDisabled)
                         }
                     },
-                    1 => {
+                    weights.hierarchy => {
                             Ok(ast::Expr::is_entity_type(
                                 self.generate_expr(max_depth - 1, u)?,
                                 u.choose(&self.schema.entity_types)?.clone(),
                             ))
                     },
-                    1 => {
+                    weights.sets => {
                         let mut l = Vec::new();
                         u.arbitrary_loop(Some(0), Some(self.settings.max_width as u32), |u| {
                             l.push(self.generate_expr(max_depth - 1, u)?);
@@ -183,7 +184,7 @@ impl ExprGenerator<'_> {
                         })?;
                         Ok(ast::Expr::record(r).expect("can't have duplicate keys because `r` was already a HashMap"))
                     },
-                    1 => {
+                    weights.extensions => {
                         if !self.settings.enable_extensions {
                             return Err(Error::ExtensionsDisabled);
                         };
@@ -211,7 +212,7 @@ impl ExprGenerator<'_> {
                                 .collect::<Result<_>>()?,
                         ))
                     },
-                    7 => {
+                    7 * weights.attributes => {
                         let attr_name = gen!(u,
                             1 => {
                                 let s: String = u.arbitrary()?;
@@ -237,7 +238,7 @@ impl ExprGenerator<'_> {
                         };
                         Ok(ast::Expr::get_attr(e, attr_name))
                     },
-                    4 => {
+                    4 * weights.attributes => {
                         let attr_name = uniform!(u,
                            self.schema.arbitrary_attr(u)?,
                             {
@@ -249,7 +250,7 @@ impl ExprGenerator<'_> {
                             attr_name,
                         ))
                     },
-                    4 => {
+                    4 * weights.tags => {
                         let tag_name = uniform!(u,
                             self.generate_expr(max_depth - 1, u)?,
                             ast::Expr::val(self.schema.arbitrary_attr(u)?)
@@ -282,6 +283,7 @@ impl ExprGenerator<'_> {
                 None => Ok(ast::Expr::unknown(ast::Unknown::new_untyped(name))),
             }
         } else {
+            let weights = self.settings.expr_weights;
             match target_type {
                 Type::Bool => {
                     if max_depth == 0 || u.len() < 10 {
@@ -323,7 +325,7 @@ impl ExprGenerator<'_> {
                             u,
                         )?)),
                         // if-then-else expression, where both arms are bools
-                        5 => Ok(ast::Expr::ite(
+                        5 * weights.conditionals => Ok(ast::Expr::ite(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -341,7 +343,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // && expression
-                        5 => Ok(ast::Expr::and(
+                        5 * weights.conditionals => Ok(ast::Expr::and(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -354,7 +356,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // || expression
-                        5 => Ok(ast::Expr::or(
+                        5 * weights.conditionals => Ok(ast::Expr::or(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -367,7 +369,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // < expression
-                        1 => Ok(ast::Expr::less(
+                        weights.arithmetic => Ok(ast::Expr::less(
                             self.generate_expr_for_type(
                                 &Type::long(),
                                 max_depth - 1,
@@ -380,7 +382,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // <= expression
-                        1 => Ok(ast::Expr::lesseq(
+                        weights.arithmetic => Ok(ast::Expr::lesseq(
                             self.generate_expr_for_type(
                                 &Type::long(),
                                 max_depth - 1,
@@ -393,7 +395,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // > expression
-                        1 => Ok(ast::Expr::greater(
+                        weights.arithmetic => Ok(ast::Expr::greater(
                             self.generate_expr_for_type(
                                 &Type::long(),
                                 max_depth - 1,
@@ -406,7 +408,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // >= expression
-                        1 => Ok(ast::Expr::greatereq(
+                        weights.arithmetic => Ok(ast::Expr::greatereq(
                             self.generate_expr_for_type(
                                 &Type::long(),
                                 max_depth - 1,
@@ -419,7 +421,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // in expression, non-set form
-                        11 => {
+                        11 * weights.hierarchy => {
                             let ety1 = u.choose(self.schema.entity_types())?;
                             let ety2 = u.choose(self.schema.entity_types())?;
                             Ok(ast::Expr::is_in(
@@ -435,7 +437,7 @@ impl ExprGenerator<'_> {
                             )?,
                         ))},
                         // in expression, set form
-                        2 => {
+                        2 * weights.hierarchy => {
                             let ety1 = u.choose(self.schema.entity_types())?;
                             let ety2 = u.choose(self.schema.entity_types())?;
                             Ok(ast::Expr::is_in(
@@ -451,7 +453,7 @@ impl ExprGenerator<'_> {
                             )?,
                         ))},
                         // contains() on a set
-                        2 => {
+                        2 * weights.sets => {
                             let element_ty = u.arbitrary()?;
                             let element = self.generate_expr_for_type(
                                 &element_ty,
@@ -466,7 +468,7 @@ impl ExprGenerator<'_> {
                             Ok(ast::Expr::contains(set, element))
                         },
                         // containsAll()
-                        1 => Ok(ast::Expr::contains_all(
+                        weights.sets => Ok(ast::Expr::contains_all(
                             // doesn't require the input sets to have the same element type
                             self.generate_expr_for_type(
                                 &Type::set_of(u.arbitrary()?),
@@ -480,7 +482,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // containsAny()
-                        1 => Ok(ast::Expr::contains_any(
+                        weights.sets => Ok(ast::Expr::contains_any(
                             // doesn't require the input sets to have the same element type
                             self.generate_expr_for_type(
                                 &Type::set_of(u.arbitrary()?),
@@ -494,7 +496,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // isEmpty()
-                        1 => Ok(ast::Expr::is_empty(
+                        weights.sets => Ok(ast::Expr::is_empty(
                             self.generate_expr_for_type(
                                 &Type::set_of(u.arbitrary()?),
                                 max_depth - 1,
@@ -502,7 +504,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // like
-                        2 => {
+                        2 * weights.like => {
                             if self.settings.enable_like {
                                 Ok(ast::Expr::like(
                                     self.generate_expr_for_type(
@@ -517,7 +519,7 @@ impl ExprGenerator<'_> {
                             }
                         },
                         // is
-                        2 => {
+                        2 * weights.hierarchy => {
                             let ety_l = u.choose(&self.schema.entity_types)?.clone();
                             let ety_r = u.choose(&self.schema.entity_types)?.clone();
                                 Ok(ast::Expr::is_entity_type(
@@ -530,13 +532,13 @@ impl ExprGenerator<'_> {
                                 ))
                         },
                         // extension function that returns bool
-                        2 => self.generate_ext_func_call_for_type(
+                        2 * weights.extensions => self.generate_ext_func_call_for_type(
                             &Type::bool(),
                             max_depth - 1,
                             u,
                         ),
                         // getting an attr (on an entity) with type bool
-                        1 => {
+                        weights.attributes => {
                             let (entity_type, attr_name) = self.schema.arbitrary_attr_for_type(
                                 &Type::Bool,
                                 u,
@@ -551,7 +553,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an attr (on a record) with type bool
-                        1 => {
+                        weights.attributes => {
                             let attr_name = self.constant_pool.arbitrary_string_constant(u)?;
                             Ok(ast::Expr::get_attr(
                                 self.generate_expr_for_type(
@@ -566,7 +568,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an entity tag with type bool
-                        1 => {
+                        weights.tags => {
                             let entity_type = self.schema.arbitrary_entity_type_with_tag_type(
                                 &Type::Bool,
                                 u,
@@ -585,7 +587,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // has expression on an entity, for a (possibly optional) attribute the entity does have in the schema
-                        2 => {
+                        2 * weights.attributes => {
                             let (entity_name, entity_type) = self
                                 .schema
                                 .schema
@@ -607,7 +609,7 @@ impl ExprGenerator<'_> {
                             let attr_name = SmolStr::clone(u.choose(&attr_names)?);
                             Ok(ast::Expr::has_attr(
                                 self.generate_expr_for_type(
-                                    &Type::Entity(ast::EntityType::from(ast::Name::from(entity_name.clone())).qualify_with(self.schema.namespace())),
+                                    &Type::Entity(self.schema.declared_entity_type(entity_name)),
                                     max_depth - 1,
                                     u,
                                 )?,
@@ -615,7 +617,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // has expression on an entity, for an arbitrary attribute name
-                        1 => Ok(ast::Expr::has_attr(
+                        weights.attributes => Ok(ast::Expr::has_attr(
                             self.generate_expr_for_type(
                                 &Type::Entity(u.choose(self.schema.entity_types())?.clone()),
                                 max_depth - 1,
@@ -624,7 +626,7 @@ impl ExprGenerator<'_> {
                             self.constant_pool.arbitrary_string_constant(u)?,
                         )),
                         // hasTag expression on an entity, for an arbitrary tag name
-                        1 => Ok(ast::Expr::has_tag(
+                        weights.tags => Ok(ast::Expr::has_tag(
                             self.generate_expr_for_type(
                                 &Type::Entity(u.choose(self.schema.entity_types())?.clone()),
                                 max_depth - 1,
@@ -637,7 +639,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // has expression on a record
-                        2 => Ok(ast::Expr::has_attr(
+                        2 * weights.attributes => Ok(ast::Expr::has_attr(
                             self.generate_expr_for_type(
                                 &Type::arbitrary_record(u, self.settings.max_width)?,
                                 max_depth - 1,
@@ -662,7 +664,7 @@ impl ExprGenerator<'_> {
                             self.constant_pool.arbitrary_int_constant(u)?,
                         )),
                         // if-then-else expression, where both arms are longs
-                        5 => Ok(ast::Expr::ite(
+                        5 * weights.conditionals => Ok(ast::Expr::ite(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -680,7 +682,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // + expression
-                        1 => Ok(ast::Expr::add(
+                        weights.arithmetic => Ok(ast::Expr::add(
                             self.generate_expr_for_type(
                                 &Type::long(),
                                 max_depth - 1,
@@ -693,7 +695,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // - expression
-                        1 => Ok(ast::Expr::sub(
+                        weights.arithmetic => Ok(ast::Expr::sub(
                             self.generate_expr_for_type(
                                 &Type::long(),
                                 max_depth - 1,
@@ -706,7 +708,7 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // * expression
-                        1 => Ok(ast::Expr::mul(
+                        weights.arithmetic => Ok(ast::Expr::mul(
                             self.generate_expr_for_type(
                                 &Type::long(),
                                 max_depth - 1,
@@ -719,19 +721,19 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // negation expression
-                        1 => Ok(ast::Expr::neg(self.generate_expr_for_type(
+                        weights.arithmetic => Ok(ast::Expr::neg(self.generate_expr_for_type(
                             &Type::long(),
                             max_depth - 1,
                             u,
                         )?)),
                         // extension function that returns a long
-                        1 => self.generate_ext_func_call_for_type(
+                        weights.extensions => self.generate_ext_func_call_for_type(
                             &Type::long(),
                             max_depth - 1,
                             u,
                         ),
                         // getting an attr (on an entity) with type long
-                        4 => {
+                        4 * weights.attributes => {
                             let (entity_type, attr_name) = self.schema.arbitrary_attr_for_type(
                                 &Type::Long,
                                 u,
@@ -746,7 +748,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an attr (on a record) with type long
-                        4 => {
+                        4 * weights.attributes => {
                             let attr_name = self.constant_pool.arbitrary_string_constant(u)?;
                             Ok(ast::Expr::get_attr(
                                 self.generate_expr_for_type(
@@ -761,7 +763,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an entity tag with type long
-                        3 => {
+                        3 * weights.tags => {
                             let entity_type = self.schema.arbitrary_entity_type_with_tag_type(
                                 &Type::Long,
                                 u,
@@ -796,7 +798,7 @@ impl ExprGenerator<'_> {
                             self.constant_pool.arbitrary_string_constant(u)?,
                         )),
                         // if-then-else expression, where both arms are strings
-                        5 => Ok(ast::Expr::ite(
+                        5 * weights.conditionals => Ok(ast::Expr::ite(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -814,13 +816,13 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // extension function that returns a string
-                        1 => self.generate_ext_func_call_for_type(
+                        weights.extensions => self.generate_ext_func_call_for_type(
                             &Type::string(),
                             max_depth - 1,
                             u,
                         ),
                         // getting an attr (on an entity) with type string
-                        4 => {
+                        4 * weights.attributes => {
                             let (entity_type, attr_name) = self.schema.arbitrary_attr_for_type(
                                 &Type::String,
                                 u,
@@ -835,7 +837,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an attr (on a record) with type string
-                        4 => {
+                        4 * weights.attributes => {
                             let attr_name = self.constant_pool.arbitrary_string_constant(u)?;
                             Ok(ast::Expr::get_attr(
                                 self.generate_expr_for_type(
@@ -850,7 +852,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an entity tag with type string
-                        3 => {
+                        3 * weights.tags => {
                             let entity_type = self.schema.arbitrary_entity_type_with_tag_type(
                                 &Type::String,
                                 u,
@@ -894,7 +896,7 @@ impl ExprGenerator<'_> {
                             Ok(ast::Expr::set(l))
                         },
                         // if-then-else expression, where both arms are (appropriate) sets
-                        2 => Ok(ast::Expr::ite(
+                        2 * weights.conditionals => Ok(ast::Expr::ite(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -912,13 +914,13 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // extension function that returns an (appropriate) set
-                        1 => self.generate_ext_func_call_for_type(
+                        weights.extensions => self.generate_ext_func_call_for_type(
                             target_type,
                             max_depth - 1,
                             u,
                         ),
                         // getting an attr (on an entity) with the appropriate set type
-                        4 => {
+                        4 * weights.attributes => {
                             let (entity_type, attr_name) =
                                 self.schema.arbitrary_attr_for_type(target_type, u)?;
                             Ok(ast::Expr::get_attr(
@@ -931,7 +933,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an attr (on a record) with the appropriate set type
-                        3 => {
+                        3 * weights.attributes => {
                             let attr_name: SmolStr =
                                 self.constant_pool.arbitrary_string_constant(u)?;
                             Ok(ast::Expr::get_attr(
@@ -944,7 +946,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an entity tag with the appropriate set type
-                        3 => {
+                        3 * weights.tags => {
                             let entity_type = self.schema.arbitrary_entity_type_with_tag_type(
                                 target_type,
                                 u,
@@ -985,7 +987,7 @@ impl ExprGenerator<'_> {
                             Ok(ast::Expr::record(r).expect("can't have duplicate keys because `r` was already a HashMap"))
                         },
                         // if-then-else expression, where both arms are records
-                        2 => Ok(ast::Expr::ite(
+                        2 * weights.conditionals => Ok(ast::Expr::ite(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -1003,13 +1005,13 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // extension function that returns a record
-                        1 => self.generate_ext_func_call_for_type(
+                        weights.extensions => self.generate_ext_func_call_for_type(
                             target_type,
                             max_depth - 1,
                             u,
                         ),
                         // getting an attr (on an entity) with type record
-                        4 => {
+                        4 * weights.attributes => {
                             let (entity_type, attr_name) = self.schema.arbitrary_attr_for_type(
                                 &Type::Record(BTreeMap::default()),
                                 u,
@@ -1024,7 +1026,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an attr (on a record) with type record
-                        3 => {
+                        3 * weights.attributes => {
                             let attr_name = self.constant_pool.arbitrary_string_constant(u)?;
                             Ok(ast::Expr::get_attr(
                                 self.generate_expr_for_type(
@@ -1039,7 +1041,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an entity tag with type record
-                        3 => {
+                        3 * weights.tags => {
                             let entity_type = self.schema.arbitrary_entity_type_with_tag_type(
                                 target_type,
                                 u,
@@ -1080,7 +1082,7 @@ impl ExprGenerator<'_> {
                         // `resource`
                         6 => Ok(ast::Expr::var(ast::Var::Resource)),
                         // if-then-else expression, where both arms are entities
-                        2 => Ok(ast::Expr::ite(
+                        2 * weights.conditionals => Ok(ast::Expr::ite(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -1098,13 +1100,13 @@ impl ExprGenerator<'_> {
                             )?,
                         )),
                         // extension function that returns an entity
-                        1 => self.generate_ext_func_call_for_type(
+                        weights.extensions => self.generate_ext_func_call_for_type(
                             target_type,
                             max_depth - 1,
                             u,
                         ),
                         // getting an attr (on an entity) with type entity
-                        6 => {
+                        6 * weights.attributes => {
                             let (entity_type, attr_name) = self.schema.arbitrary_attr_for_type(
                                 &Type::Entity(u.choose(&self.schema.entity_types)?.clone()),
                                 u,
@@ -1119,7 +1121,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an attr (on a record) with type entity
-                        5 => {
+                        5 * weights.attributes => {
                             let attr_name = self.constant_pool.arbitrary_string_constant(u)?;
                             Ok(ast::Expr::get_attr(
                                 self.generate_expr_for_type(
@@ -1136,7 +1138,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an entity tag with type entity
-                        5 => {
+                        5 * weights.tags => {
                             let entity_type = self.schema.arbitrary_entity_type_with_tag_type(
                                 &Type::Entity(u.choose(&self.schema.entity_types)?.clone()),
                                 u,
@@ -1172,7 +1174,7 @@ impl ExprGenerator<'_> {
                     } else {
                         gen!(u,
                         // if-then-else expression, where both arms are extension types
-                        2 => Ok(ast::Expr::ite(
+                        2 * weights.conditionals => Ok(ast::Expr::ite(
                             self.generate_expr_for_type(
                                 &Type::bool(),
                                 max_depth - 1,
@@ -1189,14 +1191,15 @@ impl ExprGenerator<'_> {
                                 u,
                             )?,
                         )),
-                        // extension function that returns an extension type
+                        // extension function that returns an extension type (not scaled by
+                        // `weights`, as it is the only way to build one)
                         9 => self.generate_ext_func_call_for_type(
                             target_type,
                             max_depth - 1,
                             u,
                         ),
                         // getting an attr (on an entity) with extension type
-                        2 => {
+                        2 * weights.attributes => {
                             let (entity_type, attr_name) = self.schema.arbitrary_attr_for_type(
                                 target_type,
                                 u,
@@ -1211,7 +1214,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an attr (on a record) with extension type
-                        2 => {
+                        2 * weights.attributes => {
                             let attr_name = self.constant_pool.arbitrary_string_constant(u)?;
                             Ok(ast::Expr::get_attr(
                                 self.generate_expr_for_type(
@@ -1226,7 +1229,7 @@ impl ExprGenerator<'_> {
                             ))
                         },
                         // getting an entity tag with extension type
-                        5 => {
+                        5 * weights.tags => {
                             let entity_type = self.schema.arbitrary_entity_type_with_tag_type(
                                 target_type,
                                 u,
@@ -1688,10 +1691,7 @@ impl ExprGenerator<'_> {
         let action = u
             .choose(&self.schema.actions_eids)
             .map_err(|e| while_doing("choosing an action".into(), e))?;
-        Ok(uid_for_action_name(
-            self.schema.namespace.as_ref(),
-            action.clone(),
-        ))
+        Ok(self.schema.action_uid(action.clone()))
     }
     /// size hint for arbitrary_action_uid()
     pub fn arbitrary_action_uid_size_hint(_depth: usize) -> (usize, Option<usize>) {
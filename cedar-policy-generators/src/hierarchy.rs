@@ -14,11 +14,12 @@
  * limitations under the License.
  */
 
-use crate::abac::Type;
+use crate::abac::{AttrValue, ConstantPool, Type};
 use crate::collections::{HashMap, HashSet};
 use crate::err::{while_doing, Error, Result};
 use crate::schema::Schema;
 use crate::size_hint_utils::{size_hint_for_choose, size_hint_for_ratio};
+use crate::{accum, gen, gen_inner, uniform};
 use arbitrary::{Arbitrary, Unstructured};
 use cedar_policy_core::ast::{self, Eid, Entity, EntityUID};
 use cedar_policy_core::entities::{Entities, NoEntitiesSchema, TCComputation};
@@ -395,10 +396,131 @@ pub enum NumEntities {
 pub enum AttributesMode {
     /// No attributes or tags (RBAC)
     NoAttributesOrTags,
-    // For now, we don't support any other modes. Generating attributes is only
-    // supported in schema-based mode. If you want arbitrary attributes without
-    // a schema, consider first generating an arbitrary schema and then using
-    // schema-based mode.
+    /// Attributes (and optionally tags) with arbitrary names and values of
+    /// mixed types, which don't conform to any schema. Entity-typed values
+    /// usually refer to entities in the hierarchy.
+    ///
+    /// For attributes that conform to a schema, use schema-based mode instead.
+    Arbitrary {
+        /// Maximum number of attributes (and of tags) of each entity
+        max_attrs: usize,
+        /// Whether to generate tags as well as attributes
+        with_tags: bool,
+    },
+}
+
+/// Maximum depth of the attribute values generated in
+/// `AttributesMode::Arbitrary`, i.e., of nested sets and records
+const MAX_ARBITRARY_ATTR_DEPTH: usize = 3;
+
+/// Generate up to `max_attrs` attributes (or tags) with arbitrary names and
+/// values of mixed types, for `AttributesMode::Arbitrary`
+fn arbitrary_attrs_without_schema(
+    hierarchy: &Hierarchy,
+    constant_pool: &ConstantPool,
+    extensions: &Extensions<'_>,
+    max_attrs: usize,
+    u: &mut Unstructured<'_>,
+) -> Result<HashMap<SmolStr, ast::RestrictedExpr>> {
+    let mut attrs = HashMap::new();
+    u.arbitrary_loop(None, Some(max_attrs as u32), |u| {
+        let attr_name: SmolStr = u.arbitrary()?;
+        let attr_val = arbitrary_attr_value_without_schema(
+            hierarchy,
+            constant_pool,
+            extensions,
+            MAX_ARBITRARY_ATTR_DEPTH,
+            u,
+        )?;
+        attrs.insert(attr_name, attr_val.into());
+        Ok(std::ops::ControlFlow::Continue(()))
+    })?;
+    Ok(attrs)
+}
+
+/// Generate an attribute value of an arbitrary type. Sets may be
+/// heterogeneous, and extension values are only generated for the extensions
+/// in `extensions`.
+fn arbitrary_attr_value_without_schema(
+    hierarchy: &Hierarchy,
+    constant_pool: &ConstantPool,
+    extensions: &Extensions<'_>,
+    max_depth: usize,
+    u: &mut Unstructured<'_>,
+) -> Result<AttrValue> {
+    if max_depth == 0 {
+        // no recursion allowed: just generate a primitive value
+        return Ok(uniform!(
+            u,
+            AttrValue::BoolLit(u.arbitrary()?),
+            AttrValue::IntLit(constant_pool.arbitrary_int_constant(u)?),
+            AttrValue::StringLit(constant_pool.arbitrary_string_constant(u)?)
+        ));
+    }
+    Ok(gen!(u,
+        2 => AttrValue::BoolLit(u.arbitrary()?),
+        3 => AttrValue::IntLit(constant_pool.arbitrary_int_constant(u)?),
+        3 => AttrValue::StringLit(constant_pool.arbitrary_string_constant(u)?),
+        3 => AttrValue::UIDLit(if hierarchy.uids().is_empty() {
+            u.arbitrary()?
+        } else {
+            hierarchy.arbitrary_uid(u)?
+        }),
+        2 => {
+            let (fn_name, arg) = uniform!(u,
+                ("ip", constant_pool.arbitrary_ip_str(u)?),
+                ("decimal", constant_pool.arbitrary_decimal_str(u)?),
+                ("datetime", constant_pool.arbitrary_datetime_str(u)?),
+                ("duration", constant_pool.arbitrary_duration_str(u)?)
+            );
+            let fn_name =
+                ast::Name::parse_unqualified_name(fn_name).expect("should be a valid identifier");
+            match extensions.func(&fn_name) {
+                Ok(func) if func.call(&[ast::Value::from(arg.clone())]).is_ok() => {
+                    AttrValue::ExtFuncCall {
+                        fn_name,
+                        args: vec![AttrValue::StringLit(arg)],
+                    }
+                }
+                // the extension isn't active, or the (mutated) argument isn't
+                // valid for it and the entity couldn't be constructed, so keep
+                // the argument as a string
+                _ => AttrValue::StringLit(arg),
+            }
+        },
+        1 => {
+            let mut l = Vec::new();
+            u.arbitrary_loop(None, Some(MAX_ARBITRARY_ATTR_DEPTH as u32), |u| {
+                l.push(arbitrary_attr_value_without_schema(
+                    hierarchy,
+                    constant_pool,
+                    extensions,
+                    max_depth - 1,
+                    u,
+                )?);
+                Ok(std::ops::ControlFlow::Continue(()))
+            })?;
+            AttrValue::Set(l)
+        },
+        1 => {
+            let mut r = HashMap::new();
+            u.arbitrary_loop(None, Some(MAX_ARBITRARY_ATTR_DEPTH as u32), |u| {
+                let attr_name: SmolStr = u.arbitrary()?;
+                r.insert(
+                    attr_name,
+                    arbitrary_attr_value_without_schema(
+                        hierarchy,
+                        constant_pool,
+                        extensions,
+                        max_depth - 1,
+                        u,
+                    )?,
+                );
+                Ok(std::ops::ControlFlow::Continue(()))
+            })?;
+            AttrValue::Record(r)
+        }
+    ))
 }
 
 /// Helper function that generates a new UID with the given type.
@@ -418,6 +540,88 @@ pub(crate) fn generate_uid_with_type(
     Ok(ast::EntityUID::from_components(ty, eid, None))
 }
 
+/// Generate attributes for an entity of type `ty` conforming to `schema`.
+/// `hierarchy` is the pool of UIDs that entity-typed attribute values (usually)
+/// refer to.
+pub(crate) fn arbitrary_attrs_for_schema(
+    schema: &Schema,
+    hierarchy: &Hierarchy,
+    ty: &ast::EntityType,
+    u: &mut Unstructured<'_>,
+) -> Result<HashMap<SmolStr, ast::RestrictedExpr>> {
+    let mut attrs = HashMap::new();
+    let Some((entity_attrs, additional_attrs)) = schema.attribute_by_entity_type(ty) else {
+        unreachable!("in schema-based mode, this should always be Some")
+    };
+    if additional_attrs {
+        // maybe add some additional attributes with arbitrary types
+        u.arbitrary_loop(None, Some(schema.settings.max_width as u32), |u| {
+            let attr_type = if schema.settings.enable_extensions {
+                u.arbitrary()?
+            } else {
+                Type::arbitrary_nonextension(u)?
+            };
+            let attr_name: String = u.arbitrary()?;
+            attrs.insert(
+                attr_name.into(),
+                schema
+                    .exprgenerator(Some(hierarchy))
+                    .generate_attr_value_for_type(&attr_type, schema.settings.max_depth, u)?
+                    .into(),
+            );
+            Ok(std::ops::ControlFlow::Continue(()))
+        })?;
+    }
+    for (attr, ty) in entity_attrs {
+        // now add the actual optional and required attributes, with the
+        // correct types.
+        // Doing this second ensures that we overwrite any "additional"
+        // attributes so that they definitely have the required type, in
+        // case we got a name collision between an explicitly specified
+        // attribute and one of the "additional" ones we added.
+        if ty.required || u.ratio::<u8>(1, 2)? {
+            let attr_val = schema
+                .exprgenerator(Some(hierarchy))
+                .generate_attr_value_for_type(&ty.ty, schema.settings.max_depth, u)?;
+            attrs.insert(
+                attr.parse()
+                    .expect("all attribute names in the schema should be valid identifiers"),
+                attr_val.into(),
+            );
+        }
+    }
+    Ok(attrs)
+}
+
+/// Generate tags for an entity of type `ty` conforming to `schema`, like
+/// [`arbitrary_attrs_for_schema()`]
+pub(crate) fn arbitrary_tags_for_schema(
+    schema: &Schema,
+    hierarchy: &Hierarchy,
+    ty: &ast::EntityType,
+    u: &mut Unstructured<'_>,
+) -> Result<HashMap<SmolStr, ast::RestrictedExpr>> {
+    let mut tags = HashMap::new();
+    let Some(tag_type) = schema.tag_type_by_entity_type(ty) else {
+        unreachable!("in schema-based mode, this should always be Some")
+    };
+    if let Some(tag_type) = tag_type {
+        // add tags with the type `tag_type`
+        u.arbitrary_loop(None, Some(schema.settings.max_width as u32), |u| {
+            let tag_key: SmolStr = u.arbitrary()?;
+            tags.insert(
+                tag_key,
+                schema
+                    .exprgenerator(Some(hierarchy))
+                    .generate_attr_value_for_type(&tag_type, schema.settings.max_depth, u)?
+                    .into(),
+            );
+            Ok(std::ops::ControlFlow::Continue(()))
+        })?;
+    }
+    Ok(tags)
+}
+
 impl HierarchyGenerator<'_, '_> {
     /// Generate a `Hierarchy` according to the specified parameters
     pub fn generate(&mut self) -> Result<Hierarchy> {
@@ -480,6 +684,13 @@ impl HierarchyGenerator<'_, '_> {
             })
             .collect::<Result<HashMap<ast::EntityType, HashSet<ast::EntityUID>>>>()?;
         let hierarchy_no_attrs = Hierarchy::from_uids_by_type(uids_by_type);
+        // constants for the attribute values of `AttributesMode::Arbitrary`
+        let constant_pool: Option<ConstantPool> = match &self.mode {
+            HierarchyGeneratorMode::Arbitrary {
+                attributes_mode: AttributesMode::Arbitrary { .. },
+            } => Some(self.u.arbitrary()?),
+            _ => None,
+        };
         // now create an entity hierarchy composed of those entity UIDs
         let entities = hierarchy_no_attrs
             .entities()
@@ -492,9 +703,9 @@ impl HierarchyGenerator<'_, '_> {
                     HierarchyGeneratorMode::SchemaBased { schema } => {
                         for ty in schema.allowed_parent_typenames(name).unwrap() {
                             for possible_parent_uid in
-                            // `uids_for_type` only prevent cycles resulting from self-loops in the entity types graph
-                            // It should be very unlikely where loops involving multiple entity types occur in the schemas
-                            hierarchy_no_attrs.uids_for_type(&ty, uid)
+                                // `uids_for_type` only prevent cycles resulting from self-loops in the entity types graph
+                                // It should be very unlikely where loops involving multiple entity types occur in the schemas
+                                hierarchy_no_attrs.uids_for_type(&ty, uid)
                             {
                                 if self.u.ratio::<u8>(1, 2)? {
                                     parents.insert(possible_parent_uid.clone());
@@ -522,108 +733,49 @@ impl HierarchyGenerator<'_, '_> {
                         assert!(!parents.contains(uid));
                     }
                 }
-                // generate appropriate attributes for this entity
-                let mut attrs = HashMap::new();
-                match &self.mode {
+                // generate appropriate attributes and tags for this entity
+                let (attrs, tags) = match &self.mode {
                     HierarchyGeneratorMode::Arbitrary {
                         attributes_mode: AttributesMode::NoAttributesOrTags,
                     } => {
-                        // don't add any attributes
+                        // don't add any attributes or tags
+                        (HashMap::new(), HashMap::new())
                     }
-                    HierarchyGeneratorMode::SchemaBased { schema } => {
-                        // add attributes
-                        let Some ((entity_attrs, additional_attrs)) = schema.attribute_by_entity_type(name) else {
-                            unreachable!("in schema-based mode, this should always be Some")
-                        };
-                                if additional_attrs {
-                                    // maybe add some additional attributes with arbitrary types
-                                    self.u.arbitrary_loop(
-                                        None,
-                                        Some(schema.settings.max_width as u32),
-                                        |u| {
-                                            let attr_type = if schema.settings.enable_extensions {
-                                                u.arbitrary()?
-                                            } else {
-                                                Type::arbitrary_nonextension(u)?
-                                            };
-                                            let attr_name: String = u.arbitrary()?;
-                                            attrs.insert(
-                                                attr_name.into(),
-                                                schema
-                                                    .exprgenerator(Some(&hierarchy_no_attrs))
-                                                    .generate_attr_value_for_type(
-                                                        &attr_type,
-                                                        schema.settings.max_depth,
-                                                        u,
-                                                    )?
-                                                    .into(),
-                                            );
-                                            Ok(std::ops::ControlFlow::Continue(()))
-                                        },
-                                    )?;
-                                }
-                                for (attr, ty) in entity_attrs {
-                                    // now add the actual optional and required attributes, with the
-                                    // correct types.
-                                    // Doing this second ensures that we overwrite any "additional"
-                                    // attributes so that they definitely have the required type, in
-                                    // case we got a name collision between an explicitly specified
-                                    // attribute and one of the "additional" ones we added.
-                                    if ty.required || self.u.ratio::<u8>(1, 2)? {
-                                        let attr_val = schema
-                                            .exprgenerator(Some(&hierarchy_no_attrs))
-                                            .generate_attr_value_for_type(
-                                              &ty.ty,
-                                                schema.settings.max_depth,
-                                                self.u,
-                                            )?;
-                                        attrs.insert(
-                                        attr.parse().expect(
-                                            "all attribute names in the schema should be valid identifiers",
-                                        ),
-                                        attr_val.into(),
-                                    );
-                                    }
-                                }
-                            }
-                }
-                // generate appropriate tags for the entity
-                let mut tags = HashMap::new();
-                match &self.mode {
                     HierarchyGeneratorMode::Arbitrary {
-                        attributes_mode: AttributesMode::NoAttributesOrTags,
+                        attributes_mode:
+                            AttributesMode::Arbitrary {
+                                max_attrs,
+                                with_tags,
+                            },
                     } => {
-                        // don't add any tags
-                    }
-                    HierarchyGeneratorMode::SchemaBased { schema } => {
-                        // add tags
-                       let Some(tag_type) = schema.tag_type_by_entity_type(name) else {
-                        unreachable!("in schema-based mode, this should always be Some")
-                       };
-                        if let Some(tag_type) = tag_type {
-                            // add tags with the type `tag_type`
-                            self.u.arbitrary_loop(
-                                None,
-                                Some(schema.settings.max_width as u32),
-                                |u| {
-                                    let tag_key: SmolStr = u.arbitrary()?;
-                                    tags.insert(
-                                        tag_key,
-                                        schema
-                                            .exprgenerator(Some(&hierarchy_no_attrs))
-                                            .generate_attr_value_for_type(
-                                                &tag_type,
-                                                schema.settings.max_depth,
-                                                u,
-                                            )?
-                                            .into(),
-                                    );
-                                    Ok(std::ops::ControlFlow::Continue(()))
-                                },
-                            )?;
-                        }
+                        let constant_pool = constant_pool
+                            .as_ref()
+                            .expect("the constant pool is generated in this mode");
+                        let attrs = arbitrary_attrs_without_schema(
+                            &hierarchy_no_attrs,
+                            constant_pool,
+                            self.extensions,
+                            *max_attrs,
+                            self.u,
+                        )?;
+                        let tags = if *with_tags {
+                            arbitrary_attrs_without_schema(
+                                &hierarchy_no_attrs,
+                                constant_pool,
+                                self.extensions,
+                                *max_attrs,
+                                self.u,
+                            )?
+                        } else {
+                            HashMap::new()
+                        };
+                        (attrs, tags)
                     }
-                }
+                    HierarchyGeneratorMode::SchemaBased { schema } => (
+                        arbitrary_attrs_for_schema(schema, &hierarchy_no_attrs, name, self.u)?,
+                        arbitrary_tags_for_schema(schema, &hierarchy_no_attrs, name, self.u)?,
+                    ),
+                };
                 // create the actual ast::Entity object
                 let entity = ast::Entity::new(
                     uid.clone(),
@@ -644,3 +796,54 @@ impl HierarchyGenerator<'_, '_> {
         (0, None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AttributesMode, HierarchyGenerator, HierarchyGeneratorMode, NumEntities};
+    use arbitrary::Unstructured;
+    use cedar_policy_core::entities::Entities;
+    use cedar_policy_core::extensions::Extensions;
+    use rand::{rng, RngCore};
+
+    const RANDOM_BYTE_SIZE: u16 = 1024;
+    const ITERATION: u8 = 100;
+
+    #[test]
+    fn arbitrary_hierarchies_have_attributes_and_tags() {
+        let mut rng = rng();
+        let mut with_attrs = 0;
+        let mut with_tags = 0;
+        for _ in 0..ITERATION {
+            let mut bytes = [0; RANDOM_BYTE_SIZE as usize];
+            rng.fill_bytes(&mut bytes);
+            let mut u = Unstructured::new(&bytes);
+            let Ok(h) = (HierarchyGenerator {
+                mode: HierarchyGeneratorMode::Arbitrary {
+                    attributes_mode: AttributesMode::Arbitrary {
+                        max_attrs: 4,
+                        with_tags: true,
+                    },
+                },
+                num_entities: NumEntities::RangePerEntityType(1..=4),
+                u: &mut u,
+                extensions: Extensions::all_available(),
+            })
+            .generate() else {
+                continue;
+            };
+            if h.entities().any(|e| e.attrs_len() > 0) {
+                with_attrs += 1;
+            }
+            if h.entities().any(|e| e.tags_len() > 0) {
+                with_tags += 1;
+            }
+            let entities = Entities::try_from(h);
+            assert!(
+                entities.is_ok(),
+                "hierarchy should convert to entities: {entities:?}"
+            );
+        }
+        assert!(with_attrs > 0, "no hierarchy had attributes");
+        assert!(with_tags > 0, "no hierarchy had tags");
+    }
+}
@@ -39,6 +39,18 @@ pub mod gen;
 /// This module contains the `Hierarchy` data structure
 pub mod hierarchy;
 
+/// This module contains optional histograms of what the generators produce
+pub mod metrics;
+
+/// This module contains code for injecting validation errors into generated
+/// policies
+pub mod mutation;
+
+/// This module contains generators of partial requests and entities, for
+/// type-aware partial evaluation
+#[cfg(feature = "tpe")]
+pub mod partial;
+
 /// This module contains the `GeneratedPolicy` and `GeneratedLinkedPolicy` data
 /// structures
 pub mod policy;
@@ -50,6 +62,9 @@ pub mod rbac;
 /// This module contains the `Request` data structure
 pub mod request;
 
+/// This module contains the generator of large workloads for benchmarking
+pub mod scale;
+
 /// This module contains the `Schema` data structure and methods for generating
 /// both schemas and hierarchies/policies that conform to a schema
 pub mod schema;
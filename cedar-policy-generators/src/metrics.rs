@@ -0,0 +1,176 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Histograms describing what the generators produce.
+//!
+//! Counts are recorded in a process-wide table, keyed first by histogram
+//! (e.g., `"operators"`) and then by bucket (e.g., `"=="`). Recording is only
+//! compiled in with the `metrics` feature; without it every function in this
+//! module is a no-op and [`snapshot`] returns an empty table, so callers do
+//! not need to be feature-gated themselves.
+
+use crate::err::{Error, Result};
+use cedar_policy_core::ast;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Histogram of `Schema::arbitrary` outcomes
+pub const SCHEMAS: &str = "schemas";
+/// Histogram of `Schema::arbitrary_hierarchy` outcomes
+pub const HIERARCHIES: &str = "hierarchies";
+/// Histogram of `Schema::arbitrary_policy` outcomes
+pub const POLICIES: &str = "policies";
+/// Histogram of `Schema::arbitrary_request` outcomes
+pub const REQUESTS: &str = "requests";
+/// Histogram of operators appearing in generated policies
+pub const OPERATORS: &str = "operators";
+/// Histogram of extension functions called in generated policies
+pub const EXTENSION_FUNCTIONS: &str = "extension_functions";
+/// Histogram of the types of values constructed in generated policies
+pub const TYPES: &str = "types";
+
+/// A snapshot of all histograms, ordered by histogram name and then bucket
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Metrics(pub BTreeMap<String, BTreeMap<String, u64>>);
+
+impl Metrics {
+    /// The count of `bucket` in `histogram` (0 if never recorded)
+    pub fn count(&self, histogram: &str, bucket: &str) -> u64 {
+        self.0
+            .get(histogram)
+            .and_then(|h| h.get(bucket))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(feature = "metrics")]
+static METRICS: std::sync::Mutex<Metrics> = std::sync::Mutex::new(Metrics(BTreeMap::new()));
+
+/// Increment `bucket` in `histogram`
+#[cfg(feature = "metrics")]
+pub fn record(histogram: &str, bucket: &str) {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    *metrics
+        .0
+        .entry(histogram.to_string())
+        .or_default()
+        .entry(bucket.to_string())
+        .or_default() += 1;
+}
+
+/// Increment `bucket` in `histogram`
+#[cfg(not(feature = "metrics"))]
+pub fn record(_histogram: &str, _bucket: &str) {}
+
+/// Record the outcome of a generator in `histogram`: `"ok"` on success, and
+/// the kind of error otherwise
+pub fn record_result<T>(histogram: &str, result: &Result<T>) {
+    if cfg!(feature = "metrics") {
+        let bucket = match result {
+            Ok(_) => "ok",
+            Err(Error::NotEnoughData) => "not_enough_data",
+            Err(Error::EmptyChoose { .. }) => "empty_choose",
+            Err(Error::TooDeep) => "too_deep",
+            Err(Error::NoValidPrincipalOrResourceTypes) => "no_valid_principal_or_resource_types",
+            Err(Error::ExtensionsDisabled) => "extensions_disabled",
+            Err(Error::LikeDisabled) => "like_disabled",
+            Err(Error::IncorrectFormat { .. }) => "incorrect_format",
+            Err(Error::EntitiesError(_)) => "entities_error",
+            Err(Error::ContextError(_)) => "context_error",
+            Err(Error::SchemaError(_)) => "schema_error",
+            Err(Error::OtherArbitrary(_)) => "other_arbitrary",
+            Err(Error::TooManyReqEnvsPerAction(..)) => "too_many_request_envs_per_action",
+            Err(Error::TooManyReqEnvs(..)) => "too_many_request_envs",
+        };
+        record(histogram, bucket);
+    }
+}
+
+/// Record the operators, extension functions and value types appearing in
+/// `expr`
+pub fn record_expr(expr: &ast::Expr) {
+    if cfg!(feature = "metrics") {
+        for e in expr.subexpressions() {
+            match e.expr_kind() {
+                ast::ExprKind::Lit(lit) => record(
+                    TYPES,
+                    match lit {
+                        ast::Literal::Bool(_) => "bool",
+                        ast::Literal::Long(_) => "long",
+                        ast::Literal::String(_) => "string",
+                        ast::Literal::EntityUID(_) => "entity",
+                    },
+                ),
+                ast::ExprKind::Set(_) => record(TYPES, "set"),
+                ast::ExprKind::Record(_) => record(TYPES, "record"),
+                ast::ExprKind::If { .. } => record(OPERATORS, "if"),
+                ast::ExprKind::And { .. } => record(OPERATORS, "&&"),
+                ast::ExprKind::Or { .. } => record(OPERATORS, "||"),
+                ast::ExprKind::UnaryApp { op, .. } => record(OPERATORS, &format!("{op:?}")),
+                ast::ExprKind::BinaryApp { op, .. } => record(OPERATORS, &format!("{op:?}")),
+                ast::ExprKind::GetAttr { .. } => record(OPERATORS, "."),
+                ast::ExprKind::HasAttr { .. } => record(OPERATORS, "has"),
+                ast::ExprKind::Like { .. } => record(OPERATORS, "like"),
+                ast::ExprKind::Is { .. } => record(OPERATORS, "is"),
+                ast::ExprKind::ExtensionFunctionApp { fn_name, .. } => {
+                    record(EXTENSION_FUNCTIONS, &fn_name.to_string())
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// A snapshot of all histograms recorded so far
+#[cfg(feature = "metrics")]
+pub fn snapshot() -> Metrics {
+    METRICS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// A snapshot of all histograms recorded so far
+#[cfg(not(feature = "metrics"))]
+pub fn snapshot() -> Metrics {
+    Metrics::default()
+}
+
+/// Clear all histograms
+pub fn reset() {
+    #[cfg(feature = "metrics")]
+    METRICS.lock().unwrap_or_else(|e| e.into_inner()).0.clear();
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use cedar_policy_core::parser::parse_expr;
+
+    #[test]
+    fn record_expr_counts() {
+        reset();
+        record_expr(
+            &parse_expr(r#"1 < 2 && ip("1.2.3.4").isIpv4() && [1, "a"].contains(1)"#).unwrap(),
+        );
+        let metrics = snapshot();
+        assert_eq!(metrics.count(OPERATORS, "&&"), 2);
+        assert_eq!(metrics.count(OPERATORS, "Less"), 1);
+        assert_eq!(metrics.count(EXTENSION_FUNCTIONS, "ip"), 1);
+        assert_eq!(metrics.count(TYPES, "long"), 4);
+        assert_eq!(metrics.count(TYPES, "set"), 1);
+        reset();
+        assert_eq!(snapshot(), Metrics::default());
+    }
+}
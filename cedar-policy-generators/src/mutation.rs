@@ -0,0 +1,328 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::abac::Type;
+use crate::collections::HashSet;
+use crate::err::{while_doing, Result};
+use crate::hierarchy::Hierarchy;
+use crate::policy::GeneratedPolicy;
+use crate::schema::Schema;
+use arbitrary::Unstructured;
+use cedar_policy_core::ast::{self, Eid, EntityUID, Expr};
+use smol_str::SmolStr;
+
+/// An error deliberately injected into a policy by [`MutatedPolicy::arbitrary()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyMutation {
+    /// An operator applied to an operand of the wrong type, e.g., `!1`
+    WrongOperandType,
+    /// An access to a `principal` attribute that no entity type declares
+    MissingAttribute,
+    /// An entity literal whose type the schema doesn't declare
+    UnknownEntityType,
+    /// `A::"a" in B::"b"`, where the schema doesn't allow entities of type `A`
+    /// to be descendants of entities of type `B`
+    DisallowedIn,
+    /// A (guarded) tag value used at a type other than the declared tag type
+    WrongTagType,
+}
+
+/// Kind of validation error expected for a [`PolicyMutation`], named as in the
+/// Lean validator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedValidationError {
+    /// `TypeError.unexpectedType`
+    UnexpectedType,
+    /// `TypeError.attrNotFound`
+    AttrNotFound,
+    /// `TypeError.unknownEntity`
+    UnknownEntity,
+    /// `ValidationError.impossiblePolicy`: the policy can never apply. The Rust
+    /// validator reports this as a warning.
+    ImpossiblePolicy,
+}
+
+impl PolicyMutation {
+    /// The kind of validation error this mutation causes
+    pub fn expected_error(self) -> ExpectedValidationError {
+        match self {
+            Self::WrongOperandType | Self::WrongTagType => ExpectedValidationError::UnexpectedType,
+            Self::MissingAttribute => ExpectedValidationError::AttrNotFound,
+            Self::UnknownEntityType => ExpectedValidationError::UnknownEntity,
+            Self::DisallowedIn => ExpectedValidationError::ImpossiblePolicy,
+        }
+    }
+}
+
+impl ExpectedValidationError {
+    /// The error as serialized by the Lean validator
+    pub fn lean_name(self) -> &'static str {
+        match self {
+            Self::UnexpectedType => "unexpectedType",
+            Self::AttrNotFound => "attrNotFound",
+            Self::UnknownEntity => "unknownEntity",
+            Self::ImpossiblePolicy => "impossiblePolicy",
+        }
+    }
+}
+
+/// A policy with exactly one injected error, on top of a policy that is
+/// expected to validate
+#[derive(Debug, Clone)]
+pub struct MutatedPolicy {
+    /// the mutated policy
+    pub policy: GeneratedPolicy,
+    /// the injected error
+    pub mutation: PolicyMutation,
+}
+
+/// The entity types declared in `schema`, qualified with its namespace
+fn declared_entity_types(schema: &Schema) -> Vec<ast::EntityType> {
+    schema
+        .entity_types
+        .iter()
+        .map(|ty| ty.qualify_with(schema.namespace()))
+        .collect()
+}
+
+/// The entity types that entities of type `ty` may be descendants of,
+/// including `ty` itself
+fn ancestor_types(schema: &Schema, ty: &ast::EntityType) -> HashSet<ast::EntityType> {
+    let mut ancestors = HashSet::new();
+    let mut work = vec![ty.clone()];
+    while let Some(ty) = work.pop() {
+        if ancestors.insert(ty.clone()) {
+            work.extend(schema.allowed_parent_typenames(&ty).into_iter().flatten());
+        }
+    }
+    ancestors
+}
+
+/// A name starting with `prefix` that `taken` doesn't contain
+fn fresh_name(prefix: &str, taken: impl Fn(&str) -> bool) -> SmolStr {
+    (0..)
+        .map(|i| SmolStr::from(format!("{prefix}{i}")))
+        .find(|name| !taken(name))
+        .expect("there are infinitely many candidate names")
+}
+
+impl MutatedPolicy {
+    /// Inject an arbitrary error into `policy`, which should be a policy that
+    /// validates against `schema`. The error is added as the first non-scope
+    /// constraint, so that it is the first (and usually only) error found.
+    pub fn arbitrary(
+        mut policy: GeneratedPolicy,
+        schema: &Schema,
+        hierarchy: &Hierarchy,
+        u: &mut Unstructured<'_>,
+    ) -> Result<Self> {
+        let types = declared_entity_types(schema);
+        let disallowed_in_pairs: Vec<(&ast::EntityType, &ast::EntityType)> = types
+            .iter()
+            .flat_map(|ty| {
+                let ancestors = ancestor_types(schema, ty);
+                types
+                    .iter()
+                    .filter(move |other| !ancestors.contains(*other))
+                    .map(move |other| (ty, other))
+            })
+            .collect();
+        let tagged_types: Vec<(&ast::EntityType, Type)> = types
+            .iter()
+            .filter_map(|ty| Some((ty, schema.tag_type_by_entity_type(ty)??)))
+            .collect();
+
+        let mut mutations = vec![
+            PolicyMutation::WrongOperandType,
+            PolicyMutation::MissingAttribute,
+            PolicyMutation::UnknownEntityType,
+        ];
+        if !disallowed_in_pairs.is_empty() {
+            mutations.push(PolicyMutation::DisallowedIn);
+        }
+        if !tagged_types.is_empty() {
+            mutations.push(PolicyMutation::WrongTagType);
+        }
+        let mutation = *u
+            .choose(&mutations)
+            .map_err(|e| while_doing("choosing a policy mutation".into(), e))?;
+
+        let exprgenerator = schema.exprgenerator(Some(hierarchy));
+        let constraint = match mutation {
+            PolicyMutation::WrongOperandType => Expr::not(Expr::val(u.arbitrary::<i64>()?)),
+            PolicyMutation::MissingAttribute => {
+                let declared: HashSet<SmolStr> = types
+                    .iter()
+                    .filter_map(|ty| schema.attribute_by_entity_type(ty))
+                    .flat_map(|(attrs, _)| attrs.into_keys())
+                    .collect();
+                let attr = fresh_name("missing", |name| declared.contains(name));
+                Expr::get_attr(Expr::var(ast::Var::Principal), attr)
+            }
+            PolicyMutation::UnknownEntityType => {
+                let name = fresh_name("Unknown", |name| {
+                    types.iter().any(|ty| ty.to_string() == name)
+                });
+                let ty: ast::Name = name.parse().expect("should be a valid entity type name");
+                let uid = EntityUID::from_components(ty.into(), Eid::new("unknown"), None);
+                Expr::is_eq(Expr::val(uid), Expr::var(ast::Var::Principal))
+            }
+            PolicyMutation::DisallowedIn => {
+                let (descendant, ancestor) = *u
+                    .choose(&disallowed_in_pairs)
+                    .map_err(|e| while_doing("choosing unrelated entity types".into(), e))?;
+                Expr::is_in(
+                    Expr::val(exprgenerator.arbitrary_uid_with_type(descendant, u)?),
+                    Expr::val(exprgenerator.arbitrary_uid_with_type(ancestor, u)?),
+                )
+            }
+            PolicyMutation::WrongTagType => {
+                let (ty, tag_type) = u
+                    .choose(&tagged_types)
+                    .map_err(|e| while_doing("choosing an entity type with tags".into(), e))?;
+                let entity = Expr::val(exprgenerator.arbitrary_uid_with_type(ty, u)?);
+                let tag = Expr::val(u.arbitrary::<String>()?);
+                let value = Expr::get_tag(entity.clone(), tag.clone());
+                // use the tag value as a `Long` if it is a `Bool`, and as a
+                // `Bool` otherwise
+                let misused = if *tag_type == Type::Bool {
+                    Expr::neg(value)
+                } else {
+                    Expr::not(value)
+                };
+                Expr::and(Expr::has_tag(entity, tag), misused)
+            }
+        };
+        policy.prepend_abac_constraint(constraint);
+        Ok(Self { policy, mutation })
+    }
+
+    /// size hint for [`Self::arbitrary()`]
+    pub fn arbitrary_size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExpectedValidationError, MutatedPolicy, PolicyMutation};
+    use crate::schema::Schema;
+    use crate::settings::ABACSettings;
+    use arbitrary::Unstructured;
+    use cedar_policy_core::ast::PolicySet;
+    use cedar_policy_core::validator::{
+        ValidationError, ValidationMode, ValidationResult, ValidationWarning, Validator,
+        ValidatorSchema,
+    };
+    use rand::{rng, RngCore};
+
+    const RANDOM_BYTE_SIZE: u16 = 2048;
+    const ITERATION: u16 = 300;
+
+    const TEST_SETTINGS: ABACSettings = ABACSettings {
+        match_types: true,
+        enable_extensions: false,
+        max_depth: 3,
+        max_width: 3,
+        enable_additional_attributes: false,
+        enable_like: false,
+        enable_action_groups_and_attrs: true,
+        enable_arbitrary_func_call: false,
+        enable_unknowns: false,
+        enable_action_in_constraints: true,
+        per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+        total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+        expr_weights: ABACSettings::default_expr_weights(),
+    };
+
+    /// Whether `result` contains the kind of error `expected` names
+    fn reports(result: &ValidationResult, expected: ExpectedValidationError) -> bool {
+        match expected {
+            ExpectedValidationError::UnexpectedType => result
+                .validation_errors()
+                .any(|e| matches!(e, ValidationError::UnexpectedType(_))),
+            ExpectedValidationError::AttrNotFound => result
+                .validation_errors()
+                .any(|e| matches!(e, ValidationError::UnsafeAttributeAccess(_))),
+            ExpectedValidationError::UnknownEntity => result
+                .validation_errors()
+                .any(|e| matches!(e, ValidationError::UnrecognizedEntityType(_))),
+            ExpectedValidationError::ImpossiblePolicy => {
+                result
+                    .validation_errors()
+                    .any(|e| matches!(e, ValidationError::HierarchyNotRespected(_)))
+                    || result
+                        .validation_warnings()
+                        .any(|w| matches!(w, ValidationWarning::ImpossiblePolicy(_)))
+            }
+        }
+    }
+
+    #[test]
+    fn mutations_cause_expected_errors() {
+        let mut rng = rng();
+        let mut seen = Vec::new();
+        for _ in 0..ITERATION {
+            let mut bytes = [0; RANDOM_BYTE_SIZE as usize];
+            rng.fill_bytes(&mut bytes);
+            let mut u = Unstructured::new(&bytes);
+            let Ok(schema) = Schema::arbitrary(TEST_SETTINGS, &mut u) else {
+                continue;
+            };
+            let Ok(h) = schema.arbitrary_hierarchy(&mut u) else {
+                continue;
+            };
+            let Ok(policy) = schema.arbitrary_policy(&h, &mut u) else {
+                continue;
+            };
+            let Ok(mutated) = MutatedPolicy::arbitrary(policy.0.clone(), &schema, &h, &mut u)
+            else {
+                continue;
+            };
+            let Ok(vschema) = ValidatorSchema::try_from(schema) else {
+                continue;
+            };
+            let validator = Validator::new(vschema);
+
+            // mutations are only meaningful on policies that validate cleanly
+            let mut original = PolicySet::new();
+            policy.0.add_to_policyset(&mut original);
+            let result = validator.validate(&original, ValidationMode::Strict);
+            if !result.validation_passed() || result.validation_warnings().next().is_some() {
+                continue;
+            }
+
+            let expected = mutated.mutation.expected_error();
+            let mut policyset = PolicySet::new();
+            mutated.policy.add_to_policyset(&mut policyset);
+            let result = validator.validate(&policyset, ValidationMode::Strict);
+            assert!(
+                reports(&result, expected),
+                "{:?} should cause {expected:?}, but got:\n{:?}",
+                mutated.mutation,
+                result.validation_errors().collect::<Vec<_>>()
+            );
+            seen.push(mutated.mutation);
+        }
+        for mutation in [
+            PolicyMutation::WrongOperandType,
+            PolicyMutation::MissingAttribute,
+            PolicyMutation::UnknownEntityType,
+        ] {
+            assert!(seen.contains(&mutation), "{mutation:?} was never tested");
+        }
+    }
+}
@@ -0,0 +1,141 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::abac::ABACRequest;
+use crate::collections::HashSet;
+use crate::err::Result;
+use crate::hierarchy::Hierarchy;
+use crate::schema::Schema;
+use arbitrary::Unstructured;
+use cedar_policy_core::ast::{self, EntityUID, Value};
+use cedar_policy_core::tpe::{
+    entities::{PartialEntities, PartialEntity},
+    request::{PartialEntityUID, PartialRequest},
+};
+use smol_str::SmolStr;
+use std::collections::BTreeMap;
+
+/// The known parts of partial requests and entities are the same as in the
+/// concrete ones they abstract, and each of the other parts is unknown with
+/// probability `UNKNOWN_NUMERATOR / UNKNOWN_DENOMINATOR`
+const UNKNOWN_NUMERATOR: u8 = 1;
+/// See [`UNKNOWN_NUMERATOR`]
+const UNKNOWN_DENOMINATOR: u8 = 4;
+
+/// `Some(known)`, or (sometimes) `None` for an unknown value
+fn maybe_unknown<T>(known: impl FnOnce() -> T, u: &mut Unstructured<'_>) -> Result<Option<T>> {
+    if u.ratio(UNKNOWN_NUMERATOR, UNKNOWN_DENOMINATOR)? {
+        Ok(None)
+    } else {
+        Ok(Some(known()))
+    }
+}
+
+/// `uid` with (sometimes) an unknown entity id. Its type is always known.
+fn arbitrary_partial_uid(uid: &EntityUID, u: &mut Unstructured<'_>) -> Result<PartialEntityUID> {
+    Ok(PartialEntityUID {
+        ty: uid.entity_type().clone(),
+        eid: maybe_unknown(|| uid.eid().clone(), u)?,
+    })
+}
+
+/// The concrete values of `attrs`, which are the attributes or tags of a
+/// generated entity
+fn concrete_values<'a>(
+    attrs: impl Iterator<Item = (&'a SmolStr, &'a ast::PartialValue)>,
+) -> BTreeMap<SmolStr, Value> {
+    attrs
+        .map(|(k, v)| {
+            (
+                k.clone(),
+                Value::try_from(v.clone()).expect("generated entity data should be concrete"),
+            )
+        })
+        .collect()
+}
+
+impl Schema {
+    /// Generate an arbitrary `ABACRequest` conforming to the schema, like
+    /// [`Schema::arbitrary_request()`], together with a partial request
+    /// abstracting it.
+    ///
+    /// In the partial request, the principal and resource ids, and the whole
+    /// context, may be unknown. The action and the principal and resource
+    /// types are always known, so the partial request conforms to the schema
+    /// whenever the request does.
+    pub fn arbitrary_partial_request(
+        &self,
+        hierarchy: &Hierarchy,
+        u: &mut Unstructured<'_>,
+    ) -> Result<(ABACRequest, PartialRequest)> {
+        let request = self.arbitrary_request(hierarchy, u)?;
+        let context = match &request.context {
+            ast::Context::Value(attrs) => maybe_unknown(|| attrs.clone(), u)?,
+            // generated contexts are always concrete
+            ast::Context::RestrictedResidual(_) => None,
+        };
+        let partial_request = PartialRequest::new_unchecked(
+            arbitrary_partial_uid(&request.principal, u)?,
+            arbitrary_partial_uid(&request.resource, u)?,
+            request.action.clone(),
+            context,
+        );
+        Ok((request, partial_request))
+    }
+
+    /// size hint for arbitrary_partial_request()
+    pub fn arbitrary_partial_request_size_hint(depth: usize) -> (usize, Option<usize>) {
+        Self::arbitrary_request_size_hint(depth)
+    }
+}
+
+impl Hierarchy {
+    /// Generate partial entities abstracting the entities of this hierarchy.
+    ///
+    /// The attributes and the tags of each entity may be unknown, and so may
+    /// be the ancestors of entities that aren't ancestors of any other
+    /// entity. Ancestors that other entities' ancestors depend on are always
+    /// known, so that the transitive closure of the partial entities is the
+    /// same as that of the hierarchy.
+    pub fn arbitrary_partial_entities(&self, u: &mut Unstructured<'_>) -> Result<PartialEntities> {
+        let ancestors: HashSet<&EntityUID> = self.entities().flat_map(|e| e.ancestors()).collect();
+        let entities = self
+            .entities()
+            .map(|e| {
+                let entity_ancestors = || e.ancestors().cloned().collect();
+                let partial_entity = PartialEntity {
+                    uid: e.uid().clone(),
+                    attrs: maybe_unknown(|| concrete_values(e.attrs()), u)?,
+                    ancestors: if ancestors.contains(e.uid()) {
+                        Some(entity_ancestors())
+                    } else {
+                        maybe_unknown(entity_ancestors, u)?
+                    },
+                    tags: maybe_unknown(|| concrete_values(e.tags()), u)?,
+                };
+                Ok((e.uid().clone(), partial_entity))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PartialEntities::from_entities_unchecked(
+            entities.into_iter(),
+        ))
+    }
+
+    /// size hint for arbitrary_partial_entities()
+    pub fn arbitrary_partial_entities_size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
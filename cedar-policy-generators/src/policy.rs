@@ -15,7 +15,7 @@
  */
 
 use crate::collections::HashMap;
-use crate::err::Result;
+use crate::err::{while_doing, Result};
 use crate::hierarchy::Hierarchy;
 use crate::schema::Schema;
 use crate::size_hint_utils::size_hint_for_ratio;
@@ -127,6 +127,36 @@ impl GeneratedPolicy {
             PrincipalOrResourceConstraint::arbitrary_size_hint(allow_slots, depth),
         ])
     }
+
+    /// Add `constraint` before the policy's other non-scope constraints, so
+    /// that it is evaluated (and typechecked) first
+    pub fn prepend_abac_constraint(&mut self, constraint: Expr) {
+        let abac_constraints = std::mem::replace(&mut self.abac_constraints, Expr::val(true));
+        self.abac_constraints = Expr::and(constraint, abac_constraints);
+    }
+
+    /// A `permit` policy with the same scope as this one, which applies
+    /// exactly when the scope matches and this policy's non-scope constraints
+    /// evaluate to `true` (or, if `negate`, to `false`). Requests that such a
+    /// policy allows satisfy (or just miss) the condition of this policy.
+    pub fn condition_probe(&self, negate: bool) -> Self {
+        let abac_constraints = if negate {
+            Expr::not(self.abac_constraints.clone())
+        } else {
+            self.abac_constraints.clone()
+        };
+        Self {
+            effect: Effect::Permit,
+            abac_constraints,
+            ..self.clone()
+        }
+    }
+
+    /// The policy's non-scope constraints
+    pub fn abac_constraints(&self) -> &Expr {
+        &self.abac_constraints
+    }
+
     /// Does the policy have (a nonzero number of) slots
     pub fn has_slots(&self) -> bool {
         self.principal_constraint.has_slot() || self.resource_constraint.has_slot()
@@ -513,6 +543,69 @@ impl GeneratedLinkedPolicy {
         })
     }
 
+    /// Generate a value for the slot in `prc` (if any) conforming to `schema`:
+    /// an entity of one of `types` for `==` constraints, and for `in`
+    /// constraints an entity of one of `types` (or of the type named by
+    /// `is ... in`) or of one of its parent types
+    fn arbitrary_slot_value_for_schema(
+        prc: &PrincipalOrResourceConstraint,
+        types: &[ast::EntityType],
+        schema: &Schema,
+        hierarchy: &Hierarchy,
+        u: &mut Unstructured<'_>,
+    ) -> Result<Option<EntityUID>> {
+        let with_parent_types = |ty: &ast::EntityType| {
+            std::iter::once(ty.clone())
+                .chain(schema.allowed_parent_typenames(ty).into_iter().flatten())
+                .collect::<Vec<_>>()
+        };
+        let candidates = match prc {
+            PrincipalOrResourceConstraint::EqSlot => types.to_vec(),
+            PrincipalOrResourceConstraint::InSlot => with_parent_types(
+                u.choose(types)
+                    .map_err(|e| while_doing("choosing a type for a slot".into(), e))?,
+            ),
+            PrincipalOrResourceConstraint::IsTypeInSlot(ety) => with_parent_types(ety),
+            _ => return Ok(None),
+        };
+        let ty = u
+            .choose(&candidates)
+            .map_err(|e| while_doing("choosing a type for a slot".into(), e))?;
+        let uid = schema
+            .exprgenerator(Some(hierarchy))
+            .arbitrary_uid_with_type(ty, u)?;
+        Ok(Some(uid))
+    }
+
+    /// Generate an arbitrary `GeneratedLinkedPolicy` from the given template,
+    /// filling its slots with entities whose types `schema` allows there
+    pub fn arbitrary_for_schema(
+        id: PolicyID,
+        template: &GeneratedPolicy,
+        schema: &Schema,
+        hierarchy: &Hierarchy,
+        u: &mut Unstructured<'_>,
+    ) -> Result<Self> {
+        Ok(Self {
+            id,
+            template_id: template.id.clone(),
+            principal: Self::arbitrary_slot_value_for_schema(
+                &template.principal_constraint,
+                &schema.principal_types,
+                schema,
+                hierarchy,
+                u,
+            )?,
+            resource: Self::arbitrary_slot_value_for_schema(
+                &template.resource_constraint,
+                &schema.resource_types,
+                schema,
+                hierarchy,
+                u,
+            )?,
+        })
+    }
+
     /// Add this `GeneratedLinkedPolicy` to the given `PolicySet`
     pub fn add_to_policyset(self, policyset: &mut PolicySet) {
         let mut vals = HashMap::new();
@@ -544,3 +637,16 @@ impl GeneratedLinkedPolicy {
         policyset.link(template_id, policy_id, vals.into()).unwrap();
     }
 }
+
+impl Display for GeneratedLinkedPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: link of {}", self.id, self.template_id)?;
+        if let Some(principal) = &self.principal {
+            write!(f, ", ?principal = {principal}")?;
+        }
+        if let Some(resource) = &self.resource {
+            write!(f, ", ?resource = {resource}")?;
+        }
+        Ok(())
+    }
+}
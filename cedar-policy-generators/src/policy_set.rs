@@ -14,7 +14,7 @@
  * limitations under the License.
  */
 use crate::hierarchy::Hierarchy;
-use crate::policy::GeneratedPolicy;
+use crate::policy::{GeneratedLinkedPolicy, GeneratedPolicy};
 use crate::schema::Schema;
 use arbitrary::Unstructured;
 use cedar_policy_core::ast;
@@ -27,10 +27,17 @@ use std::fmt::Display;
 const MIN_LENGTH: usize = 1;
 /// Maximum (inclusive) number of policies to attempt to generate for a [`GeneratedPolicySet`].
 const MAX_LENGTH: usize = 6;
+/// Maximum (inclusive) number of links to attempt to generate for each template
+/// in a [`GeneratedPolicySet`]. Every template gets at least one link.
+const MAX_LINKS: usize = 3;
 
-/// Data structure representing a set of generated templates and static policies.
+/// Data structure representing a set of generated templates and static
+/// policies, and links of the templates.
 #[derive(Debug, Clone, Serialize)]
-pub struct GeneratedPolicySet(Vec<GeneratedPolicy>);
+pub struct GeneratedPolicySet {
+    policies: Vec<GeneratedPolicy>,
+    links: Vec<GeneratedLinkedPolicy>,
+}
 
 impl GeneratedPolicySet {
     /// Generate an arbitrary [`GeneratedPolicySet`]
@@ -58,7 +65,20 @@ impl GeneratedPolicySet {
                 policies.push(policy);
             }
         }
-        Ok(Self(policies))
+        let mut links = Vec::new();
+        for template in policies.iter().filter(|policy| policy.has_slots()) {
+            let num_links = u.int_in_range(1..=MAX_LINKS)?;
+            for _ in 0..num_links {
+                let id: PolicyID = u.arbitrary()?;
+                // Skip IDs of policies, templates and other links
+                if ids.insert(id.clone()) {
+                    links.push(GeneratedLinkedPolicy::arbitrary_for_schema(
+                        id, template, schema, hierarchy, u,
+                    )?);
+                }
+            }
+        }
+        Ok(Self { policies, links })
     }
 
     /// size_hint for [`Self::arbitrary_for_hierarchy()`]
@@ -70,9 +90,12 @@ impl GeneratedPolicySet {
 impl From<GeneratedPolicySet> for ast::PolicySet {
     fn from(generated: GeneratedPolicySet) -> Self {
         let mut p_set = ast::PolicySet::new();
-        for policy in generated.0 {
+        for policy in generated.policies {
             policy.add_to_policyset(&mut p_set);
         }
+        for link in generated.links {
+            link.add_to_policyset(&mut p_set);
+        }
         p_set
     }
 }
@@ -87,13 +110,16 @@ impl TryFrom<GeneratedPolicySet> for cedar_policy::PolicySet {
 
 impl Display for GeneratedPolicySet {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut iter = self.0.iter();
+        let mut iter = self.policies.iter();
         if let Some(arg) = iter.next() {
             write!(f, "{}", arg)?;
             for policy in iter {
                 write!(f, "\n{}", policy)?
             }
         }
+        for link in &self.links {
+            write!(f, "\n// {link}")?
+        }
         Ok(())
     }
 }
@@ -0,0 +1,498 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generation of production-sized workloads for benchmarking: entity
+//! hierarchies of 10^5 or more entities, thousands of policies (most of them
+//! linked from a few shared templates) and request mixes with a given ratio of
+//! allowed to denied requests.
+//!
+//! The generators in the rest of this crate draw from a single
+//! `Unstructured`, which is sized for fuzzing. Here every step draws from
+//! fresh pseudorandom bytes instead, so the size of a workload is only bounded
+//! by the settings, and the same seed and settings always give the same
+//! workload.
+
+use crate::collections::{HashMap, HashSet};
+use crate::err::{Error, Result};
+use crate::hierarchy::{arbitrary_attrs_for_schema, arbitrary_tags_for_schema, Hierarchy};
+use crate::policy::{GeneratedLinkedPolicy, GeneratedPolicy, PrincipalOrResourceConstraint};
+use crate::schema::Schema;
+use crate::settings::ABACSettings;
+use arbitrary::Unstructured;
+use cedar_policy_core::ast::{self, Effect, Eid, EntityUID, PolicyID};
+use cedar_policy_core::authorizer::{Authorizer, Decision};
+use cedar_policy_core::entities::Entities;
+use cedar_policy_core::est;
+use cedar_policy_core::extensions::Extensions;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+/// Number of times to retry a generation step on fresh bytes before giving up
+const MAX_ATTEMPTS: usize = 100;
+/// Number of pseudorandom bytes each attempt at generating the schema draws
+const SCHEMA_BYTES: usize = 1 << 16;
+/// Number of pseudorandom bytes each attempt at generating a single entity,
+/// policy or request draws
+const ITEM_BYTES: usize = 1 << 12;
+/// Number of candidate requests to generate per requested one, when looking
+/// for requests with the decision needed to reach `allow_ratio`
+const REQUEST_CANDIDATES: usize = 20;
+/// Relative number of entities of types that can't be parents (users,
+/// documents, ...) compared to types that can (groups, folders, ...)
+const LEAF_WEIGHT: usize = 10;
+
+/// Settings for [`ScaleWorkload::generate()`]
+#[derive(Debug, Clone)]
+pub struct ScaleSettings {
+    /// Settings for the schema, attributes, policy conditions and contexts
+    pub abac: ABACSettings,
+    /// Total number of entities in the hierarchy (enumerated entity types get
+    /// exactly their declared entities, and every other type at least one)
+    pub num_entities: usize,
+    /// Maximum number of parents of an entity
+    pub max_parents: usize,
+    /// Number of static policies
+    pub num_static_policies: usize,
+    /// Number of templates
+    pub num_templates: usize,
+    /// Number of links of each template
+    pub links_per_template: usize,
+    /// Number of requests
+    pub num_requests: usize,
+    /// Fraction of the requests that should be allowed, between 0 and 1. This
+    /// is a target: if the policies rarely (or almost always) allow the
+    /// generated requests, the workload may have fewer (or more) allowed
+    /// requests.
+    pub allow_ratio: f64,
+}
+
+impl ScaleSettings {
+    /// Default scale settings, generating the schema and expressions with
+    /// `abac`
+    pub fn new(abac: ABACSettings) -> Self {
+        Self {
+            abac,
+            num_entities: 100_000,
+            max_parents: 4,
+            num_static_policies: 500,
+            num_templates: 20,
+            links_per_template: 100,
+            num_requests: 1000,
+            allow_ratio: 0.5,
+        }
+    }
+}
+
+/// Source of fresh pseudorandom bytes for each generation step
+struct Entropy {
+    rng: StdRng,
+    bytes: Vec<u8>,
+}
+
+impl Entropy {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Run `f` on `len` fresh pseudorandom bytes, retrying with new bytes if it
+    /// fails
+    fn generate<T>(
+        &mut self,
+        len: usize,
+        mut f: impl FnMut(&mut Unstructured<'_>) -> Result<T>,
+    ) -> Result<T> {
+        self.bytes.resize(len, 0);
+        let mut last_err = Error::NotEnoughData;
+        for _ in 0..MAX_ATTEMPTS {
+            self.rng.fill_bytes(&mut self.bytes);
+            match f(&mut Unstructured::new(&self.bytes)) {
+                Ok(t) => return Ok(t),
+                Err(err) => last_err = err,
+            }
+        }
+        Err(last_err)
+    }
+}
+
+/// A generated schema, with a large hierarchy, policy set and request mix
+/// conforming to it
+#[derive(Debug, Clone)]
+pub struct ScaleWorkload {
+    /// The schema
+    pub schema: Schema,
+    /// The entity hierarchy
+    pub hierarchy: Hierarchy,
+    /// Static policies `policy0`, `policy1`, ..., templates `template0`,
+    /// `template1`, ... and their links `template0-link0`, ...
+    pub policies: ast::PolicySet,
+    /// The requests, with the decision the Rust authorizer gives them
+    pub requests: Vec<(ast::Request, Decision)>,
+}
+
+impl ScaleWorkload {
+    /// Generate a workload from `seed`. The same seed and settings always give
+    /// the same workload.
+    pub fn generate(settings: &ScaleSettings, seed: u64) -> Result<Self> {
+        let mut entropy = Entropy::new(seed);
+        let schema = entropy.generate(SCHEMA_BYTES, |u| {
+            let schema = Schema::arbitrary(settings.abac.clone(), u)?;
+            // Only keep schemas the requests can be generated for
+            schema.arbitrary_request(&schema.arbitrary_hierarchy(u)?, u)?;
+            Ok(schema)
+        })?;
+        let hierarchy = arbitrary_scale_hierarchy(&schema, settings, &mut entropy)?;
+        let policies = arbitrary_scale_policies(&schema, &hierarchy, settings, &mut entropy)?;
+        let requests =
+            arbitrary_scale_requests(&schema, &hierarchy, &policies, settings, &mut entropy)?;
+        Ok(Self {
+            schema,
+            hierarchy,
+            policies,
+            requests,
+        })
+    }
+
+    /// Number of the requests that are allowed
+    pub fn num_allowed(&self) -> usize {
+        self.requests
+            .iter()
+            .filter(|(_, decision)| *decision == Decision::Allow)
+            .count()
+    }
+}
+
+/// The entity types of `schema`, ordered so that the parents of an entity can
+/// only have its type or a later one, except along cycles of the
+/// `memberOfTypes` relation, which are broken arbitrarily
+fn entity_types_children_first(
+    schema: &Schema,
+    parent_types: &HashMap<ast::EntityType, Vec<ast::EntityType>>,
+) -> Vec<ast::EntityType> {
+    let mut remaining: Vec<ast::EntityType> = schema
+        .entity_types
+        .iter()
+        .map(|ty| ty.qualify_with(schema.namespace()))
+        .collect();
+    let mut ordered = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        // a type is ready once no other remaining type is a child of it
+        let ready = remaining
+            .iter()
+            .position(|ty| {
+                !remaining
+                    .iter()
+                    .any(|child| child != ty && parent_types[child].contains(ty))
+            })
+            .unwrap_or(0);
+        ordered.push(remaining.remove(ready));
+    }
+    ordered
+}
+
+/// Generate a hierarchy of `settings.num_entities` entities conforming to
+/// `schema`.
+///
+/// Most entities have types that can't be parents (like users), and belong to
+/// a few entities (like groups), with a few popular groups that many entities
+/// belong to. Groups are nested in the same way, which keeps the hierarchy
+/// shallow.
+fn arbitrary_scale_hierarchy(
+    schema: &Schema,
+    settings: &ScaleSettings,
+    entropy: &mut Entropy,
+) -> Result<Hierarchy> {
+    let parent_types: HashMap<ast::EntityType, Vec<ast::EntityType>> = schema
+        .entity_types
+        .iter()
+        .map(|ty| {
+            let ty = ty.qualify_with(schema.namespace());
+            let parents = schema
+                .allowed_parent_typenames(&ty)
+                .map(Iterator::collect)
+                .unwrap_or_default();
+            (ty, parents)
+        })
+        .collect();
+    let types = entity_types_children_first(schema, &parent_types);
+    let is_parent_type = |ty: &ast::EntityType| parent_types.values().any(|ps| ps.contains(ty));
+    let weight = |ty: &ast::EntityType| {
+        if is_parent_type(ty) {
+            1
+        } else {
+            LEAF_WEIGHT
+        }
+    };
+    let total_weight: usize = types.iter().map(weight).sum();
+
+    // Entities of each type, in the order of `types`. An entity can only have
+    // parents appearing after it in this order, so the hierarchy is a DAG.
+    let uids: Vec<Vec<EntityUID>> = types
+        .iter()
+        .map(|ty| {
+            let choices = schema.get_uid_enum_choices(ty);
+            let eids: Vec<Eid> = if choices.is_empty() {
+                let n = (settings.num_entities * weight(ty) / total_weight).max(1);
+                (0..n).map(|i| Eid::new(i.to_string())).collect()
+            } else {
+                choices.into_iter().map(Eid::new).collect()
+            };
+            eids.into_iter()
+                .map(|eid| EntityUID::from_components(ty.clone(), eid, None))
+                .collect()
+        })
+        .collect();
+    let rank: HashMap<&ast::EntityType, usize> =
+        types.iter().enumerate().map(|(i, ty)| (ty, i)).collect();
+    let hierarchy_no_attrs = Hierarchy::from_uids_by_type(
+        types
+            .iter()
+            .cloned()
+            .zip(uids.iter().map(|uids| uids.iter().cloned().collect()))
+            .collect(),
+    );
+
+    let mut entities = HashMap::new();
+    for (ty_rank, (ty, uids_of_ty)) in types.iter().zip(&uids).enumerate() {
+        for (idx, uid) in uids_of_ty.iter().enumerate() {
+            // the candidate parents of each allowed parent type
+            let candidates: Vec<&[EntityUID]> = parent_types[ty]
+                .iter()
+                .filter_map(|parent_ty| {
+                    let parent_rank = *rank.get(parent_ty)?;
+                    let parent_uids = &uids[parent_rank];
+                    match parent_rank.cmp(&ty_rank) {
+                        std::cmp::Ordering::Greater => Some(&parent_uids[..]),
+                        std::cmp::Ordering::Equal => Some(&parent_uids[idx + 1..]),
+                        std::cmp::Ordering::Less => None,
+                    }
+                })
+                .filter(|candidates| !candidates.is_empty())
+                .collect();
+            let mut parents = HashSet::new();
+            if !candidates.is_empty() {
+                // most entities have a parent, and a few have several
+                let mut continue_ratio = 0.9;
+                while parents.len() < settings.max_parents
+                    && entropy.rng.random_bool(continue_ratio)
+                {
+                    let candidates = candidates[entropy.rng.random_range(0..candidates.len())];
+                    // the last entities of each type are the most popular
+                    // parents: they are the likeliest pick, and have the
+                    // fewest candidate parents themselves
+                    let skew = entropy
+                        .rng
+                        .random_range(0..candidates.len())
+                        .min(entropy.rng.random_range(0..candidates.len()));
+                    parents.insert(candidates[candidates.len() - 1 - skew].clone());
+                    continue_ratio = 0.5;
+                }
+            }
+            let (attrs, tags) = entropy.generate(ITEM_BYTES, |u| {
+                Ok((
+                    arbitrary_attrs_for_schema(schema, &hierarchy_no_attrs, ty, u)?,
+                    arbitrary_tags_for_schema(schema, &hierarchy_no_attrs, ty, u)?,
+                ))
+            })?;
+            let entity = ast::Entity::new(
+                uid.clone(),
+                attrs,
+                std::collections::HashSet::new(),
+                parents.into_iter().collect(),
+                tags,
+                Extensions::all_available(),
+            )
+            .map_err(|e| Error::EntitiesError(e.to_string()))?;
+            entities.insert(uid.clone(), entity);
+        }
+    }
+    Ok(hierarchy_no_attrs.replace_entities(entities))
+}
+
+/// Generate a permit policy, or rarely a forbid policy
+fn arbitrary_scale_effect(u: &mut Unstructured<'_>) -> Result<Effect> {
+    if u.ratio::<u8>(1, 10)? {
+        Ok(Effect::Forbid)
+    } else {
+        Ok(Effect::Permit)
+    }
+}
+
+/// Generate `settings.num_static_policies` static policies and
+/// `settings.num_templates` templates, each linked
+/// `settings.links_per_template` times
+fn arbitrary_scale_policies(
+    schema: &Schema,
+    hierarchy: &Hierarchy,
+    settings: &ScaleSettings,
+    entropy: &mut Entropy,
+) -> Result<ast::PolicySet> {
+    let mut policies = ast::PolicySet::new();
+    for i in 0..settings.num_static_policies {
+        let policy = entropy.generate(ITEM_BYTES, |u| {
+            Ok(GeneratedPolicy::new(
+                PolicyID::from_string(format!("policy{i}")),
+                est::Annotations::new(),
+                arbitrary_scale_effect(u)?,
+                schema.arbitrary_principal_constraint(hierarchy, u)?,
+                schema.arbitrary_action_constraint(u, Some(3))?,
+                schema.arbitrary_resource_constraint(hierarchy, u)?,
+                schema.arbitrary_abac_constraints(hierarchy, u)?,
+            ))
+        })?;
+        policy.add_to_policyset(&mut policies);
+    }
+    for i in 0..settings.num_templates {
+        // templates grant access to a group of principals, and either to a
+        // group of resources or to resources chosen in the template
+        let template = entropy.generate(ITEM_BYTES, |u| {
+            let principal_constraint = if u.ratio::<u8>(3, 4)? {
+                PrincipalOrResourceConstraint::InSlot
+            } else {
+                PrincipalOrResourceConstraint::EqSlot
+            };
+            let action_constraint = schema.arbitrary_action_constraint(u, Some(3))?;
+            let resource_constraint = if u.ratio::<u8>(1, 2)? {
+                PrincipalOrResourceConstraint::InSlot
+            } else {
+                schema.arbitrary_resource_constraint(hierarchy, u)?
+            };
+            Ok(GeneratedPolicy::new(
+                PolicyID::from_string(format!("template{i}")),
+                est::Annotations::new(),
+                arbitrary_scale_effect(u)?,
+                principal_constraint,
+                action_constraint,
+                resource_constraint,
+                schema.arbitrary_abac_constraints(hierarchy, u)?,
+            ))
+        })?;
+        template.clone().add_to_policyset(&mut policies);
+        for j in 0..settings.links_per_template {
+            let link = entropy.generate(ITEM_BYTES, |u| {
+                GeneratedLinkedPolicy::arbitrary(
+                    PolicyID::from_string(format!("template{i}-link{j}")),
+                    &template,
+                    hierarchy,
+                    u,
+                )
+            })?;
+            link.add_to_policyset(&mut policies);
+        }
+    }
+    Ok(policies)
+}
+
+/// Generate `settings.num_requests` requests, of which (as close as possible
+/// to) `settings.allow_ratio` are allowed by `policies`
+fn arbitrary_scale_requests(
+    schema: &Schema,
+    hierarchy: &Hierarchy,
+    policies: &ast::PolicySet,
+    settings: &ScaleSettings,
+    entropy: &mut Entropy,
+) -> Result<Vec<(ast::Request, Decision)>> {
+    let entities = Entities::try_from(hierarchy.clone()).map_err(Error::EntitiesError)?;
+    let authorizer = Authorizer::new();
+    let target_allowed =
+        (settings.num_requests as f64 * settings.allow_ratio.clamp(0.0, 1.0)).round() as usize;
+    let target_denied = settings.num_requests - target_allowed;
+    let (mut allowed, mut denied) = (0, 0);
+    let mut requests = Vec::with_capacity(settings.num_requests);
+    let mut extra = Vec::new();
+    for _ in 0..settings.num_requests * REQUEST_CANDIDATES {
+        if requests.len() == settings.num_requests {
+            break;
+        }
+        let request: ast::Request = entropy
+            .generate(ITEM_BYTES, |u| schema.arbitrary_request(hierarchy, u))?
+            .into();
+        let decision = authorizer
+            .is_authorized(request.clone(), policies, &entities)
+            .decision;
+        let (seen, target) = match decision {
+            Decision::Allow => (&mut allowed, target_allowed),
+            Decision::Deny => (&mut denied, target_denied),
+        };
+        if *seen < target {
+            *seen += 1;
+            requests.push((request, decision));
+        } else if extra.len() < settings.num_requests {
+            extra.push((request, decision));
+        }
+    }
+    // if the target ratio wasn't reached, make up the numbers with requests
+    // of the other decision
+    let missing = settings.num_requests - requests.len();
+    requests.extend(extra.into_iter().take(missing));
+    Ok(requests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_SETTINGS: ABACSettings = ABACSettings {
+        match_types: true,
+        enable_extensions: true,
+        max_depth: 3,
+        max_width: 3,
+        enable_additional_attributes: false,
+        enable_like: true,
+        enable_action_groups_and_attrs: true,
+        enable_arbitrary_func_call: false,
+        enable_unknowns: false,
+        enable_action_in_constraints: true,
+        per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
+        total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+        expr_weights: ABACSettings::default_expr_weights(),
+    };
+
+    fn small_settings() -> ScaleSettings {
+        ScaleSettings {
+            num_entities: 2000,
+            num_static_policies: 20,
+            num_templates: 4,
+            links_per_template: 10,
+            num_requests: 50,
+            ..ScaleSettings::new(TEST_SETTINGS)
+        }
+    }
+
+    #[test]
+    fn scale_workloads_are_deterministic_and_sized() {
+        let settings = small_settings();
+        for seed in 0..4 {
+            let workload = ScaleWorkload::generate(&settings, seed).unwrap();
+            assert!(workload.hierarchy.num_entities() >= settings.num_entities / 2);
+            assert_eq!(
+                workload.policies.policies().count(),
+                settings.num_static_policies + settings.num_templates * settings.links_per_template
+            );
+            assert_eq!(workload.requests.len(), settings.num_requests);
+            // the hierarchy is acyclic, so the transitive closure succeeds
+            Entities::try_from(workload.hierarchy.clone()).unwrap();
+
+            let again = ScaleWorkload::generate(&settings, seed).unwrap();
+            assert_eq!(
+                workload.policies.to_string(),
+                again.policies.to_string(),
+                "the same seed should give the same policies"
+            );
+        }
+    }
+}
@@ -22,6 +22,7 @@ use crate::collections::{HashMap, HashSet};
 use crate::err::{while_doing, Error, Result};
 use crate::expr::ExprGenerator;
 use crate::hierarchy::{Hierarchy, HierarchyGenerator, HierarchyGeneratorMode, NumEntities};
+use crate::metrics;
 use crate::policy::{ActionConstraint, GeneratedPolicy, PrincipalOrResourceConstraint};
 use crate::request::Request;
 use crate::settings::ABACSettings;
@@ -77,6 +78,26 @@ pub struct Schema {
     /// isn't Hash or Ord
     attributes_by_type: HashMap<Type, Vec<(ast::EntityType, SmolStr)>>,
     entitytypes_by_type: HashMap<ast::EntityType, json_schema::EntityType<ast::InternalName>>,
+    /// for a schema spanning several namespaces, where its declarations live.
+    /// In that case, `schema` merges the declarations of all namespaces (with
+    /// fully-qualified references) and `namespace` is `None`.
+    multi_namespace: Option<MultiNamespace>,
+}
+
+/// Where the declarations of a [`Schema`] spanning several namespaces live.
+/// Entity type, common type and action names are unique across namespaces, so
+/// that the merged `NamespaceDefinition` can be keyed by basename.
+#[derive(Debug, Clone)]
+struct MultiNamespace {
+    /// namespace of each entity type, by basename
+    entity_types: HashMap<UnreservedId, Option<ast::Name>>,
+    /// namespace of each action, by name
+    actions: HashMap<ast::Eid, Option<ast::Name>>,
+    /// the schema, with fully-qualified names
+    fragment: json_schema::Fragment<ast::InternalName>,
+    /// the schema as a user could write it, with a mix of unqualified,
+    /// qualified and `__cedar`-qualified names (see [`Placement::raw_reference`])
+    raw_fragment: json_schema::Fragment<RawName>,
 }
 
 /// internal helper function, basically `impl Arbitrary for AttributesOrContext`
@@ -298,6 +319,33 @@ pub fn uid_for_action_name(namespace: Option<&ast::Name>, action_name: ast::Eid)
     ast::EntityUID::from_components(entity_type, action_name, None)
 }
 
+/// The fully-qualified entity type declared as `id`: in `namespace` or, for a
+/// schema spanning several namespaces, in the namespace given by `multi_namespace`
+fn declared_entity_type(
+    namespace: Option<&ast::Name>,
+    multi_namespace: Option<&MultiNamespace>,
+    id: &UnreservedId,
+) -> ast::EntityType {
+    let namespace = match multi_namespace {
+        Some(multi_namespace) => multi_namespace
+            .entity_types
+            .get(id)
+            .and_then(Option::as_ref),
+        None => namespace,
+    };
+    ast::EntityType::from(ast::Name::from(id.clone())).qualify_with(namespace)
+}
+
+/// A namespace definition without declarations
+fn empty_nsdef<N>(annotations: est::Annotations) -> json_schema::NamespaceDefinition<N> {
+    json_schema::NamespaceDefinition {
+        common_types: BTreeMap::new(),
+        entity_types: BTreeMap::new(),
+        actions: BTreeMap::new(),
+        annotations,
+    }
+}
+
 /// Lookup the given `common_type_name` in the `schema`, and if it's defined,
 /// return its definition
 pub fn lookup_common_type<'a>(
@@ -375,6 +423,425 @@ fn arbitrary_namespace(u: &mut Unstructured<'_>) -> Result<Option<ast::Name>> {
         .map_err(|e| while_doing("generating namespace".into(), e))
 }
 
+/// Maximum number of nonempty namespaces in [`Schema::arbitrary_multi_namespace()`]
+const MAX_NAMESPACES: usize = 3;
+
+/// Kinds of declarations that types can refer to
+#[derive(Debug, Clone, Copy)]
+enum Declaration {
+    EntityType,
+    CommonType,
+}
+
+/// Basename of a (generated, hence unreserved) entity or common type name
+fn basename(name: &ast::InternalName) -> UnreservedId {
+    name.basename()
+        .clone()
+        .try_into()
+        .expect("generated names should be unreserved")
+}
+
+/// Assignment of the declarations of a schema generated in the empty namespace
+/// to namespaces. Rewrites the references between declarations to account for
+/// the namespaces, either fully qualified ([`Placement::qualify_type()`] and
+/// friends) or as a user could write them ([`Placement::raw_type()`] and
+/// friends).
+struct Placement {
+    entity_types: HashMap<UnreservedId, Option<ast::Name>>,
+    common_types: HashMap<UnreservedId, Option<ast::Name>>,
+    actions: HashMap<SmolStr, Option<ast::Name>>,
+    /// basenames of both an entity type and a common type, which must not be
+    /// referred to as `EntityOrCommon`
+    ambiguous: HashSet<UnreservedId>,
+}
+
+impl Placement {
+    /// The kind of declaration `name` refers to
+    fn kind_of(&self, name: &ast::InternalName) -> Declaration {
+        if self.common_types.contains_key(&basename(name)) {
+            Declaration::CommonType
+        } else {
+            Declaration::EntityType
+        }
+    }
+
+    /// The namespace of the declaration `name` refers to
+    fn namespace_of(&self, name: &ast::InternalName, kind: Declaration) -> Option<&ast::Name> {
+        let namespaces = match kind {
+            Declaration::EntityType => &self.entity_types,
+            Declaration::CommonType => &self.common_types,
+        };
+        namespaces.get(&basename(name)).and_then(Option::as_ref)
+    }
+
+    /// The namespace of the action `name`
+    fn action_namespace(&self, name: &SmolStr) -> Option<&ast::Name> {
+        self.actions.get(name).and_then(Option::as_ref)
+    }
+
+    fn qualify(&self, name: &ast::InternalName, kind: Declaration) -> ast::InternalName {
+        name.qualify_with_name(self.namespace_of(name, kind))
+    }
+
+    fn qualify_type(
+        &self,
+        ty: &json_schema::Type<ast::InternalName>,
+    ) -> json_schema::Type<ast::InternalName> {
+        match ty {
+            json_schema::Type::CommonTypeRef { type_name, loc } => {
+                json_schema::Type::CommonTypeRef {
+                    type_name: self.qualify(type_name, Declaration::CommonType),
+                    loc: loc.clone(),
+                }
+            }
+            json_schema::Type::Type { ty, loc } => json_schema::Type::Type {
+                ty: match ty {
+                    json_schema::TypeVariant::Entity { name } => json_schema::TypeVariant::Entity {
+                        name: self.qualify(name, Declaration::EntityType),
+                    },
+                    json_schema::TypeVariant::EntityOrCommon { type_name } => {
+                        json_schema::TypeVariant::EntityOrCommon {
+                            type_name: self.qualify(type_name, self.kind_of(type_name)),
+                        }
+                    }
+                    json_schema::TypeVariant::Set { element } => json_schema::TypeVariant::Set {
+                        element: Box::new(self.qualify_type(element)),
+                    },
+                    json_schema::TypeVariant::Record(json_schema::RecordType {
+                        attributes,
+                        additional_attributes,
+                    }) => json_schema::TypeVariant::Record(json_schema::RecordType {
+                        attributes: attributes
+                            .iter()
+                            .map(|(attr, attr_ty)| {
+                                (
+                                    attr.clone(),
+                                    json_schema::TypeOfAttribute {
+                                        ty: self.qualify_type(&attr_ty.ty),
+                                        required: attr_ty.required,
+                                        annotations: attr_ty.annotations.clone(),
+                                    },
+                                )
+                            })
+                            .collect(),
+                        additional_attributes: *additional_attributes,
+                    }),
+                    ty => ty.clone(),
+                },
+                loc: loc.clone(),
+            },
+        }
+    }
+
+    fn qualify_entity_type(
+        &self,
+        et: &json_schema::EntityType<ast::InternalName>,
+    ) -> json_schema::EntityType<ast::InternalName> {
+        json_schema::EntityType {
+            kind: match &et.kind {
+                EntityTypeKind::Enum { choices } => EntityTypeKind::Enum {
+                    choices: choices.clone(),
+                },
+                EntityTypeKind::Standard(StandardEntityType {
+                    member_of_types,
+                    shape,
+                    tags,
+                }) => EntityTypeKind::Standard(StandardEntityType {
+                    member_of_types: member_of_types
+                        .iter()
+                        .map(|name| self.qualify(name, Declaration::EntityType))
+                        .collect(),
+                    shape: json_schema::AttributesOrContext(self.qualify_type(&shape.0)),
+                    tags: tags.as_ref().map(|ty| self.qualify_type(ty)),
+                }),
+            },
+            annotations: et.annotations.clone(),
+            loc: et.loc.clone(),
+        }
+    }
+
+    fn qualify_action(
+        &self,
+        action: &json_schema::ActionType<ast::InternalName>,
+    ) -> json_schema::ActionType<ast::InternalName> {
+        json_schema::ActionType {
+            attributes: action.attributes.clone(),
+            applies_to: action
+                .applies_to
+                .as_ref()
+                .map(|applies| json_schema::ApplySpec {
+                    resource_types: applies
+                        .resource_types
+                        .iter()
+                        .map(|name| self.qualify(name, Declaration::EntityType))
+                        .collect(),
+                    principal_types: applies
+                        .principal_types
+                        .iter()
+                        .map(|name| self.qualify(name, Declaration::EntityType))
+                        .collect(),
+                    context: json_schema::AttributesOrContext(
+                        self.qualify_type(&applies.context.0),
+                    ),
+                }),
+            member_of: action.member_of.as_ref().map(|groups| {
+                groups
+                    .iter()
+                    .map(|group| {
+                        json_schema::ActionEntityUID::default_type(group.id.clone())
+                            .qualify_with(self.action_namespace(&group.id).map(AsRef::as_ref))
+                    })
+                    .collect()
+            }),
+            annotations: action.annotations.clone(),
+            loc: action.loc.clone(),
+        }
+    }
+
+    /// A reference, from a declaration in namespace `from`, to the declaration
+    /// `name` refers to. An unqualified name resolves to a declaration in the
+    /// current namespace if there is one, and to a declaration in the empty
+    /// namespace otherwise. Since basenames are unique across namespaces,
+    /// references to the current namespace may be unqualified or qualified,
+    /// references to the empty namespace are unqualified, and other references
+    /// are qualified.
+    fn raw_reference(
+        &self,
+        from: Option<&ast::Name>,
+        name: &ast::InternalName,
+        kind: Declaration,
+        u: &mut Unstructured<'_>,
+    ) -> Result<RawName> {
+        let unqualified = ast::InternalName::from(ast::Name::unqualified_name(basename(name)));
+        Ok(RawName::from_name(match self.namespace_of(name, kind) {
+            None => unqualified,
+            Some(namespace) if from == Some(namespace) && u.ratio(1, 2)? => unqualified,
+            Some(namespace) => unqualified.qualify_with_name(Some(namespace)),
+        }))
+    }
+
+    /// A reference to an entity or common type, written either explicitly or
+    /// as `EntityOrCommon`
+    fn raw_type_reference(
+        &self,
+        from: Option<&ast::Name>,
+        name: &ast::InternalName,
+        kind: Declaration,
+        loc: &Option<cedar_policy_core::parser::Loc>,
+        u: &mut Unstructured<'_>,
+    ) -> Result<json_schema::Type<RawName>> {
+        let type_name = self.raw_reference(from, name, kind, u)?;
+        let ty = if !self.ambiguous.contains(&basename(name)) && u.ratio(1, 2)? {
+            json_schema::TypeVariant::EntityOrCommon { type_name }
+        } else {
+            match kind {
+                Declaration::EntityType => json_schema::TypeVariant::Entity { name: type_name },
+                Declaration::CommonType => {
+                    return Ok(json_schema::Type::CommonTypeRef {
+                        type_name,
+                        loc: loc.clone(),
+                    })
+                }
+            }
+        };
+        Ok(json_schema::Type::Type {
+            ty,
+            loc: loc.clone(),
+        })
+    }
+
+    /// A builtin type, written either as is or as a `__cedar`-qualified name
+    fn raw_builtin_type(
+        ty: json_schema::TypeVariant<RawName>,
+        name: &str,
+        loc: &Option<cedar_policy_core::parser::Loc>,
+        u: &mut Unstructured<'_>,
+    ) -> Result<json_schema::Type<RawName>> {
+        let ty = if u.ratio(1, 4)? {
+            json_schema::TypeVariant::EntityOrCommon {
+                type_name: RawName::from_name(
+                    format!("__cedar::{name}")
+                        .parse()
+                        .expect("builtin type names should be valid"),
+                ),
+            }
+        } else {
+            ty
+        };
+        Ok(json_schema::Type::Type {
+            ty,
+            loc: loc.clone(),
+        })
+    }
+
+    fn raw_type(
+        &self,
+        from: Option<&ast::Name>,
+        ty: &json_schema::Type<ast::InternalName>,
+        u: &mut Unstructured<'_>,
+    ) -> Result<json_schema::Type<RawName>> {
+        match ty {
+            json_schema::Type::CommonTypeRef { type_name, loc } => {
+                self.raw_type_reference(from, type_name, Declaration::CommonType, loc, u)
+            }
+            json_schema::Type::Type { ty, loc } => match ty {
+                json_schema::TypeVariant::Entity { name } => {
+                    self.raw_type_reference(from, name, Declaration::EntityType, loc, u)
+                }
+                json_schema::TypeVariant::EntityOrCommon { type_name } => {
+                    self.raw_type_reference(from, type_name, self.kind_of(type_name), loc, u)
+                }
+                json_schema::TypeVariant::Boolean => {
+                    Self::raw_builtin_type(json_schema::TypeVariant::Boolean, "Bool", loc, u)
+                }
+                json_schema::TypeVariant::Long => {
+                    Self::raw_builtin_type(json_schema::TypeVariant::Long, "Long", loc, u)
+                }
+                json_schema::TypeVariant::String => {
+                    Self::raw_builtin_type(json_schema::TypeVariant::String, "String", loc, u)
+                }
+                json_schema::TypeVariant::Extension { name } => Self::raw_builtin_type(
+                    json_schema::TypeVariant::Extension { name: name.clone() },
+                    name.as_ref(),
+                    loc,
+                    u,
+                ),
+                json_schema::TypeVariant::Set { element } => Ok(json_schema::Type::Type {
+                    ty: json_schema::TypeVariant::Set {
+                        element: Box::new(self.raw_type(from, element, u)?),
+                    },
+                    loc: loc.clone(),
+                }),
+                json_schema::TypeVariant::Record(json_schema::RecordType {
+                    attributes,
+                    additional_attributes,
+                }) => Ok(json_schema::Type::Type {
+                    ty: json_schema::TypeVariant::Record(json_schema::RecordType {
+                        attributes: attributes
+                            .iter()
+                            .map(|(attr, attr_ty)| {
+                                Ok((
+                                    attr.clone(),
+                                    json_schema::TypeOfAttribute {
+                                        ty: self.raw_type(from, &attr_ty.ty, u)?,
+                                        required: attr_ty.required,
+                                        annotations: attr_ty.annotations.clone(),
+                                    },
+                                ))
+                            })
+                            .collect::<Result<BTreeMap<_, _>>>()?,
+                        additional_attributes: *additional_attributes,
+                    }),
+                    loc: loc.clone(),
+                }),
+            },
+        }
+    }
+
+    /// Entity shapes and action contexts must be records, so a common type
+    /// reference at the top level stays explicit
+    fn raw_attributes_or_context(
+        &self,
+        from: Option<&ast::Name>,
+        aoc: &json_schema::AttributesOrContext<ast::InternalName>,
+        u: &mut Unstructured<'_>,
+    ) -> Result<json_schema::AttributesOrContext<RawName>> {
+        Ok(json_schema::AttributesOrContext(match &aoc.0 {
+            json_schema::Type::CommonTypeRef { type_name, loc } => {
+                json_schema::Type::CommonTypeRef {
+                    type_name: self.raw_reference(from, type_name, Declaration::CommonType, u)?,
+                    loc: loc.clone(),
+                }
+            }
+            ty => self.raw_type(from, ty, u)?,
+        }))
+    }
+
+    fn raw_entity_type(
+        &self,
+        from: Option<&ast::Name>,
+        et: &json_schema::EntityType<ast::InternalName>,
+        u: &mut Unstructured<'_>,
+    ) -> Result<json_schema::EntityType<RawName>> {
+        Ok(json_schema::EntityType {
+            kind: match &et.kind {
+                EntityTypeKind::Enum { choices } => EntityTypeKind::Enum {
+                    choices: choices.clone(),
+                },
+                EntityTypeKind::Standard(StandardEntityType {
+                    member_of_types,
+                    shape,
+                    tags,
+                }) => EntityTypeKind::Standard(StandardEntityType {
+                    member_of_types: member_of_types
+                        .iter()
+                        .map(|name| self.raw_reference(from, name, Declaration::EntityType, u))
+                        .collect::<Result<_>>()?,
+                    shape: self.raw_attributes_or_context(from, shape, u)?,
+                    tags: tags
+                        .as_ref()
+                        .map(|ty| self.raw_type(from, ty, u))
+                        .transpose()?,
+                }),
+            },
+            annotations: et.annotations.clone(),
+            loc: et.loc.clone(),
+        })
+    }
+
+    fn raw_action(
+        &self,
+        from: Option<&ast::Name>,
+        action: &json_schema::ActionType<ast::InternalName>,
+        u: &mut Unstructured<'_>,
+    ) -> Result<json_schema::ActionType<RawName>> {
+        let raw_entity_types = |names: &[ast::InternalName], u: &mut Unstructured<'_>| {
+            names
+                .iter()
+                .map(|name| self.raw_reference(from, name, Declaration::EntityType, u))
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(json_schema::ActionType {
+            attributes: action.attributes.clone(),
+            applies_to: match &action.applies_to {
+                Some(applies) => Some(json_schema::ApplySpec {
+                    resource_types: raw_entity_types(&applies.resource_types, u)?,
+                    principal_types: raw_entity_types(&applies.principal_types, u)?,
+                    context: self.raw_attributes_or_context(from, &applies.context, u)?,
+                }),
+                None => None,
+            },
+            member_of: match &action.member_of {
+                Some(groups) => Some(
+                    groups
+                        .iter()
+                        .map(|group| {
+                            let namespace = self.action_namespace(&group.id);
+                            // `Action` without a type is in the current namespace
+                            if namespace == from && u.ratio(1, 2)? {
+                                Ok(json_schema::ActionEntityUID::default_type(group.id.clone()))
+                            } else {
+                                let action_type =
+                                    uid_for_action_name(namespace, ast::Eid::new(group.id.clone()))
+                                        .entity_type()
+                                        .name()
+                                        .clone();
+                                Ok(json_schema::ActionEntityUID::new(
+                                    Some(RawName::from_name(action_type.into())),
+                                    group.id.clone(),
+                                ))
+                            }
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                ),
+                None => None,
+            },
+            annotations: action.annotations.clone(),
+            loc: action.loc.clone(),
+        })
+    }
+}
+
 /// Information about attributes from the schema
 #[derive(Debug, Clone)]
 pub(crate) struct Attributes<'a> {
@@ -390,13 +857,32 @@ pub(crate) fn attrs_from_attrs_or_context<'a>(
     schema: &'a json_schema::NamespaceDefinition<ast::InternalName>,
     attrsorctx: &'a json_schema::AttributesOrContext<ast::InternalName>,
 ) -> Attributes<'a> {
-    match &attrsorctx.0 {
-        json_schema::Type::CommonTypeRef { type_name, .. } => match lookup_common_type(schema, type_name).unwrap_or_else(|| panic!("reference to undefined common type: {type_name}")) {
-            json_schema::Type::CommonTypeRef { .. } => panic!("common type `{type_name}` refers to another common type, which is not allowed as of this writing?"),
-            json_schema::Type::Type { ty: json_schema::TypeVariant::Record(json_schema::RecordType { attributes, additional_attributes }), .. } => Attributes { attrs: attributes, additional_attrs: *additional_attributes },
-            ty => panic!("expected attributes or context to be a record, got {ty:?}"),
-        }
-        json_schema::Type::Type { ty: json_schema::TypeVariant::Record(json_schema::RecordType { attributes, additional_attributes }), .. } => Attributes { attrs: attributes, additional_attrs: *additional_attributes },
+    attrs_from_record_type(schema, &attrsorctx.0)
+}
+
+/// Get the attributes of `ty`, which should be a record type, following common
+/// type references (which may refer to further common types)
+fn attrs_from_record_type<'a>(
+    schema: &'a json_schema::NamespaceDefinition<ast::InternalName>,
+    ty: &'a json_schema::Type<ast::InternalName>,
+) -> Attributes<'a> {
+    match ty {
+        json_schema::Type::CommonTypeRef { type_name, .. } => attrs_from_record_type(
+            schema,
+            lookup_common_type(schema, type_name)
+                .unwrap_or_else(|| panic!("reference to undefined common type: {type_name}")),
+        ),
+        json_schema::Type::Type {
+            ty:
+                json_schema::TypeVariant::Record(json_schema::RecordType {
+                    attributes,
+                    additional_attributes,
+                }),
+            ..
+        } => Attributes {
+            attrs: attributes,
+            additional_attrs: *additional_attributes,
+        },
         ty => panic!("expected attributes or context to be a record, got {ty:?}"),
     }
 }
@@ -454,10 +940,10 @@ fn attrs_in_schematype(
 
 /// Build `attributes_by_type` from other components of `Schema`
 fn build_attributes_by_type<'a>(
-    schema: &json_schema::NamespaceDefinition<ast::InternalName>,
+    schema: &'a json_schema::NamespaceDefinition<ast::InternalName>,
     entity_types: impl IntoIterator<
         Item = (
-            &'a UnreservedId,
+            ast::EntityType,
             &'a json_schema::EntityType<ast::InternalName>,
         ),
     >,
@@ -467,10 +953,9 @@ fn build_attributes_by_type<'a>(
         .into_iter()
         .filter_map(|(name, et)| match &et.kind {
             EntityTypeKind::Enum { .. } => None,
-            EntityTypeKind::Standard(StandardEntityType { shape, .. }) => Some((
-                ast::EntityType::from(ast::Name::from(name.clone())).qualify_with(namespace),
-                attrs_from_attrs_or_context(schema, shape),
-            )),
+            EntityTypeKind::Standard(StandardEntityType { shape, .. }) => {
+                Some((name, attrs_from_attrs_or_context(schema, shape)))
+            }
         })
         .flat_map(|(tyname, attributes)| {
             attributes.attrs.iter().map(move |(attr_name, ty)| {
@@ -546,9 +1031,7 @@ impl Bindings {
         ty: &json_schema::Type<ast::InternalName>,
     ) -> Result<json_schema::Type<ast::InternalName>> {
         match ty {
-            json_schema::Type::CommonTypeRef { .. } => {
-                unreachable!("common type references shouldn't be here")
-            }
+            json_schema::Type::CommonTypeRef { .. } => Ok(ty.clone()),
             json_schema::Type::Type {
                 ty: json_schema::TypeVariant::Set { element },
                 loc,
@@ -614,7 +1097,10 @@ impl Bindings {
                 kind: EntityTypeKind::Standard(StandardEntityType {
                     member_of_types: member_of_types.clone(),
                     shape: json_schema::AttributesOrContext(self.rewrite_record_type(u, &shape.0)?),
-                    tags: tags.clone(),
+                    tags: tags
+                        .as_ref()
+                        .map(|ty| self.rewrite_record_type(u, ty))
+                        .transpose()?,
                 }),
                 annotations: et.annotations.clone(),
                 loc: et.loc.clone(),
@@ -683,7 +1169,19 @@ fn bind_type(
 ) -> Result<()> {
     // flip a coin to decide if we should create a binding for the top-level type
     if u.ratio(1, 2)? {
-        bindings.add_binding((ty.clone(), u.arbitrary()?));
+        // sometimes name the common type after an extension type, which it
+        // then shadows in unqualified references
+        let id = if u.ratio(1, 8)? {
+            CommonTypeId::new(
+                u.choose(&["ipaddr", "decimal", "datetime", "duration"])?
+                    .parse()
+                    .expect("extension type names should be valid ids"),
+            )
+            .expect("extension type names should not be reserved")
+        } else {
+            u.arbitrary()?
+        };
+        bindings.add_binding((ty.clone(), id));
     }
     match ty {
         json_schema::Type::Type {
@@ -705,9 +1203,6 @@ fn bind_type(
                 .map(|(_, attr_ty)| bind_type(&attr_ty.ty, u, bindings))
                 .collect::<Result<Vec<()>>>()?;
         }
-        json_schema::Type::CommonTypeRef { .. } => {
-            unreachable!("common type references shouldn't exist here")
-        }
         _ => {}
     };
     Ok(())
@@ -803,12 +1298,16 @@ impl Schema {
         }
     }
 
-    /// Add common types to the existing schema and return a new schema
+    /// Add common types to the existing schema and return a new schema. Common
+    /// types the schema already declares are kept.
     pub fn add_common_types(
         &self,
         u: &mut Unstructured<'_>,
     ) -> Result<json_schema::NamespaceDefinition<ast::InternalName>> {
         let mut bindings = Bindings::new();
+        bindings
+            .ids
+            .extend(self.schema.common_types.keys().map(ToSmolStr::to_smolstr));
         for (_, ty) in &self.attributes {
             bind_type(ty, u, &mut bindings)?;
         }
@@ -850,9 +1349,12 @@ impl Schema {
                 .collect::<Result<Vec<_>>>()?,
         );
         Ok(json_schema::NamespaceDefinition {
-            common_types: common_types
-                .into_iter()
-                .map(|(id, ty)| {
+            common_types: self
+                .schema
+                .common_types
+                .iter()
+                .map(|(id, ty)| Ok((id.clone(), ty.clone())))
+                .chain(common_types.into_iter().map(|(id, ty)| {
                     Ok((
                         id,
                         CommonType {
@@ -861,7 +1363,7 @@ impl Schema {
                             loc: None,
                         },
                     ))
-                })
+                }))
                 .collect::<Result<BTreeMap<_, _>>>()?,
             entity_types,
             actions,
@@ -915,6 +1417,21 @@ impl Schema {
         settings: ABACSettings,
         u: &mut Unstructured<'_>,
     ) -> Result<Schema> {
+        Self::from_nsdef_in_namespaces(nsdef, namespace, None, settings, u)
+    }
+
+    /// Like [`Schema::from_nsdef()`], but for a merged `nsdef` whose
+    /// declarations live in the namespaces given by `multi_namespace`
+    fn from_nsdef_in_namespaces(
+        nsdef: json_schema::NamespaceDefinition<ast::InternalName>,
+        namespace: Option<ast::Name>,
+        multi_namespace: Option<MultiNamespace>,
+        settings: ABACSettings,
+        u: &mut Unstructured<'_>,
+    ) -> Result<Schema> {
+        let declared_entity_type = |id: &UnreservedId| {
+            declared_entity_type(namespace.as_ref(), multi_namespace.as_ref(), id)
+        };
         let mut principal_types = HashSet::new();
         let mut resource_types = HashSet::new();
         for atype in nsdef.actions.values() {
@@ -940,8 +1457,14 @@ impl Schema {
             )
             .flat_map(|schematype| attrs_in_schematype(&nsdef, schematype))
             .collect();
-        let attributes_by_type =
-            build_attributes_by_type(&nsdef, &nsdef.entity_types, namespace.as_ref());
+        let attributes_by_type = build_attributes_by_type(
+            &nsdef,
+            nsdef
+                .entity_types
+                .iter()
+                .map(|(id, et)| (declared_entity_type(id), et)),
+            namespace.as_ref(),
+        );
         Ok(Schema {
             constant_pool: u
                 .arbitrary()
@@ -952,8 +1475,7 @@ impl Schema {
             entity_types: nsdef
                 .entity_types
                 .keys()
-                .cloned()
-                .map(|k| ast::EntityType::from(ast::Name::from(k)))
+                .map(declared_entity_type)
                 .collect(),
             principal_types: principal_types
                 .into_iter()
@@ -971,16 +1493,11 @@ impl Schema {
             entitytypes_by_type: nsdef
                 .entity_types
                 .iter()
-                .map(|(name, et)| {
-                    (
-                        ast::EntityType::from(ast::Name::from(name.clone()))
-                            .qualify_with(namespace.as_ref()),
-                        et.clone(),
-                    )
-                })
+                .map(|(name, et)| (declared_entity_type(name), et.clone()))
                 .collect(),
             namespace,
             schema: nsdef,
+            multi_namespace,
         })
     }
 
@@ -1045,8 +1562,35 @@ impl Schema {
 
     /// Get an arbitrary `Schema`.
     pub fn arbitrary(settings: ABACSettings, u: &mut Unstructured<'_>) -> Result<Schema> {
+        let schema = Self::arbitrary_unrecorded(settings, u);
+        metrics::record_result(metrics::SCHEMAS, &schema);
+        schema
+    }
+
+    fn arbitrary_unrecorded(settings: ABACSettings, u: &mut Unstructured<'_>) -> Result<Schema> {
         let namespace = arbitrary_namespace(u)?;
+        Self::arbitrary_in_namespace(namespace, settings, u)?.with_common_types(u)
+    }
 
+    /// Declare common types for some of the types in this `Schema`, and refer
+    /// to them in place of those types
+    fn with_common_types(mut self, u: &mut Unstructured<'_>) -> Result<Schema> {
+        let nsdef = self.add_common_types(u)?;
+        self.entitytypes_by_type = nsdef
+            .entity_types
+            .iter()
+            .map(|(id, et)| (self.declared_entity_type(id), et.clone()))
+            .collect();
+        self.schema = nsdef;
+        Ok(self)
+    }
+
+    /// Get an arbitrary `Schema` whose declarations are all in `namespace`
+    fn arbitrary_in_namespace(
+        namespace: Option<ast::Name>,
+        settings: ABACSettings,
+        u: &mut Unstructured<'_>,
+    ) -> Result<Schema> {
         // first generate the pool of names. we generate a set (so there are no
         // duplicates), but then convert it to a Vec (because we want them
         // ordered, even though we want the order to be arbitrary)
@@ -1300,8 +1844,17 @@ impl Schema {
                 })
             })
             .collect();
-        let attributes_by_type =
-            build_attributes_by_type(&nsdef, nsdef.entity_types.iter(), namespace.as_ref());
+        let attributes_by_type = build_attributes_by_type(
+            &nsdef,
+            nsdef.entity_types.iter().map(|(name, et)| {
+                (
+                    ast::EntityType::from(ast::Name::from(name.clone()))
+                        .qualify_with(namespace.as_ref()),
+                    et,
+                )
+            }),
+            namespace.as_ref(),
+        );
         let actions_eids = nsdef
             .actions
             .keys()
@@ -1340,8 +1893,174 @@ impl Schema {
             attributes,
             attributes_by_type,
             entitytypes_by_type,
+            multi_namespace: None,
         })
     }
+
+    /// Get an arbitrary `Schema` whose declarations are spread over several
+    /// namespaces, including the empty namespace. Entity types, common types
+    /// and actions refer to each other across namespaces, with unqualified,
+    /// qualified and `__cedar`-qualified names.
+    pub fn arbitrary_multi_namespace(
+        settings: ABACSettings,
+        u: &mut Unstructured<'_>,
+    ) -> Result<Schema> {
+        let schema = Self::arbitrary_multi_namespace_unrecorded(settings, u);
+        metrics::record_result(metrics::SCHEMAS, &schema);
+        schema
+    }
+
+    fn arbitrary_multi_namespace_unrecorded(
+        settings: ABACSettings,
+        u: &mut Unstructured<'_>,
+    ) -> Result<Schema> {
+        // Generate all declarations in the empty namespace, then move each of
+        // them to an arbitrary namespace. Basenames stay unique across
+        // namespaces, so that an unqualified reference is never ambiguous.
+        let mut nsdef =
+            Self::arbitrary_in_namespace(None, settings.clone(), u)?.add_common_types(u)?;
+        let namespaces: HashSet<ast::Name> = u
+            .arbitrary()
+            .map_err(|e| while_doing("generating namespaces".into(), e))?;
+        let namespaces: Vec<Option<ast::Name>> = std::iter::once(None)
+            .chain(namespaces.into_iter().take(MAX_NAMESPACES).map(Some))
+            .collect();
+        let choose_namespace = |u: &mut Unstructured<'_>| -> Result<Option<ast::Name>> {
+            Ok(u.choose(&namespaces)?.clone())
+        };
+        let placement = Placement {
+            entity_types: nsdef
+                .entity_types
+                .keys()
+                .map(|id| Ok((id.clone(), choose_namespace(u)?)))
+                .collect::<Result<_>>()?,
+            common_types: nsdef
+                .common_types
+                .keys()
+                .map(|id| Ok((id.clone().into(), choose_namespace(u)?)))
+                .collect::<Result<_>>()?,
+            actions: nsdef
+                .actions
+                .keys()
+                .map(|name| Ok((name.clone(), choose_namespace(u)?)))
+                .collect::<Result<_>>()?,
+            ambiguous: nsdef
+                .common_types
+                .keys()
+                .map(|id| UnreservedId::from(id.clone()))
+                .filter(|id| nsdef.entity_types.contains_key(id))
+                .collect(),
+        };
+        // An action in a nonempty namespace can't be a member of an action
+        // group in the empty namespace
+        for (name, action) in nsdef.actions.iter_mut() {
+            if placement.action_namespace(name).is_some() {
+                if let Some(groups) = action.member_of.as_mut() {
+                    groups.retain(|group| placement.action_namespace(&group.id).is_some());
+                }
+            }
+        }
+
+        let mut fragment = BTreeMap::new();
+        let mut raw_fragment = BTreeMap::new();
+        for namespace in namespaces.iter() {
+            let annotations = match namespace {
+                Some(_) => u.arbitrary()?,
+                None => est::Annotations::new(),
+            };
+            fragment.insert(namespace.clone(), empty_nsdef(annotations.clone()));
+            raw_fragment.insert(namespace.clone(), empty_nsdef(annotations));
+        }
+        let merged = json_schema::NamespaceDefinition {
+            common_types: nsdef
+                .common_types
+                .iter()
+                .map(|(id, ct)| {
+                    (
+                        id.clone(),
+                        CommonType {
+                            ty: placement.qualify_type(&ct.ty),
+                            annotations: ct.annotations.clone(),
+                            loc: None,
+                        },
+                    )
+                })
+                .collect(),
+            entity_types: nsdef
+                .entity_types
+                .iter()
+                .map(|(id, et)| (id.clone(), placement.qualify_entity_type(et)))
+                .collect(),
+            actions: nsdef
+                .actions
+                .iter()
+                .map(|(name, action)| (name.clone(), placement.qualify_action(action)))
+                .collect(),
+            annotations: est::Annotations::new(),
+        };
+        for (id, ct) in nsdef.common_types.iter() {
+            let namespace = &placement.common_types[&UnreservedId::from(id.clone())];
+            let raw_ty = placement.raw_type(namespace.as_ref(), &ct.ty, u)?;
+            fragment
+                .get_mut(namespace)
+                .expect("namespace should exist")
+                .common_types
+                .insert(id.clone(), merged.common_types[id].clone());
+            raw_fragment
+                .get_mut(namespace)
+                .expect("namespace should exist")
+                .common_types
+                .insert(
+                    id.clone(),
+                    CommonType {
+                        ty: raw_ty,
+                        annotations: ct.annotations.clone(),
+                        loc: None,
+                    },
+                );
+        }
+        for (id, et) in nsdef.entity_types.iter() {
+            let namespace = &placement.entity_types[id];
+            let raw_et = placement.raw_entity_type(namespace.as_ref(), et, u)?;
+            fragment
+                .get_mut(namespace)
+                .expect("namespace should exist")
+                .entity_types
+                .insert(id.clone(), merged.entity_types[id].clone());
+            raw_fragment
+                .get_mut(namespace)
+                .expect("namespace should exist")
+                .entity_types
+                .insert(id.clone(), raw_et);
+        }
+        for (name, action) in nsdef.actions.iter() {
+            let namespace = &placement.actions[name];
+            let raw_action = placement.raw_action(namespace.as_ref(), action, u)?;
+            fragment
+                .get_mut(namespace)
+                .expect("namespace should exist")
+                .actions
+                .insert(name.clone(), merged.actions[name].clone());
+            raw_fragment
+                .get_mut(namespace)
+                .expect("namespace should exist")
+                .actions
+                .insert(name.clone(), raw_action);
+        }
+
+        let multi_namespace = MultiNamespace {
+            entity_types: placement.entity_types,
+            actions: placement
+                .actions
+                .into_iter()
+                .map(|(name, namespace)| (ast::Eid::new(name), namespace))
+                .collect(),
+            fragment: json_schema::Fragment(fragment),
+            raw_fragment: json_schema::Fragment(raw_fragment),
+        };
+        Self::from_nsdef_in_namespaces(merged, None, Some(multi_namespace), settings, u)
+    }
+
     /// size hint for arbitrary()
     pub fn arbitrary_size_hint(
         depth: usize,
@@ -1357,11 +2076,18 @@ impl Schema {
             arbitrary_attrspec_size_hint(depth)?,
             size_hint_for_ratio(1, 2), // actually many of these calls
             <ConstantPool as Arbitrary>::size_hint(depth),
+            size_hint_for_ratio(1, 2), // zero to many of these calls, for common types
         ]))
     }
 
     /// Get an arbitrary Hierarchy conforming to the schema.
     pub fn arbitrary_hierarchy(&self, u: &mut Unstructured<'_>) -> Result<Hierarchy> {
+        let hierarchy = self.arbitrary_hierarchy_unrecorded(u);
+        metrics::record_result(metrics::HIERARCHIES, &hierarchy);
+        hierarchy
+    }
+
+    fn arbitrary_hierarchy_unrecorded(&self, u: &mut Unstructured<'_>) -> Result<Hierarchy> {
         HierarchyGenerator {
             mode: HierarchyGeneratorMode::SchemaBased { schema: self },
             num_entities: NumEntities::RangePerEntityType(1..=self.settings.max_width),
@@ -1444,10 +2170,7 @@ impl Schema {
                 } = et
                 {
                     if &schematype_to_type(&self.schema, tag_ty, self.namespace()) == target_type {
-                        Some(
-                            ast::EntityType::from(ast::Name::from(name.clone()))
-                                .qualify_with(self.namespace()),
-                        )
+                        Some(self.declared_entity_type(name))
                     } else {
                         None
                     }
@@ -1469,6 +2192,16 @@ impl Schema {
         &self,
         hierarchy: &Hierarchy,
         u: &mut Unstructured<'_>,
+    ) -> Result<ABACPolicy> {
+        let policy = self.arbitrary_policy_unrecorded(hierarchy, u);
+        metrics::record_result(metrics::POLICIES, &policy);
+        policy
+    }
+
+    fn arbitrary_policy_unrecorded(
+        &self,
+        hierarchy: &Hierarchy,
+        u: &mut Unstructured<'_>,
     ) -> Result<ABACPolicy> {
         let id = u.arbitrary()?;
         let effect = u.arbitrary()?;
@@ -1476,6 +2209,7 @@ impl Schema {
         let action_constraint = self.arbitrary_action_constraint(u, Some(3))?;
         let resource_constraint = self.arbitrary_resource_constraint(hierarchy, u)?;
         let conjunction = self.arbitrary_abac_constraints(hierarchy, u)?;
+        metrics::record_expr(&conjunction);
         Ok(ABACPolicy(GeneratedPolicy::new(
             id,
             u.arbitrary()?,
@@ -1535,7 +2269,7 @@ impl Schema {
         ])
     }
 
-    fn arbitrary_principal_constraint(
+    pub(crate) fn arbitrary_principal_constraint(
         &self,
         hierarchy: &Hierarchy,
         u: &mut Unstructured<'_>,
@@ -1569,7 +2303,7 @@ impl Schema {
         )
     }
 
-    fn arbitrary_resource_constraint(
+    pub(crate) fn arbitrary_resource_constraint(
         &self,
         hierarchy: &Hierarchy,
         u: &mut Unstructured<'_>,
@@ -1649,6 +2383,16 @@ impl Schema {
         &self,
         hierarchy: &Hierarchy,
         u: &mut Unstructured<'_>,
+    ) -> Result<ABACRequest> {
+        let request = self.arbitrary_request_unrecorded(hierarchy, u);
+        metrics::record_result(metrics::REQUESTS, &request);
+        request
+    }
+
+    fn arbitrary_request_unrecorded(
+        &self,
+        hierarchy: &Hierarchy,
+        u: &mut Unstructured<'_>,
     ) -> Result<ABACRequest> {
         // first pick one of the valid Actions
         let applicable_actions: Vec<_> = self
@@ -1670,10 +2414,7 @@ impl Schema {
                 })?;
                 self.arbitrary_uid_with_etype_as_name(ty.try_into().unwrap(), Some(hierarchy), u)?
             },
-            action: uid_for_action_name(
-                self.namespace.as_ref(),
-                ast::Eid::new(action_name.clone()),
-            ),
+            action: self.action_uid(ast::Eid::new(action_name.clone())),
             resource: {
                 // Assert that these are vec, so it's safe to draw from directly
                 let types = &applies_to.resource_types;
@@ -1721,11 +2462,26 @@ impl Schema {
         arbitrary::size_hint::and(size_hint_for_choose(None), (1, None))
     }
 
-    /// Get the namespace of this `Schema`, if any
+    /// Get the namespace of this `Schema`, if any. This is `None` for schemas
+    /// spanning several namespaces.
     pub fn namespace(&self) -> Option<&ast::Name> {
         self.namespace.as_ref()
     }
 
+    /// Get the fully-qualified entity type declared as `id` in this `Schema`
+    pub(crate) fn declared_entity_type(&self, id: &UnreservedId) -> ast::EntityType {
+        declared_entity_type(self.namespace(), self.multi_namespace.as_ref(), id)
+    }
+
+    /// Get the UID of the action `eid` declared in this `Schema`
+    pub fn action_uid(&self, eid: ast::Eid) -> ast::EntityUID {
+        let namespace = match &self.multi_namespace {
+            Some(multi_namespace) => multi_namespace.actions.get(&eid).and_then(Option::as_ref),
+            None => self.namespace(),
+        };
+        uid_for_action_name(namespace, eid)
+    }
+
     /// Get the underlying schema file, as a `NamespaceDefinition`
     pub fn schemafile(&self) -> &json_schema::NamespaceDefinition<ast::InternalName> {
         &self.schema
@@ -1733,20 +2489,30 @@ impl Schema {
 
     /// Get the underlying schema file, as a String containing JSON
     pub fn schemafile_string(&self) -> String {
-        serde_json::to_string_pretty(&self.schema)
-            .expect("failed to serialize schema NamespaceDefinition")
+        match &self.multi_namespace {
+            Some(multi_namespace) => serde_json::to_string_pretty(&multi_namespace.raw_fragment)
+                .expect("failed to serialize schema Fragment"),
+            None => serde_json::to_string_pretty(&self.schema)
+                .expect("failed to serialize schema NamespaceDefinition"),
+        }
     }
 }
 
 impl From<Schema> for json_schema::Fragment<ast::InternalName> {
     fn from(schema: Schema) -> json_schema::Fragment<ast::InternalName> {
-        json_schema::Fragment(BTreeMap::from_iter([(schema.namespace, schema.schema)]))
+        match schema.multi_namespace {
+            Some(multi_namespace) => multi_namespace.fragment,
+            None => json_schema::Fragment(BTreeMap::from_iter([(schema.namespace, schema.schema)])),
+        }
     }
 }
 
 impl From<Schema> for json_schema::Fragment<RawName> {
     fn from(schema: Schema) -> json_schema::Fragment<RawName> {
-        downgrade_frag_to_raw(json_schema::Fragment::<ast::InternalName>::from(schema))
+        match schema.multi_namespace {
+            Some(multi_namespace) => multi_namespace.raw_fragment,
+            None => downgrade_frag_to_raw(json_schema::Fragment::<ast::InternalName>::from(schema)),
+        }
     }
 }
 
@@ -1989,8 +2755,9 @@ impl TryFrom<Schema> for cedar_policy::Schema {
 #[cfg(test)]
 mod tests {
     use super::Schema;
-    use crate::settings::ABACSettings;
+    use crate::settings::{ABACSettings, ExprWeights};
     use arbitrary::Unstructured;
+    use cedar_policy_core::ast::{BinaryOp, Expr, ExprKind, UnaryOp};
     use cedar_policy_core::entities::Entities;
     use cedar_policy_core::extensions::Extensions;
     use cedar_policy_core::validator::{json_schema, CoreSchema, RawName, ValidatorSchema};
@@ -2012,6 +2779,7 @@ mod tests {
         enable_action_in_constraints: true,
         per_action_request_env_limit: ABACSettings::default_per_action_request_env_limit(),
         total_action_request_env_limit: ABACSettings::default_total_action_request_env_limit(),
+        expr_weights: ABACSettings::default_expr_weights(),
     };
 
     const GITHUB_SCHEMA_STR: &str = r#"
@@ -2452,6 +3220,193 @@ mod tests {
         }
     }
 
+    #[test]
+    fn arbitrary_schemas_declare_common_types() {
+        let mut rng = rng();
+        let mut with_common_types = 0;
+        for _ in 0..ITERATION {
+            let mut bytes = [0; RANDOM_BYTE_SIZE as usize];
+            rng.fill_bytes(&mut bytes);
+            let mut u = Unstructured::new(&bytes);
+            let Ok(schema) = Schema::arbitrary(TEST_SETTINGS, &mut u) else {
+                continue;
+            };
+            if !schema.schema.common_types.is_empty() {
+                with_common_types += 1;
+            }
+            let Ok(h) = schema.arbitrary_hierarchy(&mut u) else {
+                continue;
+            };
+            let schemafile = schema.schemafile_string();
+            let vschema = ValidatorSchema::try_from(schema)
+                .unwrap_or_else(|e| panic!("schema should be valid: {e}\n{schemafile}"));
+            let coreschema = CoreSchema::new(&vschema);
+            #[allow(deprecated)]
+            let entities = Entities::from_entities(
+                h.entities().cloned(),
+                Some(&coreschema),
+                cedar_policy_core::entities::TCComputation::ComputeNow,
+                Extensions::all_available(),
+            );
+            assert!(
+                entities.is_ok(),
+                "hierarchy should conform to the schema: {entities:?}\n{schemafile}"
+            );
+        }
+        assert!(with_common_types > 0, "no schema declared common types");
+    }
+
+    #[test]
+    fn multi_namespace_hierarchies_validate() {
+        let mut rng = rng();
+        for _ in 0..ITERATION {
+            let mut bytes = [0; RANDOM_BYTE_SIZE as usize];
+            rng.fill_bytes(&mut bytes);
+            let mut u = Unstructured::new(&bytes);
+            let Ok(schema) = Schema::arbitrary_multi_namespace(TEST_SETTINGS, &mut u) else {
+                continue;
+            };
+            let Ok(h) = schema.arbitrary_hierarchy(&mut u) else {
+                continue;
+            };
+            let schemafile = schema.schemafile_string();
+            let vschema = ValidatorSchema::try_from(schema)
+                .unwrap_or_else(|e| panic!("schema should be valid: {e}\n{schemafile}"));
+            let coreschema = CoreSchema::new(&vschema);
+            #[allow(deprecated)]
+            let entities = Entities::from_entities(
+                h.entities().cloned(),
+                Some(&coreschema),
+                cedar_policy_core::entities::TCComputation::ComputeNow,
+                Extensions::all_available(),
+            );
+            assert!(
+                entities.is_ok(),
+                "hierarchy should conform to the schema: {entities:?}\n{schemafile}"
+            );
+        }
+    }
+
+    /// Random inputs shared by the runs of [`count_subexpressions`], so that
+    /// different weights are compared on the same bytes
+    fn random_inputs() -> Vec<Vec<u8>> {
+        let mut rng = rng();
+        (0..ITERATION)
+            .map(|_| {
+                let mut bytes = vec![0; RANDOM_BYTE_SIZE as usize];
+                rng.fill_bytes(&mut bytes);
+                bytes
+            })
+            .collect()
+    }
+
+    /// The number of subexpressions satisfying `pred` in the non-scope
+    /// constraints of the policies generated from `inputs` with `weights`
+    fn count_subexpressions(
+        inputs: &[Vec<u8>],
+        match_types: bool,
+        weights: ExprWeights,
+        pred: impl Fn(&Expr) -> bool,
+    ) -> usize {
+        let settings = ABACSettings {
+            match_types,
+            enable_extensions: true,
+            enable_like: true,
+            expr_weights: weights,
+            ..TEST_SETTINGS
+        };
+        let mut count = 0;
+        for bytes in inputs {
+            let mut u = Unstructured::new(bytes);
+            let Ok(schema) = Schema::arbitrary(settings.clone(), &mut u) else {
+                continue;
+            };
+            let Ok(h) = schema.arbitrary_hierarchy(&mut u) else {
+                continue;
+            };
+            let Ok(policy) = schema.arbitrary_policy(&h, &mut u) else {
+                continue;
+            };
+            count += policy
+                .abac_constraints()
+                .subexpressions()
+                .filter(|e| pred(e))
+                .count();
+        }
+        count
+    }
+
+    fn is_arithmetic(e: &Expr) -> bool {
+        matches!(
+            e.expr_kind(),
+            ExprKind::BinaryApp {
+                op: BinaryOp::Less
+                    | BinaryOp::LessEq
+                    | BinaryOp::Add
+                    | BinaryOp::Sub
+                    | BinaryOp::Mul,
+                ..
+            } | ExprKind::UnaryApp {
+                op: UnaryOp::Neg,
+                ..
+            }
+        )
+    }
+
+    fn is_hierarchy(e: &Expr) -> bool {
+        matches!(
+            e.expr_kind(),
+            ExprKind::BinaryApp {
+                op: BinaryOp::In,
+                ..
+            } | ExprKind::Is { .. }
+        )
+    }
+
+    #[test]
+    fn expr_weight_presets_shift_distribution() {
+        let inputs = random_inputs();
+        for match_types in [false, true] {
+            let count = |weights, pred| count_subexpressions(&inputs, match_types, weights, pred);
+            let arithmetic_heavy = ExprWeights::arithmetic_heavy();
+            let hierarchy_heavy = ExprWeights::hierarchy_heavy();
+            assert!(
+                count(arithmetic_heavy, is_arithmetic) > count(hierarchy_heavy, is_arithmetic),
+                "arithmetic-heavy should generate more arithmetic (match_types: {match_types})"
+            );
+            assert!(
+                count(hierarchy_heavy, is_hierarchy) > count(arithmetic_heavy, is_hierarchy),
+                "hierarchy-heavy should generate more `in` and `is` (match_types: {match_types})"
+            );
+        }
+    }
+
+    #[test]
+    fn zero_expr_weight_disables_group() {
+        let inputs = random_inputs();
+        let is_like = |e: &Expr| matches!(e.expr_kind(), ExprKind::Like { .. });
+        for match_types in [false, true] {
+            let no_like = ExprWeights {
+                like: 0,
+                ..ExprWeights::default_weights()
+            };
+            assert!(
+                count_subexpressions(
+                    &inputs,
+                    match_types,
+                    ExprWeights::default_weights(),
+                    is_like
+                ) > 0,
+                "default weights should generate `like` (match_types: {match_types})"
+            );
+            assert_eq!(
+                count_subexpressions(&inputs, match_types, no_like, is_like),
+                0,
+                "weight 0 should disable `like` (match_types: {match_types})"
+            );
+        }
+    }
+
     #[allow(deprecated)]
     fn generate_hierarchy_from_schema(
         rng: &mut ThreadRng,
@@ -14,9 +14,17 @@
  * limitations under the License.
  */
 
+use serde_with::DeserializeFromStr;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
 /// Maximum length of a pattern string
 pub const MAX_PATTERN_LEN: usize = 6;
 
+/// Environment variable naming the [`ExprWeights`] preset (one of
+/// [`ExprWeights::PRESETS`]) used by [`ABACSettings::default_fuzz`]
+pub const EXPR_WEIGHTS_VAR: &str = "EXPR_WEIGHTS";
+
 /// Settings controlling the generation of ABAC hierarchies/policies/requests
 #[derive(Debug, Clone)]
 pub struct ABACSettings {
@@ -95,6 +103,9 @@ pub struct ABACSettings {
     /// An upper bound on the total number of request environment a schema can
     /// produce
     pub total_action_request_env_limit: usize,
+
+    /// Relative weights of the forms of expressions in generated policies
+    pub expr_weights: ExprWeights,
 }
 
 impl ABACSettings {
@@ -107,4 +118,153 @@ impl ABACSettings {
     pub const fn default_total_action_request_env_limit() -> usize {
         1024
     }
+
+    /// Default value of `expr_weights`
+    pub const fn default_expr_weights() -> ExprWeights {
+        ExprWeights::default_weights()
+    }
+
+    /// The settings fuzz targets start from, overriding the fields they care
+    /// about with `..ABACSettings::default_fuzz()`. The expression weights are
+    /// the preset named by [`EXPR_WEIGHTS_VAR`], or the default weights if it
+    /// is unset.
+    ///
+    /// Panics if [`EXPR_WEIGHTS_VAR`] names an unknown preset, so that a typo
+    /// does not silently fuzz with the default weights.
+    pub fn default_fuzz() -> Self {
+        static WEIGHTS: OnceLock<ExprWeights> = OnceLock::new();
+        let expr_weights = *WEIGHTS.get_or_init(|| match std::env::var(EXPR_WEIGHTS_VAR) {
+            Ok(preset) => ExprWeights::from_str(&preset)
+                .unwrap_or_else(|e| panic!("invalid {EXPR_WEIGHTS_VAR}: {e}")),
+            Err(_) => Self::default_expr_weights(),
+        });
+        Self {
+            match_types: true,
+            enable_extensions: true,
+            max_depth: 7,
+            max_width: 7,
+            enable_additional_attributes: true,
+            enable_like: true,
+            enable_action_groups_and_attrs: true,
+            enable_arbitrary_func_call: true,
+            enable_unknowns: false,
+            enable_action_in_constraints: true,
+            per_action_request_env_limit: Self::default_per_action_request_env_limit(),
+            total_action_request_env_limit: Self::default_total_action_request_env_limit(),
+            expr_weights,
+        }
+    }
+}
+
+/// Multipliers for the weights with which the expression generators pick each
+/// form of expression, by group of forms. A multiplier of 0 disables the group
+/// (except where it is the only way to build a value of the requested type),
+/// and literals, variables, `==` and `!` are never scaled.
+///
+/// With the default weights (all 1) the generators use their built-in
+/// distribution. Multipliers must be at most [`ExprWeights::MAX_WEIGHT`], so
+/// that the weights of each choice still fit in a `u8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, DeserializeFromStr)]
+pub struct ExprWeights {
+    /// `if-then-else`, `&&` and `||`
+    pub conditionals: u8,
+    /// `<`, `<=`, `>`, `>=`, `+`, `-`, `*` and unary `-`
+    pub arithmetic: u8,
+    /// `in` and `is`
+    pub hierarchy: u8,
+    /// `contains`, `containsAll`, `containsAny`, `isEmpty` and (in untyped
+    /// generation) set literals
+    pub sets: u8,
+    /// `like`
+    pub like: u8,
+    /// extension function calls
+    pub extensions: u8,
+    /// attribute accesses and `has`
+    pub attributes: u8,
+    /// `hasTag` and `getTag`
+    pub tags: u8,
+}
+
+impl ExprWeights {
+    /// Maximum value of each multiplier
+    pub const MAX_WEIGHT: u8 = 3;
+
+    /// The generators' built-in distribution
+    pub const fn default_weights() -> Self {
+        Self {
+            conditionals: 1,
+            arithmetic: 1,
+            hierarchy: 1,
+            sets: 1,
+            like: 1,
+            extensions: 1,
+            attributes: 1,
+            tags: 1,
+        }
+    }
+
+    /// Favor comparisons and (overflowing) arithmetic on longs
+    pub const fn arithmetic_heavy() -> Self {
+        Self {
+            arithmetic: Self::MAX_WEIGHT,
+            like: 0,
+            extensions: 0,
+            ..Self::default_weights()
+        }
+    }
+
+    /// Favor `in` and `is`, and the attributes and tags of entities, exercising
+    /// entity hierarchies
+    pub const fn hierarchy_heavy() -> Self {
+        Self {
+            hierarchy: Self::MAX_WEIGHT,
+            attributes: 2,
+            tags: 2,
+            arithmetic: 0,
+            like: 0,
+            ..Self::default_weights()
+        }
+    }
+
+    /// Favor extension function calls
+    pub const fn extension_heavy() -> Self {
+        Self {
+            extensions: Self::MAX_WEIGHT,
+            arithmetic: 0,
+            like: 0,
+            ..Self::default_weights()
+        }
+    }
+
+    /// The names of the presets accepted by [`ExprWeights::from_str`]
+    pub const PRESETS: [&'static str; 4] = [
+        "default",
+        "arithmetic-heavy",
+        "hierarchy-heavy",
+        "extension-heavy",
+    ];
+}
+
+impl Default for ExprWeights {
+    fn default() -> Self {
+        Self::default_weights()
+    }
+}
+
+impl FromStr for ExprWeights {
+    type Err = String;
+
+    /// Parse the name of a preset
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Self::default_weights()),
+            "arithmetic-heavy" => Ok(Self::arithmetic_heavy()),
+            "hierarchy-heavy" => Ok(Self::hierarchy_heavy()),
+            "extension-heavy" => Ok(Self::extension_heavy()),
+            _ => Err(format!(
+                "unknown expression weights `{s}`, expected one of: {}",
+                Self::PRESETS.join(", ")
+            )),
+        }
+    }
 }